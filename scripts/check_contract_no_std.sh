@@ -0,0 +1,38 @@
+#!/bin/bash
+
+# Contract no_std Check
+# Builds the counter contract for wasm32 without default features and
+# confirms none of the std-only `native-tools` code or dependencies made it in.
+# Usage: ./scripts/check_contract_no_std.sh
+
+set -e
+
+PROJECT_DIR="swaptrade-contracts/counter"
+TARGET="wasm32-unknown-unknown"
+# Dependencies only the `native-tools` feature may pull in
+HOST_ONLY_DEPS="serde_json clap sha3 blake3 ed25519-dalek"
+
+cd "$(dirname "$0")/.."
+
+echo "Building contract for $TARGET with --no-default-features..."
+cargo build --manifest-path "$PROJECT_DIR/Cargo.toml" --lib \
+    --target "$TARGET" --release --no-default-features
+
+echo "Checking dependency graph for host-only crates..."
+TREE=$(cargo tree --manifest-path "$PROJECT_DIR/Cargo.toml" --no-default-features \
+    --edges normal --target "$TARGET" --prefix none)
+for dep in $HOST_ONLY_DEPS; do
+    if echo "$TREE" | grep -q "^$dep v"; then
+        echo "FAIL: $dep is in the contract build"
+        exit 1
+    fi
+done
+
+echo "Checking the audit-tools binary is not built without native-tools..."
+if cargo build --manifest-path "$PROJECT_DIR/Cargo.toml" --bin audit-tools \
+    --no-default-features 2>/dev/null; then
+    echo "FAIL: audit-tools built without native-tools"
+    exit 1
+fi
+
+echo "OK: contract builds without native-tools modules"
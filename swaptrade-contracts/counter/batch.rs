@@ -65,7 +65,7 @@ impl BatchResult {
 
 /// Validates all operations in a batch before execution
 /// Returns Ok(()) if all operations are valid, Err with first error found
-pub fn validate_batch(env: &Env, operations: &Vec<BatchOperation>) -> Result<(), Symbol> {
+pub fn validate_batch(env: &Env, portfolio: &Portfolio, operations: &Vec<BatchOperation>) -> Result<(), Symbol> {
     // Check batch size limit
     if operations.len() > MAX_BATCH_SIZE {
         return Err(Symbol::new(env, "batch_size_exceeded"));
@@ -78,7 +78,7 @@ pub fn validate_batch(env: &Env, operations: &Vec<BatchOperation>) -> Result<(),
     // Validate each operation
     for i in 0..operations.len() {
         if let Some(op) = operations.get(i) {
-            match validate_operation(env, &op) {
+            match validate_operation(env, portfolio, &op) {
                 Ok(_) => continue,
                 Err(e) => return Err(e),
             }
@@ -89,7 +89,7 @@ pub fn validate_batch(env: &Env, operations: &Vec<BatchOperation>) -> Result<(),
 }
 
 /// Validates a single operation
-fn validate_operation(env: &Env, operation: &BatchOperation) -> Result<(), Symbol> {
+fn validate_operation(env: &Env, portfolio: &Portfolio, operation: &BatchOperation) -> Result<(), Symbol> {
     match operation {
         BatchOperation::Swap(from, to, amount, _user) => {
             if *amount <= 0 {
@@ -99,7 +99,7 @@ fn validate_operation(env: &Env, operation: &BatchOperation) -> Result<(), Symbo
                 return Err(Symbol::new(env, "same_token_swap"));
             }
             // Validate tokens are supported
-            if !is_valid_token(from) || !is_valid_token(to) {
+            if !is_valid_token(portfolio, from) || !is_valid_token(portfolio, to) {
                 return Err(Symbol::new(env, "invalid_token"));
             }
             Ok(())
@@ -123,7 +123,7 @@ fn validate_operation(env: &Env, operation: &BatchOperation) -> Result<(), Symbo
             if *amount < 0 {
                 return Err(Symbol::new(env, "negative_mint"));
             }
-            if !is_valid_token(token) {
+            if !is_valid_token(portfolio, token) {
                 return Err(Symbol::new(env, "invalid_token"));
             }
             Ok(())
@@ -132,16 +132,14 @@ fn validate_operation(env: &Env, operation: &BatchOperation) -> Result<(), Symbo
 }
 
 /// Helper function to check if a token symbol is valid
-fn is_valid_token(token: &Symbol) -> bool {
-    *token == symbol_short!("XLM") || *token == symbol_short!("USDCSIM")
+fn is_valid_token(portfolio: &Portfolio, token: &Symbol) -> bool {
+    *token == symbol_short!("XLM") || *token == portfolio.quote_symbol()
 }
 
 /// Converts Symbol to Asset
 fn symbol_to_asset(sym: &Symbol) -> Asset {
     if *sym == symbol_short!("XLM") {
         Asset::XLM
-    } else if *sym == symbol_short!("USDCSIM") {
-        Asset::Custom(sym.clone())
     } else {
         Asset::Custom(sym.clone())
     }
@@ -156,7 +154,7 @@ pub fn execute_batch_atomic(
     operations: Vec<BatchOperation>,
 ) -> Result<BatchResult, Symbol> {
     // Validate entire batch first
-    validate_batch(env, &operations)?;
+    validate_batch(env, portfolio, &operations)?;
     
     // Create a snapshot of the portfolio state for rollback
     // OPTIMIZATION: Use selective cloning instead of full clone when possible
@@ -198,7 +196,7 @@ pub fn execute_batch_best_effort(
     operations: Vec<BatchOperation>,
 ) -> Result<BatchResult, Symbol> {
     // Validate entire batch first
-    validate_batch(env, &operations)?;
+    validate_batch(env, portfolio, &operations)?;
     
     // OPTIMIZATION: Pre-allocate result vector with known capacity
     let mut batch_result = BatchResult::new_with_capacity(env, operations.len() as u32);
@@ -246,11 +244,7 @@ fn execute_single_operation(
         BatchOperation::AddLiquidity(xlm_amount, usdc_amount, user) => {
             // Check balances
             let xlm_balance = portfolio.balance_of(env, Asset::XLM, user.clone());
-            let usdc_balance = portfolio.balance_of(
-                env, 
-                Asset::Custom(Symbol::new(env, "USDCSIM")), 
-                user.clone()
-            );
+            let usdc_balance = portfolio.balance_of(env, portfolio.quote_asset(), user.clone());
             
             if xlm_balance < *xlm_amount || usdc_balance < *usdc_amount {
                 return Err(Symbol::new(env, "insufficient_funds"));
@@ -262,7 +256,7 @@ fn execute_single_operation(
             
             // Deduct from user's balance
             let xlm_key = (user.clone(), Asset::XLM);
-            let usdc_key = (user.clone(), Asset::Custom(Symbol::new(env, "USDCSIM")));
+            let usdc_key = (user.clone(), portfolio.quote_asset());
             
             Ok(*xlm_amount + *usdc_amount) // Return total liquidity added
         }
@@ -272,12 +266,8 @@ fn execute_single_operation(
             
             // Return liquidity to user (simplified)
             portfolio.mint(env, Asset::XLM, user.clone(), *xlm_amount);
-            portfolio.mint(
-                env, 
-                Asset::Custom(Symbol::new(env, "USDCSIM")), 
-                user.clone(), 
-                *usdc_amount
-            );
+            let quote = portfolio.quote_asset();
+            portfolio.mint(env, quote, user.clone(), *usdc_amount);
             
             Ok(*xlm_amount + *usdc_amount) // Return total liquidity removed
         }
@@ -304,13 +294,13 @@ mod tests {
         for _ in 0..11 {
             operations.push_back(BatchOperation::Swap(
                 Symbol::new(&env, "XLM"),
-                Symbol::new(&env, "USDCSIM"),
+                Portfolio::new(&env).quote_symbol(),
                 100,
                 user.clone(),
             ));
         }
         
-        let result = validate_batch(&env, &operations);
+        let result = validate_batch(&env, &Portfolio::new(&env), &operations);
         assert!(result.is_err());
         assert_eq!(result.unwrap_err(), Symbol::new(&env, "batch_size_exceeded"));
     }
@@ -320,7 +310,7 @@ mod tests {
         let env = Env::default();
         let operations = Vec::new(&env);
         
-        let result = validate_batch(&env, &operations);
+        let result = validate_batch(&env, &Portfolio::new(&env), &operations);
         assert!(result.is_err());
         assert_eq!(result.unwrap_err(), Symbol::new(&env, "empty_batch"));
     }
@@ -333,12 +323,12 @@ mod tests {
         let mut operations = Vec::new(&env);
         operations.push_back(BatchOperation::Swap(
             Symbol::new(&env, "XLM"),
-            Symbol::new(&env, "USDCSIM"),
+            Portfolio::new(&env).quote_symbol(),
             -100, // Invalid negative amount
             user.clone(),
         ));
         
-        let result = validate_batch(&env, &operations);
+        let result = validate_batch(&env, &Portfolio::new(&env), &operations);
         assert!(result.is_err());
         assert_eq!(result.unwrap_err(), Symbol::new(&env, "invalid_amount"));
     }
@@ -356,7 +346,7 @@ mod tests {
             user.clone(),
         ));
         
-        let result = validate_batch(&env, &operations);
+        let result = validate_batch(&env, &Portfolio::new(&env), &operations);
         assert!(result.is_err());
         assert_eq!(result.unwrap_err(), Symbol::new(&env, "same_token_swap"));
     }
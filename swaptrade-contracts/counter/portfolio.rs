@@ -32,6 +32,33 @@ pub enum Badge {
     Consistency,
 }
 
+/// Half-life of a trader's leaderboard score. A trader who stops trading sees
+/// their score halve every period, so inactive accounts drift down the board.
+pub const LEADERBOARD_HALF_LIFE_SECS: u64 = 7 * 24 * 60 * 60;
+
+/// Decimal scale every amount is normalized to before it is compared or summed
+/// across assets. Assets without a configured scale are assumed to use it.
+pub const INTERNAL_DECIMALS: u32 = 7;
+
+/// Largest per-asset decimal scale accepted by `set_asset_decimals`.
+pub const MAX_ASSET_DECIMALS: u32 = 18;
+
+/// Quote asset of the built-in XLM pool until an admin calls `set_quote_asset`.
+pub const DEFAULT_QUOTE_SYMBOL: Symbol = symbol_short!("USDCSIM");
+
+/// Maximum number of entries kept on the leaderboard.
+pub const LEADERBOARD_SIZE: u32 = 100;
+
+/// Recency-weighted leaderboard score, stored as of `updated_at`.
+#[derive(Clone, Debug, PartialEq)]
+#[contracttype]
+pub struct LeaderboardScore {
+    pub score: i128,
+    pub updated_at: u64,
+    /// When the user first appeared on the board; earlier entrants win ties
+    pub first_seen: u64,
+}
+
 #[derive(Clone)]
 #[contracttype]
 pub struct Portfolio {
@@ -40,12 +67,14 @@ pub struct Portfolio {
     pnl: Map<Address, i128>,         // cumulative balance change placeholder
     badges: Map<(Address, Badge), bool>, // tracks which badges each user has earned
     metrics: Metrics,                 // lightweight aggregate metrics
+    failure_reasons: Map<FailureReason, u32>, // failed orders broken down by cause
     
     // Admin Dashboard Aggregate Stats
     total_users: u32,                 // unique traders/LPs
     total_trading_volume: i128,       // sum of all swap amounts
     active_users: Vec<Address>,       // users with activity (limited to last N blocks)
-    top_traders: Vec<(Address, i128)>, // top 100 traders by PnL
+    top_traders: Vec<(Address, i128)>, // top 100 traders by decayed activity score
+    leaderboard_scores: Map<Address, LeaderboardScore>, // per-user score before decay to now
     xlm_in_pool: i128,               // liquidity pool XLM
     usdc_in_pool: i128,              // liquidity pool USDC
     total_fees_collected: i128,       // accumulated fees
@@ -66,8 +95,17 @@ pub struct Portfolio {
     // Time-series Analytics Data
     daily_portfolio_values: Map<(Address, u64), i128>, // (user, date) -> portfolio value
     last_update_timestamp: Map<Address, u64>,          // last time portfolio was recorded
+
+    // Per-asset decimal scale; missing entries default to INTERNAL_DECIMALS
+    asset_decimals: Map<Asset, u32>,
+
+    // Quote side of the built-in XLM pool (`usdc_in_pool`); see `set_quote_symbol`
+    quote_symbol: Symbol,
 }
 
+/// Per-user cap on stored transaction history; older entries are dropped
+pub const MAX_TRANSACTIONS_PER_USER: u32 = 100;
+
 #[derive(Clone, Debug, PartialEq)] // Added derives for testing
 #[contracttype]
 pub struct Transaction {
@@ -97,10 +135,12 @@ impl Portfolio {
             pnl: Map::new(env),
             badges: Map::new(env),
             metrics: Metrics::default(),
+            failure_reasons: Map::new(env),
             total_users: 0,
             total_trading_volume: 0,
             active_users: Vec::new(env),
             top_traders: Vec::new(env),
+            leaderboard_scores: Map::new(env),
             xlm_in_pool: 0,
             usdc_in_pool: 0,
             total_fees_collected: 0,
@@ -115,9 +155,29 @@ impl Portfolio {
             migration_time: None,
             daily_portfolio_values: Map::new(env),
             last_update_timestamp: Map::new(env),
+            asset_decimals: Map::new(env),
+            quote_symbol: DEFAULT_QUOTE_SYMBOL,
         }
     }
 
+    /// Symbol of the asset XLM is quoted against in the built-in pool
+    pub fn quote_symbol(&self) -> Symbol {
+        self.quote_symbol.clone()
+    }
+
+    pub fn quote_asset(&self) -> Asset {
+        Asset::Custom(self.quote_symbol.clone())
+    }
+
+    /// Point the built-in pool at a different quote asset, e.g. the real
+    /// USDC symbol on mainnet. The pool's quote reserves are not converted,
+    /// so callers must only switch while they are empty.
+    pub fn set_quote_symbol(&mut self, symbol: Symbol) {
+        assert!(symbol != symbol_short!("XLM"), "Quote asset must differ from XLM");
+        assert!(self.usdc_in_pool == 0, "Quote pool is not empty");
+        self.quote_symbol = symbol;
+    }
+
     // NOTE: debit() implementation with PnL tracking appears later in the file.
     // The earlier, simpler debit() was removed to avoid duplicate definitions
     // which cause a compile-time error. Use the single canonical `debit` below
@@ -196,7 +256,7 @@ impl Portfolio {
     self.pnl.set(to.clone(), new_pnl);
 
         // Update top traders leaderboard
-        self.update_top_traders(env, to.clone());
+        self.record_leaderboard_activity(env, to.clone(), amount);
 
         // Metrics: one balance updated
         self.metrics.balances_updated = self.metrics.balances_updated.saturating_add(1);
@@ -258,6 +318,38 @@ impl Portfolio {
     self.badges.get(key).unwrap_or(false)
     }
 
+    /// Append a swap to the user's history, keeping only the latest
+    /// `MAX_TRANSACTIONS_PER_USER`. `rate_achieved` is `to / from` with 7 decimals.
+    pub fn record_transaction(
+        &mut self,
+        env: &Env,
+        user: Address,
+        from_token: Symbol,
+        to_token: Symbol,
+        from_amount: i128,
+        to_amount: i128,
+    ) {
+        let rate_achieved = if from_amount > 0 {
+            (to_amount.max(0) as u128).saturating_mul(10_000_000) / from_amount as u128
+        } else {
+            0
+        };
+        let mut txs = self.transactions.get(user.clone()).unwrap_or_else(|| Vec::new(env));
+        if txs.len() >= MAX_TRANSACTIONS_PER_USER {
+            txs.pop_front();
+        }
+        txs.push_back(Transaction {
+            timestamp: env.ledger().timestamp(),
+            from_token,
+            to_token,
+            from_amount,
+            to_amount,
+            rate_achieved,
+            migration_time: None,
+        });
+        self.transactions.set(user, txs);
+    }
+
     /// Get paginated transaction history for a user (most recent first up to `limit`).
     pub fn get_user_transactions(&self, env: &Env, user: Address, limit: u32) -> Vec<Transaction> {
         let mut result = Vec::new(env);
@@ -297,9 +389,27 @@ impl Portfolio {
         self.metrics.clone()
     }
 
-    /// Increment failed order counter
-    pub fn inc_failed_order(&mut self) {
+    /// Increment failed order counter, attributing the failure to `reason`
+    pub fn inc_failed_order(&mut self, reason: FailureReason) {
         self.metrics.failed_orders = self.metrics.failed_orders.saturating_add(1);
+        self.inc_failure_reason(reason);
+    }
+
+    /// Increment rate-limit rejection counter
+    pub fn inc_rate_limited(&mut self) {
+        self.metrics.rate_limited_count = self.metrics.rate_limited_count.saturating_add(1);
+        self.inc_failure_reason(FailureReason::RateLimited);
+    }
+
+    fn inc_failure_reason(&mut self, reason: FailureReason) {
+        let count = self.failure_reasons.get(reason.clone()).unwrap_or(0);
+        self.failure_reasons.set(reason, count.saturating_add(1));
+    }
+
+    /// Failed orders per reason. `RateLimited` mirrors `rate_limited_count`,
+    /// so the other reasons together sum to `failed_orders`.
+    pub fn get_failure_breakdown(&self) -> Map<FailureReason, u32> {
+        self.failure_reasons.clone()
     }
 
     /// Record daily portfolio value for analytics
@@ -313,11 +423,75 @@ impl Portfolio {
         self.last_update_timestamp.set(user, timestamp);
     }
 
+    /// Balances held by `user`, one entry per asset with a non-zero balance.
+    /// Scans the balance map, so cost grows with the total number of holdings.
+    pub fn held_assets(&self, env: &Env, user: Address) -> Vec<(Asset, i128)> {
+        let mut held = Vec::new(env);
+        for ((owner, asset), balance) in self.balances.iter() {
+            if owner == user && balance != 0 {
+                held.push_back((asset, balance));
+            }
+        }
+        held
+    }
+
+    /// Set how many decimals `asset` amounts carry.
+    pub fn set_asset_decimals(&mut self, asset: Asset, decimals: u32) {
+        assert!(decimals <= MAX_ASSET_DECIMALS, "Unsupported decimals");
+        self.asset_decimals.set(asset, decimals);
+    }
+
+    pub fn get_asset_decimals(&self, asset: &Asset) -> u32 {
+        self.asset_decimals.get(asset.clone()).unwrap_or(INTERNAL_DECIMALS)
+    }
+
+    /// Rescale a raw `asset` amount to `INTERNAL_DECIMALS`. Scaling down truncates.
+    pub fn normalize_amount(&self, asset: &Asset, amount: i128) -> i128 {
+        let decimals = self.get_asset_decimals(asset);
+        if decimals < INTERNAL_DECIMALS {
+            amount.saturating_mul(10i128.pow(INTERNAL_DECIMALS - decimals))
+        } else {
+            amount / 10i128.pow(decimals - INTERNAL_DECIMALS)
+        }
+    }
+
+    /// Value of all of `user`'s holdings at the supplied fixed-point prices
+    /// (`price_precision` = 1.0), with balances normalized to `INTERNAL_DECIMALS`.
+    /// Assets missing from `prices` contribute zero; see `unpriced_assets` to find them.
+    pub fn total_portfolio_value(
+        &self,
+        env: &Env,
+        user: Address,
+        prices: &Map<Asset, i128>,
+        price_precision: i128,
+    ) -> i128 {
+        let mut total: i128 = 0;
+        for (asset, balance) in self.held_assets(env, user).iter() {
+            if let Some(price) = prices.get(asset.clone()) {
+                let balance = self.normalize_amount(&asset, balance);
+                total = total.saturating_add(crate::fixed::mul_fp(balance, price, price_precision));
+            }
+        }
+        total
+    }
+
+    /// Assets `user` holds that have no entry in `prices`.
+    pub fn unpriced_assets(&self, env: &Env, user: Address, prices: &Map<Asset, i128>) -> Vec<Asset> {
+        let mut missing = Vec::new(env);
+        for (asset, _) in self.held_assets(env, user).iter() {
+            if !prices.contains_key(asset.clone()) {
+                missing.push_back(asset);
+            }
+        }
+        missing
+    }
+
     /// Get total portfolio value across all assets for a user
     pub fn get_total_portfolio_value(&self, env: &Env, user: Address) -> i128 {
         // Sum all asset balances (simplified - in real implementation would use current prices)
-        let xlm_balance = self.balance_of(env, Asset::XLM, user.clone());
-        let usdc_balance = self.balance_of(env, Asset::Custom(symbol_short!("USDCSIM")), user);
+        let usdc = self.quote_asset();
+        let xlm_balance = self.normalize_amount(&Asset::XLM, self.balance_of(env, Asset::XLM, user.clone()));
+        let usdc_balance = self.normalize_amount(&usdc, self.balance_of(env, usdc.clone(), user));
         xlm_balance + usdc_balance
     }
 
@@ -551,9 +725,10 @@ impl Portfolio {
         self.active_users.len()
     }
 
-    /// Get the top N traders by PnL (leaderboard)
+    /// Get the top N traders by recency-weighted score (leaderboard)
     /// Capped at top 100 for safety
-    /// Returns Vec<(Address, i128)>: list of (user, pnl) pairs sorted by PnL descending
+    /// Returns Vec<(Address, i128)>: list of (user, score) pairs sorted descending,
+    /// with scores as of the last leaderboard update
     /// Time complexity: O(1) - precomputed top 100
     pub fn get_top_traders(&self, env: &Env, limit: u32) -> Vec<(Address, i128)> {
         let max_limit: u32 = 100;
@@ -582,7 +757,7 @@ impl Portfolio {
 
     /// Helper: Update aggregate stats when a trade is recorded
     /// Called lazily during trade operations
-    fn update_stats_on_trade(&mut self, env: &Env, user: Address, swap_amount: i128) {
+    pub(crate) fn update_stats_on_trade(&mut self, env: &Env, user: Address, swap_amount: i128) {
         // Check if user is new (not in trades map)
         let trade_count = self.trades.get(user.clone()).unwrap_or(0);
         if trade_count == 0 {
@@ -605,50 +780,89 @@ impl Portfolio {
         
         // Update total trading volume
         self.total_trading_volume = self.total_trading_volume.saturating_add(swap_amount);
+
+        // Trading activity feeds the recency-weighted leaderboard
+        self.record_leaderboard_activity(env, user, swap_amount);
     }
 
-    /// Helper: Update top traders leaderboard after PnL changes
-    /// Maintains top 100 traders sorted by PnL descending
-    fn update_top_traders(&mut self, env: &Env, user: Address) {
-        let user_pnl = self.pnl.get(user.clone()).unwrap_or(0);
-        
-        // Check if user is already in top_traders
-        let mut found_index = None;
+    /// 1-based leaderboard position of `user`, or `None` if outside the top 100.
+    /// Rankings are recomputed whenever any trader's score is updated.
+    pub fn get_leaderboard_rank(&self, user: &Address) -> Option<u32> {
         for i in 0..self.top_traders.len() {
             if let Some((addr, _)) = self.top_traders.get(i) {
-                if addr == user {
-                    found_index = Some(i);
-                    break;
+                if addr == *user {
+                    return Some(i + 1);
                 }
             }
         }
-        
-        if let Some(idx) = found_index {
-            // Update existing entry
-            self.top_traders.set(idx, (user.clone(), user_pnl));
-        } else if self.top_traders.len() < 100 {
-            // Add new entry if under limit
-            self.top_traders.push_back((user.clone(), user_pnl));
-        } else {
-            // Check if new PnL beats the lowest in top 100
-            if let Some((_, lowest_pnl)) = self.top_traders.get(99) {
-                if user_pnl > lowest_pnl {
-                    self.top_traders.set(99, (user.clone(), user_pnl));
-                }
+        None
+    }
+
+    /// Add `amount` of activity to a user's leaderboard score and re-rank.
+    /// The stored score is first decayed to the current ledger time.
+    pub fn record_leaderboard_activity(&mut self, env: &Env, user: Address, amount: i128) {
+        let now = env.ledger().timestamp();
+        let current = self.current_leaderboard_score(&user, now);
+        let first_seen = self
+            .leaderboard_scores
+            .get(user.clone())
+            .map(|entry| entry.first_seen)
+            .unwrap_or(now);
+        self.leaderboard_scores.set(
+            user.clone(),
+            LeaderboardScore {
+                score: current.saturating_add(amount.saturating_abs()),
+                updated_at: now,
+                first_seen,
+            },
+        );
+        self.update_top_traders(env, user);
+    }
+
+    /// A user's leaderboard score decayed to `now`.
+    fn current_leaderboard_score(&self, user: &Address, now: u64) -> i128 {
+        match self.leaderboard_scores.get(user.clone()) {
+            Some(entry) => decay_score(entry.score, now.saturating_sub(entry.updated_at)),
+            None => 0,
+        }
+    }
+
+    /// Helper: Recompute the leaderboard after `user`'s score changes
+    /// Every ranked entry is decayed to the current time before sorting, so
+    /// traders who have gone quiet lose ground to those still trading.
+    pub fn update_top_traders(&mut self, env: &Env, user: Address) {
+        let now = env.ledger().timestamp();
+        let mut ranked = Vec::new(env);
+        let mut has_user = false;
+        for (addr, _) in self.top_traders.iter() {
+            if addr == user {
+                has_user = true;
             }
+            let score = self.current_leaderboard_score(&addr, now);
+            ranked.push_back((addr, score));
         }
-        
-        // Sort by PnL descending (simple bubble sort for small list)
+        if !has_user {
+            let score = self.current_leaderboard_score(&user, now);
+            ranked.push_back((user, score));
+        }
+        self.top_traders = ranked;
+
+        // Sort by score descending (simple bubble sort for small list)
         self.sort_top_traders();
+        while self.top_traders.len() > LEADERBOARD_SIZE {
+            self.top_traders.pop_back();
+        }
     }
 
-    /// Helper: Sort top_traders by PnL in descending order
+    /// Helper: Sort top_traders by score in descending order.
+    /// Equal scores fall back to earliest `first_seen`, then address, so the
+    /// ranking never depends on insertion order.
     fn sort_top_traders(&mut self) {
         let len = self.top_traders.len();
         for i in 0..len {
             for j in 0..(len - 1 - i) {
-                if let (Some((_, pnl1)), Some((_, pnl2))) = (self.top_traders.get(j), self.top_traders.get(j + 1)) {
-                    if pnl1 < pnl2 {
+                if let (Some(a), Some(b)) = (self.top_traders.get(j), self.top_traders.get(j + 1)) {
+                    if self.ranks_before(&b, &a) {
                         // Swap
                         let temp1 = self.top_traders.get(j).unwrap();
                         let temp2 = self.top_traders.get(j + 1).unwrap();
@@ -660,6 +874,24 @@ impl Portfolio {
         }
     }
 
+    /// Leaderboard ordering: higher score, then earlier first_seen, then lower address
+    fn ranks_before(&self, a: &(Address, i128), b: &(Address, i128)) -> bool {
+        if a.1 != b.1 {
+            return a.1 > b.1;
+        }
+        let seen = |addr: &Address| {
+            self.leaderboard_scores
+                .get(addr.clone())
+                .map(|entry| entry.first_seen)
+                .unwrap_or(u64::MAX)
+        };
+        let (seen_a, seen_b) = (seen(&a.0), seen(&b.0));
+        if seen_a != seen_b {
+            return seen_a < seen_b;
+        }
+        a.0 < b.0
+    }
+
     /// Helper: Add liquidity to pool
     pub fn add_pool_liquidity(&mut self, xlm_amount: i128, usdc_amount: i128) {
         self.xlm_in_pool = self.xlm_in_pool.saturating_add(xlm_amount);
@@ -675,7 +907,7 @@ impl Portfolio {
         match asset {
             Asset::XLM => self.xlm_in_pool = amount,
             Asset::Custom(sym) => {
-                if sym == symbol_short!("USDCSIM") {
+                if sym == self.quote_symbol {
                     self.usdc_in_pool = amount;
                 }
             }
@@ -686,7 +918,7 @@ impl Portfolio {
         match asset {
             Asset::XLM => self.xlm_in_pool,
             Asset::Custom(sym) => {
-                if sym == symbol_short!("USDCSIM") {
+                if sym == self.quote_symbol {
                     self.usdc_in_pool
                 } else {
                     0
@@ -933,6 +1165,32 @@ pub struct Metrics {
     pub trades_executed: u32,
     pub failed_orders: u32,
     pub balances_updated: u32,
+    /// Attempts rejected by the swap/LP rate limiter (not counted in failed_orders)
+    pub rate_limited_count: u32,
+}
+
+/// Why an order was declined.
+#[derive(Clone, Debug, PartialEq)]
+#[contracttype]
+pub enum FailureReason {
+    /// Unsupported token, identical pair or non-positive amount
+    InvalidInput,
+    InsufficientBalance,
+    SlippageExceeded,
+    RateLimited,
+}
+
+/// Exponentially decay `score` over `elapsed` seconds using
+/// `LEADERBOARD_HALF_LIFE_SECS`: halve once per whole half-life, then
+/// interpolate linearly toward the next halving for the remainder.
+fn decay_score(score: i128, elapsed: u64) -> i128 {
+    let halvings = elapsed / LEADERBOARD_HALF_LIFE_SECS;
+    if halvings >= 127 {
+        return 0;
+    }
+    let halved = score >> halvings;
+    let remainder = (elapsed % LEADERBOARD_HALF_LIFE_SECS) as i128;
+    halved - crate::fixed::mul_div(halved, remainder, 2 * LEADERBOARD_HALF_LIFE_SECS as i128)
 }
 
 
@@ -1,5 +1,18 @@
-use soroban_sdk::{contracttype, Address, Env, Symbol, Map, Vec, U256};
-use crate::rate_limit::TimeWindow;
+use soroban_sdk::{contracttype, xdr::ToXdr, Address, Bytes, Env, Symbol, Map, Vec, U256};
+use crate::fee_progression::FeeCalculationResult;
+
+/// Default time a commission is held before it can be claimed (30 days)
+pub const DEFAULT_HOLDING_PERIOD_SECS: u64 = 30 * 24 * 60 * 60;
+
+/// Minimum time between two commission claims by the same user (1 hour)
+pub const CLAIM_RATE_LIMIT_SECS: u64 = 3600;
+
+/// Number of `CommissionTier` levels, and the default commission depth
+pub const MAX_COMMISSION_TIERS: u32 = 3;
+
+/// Referee fee rebate is off until an admin configures one
+pub const DEFAULT_REFEREE_REBATE_BPS: u32 = 0;
+pub const DEFAULT_REFEREE_REBATE_TRADES: u32 = 0;
 
 /// Commission tiers for referral structure
 #[derive(Clone, Debug, PartialEq, Eq)]
@@ -61,6 +74,50 @@ pub struct ReferralInfo {
     pub referral_trading_volume: i128,
 }
 
+/// Commission percentages paid to each level of the referral chain
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[contracttype]
+pub struct CommissionConfig {
+    /// Percentage of the trade fee paid to the direct referrer
+    pub direct_pct: u32,
+    /// Percentage paid to the second-level referrer
+    pub secondary_pct: u32,
+    /// Percentage paid to the third-level referrer
+    pub tertiary_pct: u32,
+}
+
+impl CommissionTier {
+    /// Tier paid to the referrer `level` steps up the chain (0 = direct)
+    pub fn for_level(level: usize) -> Option<Self> {
+        match level {
+            0 => Some(CommissionTier::Direct),
+            1 => Some(CommissionTier::Secondary),
+            2 => Some(CommissionTier::Tertiary),
+            _ => None,
+        }
+    }
+}
+
+impl CommissionConfig {
+    /// Global default split (20% / 10% / 5%)
+    pub fn default_split() -> Self {
+        Self {
+            direct_pct: CommissionTier::Direct as u32,
+            secondary_pct: CommissionTier::Secondary as u32,
+            tertiary_pct: CommissionTier::Tertiary as u32,
+        }
+    }
+
+    /// Percentage for a given tier
+    pub fn rate_for(&self, tier: &CommissionTier) -> u32 {
+        match tier {
+            CommissionTier::Direct => self.direct_pct,
+            CommissionTier::Secondary => self.secondary_pct,
+            CommissionTier::Tertiary => self.tertiary_pct,
+        }
+    }
+}
+
 /// Commission claim record for anti-gaming
 #[derive(Clone, Debug)]
 #[contracttype]
@@ -69,7 +126,7 @@ pub struct CommissionRecord {
     pub amount: i128,
     /// When it was earned
     pub earned_at: u64,
-    /// When it becomes claimable (30 days later)
+    /// When it becomes claimable (`holding_period_secs` after earning)
     pub claimable_at: u64,
     /// Source of commission (which referee)
     pub source: Address,
@@ -87,7 +144,7 @@ pub struct ReferralSystem {
     // Maps referral codes to user addresses
     code_to_user: Map<Symbol, Address>,
     
-    // Pending commission records (for 30-day holding)
+    // Pending commission records (held for `holding_period_secs`)
     pending_commissions: Map<Address, Vec<CommissionRecord>>,
     
     // Rate limiting for commission claims
@@ -99,6 +156,27 @@ pub struct ReferralSystem {
     // Global referral statistics
     total_referrals: u32,
     total_commission_distributed: i128,
+
+    // Global commission split, used unless a referrer has an override
+    commission_config: CommissionConfig,
+
+    // Custom commission splits negotiated with individual referrers
+    referrer_overrides: Map<Address, CommissionConfig>,
+
+    // Seconds a new commission is held before it becomes claimable
+    holding_period_secs: u64,
+
+    // Referrer levels paid on each trade, at most MAX_COMMISSION_TIERS
+    max_commission_depth: u32,
+
+    // Fee rebate (bps) granted to referees on their first trades
+    referee_rebate_bps: u32,
+
+    // Rebated trades granted to each new referee at registration
+    referee_rebate_trades: u32,
+
+    // Rebated trades each referee has left
+    referee_rebates_remaining: Map<Address, u32>,
 }
 
 impl ReferralSystem {
@@ -108,53 +186,62 @@ impl ReferralSystem {
             code_to_user: Map::new(env),
             pending_commissions: Map::new(env),
             claim_rate_limits: Map::new(env),
-            next_token_id: U256::from_u32(1),
+            next_token_id: U256::from_u32(env, 1),
             total_referrals: 0,
             total_commission_distributed: 0,
+            commission_config: CommissionConfig::default_split(),
+            referrer_overrides: Map::new(env),
+            holding_period_secs: DEFAULT_HOLDING_PERIOD_SECS,
+            max_commission_depth: MAX_COMMISSION_TIERS,
+            referee_rebate_bps: DEFAULT_REFEREE_REBATE_BPS,
+            referee_rebate_trades: DEFAULT_REFEREE_REBATE_TRADES,
+            referee_rebates_remaining: Map::new(env),
         }
     }
 
     /// Generate a unique referral code for a user with NFT proof
     pub fn generate_referral_code(&mut self, env: &Env, user: Address) -> Symbol {
-        // Check if user already has a referral code
-        if let Some(info) = self.referral_info.get(user.clone()) {
-            return info.referral_code;
+        // Check if user already has a referral code; referees registered
+        // through someone else's code start without one
+        let existing = self.referral_info.get(user.clone());
+        if let Some(info) = &existing {
+            if info.referral_code != Symbol::new(env, "") {
+                return info.referral_code.clone();
+            }
         }
 
         // Generate a unique 8-character alphanumeric referral code
-        let code = self.generate_unique_code(env);
+        let code = self.generate_unique_code(env, &user);
         
-        // Create referral info for the user
-        let info = ReferralInfo {
-            referral_code: code,
-            referrer: None,
-            registration_timestamp: env.ledger().timestamp(),
-            total_commission_earned: 0,
-            available_commission: 0,
-            direct_referral_count: 0,
-            total_referral_count: 0,
-            last_claim_timestamp: 0,
-            badges: Vec::new(env),
-            referral_trading_volume: 0,
+        // Create referral info for the user, keeping a referee's existing record
+        let info = match existing {
+            Some(info) => ReferralInfo { referral_code: code.clone(), ..info },
+            None => ReferralInfo {
+                referral_code: code.clone(),
+                referrer: None,
+                registration_timestamp: env.ledger().timestamp(),
+                total_commission_earned: 0,
+                available_commission: 0,
+                direct_referral_count: 0,
+                total_referral_count: 0,
+                last_claim_timestamp: 0,
+                badges: Vec::new(env),
+                referral_trading_volume: 0,
+            },
         };
         
         // Store mappings
         self.referral_info.set(user.clone(), info.clone());
-        self.code_to_user.set(code, user);
+        self.code_to_user.set(code.clone(), user.clone());
 
         // Mint initial NFT badge for referral code generation
-        self.mint_referral_badge(env, user, ReferralMilestone::Starter, code);
+        self.mint_referral_badge(env, user, ReferralMilestone::Starter, code.clone());
 
         code
     }
 
     /// Register a new user with a referral code and return rewards NFT
     pub fn register_with_code(&mut self, env: &Env, referral_code: Symbol, new_user: Address) -> Result<ReferralBadge, &'static str> {
-        // Check if user already exists
-        if self.referral_info.contains_key(new_user.clone()) {
-            return Err("User already registered");
-        }
-
         // Validate referral code exists
         let referrer = self.code_to_user.get(referral_code)
             .ok_or("Invalid referral code")?;
@@ -164,6 +251,11 @@ impl ReferralSystem {
             return Err("Cannot refer yourself");
         }
 
+        // Check if user already exists
+        if self.referral_info.contains_key(new_user.clone()) {
+            return Err("User already registered");
+        }
+
         // Create referral info for new user
         let user_info = ReferralInfo {
             referral_code: Symbol::new(env, ""), // No code yet
@@ -180,6 +272,9 @@ impl ReferralSystem {
 
         // Store new user info
         self.referral_info.set(new_user.clone(), user_info);
+        if self.referee_rebate_trades > 0 {
+            self.referee_rebates_remaining.set(new_user.clone(), self.referee_rebate_trades);
+        }
 
         // Update referrer's referral count
         if let Some(mut referrer_info) = self.referral_info.get(referrer.clone()) {
@@ -200,52 +295,176 @@ impl ReferralSystem {
         Ok(welcome_badge)
     }
 
-    /// Distribute commission across 3-tier referral chain
-    pub fn distribute_commission(&mut self, env: &Env, trader: Address, trade_fee: i128, fee_tier: u32) -> Vec<(Address, i128, CommissionTier)> {
+    /// Distribute commission across the referral chain, up to `max_commission_depth` levels
+    pub fn distribute_commission(&mut self, env: &Env, trader: Address, trade_fee: i128, _fee_tier: u32) -> Vec<(Address, i128, CommissionTier)> {
         let mut distributions = Vec::new(env);
         let current_timestamp = env.ledger().timestamp();
+        let mut total_distributed = 0i128;
         
-        // Get the referral chain (up to 3 levels)
-        let referral_chain = self.get_referral_chain(env, trader, 3);
+        // Get the referral chain (up to the configured depth)
+        let referral_chain = self.get_referral_chain(env, trader.clone(), self.max_commission_depth as usize);
         
         for (level, referrer) in referral_chain.iter().enumerate() {
-            let tier = match level {
-                0 => CommissionTier::Direct,
-                1 => CommissionTier::Secondary,
-                2 => CommissionTier::Tertiary,
-                _ => break, // Only 3 tiers supported
+            let tier = match CommissionTier::for_level(level) {
+                Some(tier) => tier,
+                None => break,
             };
             
-            let commission_rate = match tier {
-                CommissionTier::Direct => 20,
-                CommissionTier::Secondary => 10,
-                CommissionTier::Tertiary => 5,
-            };
+            let commission_rate = self
+                .referrer_overrides
+                .get(referrer.clone())
+                .unwrap_or_else(|| self.commission_config.clone())
+                .rate_for(&tier);
             
             let commission_amount = (trade_fee * commission_rate as i128) / 100;
             
             if commission_amount > 0 {
-                // Create commission record with 30-day holding period
+                // Create commission record with the configured holding period
                 let record = CommissionRecord {
                     amount: commission_amount,
                     earned_at: current_timestamp,
-                    claimable_at: current_timestamp + (30 * 24 * 60 * 60), // 30 days
+                    claimable_at: current_timestamp + self.holding_period_secs,
                     source: trader.clone(),
-                    tier,
+                    tier: tier.clone(),
                 };
                 
                 // Add to pending commissions
                 let mut pending = self.pending_commissions.get(referrer.clone()).unwrap_or_else(|| Vec::new(env));
                 pending.push_back(record);
                 self.pending_commissions.set(referrer.clone(), pending);
+                if let Some(mut info) = self.referral_info.get(referrer.clone()) {
+                    info.available_commission += commission_amount;
+                    self.referral_info.set(referrer.clone(), info);
+                }
                 
+                // Forensic trail for referral payouts
+                crate::events::Events::commission_distributed(
+                    env,
+                    trader.clone(),
+                    referrer.clone(),
+                    tier.clone(),
+                    commission_amount,
+                    current_timestamp as i64,
+                );
+                total_distributed += commission_amount;
                 distributions.push_back((referrer.clone(), commission_amount, tier));
             }
         }
         
+        crate::events::Events::commission_distribution_summary(
+            env,
+            trader,
+            distributions.len(),
+            total_distributed,
+            current_timestamp as i64,
+        );
+        
         distributions
     }
 
+    /// Set a custom commission split for a referrer (admin only)
+    pub fn set_referrer_override(&mut self, env: &Env, admin: Address, referrer: Address, config: CommissionConfig) -> Result<(), &'static str> {
+        admin.require_auth();
+        if !crate::admin::is_admin(env, &admin) {
+            return Err("Unauthorized");
+        }
+        if config.direct_pct + config.secondary_pct + config.tertiary_pct > 100 {
+            return Err("Commission split exceeds 100%");
+        }
+        self.referrer_overrides.set(referrer, config);
+        Ok(())
+    }
+
+    /// Remove a referrer's custom split, reverting to the global config (admin only)
+    pub fn clear_referrer_override(&mut self, env: &Env, admin: Address, referrer: Address) -> Result<(), &'static str> {
+        admin.require_auth();
+        if !crate::admin::is_admin(env, &admin) {
+            return Err("Unauthorized");
+        }
+        self.referrer_overrides.remove(referrer);
+        Ok(())
+    }
+
+    /// Set how long new commissions are held before they can be claimed (admin only).
+    /// Commissions already pending keep the `claimable_at` they were created with.
+    pub fn set_holding_period(&mut self, env: &Env, admin: Address, holding_period_secs: u64) -> Result<(), &'static str> {
+        admin.require_auth();
+        if !crate::admin::is_admin(env, &admin) {
+            return Err("Unauthorized");
+        }
+        self.holding_period_secs = holding_period_secs;
+        Ok(())
+    }
+
+    pub fn get_holding_period(&self) -> u64 {
+        self.holding_period_secs
+    }
+
+    /// Set how many referrer levels earn commission on a trade (admin only).
+    /// Each level needs a `CommissionTier`, so the depth is capped at `MAX_COMMISSION_TIERS`.
+    pub fn set_max_commission_depth(&mut self, env: &Env, admin: Address, depth: u32) -> Result<(), &'static str> {
+        admin.require_auth();
+        if !crate::admin::is_admin(env, &admin) {
+            return Err("Unauthorized");
+        }
+        if depth > MAX_COMMISSION_TIERS {
+            return Err("Commission depth exceeds supported tiers");
+        }
+        self.max_commission_depth = depth;
+        Ok(())
+    }
+
+    pub fn get_max_commission_depth(&self) -> u32 {
+        self.max_commission_depth
+    }
+
+    /// Give each referee registered from now on `rebate_bps` off their first
+    /// `trades` trades (admin only). Referees already registered keep what
+    /// they were granted.
+    pub fn set_referee_rebate(&mut self, env: &Env, admin: Address, rebate_bps: u32, trades: u32) -> Result<(), &'static str> {
+        admin.require_auth();
+        if !crate::admin::is_admin(env, &admin) {
+            return Err("Unauthorized");
+        }
+        if rebate_bps > 10_000 {
+            return Err("Rebate exceeds 100%");
+        }
+        self.referee_rebate_bps = rebate_bps;
+        self.referee_rebate_trades = trades;
+        Ok(())
+    }
+
+    pub fn get_referee_rebate(&self) -> (u32, u32) {
+        (self.referee_rebate_bps, self.referee_rebate_trades)
+    }
+
+    pub fn get_referee_rebates_remaining(&self, user: Address) -> u32 {
+        self.referee_rebates_remaining.get(user).unwrap_or(0)
+    }
+
+    /// Fee-path hook: take `referee_rebate_bps` off a referee's effective fee
+    /// while they have rebated trades left, using up one of them. The rebate
+    /// stacks after the achievement discount already in `fee`, and the two
+    /// together never exceed `fee.max_discount_bps`; a trade whose rebate is
+    /// squeezed to zero by the cap still counts against the referee's trades.
+    pub fn apply_referee_rebate(&mut self, user: &Address, fee: &mut FeeCalculationResult) {
+        let remaining = self.referee_rebates_remaining.get(user.clone()).unwrap_or(0);
+        if remaining == 0 || self.referee_rebate_bps == 0 {
+            return;
+        }
+
+        let headroom = fee.max_discount_bps.saturating_sub(fee.achievement_discount_bps);
+        let rebate = self.referee_rebate_bps.min(headroom);
+        fee.referee_rebate_bps = rebate;
+        fee.effective_fee_bps = fee.effective_fee_bps.saturating_sub(rebate);
+
+        if remaining == 1 {
+            self.referee_rebates_remaining.remove(user.clone());
+        } else {
+            self.referee_rebates_remaining.set(user.clone(), remaining - 1);
+        }
+    }
+
     /// Get comprehensive referral statistics for a user
     pub fn get_referral_stats(&self, env: &Env, user: Address) -> ReferralInfo {
         self.referral_info.get(user).unwrap_or_else(|| ReferralInfo {
@@ -262,25 +481,43 @@ impl ReferralSystem {
         })
     }
 
-    /// Claim available commission with rate limiting
+    /// Claim the oldest matured commission record with rate limiting. Later
+    /// matured records stay pending; this partial claim shares its hourly
+    /// window with `claim_all_matured`.
     pub fn claim_commission(&mut self, env: &Env, user: Address) -> Result<i128, &'static str> {
+        self.claim_matured(env, user, 1)
+    }
+
+    /// Claim every matured commission record in one call, however many
+    /// batches have come due, so a large backlog never needs hourly-spaced
+    /// claims. Only a claim that pays out starts the `CLAIM_RATE_LIMIT_SECS`
+    /// window; a call with nothing matured leaves it untouched. All claim
+    /// entry points for a user share the one window.
+    pub fn claim_all_matured(&mut self, env: &Env, user: Address) -> Result<i128, &'static str> {
+        self.claim_matured(env, user, u32::MAX)
+    }
+
+    /// Claim up to `max_records` matured records, oldest first
+    fn claim_matured(&mut self, env: &Env, user: Address, max_records: u32) -> Result<i128, &'static str> {
         let current_timestamp = env.ledger().timestamp();
         
         // Rate limiting: max one claim per hour
         if let Some(last_claim) = self.claim_rate_limits.get(user.clone()) {
-            if current_timestamp < last_claim + 3600 {
+            if current_timestamp < last_claim.saturating_add(CLAIM_RATE_LIMIT_SECS) {
                 return Err("Rate limit: Please wait before claiming again");
             }
         }
         
         // Process pending commissions
         let mut total_claimable = 0i128;
+        let mut claimed_records = 0u32;
         let mut remaining_pending = Vec::new(env);
         
         if let Some(pending) = self.pending_commissions.get(user.clone()) {
             for record in pending.iter() {
-                if current_timestamp >= record.claimable_at {
+                if current_timestamp >= record.claimable_at && claimed_records < max_records {
                     total_claimable += record.amount;
+                    claimed_records += 1;
                 } else {
                     remaining_pending.push_back(record);
                 }
@@ -301,12 +538,12 @@ impl ReferralSystem {
         
         // Update pending commissions
         if remaining_pending.is_empty() {
-            self.pending_commissions.remove(user);
+            self.pending_commissions.remove(user.clone());
         } else {
-            self.pending_commissions.set(user, remaining_pending);
+            self.pending_commissions.set(user.clone(), remaining_pending);
         }
         
-        // Update rate limit
+        // Only a paying claim opens the next window
         self.claim_rate_limits.set(user, current_timestamp);
         
         // Update global statistics
@@ -351,7 +588,7 @@ impl ReferralSystem {
                 // Check if badge already earned
                 let has_badge = info.badges.iter().any(|badge| badge.milestone == *milestone);
                 if !has_badge {
-                    self.mint_referral_badge(env, user.clone(), milestone.clone(), info.referral_code);
+                    self.mint_referral_badge(env, user.clone(), milestone.clone(), info.referral_code.clone());
                 }
             }
         }
@@ -359,8 +596,8 @@ impl ReferralSystem {
 
     /// Mint NFT badge for achievement
     fn mint_referral_badge(&mut self, env: &Env, user: Address, milestone: ReferralMilestone, referral_code: Symbol) -> ReferralBadge {
-        let token_id = self.next_token_id;
-        self.next_token_id = token_id + U256::from_u32(1);
+        let token_id = self.next_token_id.clone();
+        self.next_token_id = token_id.add(&U256::from_u32(env, 1));
         
         let badge = ReferralBadge {
             milestone,
@@ -379,13 +616,15 @@ impl ReferralSystem {
     }
 
     /// Generate a unique referral code
-    fn generate_unique_code(&self, env: &Env) -> Symbol {
+    fn generate_unique_code(&self, env: &Env, user: &Address) -> Symbol {
         let mut attempts = 0;
         loop {
-            let code_str = self.create_random_code(env, attempts);
-            let code = Symbol::new(env, &code_str);
+            let code_chars = self.create_random_code(env, user, attempts);
+            // Every character comes from the ASCII alphabet below
+            let code = Symbol::new(env, core::str::from_utf8(&code_chars).unwrap_or_default());
             
-            if !self.code_to_user.contains_key(code) {
+            // Hash collisions are unlikely but the lookup stays as a backstop
+            if !self.code_to_user.contains_key(code.clone()) {
                 return code;
             }
             
@@ -396,28 +635,28 @@ impl ReferralSystem {
         }
     }
 
-    /// Create a random-looking referral code
-    fn create_random_code(&self, env: &Env, attempt: u32) -> String {
-        let ledger_seq = env.ledger().sequence();
-        let seed = ledger_seq as u64 + attempt as u64;
-        
-        let chars = "ABCDEFGHIJKLMNOPQRSTUVWXYZ0123456789";
-        let mut result = String::new();
-        let mut temp_seed = seed;
-        
-        for _ in 0..8 {
-            let idx = (temp_seed % 36) as usize;
-            if let Some(c) = chars.chars().nth(idx) {
-                result.push(c);
-            }
+    /// Create a random-looking referral code.
+    /// Seeds SHA-256 with the user's address, ledger sequence and attempt so
+    /// users in the same ledger get unrelated codes, then base-36 encodes the
+    /// first 16 digest bytes.
+    fn create_random_code(&self, env: &Env, user: &Address, attempt: u32) -> [u8; 8] {
+        let mut seed = Bytes::new(env);
+        seed.append(&user.clone().to_xdr(env));
+        seed.extend_from_array(&env.ledger().sequence().to_be_bytes());
+        seed.extend_from_array(&attempt.to_be_bytes());
+        let digest = env.crypto().sha256(&seed).to_array();
+
+        let mut head = [0u8; 16];
+        head.copy_from_slice(&digest[..16]);
+        let mut temp_seed = u128::from_be_bytes(head);
+
+        let chars = b"ABCDEFGHIJKLMNOPQRSTUVWXYZ0123456789";
+        let mut result = [0u8; 8];
+        for slot in result.iter_mut() {
+            *slot = chars[(temp_seed % 36) as usize];
             temp_seed /= 36;
         }
-        
-        while result.len() < 8 {
-            result.push('A');
-        }
-        
-        result[..8.min(result.len())].to_string()
+        result
     }
 
     /// Get pending commission amount for a user
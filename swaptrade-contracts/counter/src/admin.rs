@@ -1,7 +1,8 @@
 use soroban_sdk::{Address, Env};
 
-use crate::errors::SwapTradeError;
-use crate::storage::ADMIN_KEY;
+use crate::errors::{ContractError, SwapTradeError};
+use crate::events::Events;
+use crate::storage::{ADMIN_KEY, PAUSED_KEY};
 
 pub fn is_admin(env: &Env, user: &Address) -> bool {
     env.storage()
@@ -11,6 +12,17 @@ pub fn is_admin(env: &Env, user: &Address) -> bool {
         .unwrap_or(false)
 }
 
+/// Store the first admin. Only succeeds while no admin is configured, so the
+/// deployer should call it in the same transaction as deployment.
+pub fn init_admin(env: &Env, admin: &Address) -> Result<(), SwapTradeError> {
+    admin.require_auth();
+    if env.storage().persistent().has(&ADMIN_KEY) {
+        return Err(SwapTradeError::AdminAlreadySet);
+    }
+    env.storage().persistent().set(&ADMIN_KEY, admin);
+    Ok(())
+}
+
 pub fn require_admin(env: &Env, caller: &Address) -> Result<(), SwapTradeError> {
     if is_admin(env, caller) {
         Ok(())
@@ -18,3 +30,37 @@ pub fn require_admin(env: &Env, caller: &Address) -> Result<(), SwapTradeError>
         Err(SwapTradeError::NotAdmin)
     }
 }
+
+/// Halt swaps, liquidity adds and mints. Exits (`remove_liquidity`,
+/// commission claims) stay open. When governance is wired in, callers must
+/// also pass its `assert_can_pause` check for the current phase.
+pub fn pause(env: &Env, admin: &Address) -> Result<(), SwapTradeError> {
+    admin.require_auth();
+    require_admin(env, admin)?;
+    env.storage().persistent().set(&PAUSED_KEY, &true);
+    Events::admin_paused(env, admin.clone(), env.ledger().timestamp() as i64);
+    Ok(())
+}
+
+pub fn unpause(env: &Env, admin: &Address) -> Result<(), SwapTradeError> {
+    admin.require_auth();
+    require_admin(env, admin)?;
+    env.storage().persistent().set(&PAUSED_KEY, &false);
+    Events::admin_resumed(env, admin.clone(), env.ledger().timestamp() as i64);
+    Ok(())
+}
+
+pub fn is_paused(env: &Env) -> bool {
+    env.storage()
+        .persistent()
+        .get::<_, bool>(&PAUSED_KEY)
+        .unwrap_or(false)
+}
+
+pub fn require_not_paused(env: &Env) -> Result<(), ContractError> {
+    if is_paused(env) {
+        Err(ContractError::ContractPaused)
+    } else {
+        Ok(())
+    }
+}
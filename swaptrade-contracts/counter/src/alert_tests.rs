@@ -1,12 +1,19 @@
 #![cfg(test)]
 
-use soroban_sdk::{symbol_short, testutils::Address as _, Address, Env, Vec};
+use soroban_sdk::{
+    symbol_short,
+    testutils::{Address as _, Events as _, Ledger as _},
+    Address, BytesN, Env, Symbol, TryIntoVal, Vec,
+};
 
+use crate::CounterContract;
 use crate::alerts::{
-    check_market_alerts, check_portfolio_alerts, check_price_alerts, cleanup_alerts,
-    create_market_alert, create_portfolio_alert, create_price_alert, get_active_alerts,
-    subscribe_alerts, MarketSignal, NotificationMethod, PortfolioTrigger, PriceDirection,
-    AlertKind,
+    alert_delivery_id, check_liquidation_alerts, check_market_alerts, check_portfolio_alerts, check_price_alerts,
+    check_price_alerts_at, set_max_price_age_secs, DEFAULT_MAX_PRICE_AGE_SECS,
+    cleanup_alerts, create_alerts_batch, create_market_alert, create_portfolio_alert, create_price_alert,
+    create_price_move_alert, get_active_alerts, get_alert_history, snooze_alert,
+    set_alert_dedup, set_notification_method, subscribe_alerts, unsnooze_alert, AlertKind, MarketSignal, NotificationMethod,
+    PortfolioAlert, PortfolioTrigger, PriceAlert, PriceDirection, AlertSpec, ALERT_HISTORY_CAP, MAX_ALERTS_PER_USER,
 };
 
 // helpers
@@ -16,51 +23,65 @@ fn setup() -> (Env, Address) {
     (env, user)
 }
 
+/// Alerts live in contract storage, so each test body runs as the contract
+fn with_contract(env: &Env, f: impl FnOnce()) {
+    let contract_id = env.register(CounterContract, ());
+    env.as_contract(&contract_id, f);
+}
+
+/// Storage entries written by `f` when run as its own contract invocation
+fn entries_written(env: &Env, contract_id: &Address, f: impl FnOnce()) -> u32 {
+    env.as_contract(contract_id, f);
+    env.cost_estimate().resources().write_entries
+}
+
 // create_price_alert 
 
 #[test]
 fn test_create_price_alert_returns_incrementing_ids() {
     let (env, user) = setup();
-
-    let id1 = create_price_alert(
-        &env,
-        user.clone(),
-        symbol_short!("XLM"),
-        1_000_000,
-        PriceDirection::Above,
-        0,
-        NotificationMethod::Event,
-    );
-    let id2 = create_price_alert(
-        &env,
-        user.clone(),
-        symbol_short!("XLM"),
-        500_000,
-        PriceDirection::Below,
-        0,
-        NotificationMethod::Event,
-    );
-
-    assert_eq!(id1, 1);
-    assert_eq!(id2, 2);
+    with_contract(&env, || {
+        let id1 = create_price_alert(
+            &env,
+            user.clone(),
+            symbol_short!("XLM"),
+            1_000_000,
+            PriceDirection::Above,
+            0,
+            NotificationMethod::Event,
+        );
+        let id2 = create_price_alert(
+            &env,
+            user.clone(),
+            symbol_short!("XLM"),
+            500_000,
+            PriceDirection::Below,
+            0,
+            NotificationMethod::Event,
+        );
+
+        assert_eq!(id1, 1);
+        assert_eq!(id2, 2);
+    });
 }
 
 #[test]
 fn test_create_price_alert_visible_in_active_list() {
     let (env, user) = setup();
-
-    create_price_alert(
-        &env,
-        user.clone(),
-        symbol_short!("XLM"),
-        1_000_000,
-        PriceDirection::Above,
-        0,
-        NotificationMethod::Event,
-    );
-
-    let active = get_active_alerts(&env, user);
-    assert_eq!(active.len(), 1);
+    with_contract(&env, || {
+        create_price_alert(
+            &env,
+            user.clone(),
+            symbol_short!("XLM"),
+            1_000_000,
+            PriceDirection::Above,
+            0,
+            NotificationMethod::Event,
+        );
+
+        let active = get_active_alerts(&env, user);
+        assert_eq!(active.len(), 1);
+    });
 }
 
 // create_portfolio_alert
@@ -68,26 +89,27 @@ fn test_create_price_alert_visible_in_active_list() {
 #[test]
 fn test_create_portfolio_alert_stored_correctly() {
     let (env, user) = setup();
-
-    let id = create_portfolio_alert(
-        &env,
-        user.clone(),
-        PortfolioTrigger::ValueChangeBps,
-        500,   // 5% change
-        0,     // no expiry
-        NotificationMethod::Event,
-    );
-
-    assert_eq!(id, 1);
-    let active = get_active_alerts(&env, user);
-    assert_eq!(active.len(), 1);
-    match active.get(0).unwrap().kind {
-        AlertKind::Portfolio { ref trigger_type, threshold_bps } => {
-            assert!(matches!(trigger_type, PortfolioTrigger::ValueChangeBps));
-            assert_eq!(threshold_bps, 500);
+    with_contract(&env, || {
+        let id = create_portfolio_alert(
+            &env,
+            user.clone(),
+            PortfolioTrigger::ValueChangeBps,
+            500,   // 5% change
+            0,     // no expiry
+            NotificationMethod::Event,
+        );
+
+        assert_eq!(id, 1);
+        let active = get_active_alerts(&env, user);
+        assert_eq!(active.len(), 1);
+        match active.get(0).unwrap().kind {
+            AlertKind::Portfolio(PortfolioAlert { ref trigger_type, threshold_bps }) => {
+                assert!(matches!(trigger_type, PortfolioTrigger::ValueChangeBps));
+                assert_eq!(threshold_bps, 500);
+            }
+            _ => panic!("wrong kind"),
         }
-        _ => panic!("wrong kind"),
-    }
+    });
 }
 
 // create_market_alert
@@ -95,19 +117,20 @@ fn test_create_portfolio_alert_stored_correctly() {
 #[test]
 fn test_create_market_alert_stored_correctly() {
     let (env, user) = setup();
-
-    let id = create_market_alert(
-        &env,
-        user.clone(),
-        symbol_short!("XLMUSDC"),
-        MarketSignal::TrendReversal,
-        0,
-        NotificationMethod::Webhook,
-    );
-
-    assert_eq!(id, 1);
-    let active = get_active_alerts(&env, user);
-    assert_eq!(active.len(), 1);
+    with_contract(&env, || {
+        let id = create_market_alert(
+            &env,
+            user.clone(),
+            symbol_short!("XLMUSDC"),
+            MarketSignal::TrendReversal,
+            0,
+            NotificationMethod::Webhook,
+        );
+
+        assert_eq!(id, 1);
+        let active = get_active_alerts(&env, user);
+        assert_eq!(active.len(), 1);
+    });
 }
 
 // subscribe_alerts
@@ -115,27 +138,78 @@ fn test_create_market_alert_stored_correctly() {
 #[test]
 fn test_subscribe_alerts_changes_notification_method() {
     let (env, user) = setup();
+    with_contract(&env, || {
+        let id = create_price_alert(
+            &env,
+            user.clone(),
+            symbol_short!("XLM"),
+            1_000_000,
+            PriceDirection::Above,
+            0,
+            NotificationMethod::Event,
+        );
+
+        let mut ids = Vec::new(&env);
+        ids.push_back(id);
+        subscribe_alerts(&env, user.clone(), ids, NotificationMethod::Webhook);
+
+        let active = get_active_alerts(&env, user);
+        assert_eq!(active.len(), 1);
+        assert!(matches!(
+            active.get(0).unwrap().notification_method,
+            NotificationMethod::Webhook
+        ));
+    });
+}
 
-    let id = create_price_alert(
-        &env,
-        user.clone(),
-        symbol_short!("XLM"),
-        1_000_000,
-        PriceDirection::Above,
-        0,
-        NotificationMethod::Event,
-    );
-
-    let mut ids = Vec::new(&env);
-    ids.push_back(id);
-    subscribe_alerts(&env, user.clone(), ids, NotificationMethod::Webhook);
-
-    let active = get_active_alerts(&env, user);
-    assert_eq!(active.len(), 1);
-    assert!(matches!(
-        active.get(0).unwrap().notification_method,
-        NotificationMethod::Webhook
-    ));
+#[test]
+fn test_subscribe_alerts_leaves_unlisted_alerts_untouched() {
+    let (env, user) = setup();
+    let contract_id = env.register(CounterContract, ());
+    let (ids, second) = env.as_contract(&contract_id, || {
+        let first = create_price_alert(
+            &env,
+            user.clone(),
+            symbol_short!("XLM"),
+            1_000_000,
+            PriceDirection::Above,
+            0,
+            NotificationMethod::Event,
+        );
+        let second = create_price_alert(
+            &env,
+            user.clone(),
+            symbol_short!("XLM"),
+            500_000,
+            PriceDirection::Below,
+            0,
+            NotificationMethod::Event,
+        );
+
+        let mut ids = Vec::new(&env);
+        ids.push_back(first);
+        subscribe_alerts(&env, user.clone(), ids.clone(), NotificationMethod::Webhook);
+
+        let active = get_active_alerts(&env, user.clone());
+        let method_of = |id: u64| active.iter().find(|a| a.id == id).unwrap().notification_method;
+        assert_eq!(method_of(first), NotificationMethod::Webhook);
+        assert_eq!(method_of(second), NotificationMethod::Event);
+        (ids, second)
+    });
+
+    // Repeating the same subscription writes nothing
+    let written = entries_written(&env, &contract_id, || {
+        subscribe_alerts(&env, user.clone(), ids, NotificationMethod::Webhook);
+    });
+    assert_eq!(written, 0);
+
+    env.as_contract(&contract_id, || {
+        // Explicit method changes go through set_notification_method
+        assert!(set_notification_method(&env, user.clone(), second, NotificationMethod::Webhook));
+        assert!(!set_notification_method(&env, user.clone(), 999, NotificationMethod::Event));
+        let active = get_active_alerts(&env, user);
+        assert!(active.iter().all(|a| a.notification_method == NotificationMethod::Webhook));
+    });
 }
 
 // expiry
@@ -143,42 +217,46 @@ fn test_subscribe_alerts_changes_notification_method() {
 #[test]
 fn test_expired_alert_not_returned_in_active_list() {
     let env = Env::default();
-    env.ledger().with_mut(|li| li.timestamp = 2000);
-    let user = Address::generate(&env);
-
-    // expires_at = 1000, current time = 2000  → already expired
-    create_price_alert(
-        &env,
-        user.clone(),
-        symbol_short!("XLM"),
-        1_000_000,
-        PriceDirection::Above,
-        1000,   // expires in the past
-        NotificationMethod::Event,
-    );
-
-    let active = get_active_alerts(&env, user);
-    assert_eq!(active.len(), 0, "expired alert should not appear as active");
+    with_contract(&env, || {
+        env.ledger().with_mut(|li| li.timestamp = 2000);
+        let user = Address::generate(&env);
+
+        // expires_at = 1000, current time = 2000  → already expired
+        create_price_alert(
+            &env,
+            user.clone(),
+            symbol_short!("XLM"),
+            1_000_000,
+            PriceDirection::Above,
+            1000,   // expires in the past
+            NotificationMethod::Event,
+        );
+
+        let active = get_active_alerts(&env, user);
+        assert_eq!(active.len(), 0, "expired alert should not appear as active");
+    });
 }
 
 #[test]
 fn test_persistent_alert_zero_expiry_never_expires() {
     let env = Env::default();
-    env.ledger().with_mut(|li| li.timestamp = 9_999_999);
-    let user = Address::generate(&env);
-
-    create_price_alert(
-        &env,
-        user.clone(),
-        symbol_short!("XLM"),
-        1_000_000,
-        PriceDirection::Above,
-        0, // persistent
-        NotificationMethod::Event,
-    );
-
-    let active = get_active_alerts(&env, user);
-    assert_eq!(active.len(), 1, "persistent alert should always be active");
+    with_contract(&env, || {
+        env.ledger().with_mut(|li| li.timestamp = 9_999_999);
+        let user = Address::generate(&env);
+
+        create_price_alert(
+            &env,
+            user.clone(),
+            symbol_short!("XLM"),
+            1_000_000,
+            PriceDirection::Above,
+            0, // persistent
+            NotificationMethod::Event,
+        );
+
+        let active = get_active_alerts(&env, user);
+        assert_eq!(active.len(), 1, "persistent alert should always be active");
+    });
 }
 
 // check_price_alerts 
@@ -186,243 +264,858 @@ fn test_persistent_alert_zero_expiry_never_expires() {
 #[test]
 fn test_price_alert_fires_above_threshold() {
     let env = Env::default();
-    env.ledger().with_mut(|li| li.timestamp = 1000);
-    let user = Address::generate(&env);
+    with_contract(&env, || {
+        env.ledger().with_mut(|li| li.timestamp = 1000);
+        let user = Address::generate(&env);
+
+        create_price_alert(
+            &env,
+            user.clone(),
+            symbol_short!("XLM"),
+            500_000,
+            PriceDirection::Above,
+            2000, // expires in the future
+            NotificationMethod::Event,
+        );
+
+        // Price rises above target
+        check_price_alerts(&env, &symbol_short!("XLM"), 600_000);
+
+        // Temporary alert should have been deactivated after firing
+        let active = get_active_alerts(&env, user);
+        assert_eq!(active.len(), 0, "alert should be deactivated after firing");
+    });
+}
 
-    create_price_alert(
-        &env,
-        user.clone(),
-        symbol_short!("XLM"),
-        500_000,
-        PriceDirection::Above,
-        2000, // expires in the future
-        NotificationMethod::Event,
-    );
+#[test]
+fn test_price_alert_does_not_fire_if_condition_not_met() {
+    let env = Env::default();
+    with_contract(&env, || {
+        env.ledger().with_mut(|li| li.timestamp = 1000);
+        let user = Address::generate(&env);
+
+        create_price_alert(
+            &env,
+            user.clone(),
+            symbol_short!("XLM"),
+            500_000,
+            PriceDirection::Above,
+            2000,
+            NotificationMethod::Event,
+        );
+
+        // Price is still below target
+        check_price_alerts(&env, &symbol_short!("XLM"), 400_000);
+
+        let active = get_active_alerts(&env, user);
+        assert_eq!(active.len(), 1, "alert should still be active");
+    });
+}
 
-    // Price rises above target
-    check_price_alerts(&env, &symbol_short!("XLM"), 600_000);
+#[test]
+fn test_stale_price_does_not_fire() {
+    let env = Env::default();
+    with_contract(&env, || {
+        env.ledger().with_mut(|li| li.timestamp = 1000);
+        let user = Address::generate(&env);
+
+        create_price_alert(
+            &env,
+            user.clone(),
+            symbol_short!("XLM"),
+            500_000,
+            PriceDirection::Above,
+            0,
+            NotificationMethod::Event,
+        );
+
+        // Matching price, but quoted before the freshness bound
+        let stale_ts = 1000 - DEFAULT_MAX_PRICE_AGE_SECS - 1;
+        check_price_alerts_at(&env, &symbol_short!("XLM"), 600_000, Some(stale_ts));
+        assert_eq!(get_active_alerts(&env, user.clone()).get(0).unwrap().last_triggered_at, 0);
+
+        // Loosening the bound lets the same quote through
+        set_max_price_age_secs(&env, DEFAULT_MAX_PRICE_AGE_SECS + 1);
+        check_price_alerts_at(&env, &symbol_short!("XLM"), 600_000, Some(stale_ts));
+        assert_eq!(get_active_alerts(&env, user).get(0).unwrap().last_triggered_at, 1000);
+    });
+}
 
-    // Temporary alert should have been deactivated after firing
-    let active = get_active_alerts(&env, user);
-    assert_eq!(active.len(), 0, "alert should be deactivated after firing");
+#[test]
+#[should_panic(expected = "non-positive price")]
+fn test_zero_price_rejected() {
+    let env = Env::default();
+    with_contract(&env, || {
+        let user = Address::generate(&env);
+        create_price_alert(
+            &env,
+            user,
+            symbol_short!("XLM"),
+            500_000,
+            PriceDirection::Below,
+            0,
+            NotificationMethod::Event,
+        );
+        check_price_alerts(&env, &symbol_short!("XLM"), 0);
+    });
 }
 
 #[test]
-fn test_price_alert_does_not_fire_if_condition_not_met() {
+fn test_price_alert_below_direction() {
     let env = Env::default();
-    env.ledger().with_mut(|li| li.timestamp = 1000);
-    let user = Address::generate(&env);
+    with_contract(&env, || {
+        env.ledger().with_mut(|li| li.timestamp = 1000);
+        let user = Address::generate(&env);
+
+        create_price_alert(
+            &env,
+            user.clone(),
+            symbol_short!("XLM"),
+            200_000,
+            PriceDirection::Below,
+            2000,
+            NotificationMethod::Event,
+        );
+
+        check_price_alerts(&env, &symbol_short!("XLM"), 100_000); // below target
+
+        let active = get_active_alerts(&env, user);
+        assert_eq!(active.len(), 0, "below-direction alert should fire and deactivate");
+    });
+}
 
-    create_price_alert(
-        &env,
-        user.clone(),
-        symbol_short!("XLM"),
-        500_000,
-        PriceDirection::Above,
-        2000,
-        NotificationMethod::Event,
-    );
+#[test]
+fn test_persistent_price_alert_stays_active_after_trigger() {
+    let env = Env::default();
+    with_contract(&env, || {
+        env.ledger().with_mut(|li| li.timestamp = 1000);
+        let user = Address::generate(&env);
+
+        // expires_at = 0 → persistent
+        create_price_alert(
+            &env,
+            user.clone(),
+            symbol_short!("XLM"),
+            500_000,
+            PriceDirection::Above,
+            0,
+            NotificationMethod::Event,
+        );
+
+        check_price_alerts(&env, &symbol_short!("XLM"), 600_000);
+
+        let active = get_active_alerts(&env, user);
+        assert_eq!(active.len(), 1, "persistent alert must remain active after firing");
+    });
+}
 
-    // Price is still below target
-    check_price_alerts(&env, &symbol_short!("XLM"), 400_000);
+// check_portfolio_alerts 
 
-    let active = get_active_alerts(&env, user);
-    assert_eq!(active.len(), 1, "alert should still be active");
+#[test]
+fn test_portfolio_value_change_alert_fires() {
+    let env = Env::default();
+    with_contract(&env, || {
+        env.ledger().with_mut(|li| li.timestamp = 1000);
+        let user = Address::generate(&env);
+
+        create_portfolio_alert(
+            &env,
+            user.clone(),
+            PortfolioTrigger::ValueChangeBps,
+            500, // 5%
+            2000,
+            NotificationMethod::Event,
+        );
+
+        // Portfolio dropped from 10_000 to 9_000 → 10% change > 5% threshold
+        check_portfolio_alerts(&env, &user, 9_000, 10_000);
+
+        let active = get_active_alerts(&env, user);
+        assert_eq!(active.len(), 0);
+    });
 }
 
 #[test]
-fn test_price_alert_below_direction() {
+fn test_portfolio_loss_alert_fires_on_drop_only() {
     let env = Env::default();
-    env.ledger().with_mut(|li| li.timestamp = 1000);
-    let user = Address::generate(&env);
+    with_contract(&env, || {
+        env.ledger().with_mut(|li| li.timestamp = 1000);
+        let user = Address::generate(&env);
+
+        create_portfolio_alert(
+            &env,
+            user.clone(),
+            PortfolioTrigger::LossBps,
+            500, // 5%
+            2000,
+            NotificationMethod::Event,
+        );
+
+        // A 10% gain does not fire a loss alert
+        check_portfolio_alerts(&env, &user, 11_000, 10_000);
+        assert_eq!(get_active_alerts(&env, user.clone()).len(), 1);
+
+        // An equal-magnitude drop does
+        check_portfolio_alerts(&env, &user, 9_000, 10_000);
+        assert_eq!(get_active_alerts(&env, user).len(), 0);
+    });
+}
 
-    create_price_alert(
-        &env,
-        user.clone(),
-        symbol_short!("XLM"),
-        200_000,
-        PriceDirection::Below,
-        2000,
-        NotificationMethod::Event,
-    );
+#[test]
+fn test_portfolio_gain_alert_fires_on_rise_only() {
+    let env = Env::default();
+    with_contract(&env, || {
+        env.ledger().with_mut(|li| li.timestamp = 1000);
+        let user = Address::generate(&env);
+
+        create_portfolio_alert(
+            &env,
+            user.clone(),
+            PortfolioTrigger::GainBps,
+            500, // 5%
+            2000,
+            NotificationMethod::Event,
+        );
+
+        check_portfolio_alerts(&env, &user, 9_000, 10_000);
+        assert_eq!(get_active_alerts(&env, user.clone()).len(), 1);
+
+        // Below the threshold
+        check_portfolio_alerts(&env, &user, 10_400, 10_000);
+        assert_eq!(get_active_alerts(&env, user.clone()).len(), 1);
+
+        check_portfolio_alerts(&env, &user, 10_500, 10_000);
+        assert_eq!(get_active_alerts(&env, user).len(), 0);
+    });
+}
 
-    check_price_alerts(&env, &symbol_short!("XLM"), 100_000); // below target
+#[test]
+fn test_portfolio_liquidation_alert_fires() {
+    let env = Env::default();
+    with_contract(&env, || {
+        env.ledger().with_mut(|li| li.timestamp = 1000);
+        let user = Address::generate(&env);
+
+        create_portfolio_alert(
+            &env,
+            user.clone(),
+            PortfolioTrigger::LiquidationRisk,
+            1500, // threshold: collateral ratio 15%
+            2000,
+            NotificationMethod::Webhook,
+        );
+
+        // collateral ratio 1200 bps < 1500 threshold → fires
+        check_liquidation_alerts(&env, &user, 1200);
+
+        let active = get_active_alerts(&env, user);
+        assert_eq!(active.len(), 0);
+    });
+}
 
-    let active = get_active_alerts(&env, user);
-    assert_eq!(active.len(), 0, "below-direction alert should fire and deactivate");
+// check_market_alerts 
+#[test]
+fn test_market_alert_fires_on_matching_signal() {
+    let env = Env::default();
+    with_contract(&env, || {
+        env.ledger().with_mut(|li| li.timestamp = 1000);
+        let user = Address::generate(&env);
+
+        create_market_alert(
+            &env,
+            user.clone(),
+            symbol_short!("XLMUSDC"),
+            MarketSignal::VolatilitySpike,
+            2000,
+            NotificationMethod::Event,
+        );
+
+        check_market_alerts(&env, &symbol_short!("XLMUSDC"), &MarketSignal::VolatilitySpike);
+
+        let active = get_active_alerts(&env, user);
+        assert_eq!(active.len(), 0);
+    });
 }
 
 #[test]
-fn test_persistent_price_alert_stays_active_after_trigger() {
+fn test_market_alert_does_not_fire_for_different_signal() {
     let env = Env::default();
-    env.ledger().with_mut(|li| li.timestamp = 1000);
-    let user = Address::generate(&env);
+    with_contract(&env, || {
+        env.ledger().with_mut(|li| li.timestamp = 1000);
+        let user = Address::generate(&env);
+
+        create_market_alert(
+            &env,
+            user.clone(),
+            symbol_short!("XLMUSDC"),
+            MarketSignal::TrendReversal,
+            2000,
+            NotificationMethod::Event,
+        );
+
+        check_market_alerts(&env, &symbol_short!("XLMUSDC"), &MarketSignal::VolatilitySpike);
+
+        let active = get_active_alerts(&env, user);
+        assert_eq!(active.len(), 1, "alert for different signal must not fire");
+    });
+}
 
-    // expires_at = 0 → persistent
-    create_price_alert(
-        &env,
-        user.clone(),
-        symbol_short!("XLM"),
-        500_000,
-        PriceDirection::Above,
-        0,
-        NotificationMethod::Event,
-    );
+// cleanup_alerts 
 
-    check_price_alerts(&env, &symbol_short!("XLM"), 600_000);
+#[test]
+fn test_cleanup_removes_expired_alerts() {
+    let env = Env::default();
+    with_contract(&env, || {
+        env.ledger().with_mut(|li| li.timestamp = 5000);
+        let user = Address::generate(&env);
+
+        // One expired, one persistent
+        create_price_alert(
+            &env,
+            user.clone(),
+            symbol_short!("XLM"),
+            1_000_000,
+            PriceDirection::Above,
+            1000, // expired
+            NotificationMethod::Event,
+        );
+        create_price_alert(
+            &env,
+            user.clone(),
+            symbol_short!("XLM"),
+            2_000_000,
+            PriceDirection::Above,
+            0, // persistent
+            NotificationMethod::Event,
+        );
+
+        cleanup_alerts(&env, user.clone());
+
+        // After cleanup we should only see persistent alert via storage directly;
+        // get_active_alerts already filters, but let's confirm cleanup worked.
+        let active = get_active_alerts(&env, user);
+        assert_eq!(active.len(), 1);
+    });
+}
+
+// multi-user isolation
 
-    let active = get_active_alerts(&env, user);
-    assert_eq!(active.len(), 1, "persistent alert must remain active after firing");
+#[test]
+fn test_alerts_are_isolated_per_user() {
+    let env = Env::default();
+    with_contract(&env, || {
+        env.ledger().with_mut(|li| li.timestamp = 1000);
+        let user_a = Address::generate(&env);
+        let user_b = Address::generate(&env);
+
+        create_price_alert(
+            &env,
+            user_a.clone(),
+            symbol_short!("XLM"),
+            500_000,
+            PriceDirection::Above,
+            0,
+            NotificationMethod::Event,
+        );
+
+        // user_b has no alerts
+        let active_b = get_active_alerts(&env, user_b);
+        assert_eq!(active_b.len(), 0);
+
+        let active_a = get_active_alerts(&env, user_a);
+        assert_eq!(active_a.len(), 1);
+    });
 }
 
-// check_portfolio_alerts 
+// alert history
 
 #[test]
-fn test_portfolio_value_change_alert_fires() {
+fn test_alert_history_newest_first_within_cap() {
     let env = Env::default();
-    env.ledger().with_mut(|li| li.timestamp = 1000);
-    let user = Address::generate(&env);
+    with_contract(&env, || {
+        let user = Address::generate(&env);
+
+        // Persistent alert fires on every check, so each tick adds one entry.
+        let id = create_price_alert(
+            &env,
+            user.clone(),
+            symbol_short!("XLM"),
+            500_000,
+            PriceDirection::Above,
+            0,
+            NotificationMethod::Event,
+        );
+
+        let fires = ALERT_HISTORY_CAP as u64 + 5;
+        for t in 1..=fires {
+            env.ledger().with_mut(|li| li.timestamp = t * 100);
+            check_price_alerts(&env, &symbol_short!("XLM"), 600_000);
+        }
 
-    create_portfolio_alert(
-        &env,
+        let history = get_alert_history(&env, user.clone(), 100);
+        assert_eq!(history.len(), ALERT_HISTORY_CAP, "history must be capped");
+        assert_eq!(history.get(0).unwrap().timestamp, fires * 100, "newest first");
+        assert_eq!(
+            history.get(ALERT_HISTORY_CAP - 1).unwrap().timestamp,
+            (fires - ALERT_HISTORY_CAP as u64 + 1) * 100,
+            "oldest entries evicted"
+        );
+        assert_eq!(history.get(0).unwrap().alert_id, id);
+
+        let limited = get_alert_history(&env, user, 3);
+        assert_eq!(limited.len(), 3);
+        assert_eq!(limited.get(2).unwrap().timestamp, (fires - 2) * 100);
+    });
+}
+
+// price move alerts
+
+fn setup_price_move(env: &Env, user: &Address) {
+    env.ledger().with_mut(|li| li.timestamp = 1000);
+    // ±5% band around 1_000_000
+    create_price_move_alert(
+        env,
         user.clone(),
-        PortfolioTrigger::ValueChangeBps,
-        500, // 5%
+        symbol_short!("XLM"),
+        1_000_000,
+        500,
         2000,
         NotificationMethod::Event,
     );
+}
+
+#[test]
+fn test_price_move_alert_fires_on_upward_move() {
+    let (env, user) = setup();
+    with_contract(&env, || {
+        setup_price_move(&env, &user);
 
-    // Portfolio dropped from 10_000 to 9_000 → 10% change > 5% threshold
-    check_portfolio_alerts(&env, &user, 9_000, 10_000);
+        check_price_alerts(&env, &symbol_short!("XLM"), 1_060_000);
 
-    let active = get_active_alerts(&env, user);
-    assert_eq!(active.len(), 0);
+        let active = get_active_alerts(&env, user);
+        assert_eq!(active.len(), 0, "6% rise should fire a 5% move alert");
+    });
 }
 
 #[test]
-fn test_portfolio_liquidation_alert_fires() {
-    let env = Env::default();
-    env.ledger().with_mut(|li| li.timestamp = 1000);
-    let user = Address::generate(&env);
+fn test_price_move_alert_fires_on_downward_move() {
+    let (env, user) = setup();
+    with_contract(&env, || {
+        setup_price_move(&env, &user);
 
-    create_portfolio_alert(
-        &env,
-        user.clone(),
-        PortfolioTrigger::LiquidationRisk,
-        1500, // threshold: collateral ratio 15%
-        2000,
-        NotificationMethod::Webhook,
-    );
+        check_price_alerts(&env, &symbol_short!("XLM"), 940_000);
+
+        let active = get_active_alerts(&env, user);
+        assert_eq!(active.len(), 0, "6% drop should fire a 5% move alert");
+    });
+}
 
-    // current_value = 1200 bps collateral ratio < 1500 threshold → fires
-    check_portfolio_alerts(&env, &user, 1200, 0);
+#[test]
+fn test_price_move_alert_within_band_does_not_fire() {
+    let (env, user) = setup();
+    with_contract(&env, || {
+        setup_price_move(&env, &user);
 
-    let active = get_active_alerts(&env, user);
-    assert_eq!(active.len(), 0);
+        check_price_alerts(&env, &symbol_short!("XLM"), 1_030_000);
+        check_price_alerts(&env, &symbol_short!("XLM"), 970_000);
+
+        let active = get_active_alerts(&env, user);
+        assert_eq!(active.len(), 1, "3% move must stay inside the band");
+    });
 }
 
-// check_market_alerts 
 #[test]
-fn test_market_alert_fires_on_matching_signal() {
-    let env = Env::default();
-    env.ledger().with_mut(|li| li.timestamp = 1000);
-    let user = Address::generate(&env);
+#[should_panic(expected = "non-positive move_bps")]
+fn test_price_move_alert_rejects_zero_band() {
+    let (env, user) = setup();
+    with_contract(&env, || {
+        create_price_move_alert(&env, user, symbol_short!("XLM"), 1_000_000, 0, 2000, NotificationMethod::Event);
+    });
+}
 
-    create_market_alert(
-        &env,
-        user.clone(),
-        symbol_short!("XLMUSDC"),
-        MarketSignal::VolatilitySpike,
-        2000,
-        NotificationMethod::Event,
-    );
+#[test]
+#[should_panic(expected = "non-positive price")]
+fn test_price_move_alert_rejects_non_positive_reference() {
+    let (env, user) = setup();
+    with_contract(&env, || {
+        create_price_move_alert(&env, user, symbol_short!("XLM"), 0, 500, 2000, NotificationMethod::Event);
+    });
+}
 
-    check_market_alerts(&env, &symbol_short!("XLMUSDC"), &MarketSignal::VolatilitySpike);
+// snooze
 
-    let active = get_active_alerts(&env, user);
-    assert_eq!(active.len(), 0);
+#[test]
+fn test_snoozed_alert_fires_after_window() {
+    let (env, user) = setup();
+    with_contract(&env, || {
+        env.ledger().with_mut(|li| li.timestamp = 1000);
+
+        let id = create_price_alert(
+            &env,
+            user.clone(),
+            symbol_short!("XLM"),
+            500_000,
+            PriceDirection::Above,
+            0,
+            NotificationMethod::Event,
+        );
+        assert!(snooze_alert(&env, user.clone(), id, 2000));
+
+        check_price_alerts(&env, &symbol_short!("XLM"), 600_000);
+        let active = get_active_alerts(&env, user.clone());
+        assert_eq!(active.len(), 1, "snoozed alert stays active");
+        assert_eq!(active.get(0).unwrap().last_triggered_at, 0, "must not fire while snoozed");
+
+        env.ledger().with_mut(|li| li.timestamp = 2000);
+        check_price_alerts(&env, &symbol_short!("XLM"), 600_000);
+        let active = get_active_alerts(&env, user);
+        assert_eq!(active.get(0).unwrap().last_triggered_at, 2000, "fires once snooze ends");
+    });
 }
 
 #[test]
-fn test_market_alert_does_not_fire_for_different_signal() {
-    let env = Env::default();
-    env.ledger().with_mut(|li| li.timestamp = 1000);
-    let user = Address::generate(&env);
+fn test_unsnooze_restores_firing() {
+    let (env, user) = setup();
+    with_contract(&env, || {
+        env.ledger().with_mut(|li| li.timestamp = 1000);
+
+        let id = create_price_alert(
+            &env,
+            user.clone(),
+            symbol_short!("XLM"),
+            500_000,
+            PriceDirection::Above,
+            0,
+            NotificationMethod::Event,
+        );
+        snooze_alert(&env, user.clone(), id, 5000);
+        assert!(unsnooze_alert(&env, user.clone(), id));
+        assert!(!unsnooze_alert(&env, user.clone(), id + 1), "unknown id");
+
+        check_price_alerts(&env, &symbol_short!("XLM"), 600_000);
+        let active = get_active_alerts(&env, user);
+        assert_eq!(active.get(0).unwrap().last_triggered_at, 1000);
+    });
+}
 
-    create_market_alert(
-        &env,
-        user.clone(),
-        symbol_short!("XLMUSDC"),
-        MarketSignal::TrendReversal,
-        2000,
-        NotificationMethod::Event,
-    );
+// delivery metadata
 
-    check_market_alerts(&env, &symbol_short!("XLMUSDC"), &MarketSignal::VolatilitySpike);
+#[test]
+fn test_recurring_alert_events_carry_ordered_delivery_ids() {
+    let (env, user) = setup();
+    with_contract(&env, || {
+        let id = create_price_alert(
+            &env,
+            user.clone(),
+            symbol_short!("XLM"),
+            500_000,
+            PriceDirection::Above,
+            0,
+            NotificationMethod::Webhook,
+        );
+
+        env.ledger().with_mut(|li| li.timestamp = 1000);
+        check_price_alerts(&env, &symbol_short!("XLM"), 600_000);
+        env.ledger().with_mut(|li| li.timestamp = 2000);
+        check_price_alerts(&env, &symbol_short!("XLM"), 700_000);
+
+        let mut deliveries: std::vec::Vec<(u32, BytesN<32>)> = std::vec::Vec::new();
+        for (_, topics, data) in env.events().all().iter() {
+            let name: Symbol = topics.get(0).unwrap().try_into_val(&env).unwrap();
+            if name != Symbol::new(&env, "AlertTriggered") {
+                continue;
+            }
+            let payload: (AlertKind, NotificationMethod, u64, u32, BytesN<32>) =
+                data.try_into_val(&env).unwrap();
+            deliveries.push((payload.3, payload.4));
+        }
 
-    let active = get_active_alerts(&env, user);
-    assert_eq!(active.len(), 1, "alert for different signal must not fire");
+        assert_eq!(deliveries.len(), 2);
+        assert_eq!(deliveries[0].0, 1);
+        assert_eq!(deliveries[1].0, 2, "trigger_seq must increase per fire");
+        assert_ne!(deliveries[0].1, deliveries[1].1, "delivery ids must differ");
+        assert_eq!(deliveries[1].1, alert_delivery_id(&env, id, 2));
+    });
 }
 
-// cleanup_alerts 
+// batch creation
+
+fn price_spec(target_price: i128) -> AlertSpec {
+    AlertSpec {
+        kind: AlertKind::Price(PriceAlert {
+            token: symbol_short!("XLM"),
+            target_price,
+            direction: PriceDirection::Above,
+        }),
+        expires_at: 0,
+        notification_method: NotificationMethod::Event,
+    }
+}
 
 #[test]
-fn test_cleanup_removes_expired_alerts() {
-    let env = Env::default();
-    env.ledger().with_mut(|li| li.timestamp = 5000);
-    let user = Address::generate(&env);
+fn test_create_alerts_batch_single_write_unique_ids() {
+    let (env, user) = setup();
+    let contract_id = env.register(CounterContract, ());
+    let mut specs = Vec::new(&env);
+    for i in 0..20 {
+        specs.push_back(price_spec(1_000_000 + i));
+    }
 
-    // One expired, one persistent
-    create_price_alert(
-        &env,
-        user.clone(),
-        symbol_short!("XLM"),
-        1_000_000,
-        PriceDirection::Above,
-        1000, // expired
-        NotificationMethod::Event,
+    let ids = env.as_contract(&contract_id, || create_alerts_batch(&env, user.clone(), specs.clone()));
+    let batch = env.cost_estimate().resources();
+    // One write each for the alert map, the token index and the id counter
+    assert_eq!(batch.write_entries, 3);
+
+    // Single-alert calls each load and save the whole map; the batch pays that once
+    let (single_env, single_user) = setup();
+    let single_id = single_env.register(CounterContract, ());
+    let mut one_by_one = 0;
+    for spec in specs.iter() {
+        single_env.as_contract(&single_id, || {
+            create_alerts_batch(&single_env, single_user.clone(), Vec::from_array(&single_env, [spec]));
+        });
+        one_by_one += single_env.cost_estimate().resources().instructions;
+    }
+    assert!(
+        batch.instructions < one_by_one / 2,
+        "batch of 20 cost {} instructions, one by one {}",
+        batch.instructions,
+        one_by_one
     );
-    create_price_alert(
-        &env,
+
+    env.as_contract(&contract_id, || {
+        assert_eq!(ids.len(), 20);
+        for i in 0..ids.len() {
+            for j in (i + 1)..ids.len() {
+                assert_ne!(ids.get(i).unwrap(), ids.get(j).unwrap());
+            }
+        }
+        assert_eq!(get_active_alerts(&env, user).len(), 20);
+
+        // Ids continue from the shared counter.
+        let other = Address::generate(&env);
+        let next = create_price_alert(
+            &env,
+            other,
+            symbol_short!("XLM"),
+            1,
+            PriceDirection::Above,
+            0,
+            NotificationMethod::Event,
+        );
+        assert_eq!(next, 21);
+    });
+}
+
+#[test]
+#[should_panic(expected = "alert cap reached")]
+fn test_create_alerts_batch_respects_user_cap() {
+    let (env, user) = setup();
+    with_contract(&env, || {
+        let mut specs = Vec::new(&env);
+        for i in 0..(MAX_ALERTS_PER_USER + 1) {
+            specs.push_back(price_spec(i as i128));
+        }
+        create_alerts_batch(&env, user, specs);
+    });
+}
+
+// secondary index
+
+/// Instructions spent checking an XLM price of 600_000 while alice watches XLM
+/// (and fires) and bob holds five non-firing alerts on `bob_token`
+fn xlm_price_check_cost(bob_token: Symbol) -> i64 {
+    let (env, alice) = setup();
+    let contract_id = env.register(CounterContract, ());
+    let bob = Address::generate(&env);
+    env.ledger().with_mut(|li| li.timestamp = 1000);
+    env.as_contract(&contract_id, || {
+        for i in 0..5 {
+            create_price_alert(
+                &env,
+                bob.clone(),
+                bob_token.clone(),
+                1_000_000 + i,
+                PriceDirection::Above,
+                0,
+                NotificationMethod::Event,
+            );
+        }
+        create_price_alert(
+            &env,
+            alice.clone(),
+            symbol_short!("XLM"),
+            500_000,
+            PriceDirection::Above,
+            0,
+            NotificationMethod::Event,
+        );
+        create_market_alert(
+            &env,
+            alice.clone(),
+            symbol_short!("XLMUSDC"),
+            MarketSignal::TrendReversal,
+            0,
+            NotificationMethod::Event,
+        );
+    });
+
+    env.ledger().with_mut(|li| li.timestamp = 2000);
+    env.as_contract(&contract_id, || check_price_alerts(&env, &symbol_short!("XLM"), 600_000));
+    let cost = env.cost_estimate().resources().instructions;
+
+    env.as_contract(&contract_id, || {
+        assert_eq!(get_active_alerts(&env, alice).get(0).unwrap().last_triggered_at, 2000);
+        assert_eq!(get_active_alerts(&env, bob).len(), 5);
+    });
+    cost
+}
+
+#[test]
+fn test_price_check_only_visits_alerts_watching_token() {
+    // Same map size either way; only alerts on the checked token are evaluated
+    let unrelated = xlm_price_check_cost(symbol_short!("BTC"));
+    let watching = xlm_price_check_cost(symbol_short!("XLM"));
+    assert!(
+        unrelated < watching,
+        "BTC alerts cost {} instructions, XLM alerts {}",
+        unrelated,
+        watching
+    );
+
+    // A token nobody watches returns without writing anything back
+    let (env, user) = setup();
+    let contract_id = env.register(CounterContract, ());
+    env.as_contract(&contract_id, || {
+        create_price_alert(&env, user.clone(), symbol_short!("XLM"), 1, PriceDirection::Above, 0, NotificationMethod::Event);
+    });
+    let written = entries_written(&env, &contract_id, || check_price_alerts(&env, &symbol_short!("ETH"), 1));
+    assert_eq!(written, 0);
+}
+
+// liquidation alerts
+
+fn setup_liquidation_alert(env: &Env, user: &Address) {
+    env.ledger().with_mut(|li| li.timestamp = 1000);
+    create_portfolio_alert(
+        env,
         user.clone(),
-        symbol_short!("XLM"),
-        2_000_000,
-        PriceDirection::Above,
-        0, // persistent
+        PortfolioTrigger::LiquidationRisk,
+        1500,
+        2000,
         NotificationMethod::Event,
     );
+}
+
+#[test]
+fn test_liquidation_alert_above_threshold_does_not_fire() {
+    let (env, user) = setup();
+    with_contract(&env, || {
+        setup_liquidation_alert(&env, &user);
 
-    cleanup_alerts(&env, user.clone());
+        check_liquidation_alerts(&env, &user, 1800);
 
-    // After cleanup we should only see persistent alert via storage directly;
-    // get_active_alerts already filters, but let's confirm cleanup worked.
-    let active = get_active_alerts(&env, user);
-    assert_eq!(active.len(), 1);
+        assert_eq!(get_active_alerts(&env, user).len(), 1);
+    });
 }
 
-// multi-user isolation
+#[test]
+fn test_liquidation_alert_at_threshold_fires() {
+    let (env, user) = setup();
+    with_contract(&env, || {
+        setup_liquidation_alert(&env, &user);
+
+        check_liquidation_alerts(&env, &user, 1500);
+
+        assert_eq!(get_active_alerts(&env, user).len(), 0);
+    });
+}
 
 #[test]
-fn test_alerts_are_isolated_per_user() {
-    let env = Env::default();
-    env.ledger().with_mut(|li| li.timestamp = 1000);
-    let user_a = Address::generate(&env);
-    let user_b = Address::generate(&env);
+fn test_portfolio_check_ignores_liquidation_alerts() {
+    let (env, user) = setup();
+    with_contract(&env, || {
+        setup_liquidation_alert(&env, &user);
+
+        // The old overload would have read 1200 as a collateral ratio and fired.
+        check_portfolio_alerts(&env, &user, 1200, 0);
+        check_portfolio_alerts(&env, &user, 1200, 10_000);
+
+        assert_eq!(get_active_alerts(&env, user).len(), 1);
+    });
+}
 
+// duplicate / conflict checks
+
+fn xlm_price_alert(env: &Env, user: &Address, direction: PriceDirection) -> u64 {
     create_price_alert(
-        &env,
-        user_a.clone(),
+        env,
+        user.clone(),
         symbol_short!("XLM"),
-        500_000,
-        PriceDirection::Above,
+        1_000_000,
+        direction,
         0,
         NotificationMethod::Event,
-    );
+    )
+}
+
+#[test]
+#[should_panic(expected = "duplicate alert")]
+fn test_duplicate_price_alert_rejected() {
+    let (env, user) = setup();
+    with_contract(&env, || {
+        xlm_price_alert(&env, &user, PriceDirection::Above);
+        xlm_price_alert(&env, &user, PriceDirection::Above);
+    });
+}
+
+#[test]
+fn test_duplicate_allowed_once_original_inactive_or_dedup_disabled() {
+    let (env, user) = setup();
+    with_contract(&env, || {
+        let other = Address::generate(&env);
+
+        // Same alert for a different user is not a duplicate
+        xlm_price_alert(&env, &user, PriceDirection::Above);
+        xlm_price_alert(&env, &other, PriceDirection::Above);
 
-    // user_b has no alerts
-    let active_b = get_active_alerts(&env, user_b);
-    assert_eq!(active_b.len(), 0);
+        set_alert_dedup(&env, false);
+        xlm_price_alert(&env, &user, PriceDirection::Above);
+        assert_eq!(get_active_alerts(&env, user).len(), 2);
+    });
+}
 
-    let active_a = get_active_alerts(&env, user_a);
-    assert_eq!(active_a.len(), 1);
-}
\ No newline at end of file
+#[test]
+fn test_contradictory_price_alert_warns_but_is_created() {
+    let (env, user) = setup();
+    with_contract(&env, || {
+        let above = xlm_price_alert(&env, &user, PriceDirection::Above);
+        let below = xlm_price_alert(&env, &user, PriceDirection::Below);
+
+        let mut conflicts = 0;
+        for (_, topics, data) in env.events().all().iter() {
+            let name: Symbol = topics.get(0).unwrap().try_into_val(&env).unwrap();
+            if name != Symbol::new(&env, "AlertConflict") {
+                continue;
+            }
+            let alert_id: u64 = topics.get(2).unwrap().try_into_val(&env).unwrap();
+            let (conflicting_id, _kind): (u64, AlertKind) = data.try_into_val(&env).unwrap();
+            assert_eq!((alert_id, conflicting_id), (below, above));
+            conflicts += 1;
+        }
+        assert_eq!(conflicts, 1);
+        assert_eq!(get_active_alerts(&env, user).len(), 2);
+    });
+}
+
+#[test]
+#[should_panic(expected = "duplicate alert")]
+fn test_create_alerts_batch_rejects_duplicates_within_batch() {
+    let (env, user) = setup();
+    with_contract(&env, || {
+        let mut specs = Vec::new(&env);
+        specs.push_back(price_spec(1_000_000));
+        specs.push_back(price_spec(1_000_000));
+        create_alerts_batch(&env, user, specs);
+    });
+}
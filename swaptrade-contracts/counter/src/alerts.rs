@@ -1,5 +1,5 @@
 use soroban_sdk::{
-    contracttype, symbol_short, Address, Env, Map, Symbol, Vec,
+    contracttype, symbol_short, Address, Bytes, BytesN, Env, Map, Symbol, Vec,
 };
 
 // Data Types
@@ -18,6 +18,10 @@ pub enum PriceDirection {
 pub enum PortfolioTrigger {
     /// Portfolio value has changed by `threshold` percent (basis points, e.g. 500 = 5%).
     ValueChangeBps,
+    /// Portfolio value has risen by at least `threshold` basis points.
+    GainBps,
+    /// Portfolio value has fallen by at least `threshold` basis points.
+    LossBps,
     /// User's collateral ratio drops below `threshold` (in basis points).
     LiquidationRisk,
 }
@@ -38,22 +42,48 @@ pub enum NotificationMethod {
     Webhook,
 }
 
+/// Fires when the price crosses `target_price` in `direction`.
 #[contracttype]
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq)]
+pub struct PriceAlert {
+    pub token: Symbol,
+    pub target_price: i128,
+    pub direction: PriceDirection,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub struct PortfolioAlert {
+    pub trigger_type: PortfolioTrigger,
+    pub threshold_bps: i128,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub struct MarketAlert {
+    pub market_id: Symbol,
+    pub signal_type: MarketSignal,
+}
+
+/// Fires when the price moves at least `move_bps` away from
+/// `reference_price` (captured at creation) in either direction.
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub struct PriceMoveAlert {
+    pub token: Symbol,
+    pub reference_price: i128,
+    pub move_bps: i128,
+}
+
+/// Contract enums only carry tuple variants, so each kind's fields live in
+/// their own struct.
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
 pub enum AlertKind {
-    Price {
-        token: Symbol,
-        target_price: i128,
-        direction: PriceDirection,
-    },
-    Portfolio {
-        trigger_type: PortfolioTrigger,
-        threshold_bps: i128,
-    },
-    Market {
-        market_id: Symbol,
-        signal_type: MarketSignal,
-    },
+    Price(PriceAlert),
+    Portfolio(PortfolioAlert),
+    Market(MarketAlert),
+    PriceMove(PriceMoveAlert),
 }
 
 /// A single alert record.
@@ -67,14 +97,59 @@ pub struct Alert {
     pub expires_at: u64,
     pub active: bool,
     pub last_triggered_at: u64,
+    /// Checks skip firing while `now < snoozed_until`; 0 means not snoozed.
+    pub snoozed_until: u64,
+    /// Number of times this alert has fired; carried in the event so relays
+    /// can order and dedupe deliveries.
+    pub trigger_seq: u32,
+}
+
+/// Input for `create_alerts_batch`; mirrors the arguments of the single
+/// `create_*_alert` helpers.
+#[contracttype]
+#[derive(Clone, Debug)]
+pub struct AlertSpec {
+    pub kind: AlertKind,
+    pub expires_at: u64,
+    pub notification_method: NotificationMethod,
 }
 
+/// Maximum number of alerts a single user may hold.
+pub const MAX_ALERTS_PER_USER: u32 = 50;
+
+/// One entry in a user's trigger history ring.
+#[contracttype]
+#[derive(Clone, Debug)]
+pub struct AlertHistoryEntry {
+    pub alert_id: u64,
+    pub kind: AlertKind,
+    pub timestamp: u64,
+}
+
+/// Maximum number of triggers retained per user; the oldest entry is dropped
+/// once the ring is full.
+pub const ALERT_HISTORY_CAP: u32 = 20;
+
+/// Prices older than this (relative to the ledger) never fire alerts,
+/// unless overridden with `set_max_price_age_secs`.
+pub const DEFAULT_MAX_PRICE_AGE_SECS: u64 = 300;
+
 // Storage Keys
 
 const ALERT_COUNTER_KEY: Symbol = symbol_short!("alrt_cnt");
 
 const ALERT_MAP_KEY: Symbol = symbol_short!("alrt_map");
 
+const ALERT_HISTORY_KEY: Symbol = symbol_short!("alrt_hist");
+
+/// Secondary index: token / market id -> `(owner, alert_id)` pairs watching it.
+/// The per-user vectors in `ALERT_MAP_KEY` remain the source of truth.
+const ALERT_INDEX_KEY: Symbol = symbol_short!("alrt_idx");
+
+const MAX_PRICE_AGE_KEY: Symbol = symbol_short!("alrt_age");
+
+const ALERT_DEDUP_KEY: Symbol = symbol_short!("alrt_ddp");
+
 // Registry helpers
 
 fn load_map(env: &Env) -> Map<Address, Vec<Alert>> {
@@ -116,15 +191,58 @@ pub fn create_price_alert(
     let alert = Alert {
         id,
         owner: owner.clone(),
-        kind: AlertKind::Price {
+        kind: AlertKind::Price(PriceAlert {
             token,
             target_price,
             direction,
-        },
+        }),
         notification_method,
         expires_at,
         active: true,
         last_triggered_at: 0,
+        snoozed_until: 0,
+        trigger_seq: 0,
+    };
+    push_alert(env, owner, alert);
+    id
+}
+
+fn validate_price_move(reference_price: i128, move_bps: i128) {
+    if reference_price <= 0 {
+        panic!("non-positive price");
+    }
+    if move_bps <= 0 {
+        panic!("non-positive move_bps");
+    }
+}
+
+/// Create a percentage-move alert anchored at `reference_price`.
+/// Returns the new `alert_id`.
+pub fn create_price_move_alert(
+    env: &Env,
+    owner: Address,
+    token: Symbol,
+    reference_price: i128,
+    move_bps: i128,
+    expires_at: u64,
+    notification_method: NotificationMethod,
+) -> u64 {
+    validate_price_move(reference_price, move_bps);
+    let id = next_id(env);
+    let alert = Alert {
+        id,
+        owner: owner.clone(),
+        kind: AlertKind::PriceMove(PriceMoveAlert {
+            token,
+            reference_price,
+            move_bps,
+        }),
+        notification_method,
+        expires_at,
+        active: true,
+        last_triggered_at: 0,
+        snoozed_until: 0,
+        trigger_seq: 0,
     };
     push_alert(env, owner, alert);
     id
@@ -142,14 +260,16 @@ pub fn create_portfolio_alert(
     let alert = Alert {
         id,
         owner: owner.clone(),
-        kind: AlertKind::Portfolio {
+        kind: AlertKind::Portfolio(PortfolioAlert {
             trigger_type,
             threshold_bps,
-        },
+        }),
         notification_method,
         expires_at,
         active: true,
         last_triggered_at: 0,
+        snoozed_until: 0,
+        trigger_seq: 0,
     };
     push_alert(env, owner, alert);
     id
@@ -169,21 +289,76 @@ pub fn create_market_alert(
     let alert = Alert {
         id,
         owner: owner.clone(),
-        kind: AlertKind::Market {
+        kind: AlertKind::Market(MarketAlert {
             market_id,
             signal_type,
-        },
+        }),
         notification_method,
         expires_at,
         active: true,
         last_triggered_at: 0,
+        snoozed_until: 0,
+        trigger_seq: 0,
     };
     push_alert(env, owner, alert);
     id
 }
 
+/// Create several alerts for `owner` in one pass: the alert map and id
+/// counter are each loaded and saved once. Returns the assigned ids in the
+/// order of `specs`. Panics if the batch would exceed `MAX_ALERTS_PER_USER`.
+pub fn create_alerts_batch(env: &Env, owner: Address, specs: Vec<AlertSpec>) -> Vec<u64> {
+    let mut map = load_map(env);
+    let mut user_alerts: Vec<Alert> = map.get(owner.clone()).unwrap_or_else(|| Vec::new(env));
+    if user_alerts.len().saturating_add(specs.len()) > MAX_ALERTS_PER_USER {
+        panic!("alert cap reached");
+    }
+
+    let mut counter: u64 = env
+        .storage()
+        .persistent()
+        .get(&ALERT_COUNTER_KEY)
+        .unwrap_or(0u64);
+    let mut index = load_index(env);
+    let mut ids = Vec::new(env);
+    for spec in specs.iter() {
+        if let AlertKind::PriceMove(ref m) = spec.kind {
+            validate_price_move(m.reference_price, m.move_bps);
+        }
+        counter = counter.saturating_add(1);
+        let alert = Alert {
+            id: counter,
+            owner: owner.clone(),
+            kind: spec.kind,
+            notification_method: spec.notification_method,
+            expires_at: spec.expires_at,
+            active: true,
+            last_triggered_at: 0,
+            snoozed_until: 0,
+            trigger_seq: 0,
+        };
+        // Earlier specs in the same batch count as existing alerts
+        check_conflicts(env, &user_alerts, &alert);
+        if let Some(key) = index_key(&alert.kind) {
+            index_insert(env, &mut index, key, owner.clone(), counter);
+        }
+        user_alerts.push_back(alert);
+        ids.push_back(counter);
+    }
+    env.storage()
+        .persistent()
+        .set(&ALERT_COUNTER_KEY, &counter);
+
+    map.set(owner, user_alerts);
+    save_map(env, &map);
+    save_index(env, &index);
+    ids
+}
+
 /// Subscribe (activate) a set of existing alert IDs for a user.
-/// Also updates the notification method on those alerts.
+/// Also updates the notification method on those alerts; alerts not listed
+/// in `alert_ids` are left untouched. Re-subscribing an alert that is already
+/// active with the same method is a no-op.
 pub fn subscribe_alerts(
     env: &Env,
     user: Address,
@@ -192,24 +367,64 @@ pub fn subscribe_alerts(
 ) {
     let mut map = load_map(env);
     let mut user_alerts: Vec<Alert> = map.get(user.clone()).unwrap_or_else(|| Vec::new(env));
+    let mut index = load_index(env);
+    let mut changed = false;
 
     let len = user_alerts.len();
     for i in 0..len {
         let mut alert = user_alerts.get(i).unwrap();
-        let alert_id = alert.id;
-        let ids_len = alert_ids.len();
-        for j in 0..ids_len {
-            if alert_ids.get(j).unwrap() == alert_id {
-                alert.active = true;
-                alert.notification_method = notification_method.clone();
-                break;
+        if !alert_ids.contains(alert.id) {
+            continue;
+        }
+        if alert.active && alert.notification_method == notification_method {
+            continue;
+        }
+        if !alert.active {
+            if let Some(key) = index_key(&alert.kind) {
+                index_insert(env, &mut index, key, user.clone(), alert.id);
             }
         }
+        alert.active = true;
+        alert.notification_method = notification_method.clone();
         user_alerts.set(i, alert);
+        changed = true;
     }
 
-    map.set(user, user_alerts);
-    save_map(env, &map);
+    if changed {
+        map.set(user, user_alerts);
+        save_map(env, &map);
+        save_index(env, &index);
+    }
+}
+
+/// Change how `alert_id` notifies its owner without touching its active state.
+/// Returns `false` if the owner has no such alert.
+pub fn set_notification_method(env: &Env, user: Address, alert_id: u64, method: NotificationMethod) -> bool {
+    let mut map = load_map(env);
+    let mut user_alerts: Vec<Alert> = map.get(user.clone()).unwrap_or_else(|| Vec::new(env));
+    let len = user_alerts.len();
+    for i in 0..len {
+        let mut alert = user_alerts.get(i).unwrap();
+        if alert.id == alert_id {
+            alert.notification_method = method;
+            user_alerts.set(i, alert);
+            map.set(user, user_alerts);
+            save_map(env, &map);
+            return true;
+        }
+    }
+    false
+}
+
+/// Silence `alert_id` until `until_ts` without deactivating it.
+/// Returns `false` if the owner has no such alert.
+pub fn snooze_alert(env: &Env, owner: Address, alert_id: u64, until_ts: u64) -> bool {
+    set_snoozed_until(env, owner, alert_id, until_ts)
+}
+
+/// Clear any snooze on `alert_id`. Returns `false` if the owner has no such alert.
+pub fn unsnooze_alert(env: &Env, owner: Address, alert_id: u64) -> bool {
+    set_snoozed_until(env, owner, alert_id, 0)
 }
 
 /// Returns all active (non-expired) alerts for a user.
@@ -233,73 +448,240 @@ pub fn get_active_alerts(env: &Env, user: Address) -> Vec<Alert> {
 // Trigger Checks (called from trading / LP operations)
 
 /// Check all price alerts for `token` against `current_price`.
-/// Fires any that match and emits the appropriate event.
+/// Fires any that match and emits the appropriate event. Only alerts listed
+/// under `token` in the secondary index are visited.
 pub fn check_price_alerts(env: &Env, token: &Symbol, current_price: i128) {
-    let now = env.ledger().timestamp();
-    let mut map = load_map(env);
-    let keys = map.keys();
-    let keys_len = keys.len();
-
-    for k in 0..keys_len {
-        let user = keys.get(k).unwrap();
-        let mut user_alerts: Vec<Alert> = map.get(user.clone()).unwrap_or_else(|| Vec::new(env));
-        let mut changed = false;
-
-        let len = user_alerts.len();
-        for i in 0..len {
-            let mut alert = user_alerts.get(i).unwrap();
-            if !alert.active {
-                continue;
-            }
-            // Expire stale alerts
-            if alert.expires_at != 0 && alert.expires_at <= now {
-                alert.active = false;
-                user_alerts.set(i, alert);
-                changed = true;
-                continue;
-            }
-
-            if let AlertKind::Price {
-                token: ref alert_token,
-                target_price,
-                ref direction,
-            } = alert.kind.clone()
-            {
-                if alert_token == token {
-                    let fired = match direction {
-                        PriceDirection::Above => current_price >= target_price,
-                        PriceDirection::Below => current_price <= target_price,
-                    };
-                    if fired {
-                        alert.last_triggered_at = now;
-                        // Deactivate one-shot style – keep persistent alerts active
-                        if alert.expires_at != 0 {
-                            alert.active = false;
-                        }
-                        user_alerts.set(i, alert.clone());
-                        changed = true;
-                        emit_alert_triggered(env, &alert, now);
-                    }
-                }
-            }
-        }
+    check_price_alerts_at(env, token, current_price, None);
+}
 
-        if changed {
-            map.set(user, user_alerts);
+/// Like `check_price_alerts`, but skips firing when `price_timestamp` is more
+/// than `max_price_age_secs` behind the ledger. Non-positive prices can only
+/// come from a broken feed and are rejected outright.
+pub fn check_price_alerts_at(
+    env: &Env,
+    token: &Symbol,
+    current_price: i128,
+    price_timestamp: Option<u64>,
+) {
+    if current_price <= 0 {
+        panic!("non-positive price");
+    }
+    if let Some(ts) = price_timestamp {
+        if env.ledger().timestamp().saturating_sub(ts) > max_price_age_secs(env) {
+            return;
         }
     }
+    check_indexed_alerts(env, token, |kind| match kind {
+        AlertKind::Price(PriceAlert {
+            target_price,
+            direction,
+            ..
+        }) => match direction {
+            PriceDirection::Above => current_price >= *target_price,
+            PriceDirection::Below => current_price <= *target_price,
+        },
+        AlertKind::PriceMove(PriceMoveAlert {
+            reference_price,
+            move_bps,
+            ..
+        }) => price_move_bps(*reference_price, current_price) >= *move_bps,
+        _ => false,
+    });
+}
 
-    save_map(env, &map);
+/// Set the oldest price (in seconds) `check_price_alerts_at` will act on.
+pub fn set_max_price_age_secs(env: &Env, secs: u64) {
+    env.storage().persistent().set(&MAX_PRICE_AGE_KEY, &secs);
+}
+
+pub fn max_price_age_secs(env: &Env) -> u64 {
+    env.storage()
+        .persistent()
+        .get(&MAX_PRICE_AGE_KEY)
+        .unwrap_or(DEFAULT_MAX_PRICE_AGE_SECS)
+}
+
+/// Turn the duplicate / conflict check on alert creation on or off (on by default).
+pub fn set_alert_dedup(env: &Env, enabled: bool) {
+    env.storage().persistent().set(&ALERT_DEDUP_KEY, &enabled);
 }
 
-/// Check all portfolio alerts for `user` against a current portfolio value and
-/// the value recorded at alert creation time (passed in as `reference_value`).
+pub fn alert_dedup_enabled(env: &Env) -> bool {
+    env.storage()
+        .persistent()
+        .get(&ALERT_DEDUP_KEY)
+        .unwrap_or(true)
+}
+
+/// Check all portfolio value-change alerts for `user` against a current
+/// portfolio value and the value recorded at alert creation time (passed in
+/// as `reference_value`). `ValueChangeBps` fires on a move either way;
+/// `GainBps` and `LossBps` only on a move in their direction.
+///
+/// `LiquidationRisk` alerts are no longer evaluated here — overloading
+/// `current_value` as a collateral ratio was deprecated in favour of
+/// `check_liquidation_alerts`.
 pub fn check_portfolio_alerts(
     env: &Env,
     user: &Address,
     current_value: i128,
     reference_value: i128,
 ) {
+    check_user_alerts(env, user, |kind| match kind {
+        AlertKind::Portfolio(PortfolioAlert {
+            trigger_type,
+            threshold_bps,
+        }) => {
+            if reference_value == 0 {
+                false
+            } else {
+                let change_bps = ((current_value - reference_value) * 10_000) / reference_value;
+                match trigger_type {
+                    PortfolioTrigger::ValueChangeBps => change_bps.abs() >= *threshold_bps,
+                    PortfolioTrigger::GainBps => change_bps >= *threshold_bps,
+                    PortfolioTrigger::LossBps => -change_bps >= *threshold_bps,
+                    PortfolioTrigger::LiquidationRisk => false,
+                }
+            }
+        }
+        _ => false,
+    });
+}
+
+/// Check `LiquidationRisk` alerts for `user`, firing when the collateral
+/// ratio drops to or below the alert's `threshold_bps`.
+pub fn check_liquidation_alerts(env: &Env, user: &Address, collateral_ratio_bps: i128) {
+    check_user_alerts(env, user, |kind| match kind {
+        AlertKind::Portfolio(PortfolioAlert {
+            trigger_type: PortfolioTrigger::LiquidationRisk,
+            threshold_bps,
+        }) => collateral_ratio_bps <= *threshold_bps,
+        _ => false,
+    });
+}
+
+/// Check market alerts for a given `market_id` and `signal_type`.
+pub fn check_market_alerts(env: &Env, market_id: &Symbol, signal_type: &MarketSignal) {
+    check_indexed_alerts(env, market_id, |kind| match kind {
+        AlertKind::Market(MarketAlert {
+            signal_type: alert_signal,
+            ..
+        }) => alert_signal == signal_type,
+        _ => false,
+    });
+}
+
+// ─── History ─────────────────────────────────────────────────────────────────
+
+/// Return up to `limit` of the user's most recent alert triggers, newest first.
+pub fn get_alert_history(env: &Env, user: Address, limit: u32) -> Vec<AlertHistoryEntry> {
+    let history = load_history(env, &user);
+    let mut out = Vec::new(env);
+    let len = history.len();
+    let take = if limit < len { limit } else { len };
+    for i in 0..take {
+        out.push_back(history.get(len - 1 - i).unwrap());
+    }
+    out
+}
+
+// ─── Cleanup ─────────────────────────────────────────────────────────────────
+
+/// Remove all expired / inactive alerts for a user to prevent accumulation.
+pub fn cleanup_alerts(env: &Env, user: Address) {
+    let now = env.ledger().timestamp();
+    let mut map = load_map(env);
+    let user_alerts: Vec<Alert> = map.get(user.clone()).unwrap_or_else(|| Vec::new(env));
+    let mut index = load_index(env);
+
+    let mut retained = Vec::new(env);
+    let len = user_alerts.len();
+    for i in 0..len {
+        let alert = user_alerts.get(i).unwrap();
+        let not_expired = alert.expires_at == 0 || alert.expires_at > now;
+        if alert.active && not_expired {
+            retained.push_back(alert);
+        } else if let Some(key) = index_key(&alert.kind) {
+            index_remove(env, &mut index, key, &user, alert.id);
+        }
+    }
+
+    map.set(user, retained);
+    save_map(env, &map);
+    save_index(env, &index);
+}
+
+// Internal helpers
+
+fn set_snoozed_until(env: &Env, owner: Address, alert_id: u64, until_ts: u64) -> bool {
+    let mut map = load_map(env);
+    let mut user_alerts: Vec<Alert> = map.get(owner.clone()).unwrap_or_else(|| Vec::new(env));
+    let len = user_alerts.len();
+    for i in 0..len {
+        let mut alert = user_alerts.get(i).unwrap();
+        if alert.id == alert_id {
+            alert.snoozed_until = until_ts;
+            user_alerts.set(i, alert);
+            map.set(owner, user_alerts);
+            save_map(env, &map);
+            return true;
+        }
+    }
+    false
+}
+
+fn push_alert(env: &Env, owner: Address, alert: Alert) {
+    let mut map = load_map(env);
+    let mut user_alerts: Vec<Alert> = map.get(owner.clone()).unwrap_or_else(|| Vec::new(env));
+    if user_alerts.len() >= MAX_ALERTS_PER_USER {
+        panic!("alert cap reached");
+    }
+    check_conflicts(env, &user_alerts, &alert);
+    if let Some(key) = index_key(&alert.kind) {
+        let mut index = load_index(env);
+        index_insert(env, &mut index, key, owner.clone(), alert.id);
+        save_index(env, &index);
+    }
+    user_alerts.push_back(alert);
+    map.set(owner, user_alerts);
+    save_map(env, &map);
+}
+
+/// Panic if `alert` exactly duplicates one of `existing`'s live alerts, and
+/// emit an `AlertConflict` warning for each live alert it contradicts (a price
+/// alert on the same token and target but the opposite direction). Skipped
+/// when dedup is disabled.
+fn check_conflicts(env: &Env, existing: &Vec<Alert>, alert: &Alert) {
+    if !alert_dedup_enabled(env) {
+        return;
+    }
+    let now = env.ledger().timestamp();
+    for other in existing.iter() {
+        let live = other.active && (other.expires_at == 0 || other.expires_at > now);
+        if !live {
+            continue;
+        }
+        if other.kind == alert.kind {
+            panic!("duplicate alert");
+        }
+        if let (
+            AlertKind::Price(PriceAlert { token, target_price, direction }),
+            AlertKind::Price(PriceAlert { token: other_token, target_price: other_target, direction: other_direction }),
+        ) = (&alert.kind, &other.kind)
+        {
+            if token == other_token && target_price == other_target && direction != other_direction {
+                emit_alert_conflict(env, alert, other.id);
+            }
+        }
+    }
+}
+
+/// Evaluate every alert owned by `user`, firing those for which `fires`
+/// returns true. Used for per-user triggers that are not indexed by symbol.
+fn check_user_alerts<F>(env: &Env, user: &Address, fires: F)
+where
+    F: Fn(&AlertKind) -> bool,
+{
     let now = env.ledger().timestamp();
     let mut map = load_map(env);
     let mut user_alerts: Vec<Alert> = map
@@ -319,38 +701,18 @@ pub fn check_portfolio_alerts(
             changed = true;
             continue;
         }
+        if now < alert.snoozed_until || !fires(&alert.kind) {
+            continue;
+        }
 
-        if let AlertKind::Portfolio {
-            ref trigger_type,
-            threshold_bps,
-        } = alert.kind.clone()
-        {
-            let fired = match trigger_type {
-                PortfolioTrigger::ValueChangeBps => {
-                    if reference_value == 0 {
-                        false
-                    } else {
-                        let change_bps = ((current_value - reference_value).abs() * 10_000)
-                            / reference_value;
-                        change_bps >= threshold_bps
-                    }
-                }
-                PortfolioTrigger::LiquidationRisk => {
-                    // current_value here is treated as collateral ratio in bps
-                    current_value <= threshold_bps
-                }
-            };
-
-            if fired {
-                alert.last_triggered_at = now;
-                if alert.expires_at != 0 {
-                    alert.active = false;
-                }
-                user_alerts.set(i, alert.clone());
-                changed = true;
-                emit_alert_triggered(env, &alert, now);
-            }
+        alert.last_triggered_at = now;
+        alert.trigger_seq = alert.trigger_seq.saturating_add(1);
+        if alert.expires_at != 0 {
+            alert.active = false;
         }
+        user_alerts.set(i, alert.clone());
+        changed = true;
+        emit_alert_triggered(env, &alert, now);
     }
 
     if changed {
@@ -359,100 +721,197 @@ pub fn check_portfolio_alerts(
     }
 }
 
-/// Check market alerts for a given `market_id` and `signal_type`.
-pub fn check_market_alerts(env: &Env, market_id: &Symbol, signal_type: &MarketSignal) {
+/// Evaluate every alert indexed under `key`, firing those for which `fires`
+/// returns true. Expired, one-shot-fired and dangling entries are pruned from
+/// the index as they are encountered.
+fn check_indexed_alerts<F>(env: &Env, key: &Symbol, fires: F)
+where
+    F: Fn(&AlertKind) -> bool,
+{
     let now = env.ledger().timestamp();
+    let mut index = load_index(env);
+    let watchers = match index.get(key.clone()) {
+        Some(w) => w,
+        None => return,
+    };
     let mut map = load_map(env);
+    let mut retained: Vec<(Address, u64)> = Vec::new(env);
 
-    let keys = map.keys();
-    let keys_len = keys.len();
-
-    for k in 0..keys_len {
-        let user = keys.get(k).unwrap();
-        let mut user_alerts: Vec<Alert> = map.get(user.clone()).unwrap_or_else(|| Vec::new(env));
-        let mut changed = false;
+    for (owner, alert_id) in watchers.iter() {
+        let mut user_alerts: Vec<Alert> = map.get(owner.clone()).unwrap_or_else(|| Vec::new(env));
+        let pos = match (0..user_alerts.len()).find(|&i| user_alerts.get(i).unwrap().id == alert_id) {
+            Some(pos) => pos,
+            None => continue,
+        };
+        let mut alert = user_alerts.get(pos).unwrap();
 
-        let len = user_alerts.len();
-        for i in 0..len {
-            let mut alert = user_alerts.get(i).unwrap();
-            if !alert.active {
-                continue;
-            }
-            if alert.expires_at != 0 && alert.expires_at <= now {
-                alert.active = false;
-                user_alerts.set(i, alert);
-                changed = true;
-                continue;
-            }
-
-            if let AlertKind::Market {
-                market_id: ref alert_market,
-                signal_type: ref alert_signal,
-            } = alert.kind.clone()
-            {
-                if alert_market == market_id && alert_signal == signal_type {
-                    alert.last_triggered_at = now;
-                    if alert.expires_at != 0 {
-                        alert.active = false;
-                    }
-                    user_alerts.set(i, alert.clone());
-                    changed = true;
-                    emit_alert_triggered(env, &alert, now);
-                }
-            }
+        if !alert.active {
+            continue;
+        }
+        // Expire stale alerts
+        if alert.expires_at != 0 && alert.expires_at <= now {
+            alert.active = false;
+            user_alerts.set(pos, alert);
+            map.set(owner, user_alerts);
+            continue;
+        }
+        if now < alert.snoozed_until || !fires(&alert.kind) {
+            retained.push_back((owner, alert_id));
+            continue;
         }
 
-        if changed {
-            map.set(user, user_alerts);
+        alert.last_triggered_at = now;
+        alert.trigger_seq = alert.trigger_seq.saturating_add(1);
+        // Deactivate one-shot style – keep persistent alerts active
+        if alert.expires_at != 0 {
+            alert.active = false;
+        } else {
+            retained.push_back((owner.clone(), alert_id));
         }
+        user_alerts.set(pos, alert.clone());
+        map.set(owner, user_alerts);
+        emit_alert_triggered(env, &alert, now);
     }
 
+    if retained.is_empty() {
+        index.remove(key.clone());
+    } else {
+        index.set(key.clone(), retained);
+    }
+    save_index(env, &index);
     save_map(env, &map);
 }
 
-// ─── Cleanup ─────────────────────────────────────────────────────────────────
+/// The token or market an alert watches, if it participates in the index.
+fn index_key(kind: &AlertKind) -> Option<Symbol> {
+    match kind {
+        AlertKind::Price(PriceAlert { token, .. }) | AlertKind::PriceMove(PriceMoveAlert { token, .. }) => Some(token.clone()),
+        AlertKind::Market(MarketAlert { market_id, .. }) => Some(market_id.clone()),
+        AlertKind::Portfolio(_) => None,
+    }
+}
 
-/// Remove all expired / inactive alerts for a user to prevent accumulation.
-pub fn cleanup_alerts(env: &Env, user: Address) {
-    let now = env.ledger().timestamp();
-    let mut map = load_map(env);
-    let user_alerts: Vec<Alert> = map.get(user.clone()).unwrap_or_else(|| Vec::new(env));
+fn load_index(env: &Env) -> Map<Symbol, Vec<(Address, u64)>> {
+    env.storage()
+        .persistent()
+        .get(&ALERT_INDEX_KEY)
+        .unwrap_or_else(|| Map::new(env))
+}
 
-    let mut retained = Vec::new(env);
-    let len = user_alerts.len();
-    for i in 0..len {
-        let alert = user_alerts.get(i).unwrap();
-        let not_expired = alert.expires_at == 0 || alert.expires_at > now;
-        if alert.active && not_expired {
-            retained.push_back(alert);
+fn save_index(env: &Env, index: &Map<Symbol, Vec<(Address, u64)>>) {
+    env.storage().persistent().set(&ALERT_INDEX_KEY, index);
+}
+
+fn index_insert(
+    env: &Env,
+    index: &mut Map<Symbol, Vec<(Address, u64)>>,
+    key: Symbol,
+    owner: Address,
+    alert_id: u64,
+) {
+    let mut entries = index.get(key.clone()).unwrap_or_else(|| Vec::new(env));
+    let entry = (owner, alert_id);
+    if !entries.contains(&entry) {
+        entries.push_back(entry);
+        index.set(key, entries);
+    }
+}
+
+fn index_remove(
+    env: &Env,
+    index: &mut Map<Symbol, Vec<(Address, u64)>>,
+    key: Symbol,
+    owner: &Address,
+    alert_id: u64,
+) {
+    let entries = match index.get(key.clone()) {
+        Some(e) => e,
+        None => return,
+    };
+    let mut kept = Vec::new(env);
+    for (o, id) in entries.iter() {
+        if !(id == alert_id && &o == owner) {
+            kept.push_back((o, id));
         }
     }
+    if kept.is_empty() {
+        index.remove(key);
+    } else {
+        index.set(key, kept);
+    }
+}
 
-    map.set(user, retained);
-    save_map(env, &map);
+/// Absolute move of `current` away from `reference`, in basis points.
+/// A non-positive reference never registers a move.
+fn price_move_bps(reference: i128, current: i128) -> i128 {
+    if reference <= 0 {
+        return 0;
+    }
+    (current - reference).abs().saturating_mul(10_000) / reference
 }
 
-// Internal helpers
+fn load_history(env: &Env, user: &Address) -> Vec<AlertHistoryEntry> {
+    env.storage()
+        .persistent()
+        .get(&(ALERT_HISTORY_KEY, user.clone()))
+        .unwrap_or_else(|| Vec::new(env))
+}
 
-fn push_alert(env: &Env, owner: Address, alert: Alert) {
-    let mut map = load_map(env);
-    let mut user_alerts: Vec<Alert> = map.get(owner.clone()).unwrap_or_else(|| Vec::new(env));
-    user_alerts.push_back(alert);
-    map.set(owner, user_alerts);
-    save_map(env, &map);
+/// Append a trigger to the owner's history, evicting the oldest entry when
+/// the ring is at capacity.
+fn record_history(env: &Env, alert: &Alert, timestamp: u64) {
+    let mut history = load_history(env, &alert.owner);
+    while history.len() >= ALERT_HISTORY_CAP {
+        history.pop_front();
+    }
+    history.push_back(AlertHistoryEntry {
+        alert_id: alert.id,
+        kind: alert.kind.clone(),
+        timestamp,
+    });
+    env.storage()
+        .persistent()
+        .set(&(ALERT_HISTORY_KEY, alert.owner.clone()), &history);
+}
+
+/// Deterministic idempotency key for one delivery: `sha256(alert_id || trigger_seq)`.
+pub fn alert_delivery_id(env: &Env, alert_id: u64, trigger_seq: u32) -> BytesN<32> {
+    let mut buf = Bytes::from_array(env, &alert_id.to_be_bytes());
+    buf.extend_from_array(&trigger_seq.to_be_bytes());
+    env.crypto().sha256(&buf).into()
+}
+
+/// Warn that `alert` contradicts the owner's existing alert `conflicting_id`.
+fn emit_alert_conflict(env: &Env, alert: &Alert, conflicting_id: u64) {
+    env.events().publish(
+        (
+            Symbol::new(env, "AlertConflict"),
+            alert.owner.clone(),
+            alert.id,
+        ),
+        (conflicting_id, alert.kind.clone()),
+    );
 }
 
 /// Emit a structured `AlertTriggered` event that any off-chain indexer or
 /// webhook relay can subscribe to.
 fn emit_alert_triggered(env: &Env, alert: &Alert, timestamp: u64) {
     // The topic contains the alert id and owner so indexers can filter cheaply.
-    // The data payload carries the full alert kind for rich notification content.
+    // The data payload carries the full alert kind for rich notification content,
+    // plus the trigger sequence and delivery id webhook relays dedupe on.
     env.events().publish(
         (
             Symbol::new(env, "AlertTriggered"),
             alert.owner.clone(),
             alert.id,
         ),
-        (alert.kind.clone(), alert.notification_method.clone(), timestamp),
+        (
+            alert.kind.clone(),
+            alert.notification_method.clone(),
+            timestamp,
+            alert.trigger_seq,
+            alert_delivery_id(env, alert.id, alert.trigger_seq),
+        ),
     );
+    record_history(env, alert, timestamp);
 }
\ No newline at end of file
@@ -0,0 +1,45 @@
+//! Delegated spending.
+//!
+//! An owner approves a spender (an aggregator, a vault contract) for up to a
+//! set amount of one asset. The spender can then move or swap that much of the
+//! owner's balance on its own signature, each use drawing the allowance down.
+
+use soroban_sdk::{Address, Env, Symbol};
+
+use crate::errors::ContractError;
+use crate::storage::ALLOWANCE_KEY;
+
+fn key(owner: &Address, spender: &Address, token: &Symbol) -> (Symbol, Address, Address, Symbol) {
+    (ALLOWANCE_KEY, owner.clone(), spender.clone(), token.clone())
+}
+
+/// Amount of `token` that `spender` may still use from `owner`'s balance
+pub fn get_allowance(env: &Env, owner: &Address, spender: &Address, token: &Symbol) -> i128 {
+    env.storage().persistent().get(&key(owner, spender, token)).unwrap_or(0)
+}
+
+/// Set `spender`'s allowance over `owner`'s `token` to `amount`, replacing
+/// the previous one. Zero revokes it.
+pub fn approve(env: &Env, owner: &Address, spender: &Address, token: &Symbol, amount: i128) -> Result<(), ContractError> {
+    if amount < 0 {
+        return Err(ContractError::InvalidAmount);
+    }
+    if amount == 0 {
+        env.storage().persistent().remove(&key(owner, spender, token));
+    } else {
+        env.storage().persistent().set(&key(owner, spender, token), &amount);
+    }
+    Ok(())
+}
+
+/// Draw `amount` from `spender`'s allowance over `owner`'s `token`
+pub fn spend(env: &Env, owner: &Address, spender: &Address, token: &Symbol, amount: i128) -> Result<(), ContractError> {
+    if amount <= 0 {
+        return Err(ContractError::InvalidAmount);
+    }
+    let current = get_allowance(env, owner, spender, token);
+    if amount > current {
+        return Err(ContractError::InsufficientAllowance);
+    }
+    approve(env, owner, spender, token, current - amount)
+}
@@ -0,0 +1,102 @@
+#![cfg(test)]
+
+use super::*;
+use soroban_sdk::{symbol_short, testutils::{Address as _, Ledger}, Address, Env};
+
+fn setup(env: &Env) -> (CounterContractClient<'_>, Address, Address) {
+    env.mock_all_auths();
+    env.ledger().with_mut(|li| li.timestamp = 1_000);
+    let contract_id = env.register(CounterContract, ());
+    let client = CounterContractClient::new(env, &contract_id);
+    let owner = Address::generate(env);
+    let spender = Address::generate(env);
+    client.mint(&symbol_short!("XLM"), &owner, &1_000);
+    (client, owner, spender)
+}
+
+#[test]
+fn test_approved_spender_swaps_within_allowance() {
+    let env = Env::default();
+    let (client, owner, spender) = setup(&env);
+    let xlm = symbol_short!("XLM");
+    let usdc = symbol_short!("USDCSIM");
+
+    client.approve(&owner, &spender, &xlm, &600);
+    let out = client.swap_from(&spender, &owner, &xlm, &usdc, &400);
+
+    assert!(out > 0);
+    assert_eq!(client.get_balance(&xlm, &owner), 600);
+    assert_eq!(client.get_balance(&usdc, &owner), out);
+    assert_eq!(client.get_balance(&usdc, &spender), 0);
+    assert_eq!(client.allowance(&owner, &spender, &xlm), 200);
+
+    // transfer_from draws on the same allowance
+    let recipient = Address::generate(&env);
+    client.transfer_from(&spender, &owner, &recipient, &xlm, &200);
+    assert_eq!(client.get_balance(&xlm, &recipient), 200);
+    assert_eq!(client.allowance(&owner, &spender, &xlm), 0);
+}
+
+#[test]
+fn test_swap_beyond_allowance_fails() {
+    let env = Env::default();
+    let (client, owner, spender) = setup(&env);
+    let xlm = symbol_short!("XLM");
+    let usdc = symbol_short!("USDCSIM");
+
+    client.approve(&owner, &spender, &xlm, &300);
+    assert_eq!(
+        client.try_swap_from(&spender, &owner, &xlm, &usdc, &301),
+        Err(Ok(ContractError::InsufficientAllowance))
+    );
+    // An allowance over one asset does not cover another
+    assert_eq!(
+        client.try_swap_from(&spender, &owner, &usdc, &xlm, &1),
+        Err(Ok(ContractError::InsufficientAllowance))
+    );
+    assert_eq!(client.get_balance(&xlm, &owner), 1_000);
+    assert_eq!(client.allowance(&owner, &spender, &xlm), 300);
+}
+
+#[test]
+fn test_revoked_allowance_blocks_spender() {
+    let env = Env::default();
+    let (client, owner, spender) = setup(&env);
+    let xlm = symbol_short!("XLM");
+    let usdc = symbol_short!("USDCSIM");
+
+    client.approve(&owner, &spender, &xlm, &500);
+    client.approve(&owner, &spender, &xlm, &0);
+
+    assert_eq!(client.allowance(&owner, &spender, &xlm), 0);
+    assert_eq!(
+        client.try_swap_from(&spender, &owner, &xlm, &usdc, &100),
+        Err(Ok(ContractError::InsufficientAllowance))
+    );
+    assert_eq!(
+        client.try_transfer_from(&spender, &owner, &spender, &xlm, &100),
+        Err(Ok(ContractError::InsufficientAllowance))
+    );
+    assert_eq!(client.get_balance(&xlm, &owner), 1_000);
+}
+
+#[test]
+fn test_swap_for_another_user_requires_their_auth() {
+    let env = Env::default();
+    let (client, owner, spender) = setup(&env);
+    let xlm = symbol_short!("XLM");
+    let usdc = symbol_short!("USDCSIM");
+
+    // Only the spender signs; a direct swap of the owner's funds is rejected
+    env.mock_auths(&[]);
+    assert!(client.try_swap(&xlm, &usdc, &100, &owner).is_err());
+    assert_eq!(client.get_balance(&xlm, &owner), 1_000);
+
+    // swap_from needs the spender alone, with the allowance standing in for the owner
+    env.mock_all_auths();
+    client.approve(&owner, &spender, &xlm, &100);
+    client.swap_from(&spender, &owner, &xlm, &usdc, &100);
+    let signers: std::vec::Vec<Address> = env.auths().into_iter().map(|(addr, _)| addr).collect();
+    assert_eq!(signers, std::vec![spender]);
+    assert_eq!(client.get_balance(&xlm, &owner), 900);
+}
@@ -1,4 +1,5 @@
-use soroban_sdk::{contracttype, Address, Env, Map, Vec, symbol_short};
+use soroban_sdk::{contracttype, Address, Env, Map, Symbol, Vec};
+use crate::fixed::{div_fp, div_fp_u128, exp_fp, ln_fp, mul_div, mul_fp, mul_fp_u128};
 use crate::portfolio::{Asset, Portfolio};
 
 #[derive(Clone, Debug, PartialEq)]
@@ -20,6 +21,9 @@ pub struct PerformanceMetrics {
     pub volatility: u128,          // Fixed-point: 7 decimals (annualized)
     pub total_return: i128,        // Raw return amount
     pub win_rate: u128,            // Fixed-point: 7 decimals (percentage)
+    /// Fewer than `MIN_DATA_POINTS` daily values in the window; every other
+    /// field is 0 because there is nothing to measure, not a flat return
+    pub insufficient_data: bool,
 }
 
 #[derive(Clone, Debug, PartialEq)]
@@ -28,6 +32,7 @@ pub struct AssetAllocation {
     pub assets: Vec<(Asset, u128)>,  // Asset and percentage allocation (fixed-point: 7 decimals)
     pub correlations: Map<(Asset, Asset), i128>, // Correlation matrix (fixed-point: 7 decimals)
     pub diversification_score: u128, // Fixed-point: 7 decimals
+    pub unpriced_assets: Vec<Asset>, // Held assets with no supplied price (valued at zero)
 }
 
 #[derive(Clone, Debug, PartialEq)]
@@ -47,7 +52,7 @@ pub struct PeriodReturns {
     pub geometric_return: i128,        // Compound return
     pub start_value: i128,
     pub end_value: i128,
-    pub period_days: u32,
+    pub period_days: u32,          // Days between the first and last recorded value
 }
 
 pub struct PortfolioAnalytics;
@@ -57,6 +62,18 @@ impl PortfolioAnalytics {
     const FIXED_POINT_PRECISION: u128 = 10_000_000; // 10^7 for 7 decimal places
     const FIXED_POINT_ONE: u128 = 10_000_000;       // 1.0 in fixed-point
 
+    /// Periods per year for daily returns, used to annualize Sharpe/Sortino
+    pub const TRADING_DAYS_PER_YEAR: u32 = 252;
+
+    /// Days per year used to annualize period returns
+    pub const DAYS_PER_YEAR: u32 = 365;
+
+    /// Daily closes consulted for asset returns, volatility and correlations
+    pub const PRICE_HISTORY_LOOKBACK_DAYS: u32 = 30;
+
+    /// Daily values needed before returns and ratios mean anything
+    pub const MIN_DATA_POINTS: u32 = 2;
+
     /// Calculate performance metrics for a user over a time window
     pub fn get_performance_metrics(
         env: &Env,
@@ -64,8 +81,34 @@ impl PortfolioAnalytics {
         user: Address,
         time_window: TimeWindow,
     ) -> PerformanceMetrics {
-        let daily_values = Self::get_daily_portfolio_values(env, portfolio, user.clone(), time_window);
-        if daily_values.is_empty() {
+        Self::get_performance_metrics_with_target(env, portfolio, user, time_window, None)
+    }
+
+    /// Calculate performance metrics with an optional minimum acceptable return (MAR)
+    /// for the Sortino ratio. `None` keeps the implicit 0% target.
+    pub fn get_performance_metrics_with_target(
+        env: &Env,
+        portfolio: &Portfolio,
+        user: Address,
+        time_window: TimeWindow,
+        target_return: Option<i128>,
+    ) -> PerformanceMetrics {
+        Self::get_performance_metrics_annualized(env, portfolio, user, time_window, target_return, None)
+    }
+
+    /// Calculate performance metrics, scaling Sharpe and Sortino by
+    /// √`annualization_periods` (e.g. `TRADING_DAYS_PER_YEAR` for daily data).
+    /// `None` returns the raw per-period ratios.
+    pub fn get_performance_metrics_annualized(
+        env: &Env,
+        portfolio: &Portfolio,
+        user: Address,
+        time_window: TimeWindow,
+        target_return: Option<i128>,
+        annualization_periods: Option<u32>,
+    ) -> PerformanceMetrics {
+        let daily_values = Self::get_daily_portfolio_values(env, portfolio, user.clone(), time_window.clone());
+        if daily_values.len() < Self::MIN_DATA_POINTS {
             return PerformanceMetrics {
                 sharpe_ratio: 0,
                 sortino_ratio: 0,
@@ -73,33 +116,50 @@ impl PortfolioAnalytics {
                 volatility: 0,
                 total_return: 0,
                 win_rate: 0,
+                insufficient_data: true,
             };
         }
 
         let returns = Self::calculate_daily_returns(&daily_values);
         let total_return = Self::calculate_total_return(&daily_values);
         let volatility = Self::calculate_volatility(&returns);
-        let downside_volatility = Self::calculate_downside_volatility(&returns);
+        let downside_volatility =
+            Self::calculate_downside_volatility_target(&returns, target_return.unwrap_or(0));
         let max_drawdown = Self::calculate_max_drawdown(&daily_values);
         let win_rate = Self::calculate_win_rate(&returns);
 
         // Assume risk-free rate of 2% annualized (0.02 in fixed-point)
         let risk_free_rate = 2_000_000; // 0.02 * FIXED_POINT_PRECISION
+        let mean_return = div_fp_u128(
+            total_return as u128,
+            daily_values.len() as u128,
+            Self::FIXED_POINT_PRECISION,
+        );
+        let excess_return = mean_return.saturating_sub(risk_free_rate);
 
         let sharpe_ratio = if volatility > 0 {
-            ((total_return as u128 * Self::FIXED_POINT_PRECISION / daily_values.len() as u128).saturating_sub(risk_free_rate))
-                .saturating_mul(Self::FIXED_POINT_PRECISION) / volatility
+            div_fp_u128(excess_return, volatility, Self::FIXED_POINT_PRECISION)
         } else {
             0
         };
 
         let sortino_ratio = if downside_volatility > 0 {
-            ((total_return as u128 * Self::FIXED_POINT_PRECISION / daily_values.len() as u128).saturating_sub(risk_free_rate))
-                .saturating_mul(Self::FIXED_POINT_PRECISION) / downside_volatility
+            div_fp_u128(excess_return, downside_volatility, Self::FIXED_POINT_PRECISION)
         } else {
             0
         };
 
+        let (sharpe_ratio, sortino_ratio) = match annualization_periods {
+            Some(periods) if periods > 0 => {
+                let factor = Self::annualization_factor(periods);
+                (
+                    mul_fp_u128(sharpe_ratio, factor, Self::FIXED_POINT_PRECISION),
+                    mul_fp_u128(sortino_ratio, factor, Self::FIXED_POINT_PRECISION),
+                )
+            }
+            _ => (sharpe_ratio, sortino_ratio),
+        };
+
         let metrics = PerformanceMetrics {
             sharpe_ratio,
             sortino_ratio,
@@ -107,6 +167,7 @@ impl PortfolioAnalytics {
             volatility,
             total_return,
             win_rate,
+            insufficient_data: false,
         };
 
         // Emit event for analytics calculation
@@ -122,40 +183,50 @@ impl PortfolioAnalytics {
         metrics
     }
 
-    /// Get asset allocation breakdown with correlation analysis
+    /// Get asset allocation breakdown with correlation analysis. Without
+    /// `prices` every held asset is valued at 1.0.
     pub fn get_asset_allocation(
         env: &Env,
         portfolio: &Portfolio,
         user: Address,
+        prices: Option<Map<Asset, i128>>,
     ) -> AssetAllocation {
         let mut assets = Vec::new(env);
-        let mut total_value: i128 = 0;
-
-        // Get all user balances
-        // Note: In a real implementation, we'd need to get current prices for each asset
-        // For now, we'll use simplified logic assuming XLM = 1 USD, USDC = 1 USD
-
-        let xlm_balance = portfolio.balance_of(env, Asset::XLM, user.clone());
-        let usdc_balance = portfolio.balance_of(env, Asset::Custom(symbol_short!("USDCSIM")), user.clone());
+        let prices = prices.unwrap_or_else(|| {
+            let mut unit = Map::new(env);
+            for (asset, _) in portfolio.held_assets(env, user.clone()).iter() {
+                unit.set(asset, Self::FIXED_POINT_ONE as i128);
+            }
+            unit
+        });
 
-        total_value = xlm_balance + usdc_balance;
+        // Value every holding at its supplied price (fixed-point: 7 decimals),
+        // after normalizing balances to a common decimal scale;
+        // assets without a price are left out and reported in `unpriced_assets`
+        let precision = Self::FIXED_POINT_PRECISION as i128;
+        let total_value = portfolio.total_portfolio_value(env, user.clone(), &prices, precision);
+        let unpriced_assets = portfolio.unpriced_assets(env, user.clone(), &prices);
 
         if total_value > 0 {
-            let xlm_percentage = (xlm_balance as u128 * Self::FIXED_POINT_PRECISION) / total_value as u128;
-            let usdc_percentage = (usdc_balance as u128 * Self::FIXED_POINT_PRECISION) / total_value as u128;
-
-            assets.push_back((Asset::XLM, xlm_percentage));
-            assets.push_back((Asset::Custom(symbol_short!("USDCSIM")), usdc_percentage));
+            for (asset, balance) in portfolio.held_assets(env, user.clone()).iter() {
+                if let Some(price) = prices.get(asset.clone()) {
+                    let balance = portfolio.normalize_amount(&asset, balance);
+                    let value = mul_fp(balance, price, precision).max(0);
+                    let percentage = div_fp_u128(value as u128, total_value as u128, Self::FIXED_POINT_PRECISION);
+                    assets.push_back((asset, percentage));
+                }
+            }
         }
 
-        // Calculate correlations (simplified - would need historical price data)
-        let correlations = Map::new(env);
-        let diversification_score = Self::calculate_diversification_score(&assets);
+        // Correlate held assets on the daily closes they share
+        let correlations = Self::historical_correlations(env, &assets, Self::PRICE_HISTORY_LOOKBACK_DAYS);
+        let diversification_score = Self::calculate_correlated_diversification_score(&assets, &correlations);
 
         let allocation = AssetAllocation {
             assets,
             correlations,
             diversification_score,
+            unpriced_assets,
         };
 
         // Emit event for asset allocation analysis
@@ -181,7 +252,7 @@ impl PortfolioAnalytics {
         let portfolio_returns = Self::get_daily_portfolio_values(env, portfolio, user.clone(), time_window);
         // In a real implementation, we'd fetch benchmark data
         // For now, return placeholder values
-        let benchmark_returns = Vec::new(env); // Placeholder
+        let benchmark_returns: Vec<i128> = Vec::new(env); // Placeholder
 
         if portfolio_returns.is_empty() {
             let comparison = BenchmarkComparison {
@@ -265,11 +336,12 @@ impl PortfolioAnalytics {
 
         let start_value = daily_values.get(0).unwrap_or(0);
         let end_value = daily_values.get(daily_values.len() - 1).unwrap_or(0);
-        let period_days = daily_values.len() as u32;
+        // One value per day, so the period spans len - 1 days
+        let period_days = daily_values.len() - 1;
 
         let arithmetic_return = end_value - start_value;
         let time_weighted_return = Self::calculate_time_weighted_return(&daily_values);
-        let geometric_return = Self::calculate_geometric_return(&daily_values);
+        let geometric_return = Self::calculate_geometric_return(&daily_values, period_days);
 
         let returns = PeriodReturns {
             time_weighted_return,
@@ -295,7 +367,7 @@ impl PortfolioAnalytics {
 
     // Helper methods for calculations
 
-    fn get_daily_portfolio_values(
+    pub fn get_daily_portfolio_values(
         env: &Env,
         portfolio: &Portfolio,
         user: Address,
@@ -341,14 +413,14 @@ impl PortfolioAnalytics {
             let prev = values.get(i - 1).unwrap_or(0);
             let curr = values.get(i).unwrap_or(0);
             if prev != 0 {
-                let ret = ((curr - prev) as i128 * Self::FIXED_POINT_PRECISION as i128) / prev;
+                let ret = div_fp(curr.saturating_sub(prev), prev, Self::FIXED_POINT_PRECISION as i128);
                 returns.push_back(ret);
             }
         }
         returns
     }
 
-    pub fn calculate_volatility(returns: &Vec<i128>) -> u128 {
+    pub fn calculate_total_return(values: &Vec<i128>) -> i128 {
         if values.is_empty() {
             return 0;
         }
@@ -357,7 +429,7 @@ impl PortfolioAnalytics {
         end - start
     }
 
-    fn calculate_volatility(returns: &Vec<i128>) -> u128 {
+    pub fn calculate_volatility(returns: &Vec<i128>) -> u128 {
         if returns.is_empty() {
             return 0;
         }
@@ -373,7 +445,7 @@ impl PortfolioAnalytics {
         let mut variance: u128 = 0;
         for i in 0..returns.len() {
             let diff = returns.get(i).unwrap_or(0) - mean;
-            variance += (diff * diff) as u128;
+            variance = variance.saturating_add(diff.unsigned_abs().saturating_mul(diff.unsigned_abs()));
         }
         variance /= returns.len() as u128;
 
@@ -381,21 +453,23 @@ impl PortfolioAnalytics {
         Self::sqrt_fixed_point(variance)
     }
 
-    fn calculate_downside_volatility(returns: &Vec<i128>) -> u128 {
+    /// Downside deviation relative to a minimum acceptable return (fixed-point: 7 decimals).
+    /// Only shortfalls below `target` contribute, averaged over all observations.
+    pub fn calculate_downside_volatility_target(returns: &Vec<i128>, target: i128) -> u128 {
         if returns.is_empty() {
             return 0;
         }
 
-        // Only consider negative returns
-        let mut negative_returns = Vec::new(returns.env());
+        let mut sum_squares: u128 = 0;
         for i in 0..returns.len() {
-            let ret = returns.get(i).unwrap_or(0);
-            if ret < 0 {
-                negative_returns.push_back(ret);
+            let shortfall = returns.get(i).unwrap_or(0) - target;
+            if shortfall < 0 {
+                sum_squares = sum_squares
+                    .saturating_add(shortfall.unsigned_abs().saturating_mul(shortfall.unsigned_abs()));
             }
         }
 
-        Self::calculate_volatility(&negative_returns)
+        Self::sqrt_fixed_point(sum_squares / returns.len() as u128)
     }
 
     pub fn calculate_max_drawdown(values: &Vec<i128>) -> u128 {
@@ -411,7 +485,7 @@ impl PortfolioAnalytics {
             if current > peak {
                 peak = current;
             } else {
-                let drawdown = ((peak - current) as u128 * Self::FIXED_POINT_PRECISION) / peak as u128;
+                let drawdown = div_fp_u128((peak - current) as u128, peak as u128, Self::FIXED_POINT_PRECISION);
                 if drawdown > max_drawdown {
                     max_drawdown = drawdown;
                 }
@@ -459,6 +533,114 @@ impl PortfolioAnalytics {
         }
     }
 
+    /// Daily returns (fixed-point: 7 decimals) of `asset` over its last `days` closes
+    pub fn get_asset_returns(env: &Env, asset: &Asset, days: u32) -> Vec<i128> {
+        Self::calculate_daily_returns(&crate::price_history::get_price_history(env, asset, days))
+    }
+
+    /// Annualized volatility of `asset` over its last `days` closes
+    pub fn get_asset_volatility(env: &Env, asset: &Asset, days: u32) -> u128 {
+        Self::calculate_volatility(&Self::get_asset_returns(env, asset, days))
+    }
+
+    /// Pearson correlation of two equally long return series (fixed-point: 7 decimals).
+    /// Returns 0 when either series is flat or shorter than two points.
+    pub fn calculate_correlation(returns_a: &Vec<i128>, returns_b: &Vec<i128>) -> i128 {
+        let n = returns_a.len().min(returns_b.len());
+        if n < 2 {
+            return 0;
+        }
+
+        let mut sum_a: i128 = 0;
+        let mut sum_b: i128 = 0;
+        for i in 0..n {
+            sum_a = sum_a.saturating_add(returns_a.get(i).unwrap_or(0));
+            sum_b = sum_b.saturating_add(returns_b.get(i).unwrap_or(0));
+        }
+        let mean_a = sum_a / n as i128;
+        let mean_b = sum_b / n as i128;
+
+        let mut covariance: i128 = 0;
+        let mut variance_a: u128 = 0;
+        let mut variance_b: u128 = 0;
+        for i in 0..n {
+            let da = returns_a.get(i).unwrap_or(0) - mean_a;
+            let db = returns_b.get(i).unwrap_or(0) - mean_b;
+            covariance = covariance.saturating_add(da.saturating_mul(db));
+            variance_a = variance_a.saturating_add(da.unsigned_abs().saturating_mul(da.unsigned_abs()));
+            variance_b = variance_b.saturating_add(db.unsigned_abs().saturating_mul(db.unsigned_abs()));
+        }
+
+        let denominator = Self::sqrt_fixed_point(variance_a).saturating_mul(Self::sqrt_fixed_point(variance_b));
+        if denominator == 0 {
+            return 0;
+        }
+        let precision = Self::FIXED_POINT_PRECISION as i128;
+        (covariance.saturating_mul(precision) / denominator as i128).clamp(-precision, precision)
+    }
+
+    /// Pairwise return correlations for `assets` from their recorded daily closes.
+    /// Pairs with fewer than two shared returns are left out (treated as uncorrelated).
+    fn historical_correlations(env: &Env, assets: &Vec<(Asset, u128)>, days: u32) -> Map<(Asset, Asset), i128> {
+        let mut correlations = Map::new(env);
+        for i in 0..assets.len() {
+            for j in (i + 1)..assets.len() {
+                let (asset_i, _) = assets.get(i).unwrap_or((Asset::XLM, 0));
+                let (asset_j, _) = assets.get(j).unwrap_or((Asset::XLM, 0));
+                let (prices_i, prices_j) = crate::price_history::get_aligned_history(env, &asset_i, &asset_j, days);
+                let returns_i = Self::calculate_daily_returns(&prices_i);
+                let returns_j = Self::calculate_daily_returns(&prices_j);
+                if returns_i.len() >= 2 && returns_i.len() == returns_j.len() {
+                    correlations.set((asset_i, asset_j), Self::calculate_correlation(&returns_i, &returns_j));
+                }
+            }
+        }
+        correlations
+    }
+
+    /// Diversification score that penalizes correlated holdings.
+    ///
+    /// Uses the concentration term sum(w_i * w_j * rho_ij) with rho_ii = 1, so
+    /// uncorrelated assets reduce to the Herfindahl index and perfectly correlated
+    /// assets score as a single holding. Pairs missing from the matrix are treated
+    /// as uncorrelated; an empty matrix falls back to the weight-only score.
+    pub fn calculate_correlated_diversification_score(
+        assets: &Vec<(Asset, u128)>,
+        correlations: &Map<(Asset, Asset), i128>,
+    ) -> u128 {
+        if correlations.is_empty() {
+            return Self::calculate_diversification_score(assets);
+        }
+        if assets.is_empty() {
+            return 0;
+        }
+
+        let precision = Self::FIXED_POINT_PRECISION as i128;
+        let mut concentration: i128 = 0;
+
+        for i in 0..assets.len() {
+            let (asset_i, weight_i) = assets.get(i).unwrap_or((Asset::XLM, 0));
+            for j in 0..assets.len() {
+                let (asset_j, weight_j) = assets.get(j).unwrap_or((Asset::XLM, 0));
+                let rho = if i == j {
+                    precision
+                } else {
+                    correlations
+                        .get((asset_i.clone(), asset_j.clone()))
+                        .or_else(|| correlations.get((asset_j.clone(), asset_i.clone())))
+                        .unwrap_or(0)
+                        .clamp(-precision, precision)
+                };
+                let weight_product = mul_fp(weight_i as i128, weight_j as i128, precision);
+                concentration = concentration.saturating_add(mul_fp(weight_product, rho, precision));
+            }
+        }
+
+        // Negative correlations can push the term below zero; cap the score at 1.0
+        let concentration = concentration.max(0) as u128;
+        Self::FIXED_POINT_PRECISION.saturating_sub(concentration)
+    }
+
     fn calculate_time_weighted_return(values: &Vec<i128>) -> i128 {
         if values.len() < 2 {
             return 0;
@@ -469,23 +651,48 @@ impl PortfolioAnalytics {
             let prev = values.get(i - 1).unwrap_or(0);
             let curr = values.get(i).unwrap_or(0);
             if prev > 0 {
-                let period_return = (curr as i128 * Self::FIXED_POINT_PRECISION as i128) / prev;
-                twr = (twr * period_return) / Self::FIXED_POINT_PRECISION as i128;
+                let precision = Self::FIXED_POINT_PRECISION as i128;
+                let period_return = div_fp(curr, prev, precision);
+                twr = mul_fp(twr, period_return, precision);
             }
         }
 
         twr - Self::FIXED_POINT_ONE as i128
     }
 
-    fn calculate_geometric_return(values: &Vec<i128>) -> i128 {
+    /// Time-weighted return over `values`, annualized across `period_days`
+    fn calculate_geometric_return(values: &Vec<i128>, period_days: u32) -> i128 {
         if values.len() < 2 {
             return 0;
         }
 
         let twr = Self::calculate_time_weighted_return(values);
-        // For geometric return, we need to annualize if we had time periods
-        // For now, return the TWR as approximation
-        twr
+        Self::annualize_return(twr, period_days)
+    }
+
+    /// Annualized equivalent of `total_return` (fixed-point: 7 decimals) earned
+    /// over `days`: (1 + r)^(365 / days) - 1. A loss of 100% or more stays at
+    /// -100%, and a zero-day period is returned unadjusted.
+    pub fn annualize_return(total_return: i128, days: u32) -> i128 {
+        let one = Self::FIXED_POINT_ONE as i128;
+        if days == 0 {
+            return total_return;
+        }
+        let growth = one.saturating_add(total_return);
+        if growth <= 0 {
+            return -one;
+        }
+
+        // (1 + r)^(365 / days) = e^(ln(1 + r) · 365 / days)
+        let ln_growth = ln_fp(growth).unwrap_or(0);
+        let annual_growth = exp_fp(mul_div(ln_growth, Self::DAYS_PER_YEAR as i128, days as i128));
+        annual_growth.saturating_sub(one)
+    }
+
+    /// √periods in fixed-point (7 decimals)
+    fn annualization_factor(periods: u32) -> u128 {
+        let scaled = (periods as u128) * Self::FIXED_POINT_PRECISION * Self::FIXED_POINT_PRECISION;
+        Self::sqrt_fixed_point(scaled)
     }
 
     // Fixed-point square root approximation
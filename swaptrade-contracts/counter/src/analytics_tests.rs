@@ -1,7 +1,7 @@
 #[cfg(test)]
 mod analytics_tests {
     use super::*;
-    use soroban_sdk::{testutils::Address as _, Env, Symbol, symbol_short};
+    use soroban_sdk::{symbol_short, testutils::Address as _, Address, Env, Map, Symbol, Vec};
     use crate::portfolio::{Asset, Portfolio};
     use crate::analytics::{PortfolioAnalytics, TimeWindow, PerformanceMetrics};
 
@@ -25,11 +25,42 @@ mod analytics_tests {
         assert_eq!(metrics.volatility, 0);
         assert_eq!(metrics.total_return, 0);
         assert_eq!(metrics.win_rate, 0);
+        assert!(metrics.insufficient_data);
+    }
+
+    #[test]
+    fn test_performance_metrics_require_two_data_points() {
+        use soroban_sdk::testutils::Ledger;
+
+        let env = Env::default();
+        let mut portfolio = Portfolio::new(&env);
+        let user = Address::generate(&env);
+        env.ledger().set_timestamp(10 * 86400);
+        let metrics = |portfolio: &Portfolio| {
+            PortfolioAnalytics::get_performance_metrics(&env, portfolio, user.clone(), TimeWindow::Day7)
+        };
+
+        assert!(metrics(&portfolio).insufficient_data);
+
+        // One value: still no return to speak of, and flagged as such
+        portfolio.mint(&env, Asset::XLM, user.clone(), 1000);
+        portfolio.record_daily_portfolio_value(&env, user.clone(), 5 * 86400);
+        let one = metrics(&portfolio);
+        assert!(one.insufficient_data);
+        assert_eq!(one.total_return, 0);
+        assert_eq!(one.sharpe_ratio, 0);
+
+        // Two values: a real (here flat) return
+        portfolio.record_daily_portfolio_value(&env, user.clone(), 6 * 86400);
+        let two = metrics(&portfolio);
+        assert!(!two.insufficient_data);
+        assert_eq!(two.total_return, 0);
     }
 
     #[test]
     fn test_get_asset_allocation() {
         let env = Env::default();
+        let contract_id = env.register(crate::CounterContract, ());
         let mut portfolio = Portfolio::new(&env);
         let user = Address::generate(&env);
 
@@ -37,18 +68,126 @@ mod analytics_tests {
         portfolio.mint(&env, Asset::XLM, user.clone(), 1000);
         portfolio.mint(&env, Asset::Custom(symbol_short!("USDCSIM")), user.clone(), 500);
 
-        let allocation = PortfolioAnalytics::get_asset_allocation(&env, &portfolio, user);
+        // No prices: every asset is valued at 1.0
+        let allocation = env.as_contract(&contract_id, || {
+            PortfolioAnalytics::get_asset_allocation(&env, &portfolio, user, None)
+        });
 
         // Should have 2 assets
         assert_eq!(allocation.assets.len(), 2);
 
-        // Check allocations (simplified - both should be non-zero)
-        let xlm_allocation = allocation.assets.get(0).unwrap().1;
-        let usdc_allocation = allocation.assets.get(1).unwrap().1;
+        let xlm_allocation = allocation.assets.iter().find(|(a, _)| *a == Asset::XLM).unwrap().1;
+        let usdc_allocation = allocation.assets.iter().find(|(a, _)| *a != Asset::XLM).unwrap().1;
+
+        // 1000 / 1500 and 500 / 1500, each rounded down
+        assert_eq!(xlm_allocation, 6_666_666);
+        assert_eq!(usdc_allocation, 3_333_333);
+        assert_eq!(allocation.unpriced_assets.len(), 0);
+    }
+
+    #[test]
+    fn test_get_asset_allocation_uses_supplied_prices() {
+        let env = Env::default();
+        let contract_id = env.register(crate::CounterContract, ());
+        let mut portfolio = Portfolio::new(&env);
+        let user = Address::generate(&env);
+        let usdc = Asset::Custom(symbol_short!("USDCSIM"));
+
+        // 1000 XLM @ 0.12 = 120, 380 USDC @ 1.0 = 380 -> 24% / 76%
+        portfolio.mint(&env, Asset::XLM, user.clone(), 1000);
+        portfolio.mint(&env, usdc.clone(), user.clone(), 380);
+
+        let mut prices = Map::new(&env);
+        prices.set(Asset::XLM, 1_200_000);
+        prices.set(usdc.clone(), 10_000_000);
+
+        assert_eq!(portfolio.total_portfolio_value(&env, user.clone(), &prices, 10_000_000), 500);
+
+        let allocation = env.as_contract(&contract_id, || {
+            PortfolioAnalytics::get_asset_allocation(&env, &portfolio, user, Some(prices))
+        });
+        let xlm_weight = allocation.assets.iter().find(|(a, _)| *a == Asset::XLM).unwrap().1;
+        let usdc_weight = allocation.assets.iter().find(|(a, _)| *a == usdc).unwrap().1;
+        assert_eq!(xlm_weight, 2_400_000);
+        assert_eq!(usdc_weight, 7_600_000);
+    }
+
+    #[test]
+    fn test_get_asset_allocation_normalizes_decimals() {
+        let env = Env::default();
+        let contract_id = env.register(crate::CounterContract, ());
+        let mut portfolio = Portfolio::new(&env);
+        let user = Address::generate(&env);
+        let usdc = Asset::Custom(symbol_short!("USDCSIM"));
+        portfolio.set_asset_decimals(usdc.clone(), 6);
+
+        // 100 XLM (7 dp) @ 0.12 = 12, 38 USDC (6 dp) @ 1.0 = 38 -> 24% / 76%
+        portfolio.mint(&env, Asset::XLM, user.clone(), 1_000_000_000);
+        portfolio.mint(&env, usdc.clone(), user.clone(), 38_000_000);
+
+        let mut prices = Map::new(&env);
+        prices.set(Asset::XLM, 1_200_000);
+        prices.set(usdc.clone(), 10_000_000);
+
+        assert_eq!(portfolio.total_portfolio_value(&env, user.clone(), &prices, 10_000_000), 500_000_000);
+
+        let allocation = env.as_contract(&contract_id, || {
+            PortfolioAnalytics::get_asset_allocation(&env, &portfolio, user, Some(prices))
+        });
+        let xlm_weight = allocation.assets.iter().find(|(a, _)| *a == Asset::XLM).unwrap().1;
+        let usdc_weight = allocation.assets.iter().find(|(a, _)| *a == usdc).unwrap().1;
+        assert_eq!(xlm_weight, 2_400_000);
+        assert_eq!(usdc_weight, 7_600_000);
+    }
+
+    #[test]
+    fn test_swap_fees_accumulate_on_normalized_scale() {
+        let env = Env::default();
+        let contract_id = env.register(crate::CounterContract, ());
+        let user = Address::generate(&env);
+        let usdc = Asset::Custom(symbol_short!("USDCSIM"));
+
+        env.as_contract(&contract_id, || {
+            let mut portfolio = Portfolio::new(&env);
+            portfolio.set_asset_decimals(usdc.clone(), 6);
+            portfolio.set_liquidity(Asset::XLM, 1_000_000_000_000);
+            portfolio.set_liquidity(usdc.clone(), 100_000_000_000);
+            portfolio.mint(&env, usdc.clone(), user.clone(), 100_000_000);
+            portfolio.mint(&env, Asset::XLM, user.clone(), 1_000_000_000);
+
+            // 10 USDC in: the 0.3% fee is 30_000 raw units at 6 dp, 300_000 at 7 dp
+            crate::trading::perform_swap(&env, &mut portfolio, symbol_short!("USDCSIM"), symbol_short!("XLM"), 10_000_000, user.clone());
+            assert_eq!(portfolio.get_lp_fees_accumulated(), 300_000);
+
+            // 10 XLM in is already on the internal scale
+            crate::trading::perform_swap(&env, &mut portfolio, symbol_short!("XLM"), symbol_short!("USDCSIM"), 100_000_000, user);
+            assert_eq!(portfolio.get_lp_fees_accumulated(), 600_000);
+        });
+    }
 
-        assert!(xlm_allocation > 0);
-        assert!(usdc_allocation > 0);
-        assert_eq!(xlm_allocation + usdc_allocation, 2_000_000_000); // 2.0 in fixed-point
+    #[test]
+    fn test_get_asset_allocation_flags_unpriced_assets() {
+        let env = Env::default();
+        let contract_id = env.register(crate::CounterContract, ());
+        let mut portfolio = Portfolio::new(&env);
+        let user = Address::generate(&env);
+        let usdc = Asset::Custom(symbol_short!("USDCSIM"));
+
+        portfolio.mint(&env, Asset::XLM, user.clone(), 1000);
+        portfolio.mint(&env, usdc.clone(), user.clone(), 500);
+
+        let mut prices = Map::new(&env);
+        prices.set(usdc.clone(), 10_000_000);
+
+        // XLM has no price: it contributes nothing and is flagged
+        assert_eq!(portfolio.total_portfolio_value(&env, user.clone(), &prices, 10_000_000), 500);
+        let allocation = env.as_contract(&contract_id, || {
+            PortfolioAnalytics::get_asset_allocation(&env, &portfolio, user, Some(prices))
+        });
+        assert_eq!(allocation.assets.len(), 1);
+        assert_eq!(allocation.assets.get(0).unwrap(), (usdc, 10_000_000));
+        assert_eq!(allocation.unpriced_assets.len(), 1);
+        assert_eq!(allocation.unpriced_assets.get(0).unwrap(), Asset::XLM);
     }
 
     #[test]
@@ -96,6 +235,31 @@ mod analytics_tests {
         assert_eq!(returns.period_days, 0);
     }
 
+    #[test]
+    fn test_period_returns_annualize_over_period_days() {
+        let env = Env::default();
+        let mut portfolio = Portfolio::new(&env);
+        let user = Address::generate(&env);
+
+        // Daily values on days 5..=9: 1000, 1010, 1020, 1030, 1050
+        let balances = [1000i128, 1010, 1020, 1030, 1050];
+        let mut held = 0i128;
+        for (day, balance) in balances.iter().enumerate() {
+            portfolio.mint(&env, Asset::XLM, user.clone(), balance - held);
+            held = *balance;
+            portfolio.record_daily_portfolio_value(&env, user.clone(), (day as u64 + 5) * 86400);
+        }
+
+        let returns = PortfolioAnalytics::get_period_returns(&env, &portfolio, user, 5 * 86400, 9 * 86400);
+
+        assert_eq!(returns.period_days, 4);
+        assert_eq!(
+            returns.geometric_return,
+            PortfolioAnalytics::annualize_return(returns.time_weighted_return, returns.period_days)
+        );
+        assert!(returns.geometric_return > returns.time_weighted_return);
+    }
+
     #[test]
     fn test_calculate_daily_returns() {
         let env = Env::default();
@@ -140,7 +304,7 @@ mod analytics_tests {
         let max_drawdown = PortfolioAnalytics::calculate_max_drawdown(&values);
 
         // Max drawdown should be (120-90)/120 = 0.25 = 25% = 2_500_000 in fixed-point
-        let expected_drawdown = (30i128 * 10_000_000i128) / 120i128;
+        let expected_drawdown = (30u128 * 10_000_000u128) / 120u128;
         assert_eq!(max_drawdown, expected_drawdown);
     }
 
@@ -174,6 +338,139 @@ mod analytics_tests {
         assert!(score > 8_000_000); // > 0.8
     }
 
+    #[test]
+    fn test_correlated_diversification_score() {
+        let env = Env::default();
+        let usdc = Asset::Custom(symbol_short!("USDCSIM"));
+        let mut assets = Vec::new(&env);
+        assets.push_back((Asset::XLM, 5_000_000));
+        assets.push_back((usdc.clone(), 5_000_000));
+
+        let mut uncorrelated = Map::new(&env);
+        uncorrelated.set((Asset::XLM, usdc.clone()), 0i128);
+        let mut correlated = Map::new(&env);
+        correlated.set((Asset::XLM, usdc.clone()), 9_000_000i128); // 0.9
+
+        let weight_only = PortfolioAnalytics::calculate_diversification_score(&assets);
+        let low_corr = PortfolioAnalytics::calculate_correlated_diversification_score(&assets, &uncorrelated);
+        let high_corr = PortfolioAnalytics::calculate_correlated_diversification_score(&assets, &correlated);
+
+        // Zero correlation matches the Herfindahl score
+        assert_eq!(low_corr, weight_only);
+        // Same weights, correlated holdings score lower
+        assert!(high_corr < low_corr);
+        // 1 - (0.25 + 0.25 + 2 * 0.25 * 0.9) = 0.05
+        assert_eq!(high_corr, 500_000);
+    }
+
+    #[test]
+    fn test_correlated_diversification_score_empty_matrix_fallback() {
+        let env = Env::default();
+        let mut assets = Vec::new(&env);
+        assets.push_back((Asset::XLM, 7_000_000));
+        assets.push_back((Asset::Custom(symbol_short!("USDCSIM")), 3_000_000));
+
+        let score = PortfolioAnalytics::calculate_correlated_diversification_score(&assets, &Map::new(&env));
+        assert_eq!(score, PortfolioAnalytics::calculate_diversification_score(&assets));
+    }
+
+    #[test]
+    fn test_calculate_downside_volatility_target() {
+        let env = Env::default();
+        let mut returns = Vec::new(&env);
+        returns.push_back(1_000_000);   // 0.10
+        returns.push_back(-500_000);    // -0.05
+        returns.push_back(300_000);     // 0.03
+
+        // Only the -0.05 return is below a 0% target
+        let at_zero = PortfolioAnalytics::calculate_downside_volatility_target(&returns, 0);
+        // 0.03 and -0.05 both fall short of a 5% target
+        let at_five = PortfolioAnalytics::calculate_downside_volatility_target(&returns, 500_000);
+
+        assert!(at_zero > 0);
+        assert!(at_five > at_zero);
+        assert_eq!(PortfolioAnalytics::calculate_downside_volatility_target(&returns, -1_000_000), 0);
+    }
+
+    #[test]
+    fn test_sortino_changes_with_target_return() {
+        use soroban_sdk::testutils::Ledger;
+
+        let env = Env::default();
+        let mut portfolio = Portfolio::new(&env);
+        let user = Address::generate(&env);
+
+        // Daily values: 1000, 1100, 1050, 1200, 1150
+        let balances = [1000i128, 1100, 1050, 1200, 1150];
+        let mut held = 0i128;
+        for (day, balance) in balances.iter().enumerate() {
+            if *balance > held {
+                portfolio.mint(&env, Asset::XLM, user.clone(), balance - held);
+            } else {
+                portfolio.debit(&env, Asset::XLM, user.clone(), held - balance);
+            }
+            held = *balance;
+            portfolio.record_daily_portfolio_value(&env, user.clone(), (day as u64 + 5) * 86400);
+        }
+        env.ledger().set_timestamp(10 * 86400);
+
+        let at_zero = PortfolioAnalytics::get_performance_metrics_with_target(
+            &env, &portfolio, user.clone(), TimeWindow::Day7, Some(0),
+        );
+        let raised = PortfolioAnalytics::get_performance_metrics_with_target(
+            &env, &portfolio, user.clone(), TimeWindow::Day7, Some(500_000),
+        );
+
+        assert!(at_zero.sortino_ratio > 0);
+        assert!(raised.sortino_ratio < at_zero.sortino_ratio);
+        assert_eq!(raised.sharpe_ratio, at_zero.sharpe_ratio);
+
+        // No target is the same as an explicit 0% target
+        let default = PortfolioAnalytics::get_performance_metrics(&env, &portfolio, user.clone(), TimeWindow::Day7);
+        assert_eq!(default, at_zero);
+    }
+
+    #[test]
+    fn test_annualized_sharpe_scales_by_sqrt_252() {
+        use soroban_sdk::testutils::Ledger;
+
+        let env = Env::default();
+        let mut portfolio = Portfolio::new(&env);
+        let user = Address::generate(&env);
+
+        let balances = [1000i128, 1100, 1050, 1200, 1150];
+        let mut held = 0i128;
+        for (day, balance) in balances.iter().enumerate() {
+            if *balance > held {
+                portfolio.mint(&env, Asset::XLM, user.clone(), balance - held);
+            } else {
+                portfolio.debit(&env, Asset::XLM, user.clone(), held - balance);
+            }
+            held = *balance;
+            portfolio.record_daily_portfolio_value(&env, user.clone(), (day as u64 + 5) * 86400);
+        }
+        env.ledger().set_timestamp(10 * 86400);
+
+        let raw = PortfolioAnalytics::get_performance_metrics(&env, &portfolio, user.clone(), TimeWindow::Day7);
+        let annualized = PortfolioAnalytics::get_performance_metrics_annualized(
+            &env,
+            &portfolio,
+            user,
+            TimeWindow::Day7,
+            None,
+            Some(PortfolioAnalytics::TRADING_DAYS_PER_YEAR),
+        );
+
+        assert!(raw.sharpe_ratio > 0);
+        let expected = raw.sharpe_ratio as f64 * 252f64.sqrt();
+        let diff = (annualized.sharpe_ratio as f64 - expected).abs();
+        assert!(diff <= 1.0 + expected * 1e-7, "annualized {} vs expected {}", annualized.sharpe_ratio, expected);
+
+        // Non-ratio metrics are untouched
+        assert_eq!(annualized.volatility, raw.volatility);
+        assert_eq!(annualized.total_return, raw.total_return);
+    }
+
     #[test]
     fn test_portfolio_record_daily_value() {
         let env = Env::default();
@@ -225,4 +522,129 @@ mod analytics_tests {
         assert_eq!(day7_values.len(), 0);
         assert_eq!(day30_values.len(), 0);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_daily_returns_and_drawdown_near_fixed_point_limit() {
+        let env = Env::default();
+        let big = i128::MAX / 10_000_000;
+        let mut values = Vec::new(&env);
+        values.push_back(big / 2);
+        values.push_back(big);
+        values.push_back(big / 4);
+
+        // Plain `delta * 10^7` would overflow i128 here.
+        let returns = PortfolioAnalytics::calculate_daily_returns(&values);
+        assert_eq!(returns.len(), 2);
+        assert_eq!(returns.get(0).unwrap(), 10_000_000, "doubling is +100%");
+        assert_eq!(returns.get(1).unwrap(), -7_500_000, "drop to a quarter is -75%");
+
+        let max_drawdown = PortfolioAnalytics::calculate_max_drawdown(&values);
+        assert_eq!(max_drawdown, 7_500_000);
+    }
+
+    #[test]
+    fn test_daily_returns_over_pushed_price_history() {
+        use soroban_sdk::testutils::Ledger;
+
+        let env = Env::default();
+        env.mock_all_auths();
+        let contract_id = env.register(crate::CounterContract, ());
+        let client = crate::CounterContractClient::new(&env, &contract_id);
+        let admin = Address::generate(&env);
+        env.as_contract(&contract_id, || {
+            env.storage().persistent().set(&crate::storage::ADMIN_KEY, &admin);
+        });
+
+        // One week of closes: +10%, -10%, flat, +10%, -50%, +20%
+        let closes = [10_000_000i128, 11_000_000, 9_900_000, 9_900_000, 10_890_000, 5_445_000, 6_534_000];
+        for (day, price) in closes.iter().enumerate() {
+            env.ledger().with_mut(|l| l.timestamp = day as u64 * 86_400 + 3_600);
+            // An earlier intraday print is superseded by the day's close
+            client.push_price(&admin, &Asset::XLM, &(price * 2));
+            client.push_price(&admin, &Asset::XLM, price);
+        }
+
+        let history = client.get_price_history(&Asset::XLM, &30);
+        assert_eq!(history, Vec::from_array(&env, closes));
+        assert_eq!(client.get_price_history(&Asset::XLM, &2), Vec::from_array(&env, [5_445_000i128, 6_534_000]));
+
+        let returns = PortfolioAnalytics::calculate_daily_returns(&history);
+        assert_eq!(returns, Vec::from_array(&env, [1_000_000i128, -1_000_000, 0, 1_000_000, -5_000_000, 2_000_000]));
+        env.as_contract(&contract_id, || {
+            assert_eq!(PortfolioAnalytics::get_asset_returns(&env, &Asset::XLM, 7), returns);
+        });
+    }
+
+    #[test]
+    fn test_configured_quote_asset_drives_pool_invariants_and_allocation() {
+        let env = Env::default();
+        env.mock_all_auths();
+        let contract_id = env.register(crate::CounterContract, ());
+        let client = crate::CounterContractClient::new(&env, &contract_id);
+        let admin = Address::generate(&env);
+        let user = Address::generate(&env);
+        env.as_contract(&contract_id, || {
+            env.storage().persistent().set(&crate::storage::ADMIN_KEY, &admin);
+        });
+
+        let usdc = symbol_short!("USDC");
+        client.set_quote_asset(&admin, &usdc);
+        assert_eq!(client.get_quote_asset(), usdc);
+
+        client.mint(&symbol_short!("XLM"), &user, &5_000);
+        client.mint(&usdc, &user, &5_000);
+        client.add_liquidity(&2_000, &2_000, &user);
+        let out = client.swap(&symbol_short!("XLM"), &usdc, &100, &user);
+        assert!(out > 0);
+
+        env.as_contract(&contract_id, || {
+            let portfolio: Portfolio = env.storage().instance().get(&()).unwrap();
+            assert_eq!(portfolio.quote_asset(), Asset::Custom(usdc.clone()));
+            assert!(portfolio.get_liquidity(Asset::Custom(usdc.clone())) > 0);
+            assert_eq!(portfolio.get_liquidity(Asset::Custom(symbol_short!("USDCSIM"))), 0);
+            assert_eq!(crate::invariants::verify_contract_invariants(&env, &portfolio), Ok(()));
+        });
+
+        let mut prices = Map::new(&env);
+        prices.set(Asset::XLM, 10_000_000);
+        prices.set(Asset::Custom(usdc.clone()), 10_000_000);
+        let allocation = client.get_asset_allocation(&user, &Some(prices));
+        assert_eq!(allocation.assets.len(), 2);
+        assert!(allocation.assets.iter().any(|(a, _)| a == Asset::Custom(usdc.clone())));
+        assert!(allocation.unpriced_assets.is_empty());
+
+        // The old symbol no longer trades, and the pool can't be relabelled while funded
+        assert_eq!(client.safe_swap(&symbol_short!("XLM"), &symbol_short!("USDCSIM"), &100, &user), 0);
+        assert!(client.try_set_quote_asset(&admin, &symbol_short!("USDCSIM")).is_err());
+    }
+
+    #[test]
+    fn test_price_history_is_capped_per_asset() {
+        let env = Env::default();
+        let contract_id = env.register(crate::CounterContract, ());
+
+        env.as_contract(&contract_id, || {
+            let cap = crate::price_history::PRICE_HISTORY_CAP_DAYS as u64;
+            for day in 0..cap + 5 {
+                crate::price_history::record_price(&env, Asset::XLM, 1_000_000 + day as i128, day * 86_400);
+            }
+
+            let history = crate::price_history::get_price_history(&env, &Asset::XLM, u32::MAX);
+            assert_eq!(history.len() as u64, cap);
+            assert_eq!(history.get(0), Some(1_000_005));
+        });
+    }
+
+    #[test]
+    fn test_annualize_monthly_return() {
+        // 5% over 30 days compounds to 1.05^(365/30) - 1 = 81.052% a year
+        let annualized = PortfolioAnalytics::annualize_return(500_000, 30);
+        assert!((annualized - 8_105_192).abs() <= 1_000, "got {}", annualized);
+
+        // A full year is left as is, a total loss stays at -100%
+        assert!((PortfolioAnalytics::annualize_return(500_000, 365) - 500_000).abs() <= 1_000);
+        assert_eq!(PortfolioAnalytics::annualize_return(-10_000_000, 30), -10_000_000);
+        assert_eq!(PortfolioAnalytics::annualize_return(-12_000_000, 30), -10_000_000);
+        assert_eq!(PortfolioAnalytics::annualize_return(500_000, 0), 500_000);
+    }
+}
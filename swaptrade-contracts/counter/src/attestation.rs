@@ -0,0 +1,130 @@
+//! Proof-of-reserves style attestation.
+//!
+//! One call snapshots every pool's reserves, LP supply and accumulated fees,
+//! together with the audit and governance Merkle roots last anchored by the
+//! admin, and hashes the lot into a single digest. Off-chain verifiers can
+//! record digests over time and re-derive them from the published fields.
+
+use soroban_sdk::{contracttype, symbol_short, xdr::ToXdr, BytesN, Env, Symbol, Vec};
+
+use crate::liquidity_pool::PoolRegistry;
+use crate::portfolio::Portfolio;
+use crate::storage::{ATTESTED_ROOTS_KEY, POOL_REGISTRY_KEY};
+
+/// Id the built-in XLM/quote pool is attested under; registry ids start at 1
+pub const BUILTIN_POOL_ID: u64 = 0;
+
+/// Reserves of one pool at attestation time
+#[derive(Clone, Debug, PartialEq)]
+#[contracttype]
+pub struct PoolReserves {
+    pub pool_id: u64,
+    pub token_a: Symbol,
+    pub token_b: Symbol,
+    pub reserve_a: i128,
+    pub reserve_b: i128,
+    pub total_lp_tokens: i128,
+    pub accumulated_fees: i128,
+}
+
+/// Audit-log and governance Merkle roots anchored on-chain by the admin.
+/// `anchored` is false, and the remaining fields all zero, until roots are
+/// first anchored.
+#[derive(Clone, Debug, PartialEq)]
+#[contracttype]
+pub struct AttestedRoots {
+    pub anchored: bool,
+    pub audit_root: BytesN<32>,
+    pub governance_root: BytesN<32>,
+    pub anchored_at: u64,
+}
+
+/// Snapshot of all pool reserves plus the anchored roots, with its digest
+#[derive(Clone, Debug, PartialEq)]
+#[contracttype]
+pub struct ReservesAttestation {
+    pub timestamp: u64,
+    /// Built-in pool first, then registry pools in ascending id order
+    pub pools: Vec<PoolReserves>,
+    pub roots: AttestedRoots,
+    /// sha256 over the XDR of `(timestamp, pools, roots)`
+    pub digest: BytesN<32>,
+}
+
+/// Digest a verifier recomputes from an attestation's published fields
+pub fn attestation_digest(
+    env: &Env,
+    timestamp: u64,
+    pools: &Vec<PoolReserves>,
+    roots: &AttestedRoots,
+) -> BytesN<32> {
+    let encoded = (timestamp, pools.clone(), roots.clone()).to_xdr(env);
+    env.crypto().sha256(&encoded).into()
+}
+
+/// Record the latest audit and governance roots for future attestations
+pub fn anchor_roots(env: &Env, audit_root: BytesN<32>, governance_root: BytesN<32>) {
+    let roots = AttestedRoots {
+        anchored: true,
+        audit_root,
+        governance_root,
+        anchored_at: env.ledger().timestamp(),
+    };
+    env.storage().instance().set(&ATTESTED_ROOTS_KEY, &roots);
+}
+
+/// Build an attestation from current contract state. Read-only.
+pub fn build_attestation(env: &Env) -> ReservesAttestation {
+    let portfolio: Portfolio = env
+        .storage()
+        .instance()
+        .get(&())
+        .unwrap_or_else(|| Portfolio::new(env));
+    let registry: PoolRegistry = env
+        .storage()
+        .instance()
+        .get(&POOL_REGISTRY_KEY)
+        .unwrap_or_else(|| PoolRegistry::new(env));
+    let roots: AttestedRoots = env
+        .storage()
+        .instance()
+        .get(&ATTESTED_ROOTS_KEY)
+        .unwrap_or_else(|| AttestedRoots {
+            anchored: false,
+            audit_root: BytesN::from_array(env, &[0u8; 32]),
+            governance_root: BytesN::from_array(env, &[0u8; 32]),
+            anchored_at: 0,
+        });
+
+    let (xlm_in_pool, quote_in_pool, fees_collected) = portfolio.get_pool_stats();
+    let mut pools = Vec::new(env);
+    pools.push_back(PoolReserves {
+        pool_id: BUILTIN_POOL_ID,
+        token_a: symbol_short!("XLM"),
+        token_b: portfolio.quote_symbol(),
+        reserve_a: xlm_in_pool,
+        reserve_b: quote_in_pool,
+        total_lp_tokens: portfolio.get_total_lp_tokens(),
+        accumulated_fees: fees_collected,
+    });
+    for pool in registry.get_all_pools(env, 0, registry.pool_count()).iter() {
+        pools.push_back(PoolReserves {
+            pool_id: pool.pool_id,
+            token_a: pool.token_a,
+            token_b: pool.token_b,
+            reserve_a: pool.reserve_a,
+            reserve_b: pool.reserve_b,
+            total_lp_tokens: pool.total_lp_tokens,
+            accumulated_fees: pool.cumulative_fees,
+        });
+    }
+
+    let timestamp = env.ledger().timestamp();
+    let digest = attestation_digest(env, timestamp, &pools, &roots);
+    ReservesAttestation {
+        timestamp,
+        pools,
+        roots,
+        digest,
+    }
+}
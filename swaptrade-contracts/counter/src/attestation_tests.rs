@@ -0,0 +1,67 @@
+#![cfg(test)]
+
+use super::*;
+use crate::attestation::{attestation_digest, BUILTIN_POOL_ID};
+use soroban_sdk::{symbol_short, testutils::{Address as _, Ledger}, Address, BytesN, Env};
+
+fn setup(env: &Env) -> (CounterContractClient<'_>, Address) {
+    env.mock_all_auths();
+    env.ledger().with_mut(|li| li.timestamp = 1_000);
+    let contract_id = env.register(CounterContract, ());
+    let client = CounterContractClient::new(env, &contract_id);
+    let user = Address::generate(env);
+    client.mint(&symbol_short!("XLM"), &user, &1_000);
+    (client, user)
+}
+
+#[test]
+fn test_attestation_digest_changes_only_with_state() {
+    let env = Env::default();
+    let (client, user) = setup(&env);
+
+    let first = client.attestation();
+    let second = client.attestation();
+    assert_eq!(first.digest, second.digest);
+    assert_eq!(first.pools.get(0).unwrap().pool_id, BUILTIN_POOL_ID);
+    assert!(!first.roots.anchored);
+    assert_eq!(first.roots.anchored_at, 0);
+
+    // The digest is reproducible from the published fields
+    let recomputed = env.as_contract(&client.address, || {
+        attestation_digest(&env, first.timestamp, &first.pools, &first.roots)
+    });
+    assert_eq!(recomputed, first.digest);
+
+    client.swap(&symbol_short!("XLM"), &symbol_short!("USDCSIM"), &500, &user);
+    let after_swap = client.attestation();
+    assert_ne!(after_swap.digest, first.digest);
+    assert_ne!(after_swap.pools.get(0).unwrap(), first.pools.get(0).unwrap());
+}
+
+#[test]
+fn test_anchored_roots_are_attested() {
+    let env = Env::default();
+    let (client, _) = setup(&env);
+    let admin = Address::generate(&env);
+    env.as_contract(&client.address, || {
+        env.storage().persistent().set(&crate::storage::ADMIN_KEY, &admin);
+    });
+
+    let audit_root = BytesN::from_array(&env, &[1u8; 32]);
+    let governance_root = BytesN::from_array(&env, &[2u8; 32]);
+    let outsider = Address::generate(&env);
+    assert_eq!(
+        client.try_anchor_attestation_roots(&outsider, &audit_root, &governance_root),
+        Err(Ok(SwapTradeError::NotAdmin))
+    );
+
+    let before = client.attestation();
+    client.anchor_attestation_roots(&admin, &audit_root, &governance_root);
+    let after = client.attestation();
+
+    assert!(!before.roots.anchored);
+    let roots = after.roots.clone();
+    assert!(roots.anchored);
+    assert_eq!((roots.audit_root, roots.governance_root), (audit_root, governance_root));
+    assert_ne!(after.digest, before.digest);
+}
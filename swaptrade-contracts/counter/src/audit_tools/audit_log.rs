@@ -2,9 +2,21 @@
 // Comprehensive audit trail with cryptographic chain-of-custody
 
 use std::collections::HashMap;
+use std::io::{self, Write};
 use std::time::{SystemTime, UNIX_EPOCH};
-use sha2::{Sha256, Digest};
 use serde::{Deserialize, Serialize};
+#[cfg(feature = "ed25519")]
+use ed25519_dalek::{Signature, Signer, SigningKey, Verifier, VerifyingKey};
+
+#[path = "canonical.rs"]
+mod canonical;
+use canonical::{canonical_encode, Field};
+
+#[path = "hash_alg.rs"]
+pub mod hash_alg;
+
+use crate::windowed_counter::WindowedCounter;
+pub use hash_alg::{HashAlg, HashAlgorithm};
 
 // ─── Event Taxonomy ──────────────────────────────────────────────────────────
 
@@ -24,8 +36,28 @@ pub enum Severity {
     Emergency,
 }
 
+impl Severity {
+    /// Numeric 0–10 level used by SIEM alerting rules.
+    pub fn level(&self) -> u8 {
+        match self {
+            Severity::Info => 2,
+            Severity::Warning => 5,
+            Severity::Critical => 8,
+            Severity::Emergency => 10,
+        }
+    }
+}
+
 // ─── Core Event Schema ────────────────────────────────────────────────────────
 
+/// Balance change carried by an event so historical balances can be replayed.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct StateDelta {
+    pub actor: String,
+    pub asset: String,
+    pub delta: i128,
+}
+
 /// The canonical on-chain event record.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AuditEvent {
@@ -51,26 +83,145 @@ pub struct AuditEvent {
     pub prev_hash: [u8; 32],
     /// SHA-256 fingerprint of this event (computed after all other fields are set)
     pub event_hash: [u8; 32],
+    /// Optional balance change; absent on events that don't move funds
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub state_delta: Option<StateDelta>,
 }
 
 impl AuditEvent {
-    /// Compute the canonical hash for this event (excluding the `event_hash` field itself).
+    /// Compute the canonical SHA-256 hash for this event (excluding the `event_hash` field itself).
     pub fn compute_hash(&self) -> [u8; 32] {
-        let mut hasher = Sha256::new();
-        hasher.update(self.id.to_le_bytes());
-        hasher.update(self.timestamp.to_le_bytes());
-        hasher.update(self.actor.as_bytes());
-        hasher.update(self.action.as_bytes());
-        hasher.update(self.target.as_bytes());
-        hasher.update(self.result.as_bytes());
-        hasher.update(self.gas_used.to_le_bytes());
-        hasher.update(self.state_hash);
-        hasher.update(self.prev_hash);
-        hasher.finalize().into()
+        self.compute_hash_with(HashAlgorithm::default())
+    }
+
+    /// `compute_hash` under an explicit algorithm.
+    pub fn compute_hash_with(&self, alg: HashAlgorithm) -> [u8; 32] {
+        let mut fields = vec![
+            Field::U64(self.id),
+            Field::U128(self.timestamp),
+            Field::Str(&self.actor),
+            Field::Str(&self.action),
+            Field::Str(&self.target),
+            Field::Str(&self.result),
+            Field::U64(self.gas_used),
+            Field::Bytes(&self.state_hash),
+            Field::Bytes(&self.prev_hash),
+        ];
+        if let Some(d) = &self.state_delta {
+            fields.push(Field::Str(&d.actor));
+            fields.push(Field::Str(&d.asset));
+            fields.push(Field::I128(d.delta));
+        }
+        alg.digest(&canonical_encode(&fields))
     }
 
     pub fn is_self_consistent(&self) -> bool {
-        self.event_hash == self.compute_hash()
+        self.is_self_consistent_with(HashAlgorithm::default())
+    }
+
+    pub fn is_self_consistent_with(&self, alg: HashAlgorithm) -> bool {
+        self.event_hash == self.compute_hash_with(alg)
+    }
+}
+
+/// Event contents before the log assigns an id, `prev_hash` and `event_hash`.
+/// Build with named setters so fields can't be swapped by position:
+///
+/// ```ignore
+/// log.record_event(
+///     RawEvent::new()
+///         .actor("alice")
+///         .action("TRADE_EXECUTE")
+///         .category(EventCategory::Trading)
+///         .severity(Severity::Info),
+/// );
+/// ```
+#[derive(Debug, Clone)]
+pub struct RawEvent {
+    pub actor: String,
+    pub action: String,
+    pub target: String,
+    pub result: String,
+    pub gas_used: u64,
+    pub state_hash: [u8; 32],
+    pub category: EventCategory,
+    pub severity: Severity,
+    pub state_delta: Option<StateDelta>,
+    /// Source timestamp for replayed events; `None` stamps the event on ingestion
+    pub timestamp: Option<u128>,
+}
+
+impl Default for RawEvent {
+    fn default() -> Self {
+        Self {
+            actor: String::new(),
+            action: String::new(),
+            target: String::new(),
+            result: "OK".into(),
+            gas_used: 0,
+            state_hash: [0u8; 32],
+            category: EventCategory::System,
+            severity: Severity::Info,
+            state_delta: None,
+            timestamp: None,
+        }
+    }
+}
+
+impl RawEvent {
+    /// Empty event: result "OK", no gas, zeroed state hash, System / Info.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn actor(mut self, actor: impl Into<String>) -> Self {
+        self.actor = actor.into();
+        self
+    }
+
+    pub fn action(mut self, action: impl Into<String>) -> Self {
+        self.action = action.into();
+        self
+    }
+
+    pub fn target(mut self, target: impl Into<String>) -> Self {
+        self.target = target.into();
+        self
+    }
+
+    pub fn result(mut self, result: impl Into<String>) -> Self {
+        self.result = result.into();
+        self
+    }
+
+    pub fn gas_used(mut self, gas_used: u64) -> Self {
+        self.gas_used = gas_used;
+        self
+    }
+
+    pub fn state_hash(mut self, state_hash: [u8; 32]) -> Self {
+        self.state_hash = state_hash;
+        self
+    }
+
+    pub fn category(mut self, category: EventCategory) -> Self {
+        self.category = category;
+        self
+    }
+
+    pub fn severity(mut self, severity: Severity) -> Self {
+        self.severity = severity;
+        self
+    }
+
+    pub fn state_delta(mut self, state_delta: StateDelta) -> Self {
+        self.state_delta = Some(state_delta);
+        self
+    }
+
+    pub fn timestamp(mut self, timestamp: u128) -> Self {
+        self.timestamp = Some(timestamp);
+        self
     }
 }
 
@@ -85,6 +236,10 @@ pub struct MerkleTree {
 
 impl MerkleTree {
     pub fn build(hashes: &[[u8; 32]]) -> Self {
+        Self::build_with(HashAlgorithm::default(), hashes)
+    }
+
+    pub fn build_with(alg: HashAlgorithm, hashes: &[[u8; 32]]) -> Self {
         if hashes.is_empty() {
             return Self { leaves: vec![], levels: vec![] };
         }
@@ -94,10 +249,8 @@ impl MerkleTree {
         while current.len() > 1 {
             let mut next = Vec::new();
             for chunk in current.chunks(2) {
-                let mut h = Sha256::new();
-                h.update(chunk[0]);
-                h.update(chunk.get(1).unwrap_or(&chunk[0])); // duplicate last if odd
-                next.push(h.finalize().into());
+                // duplicate last if odd
+                next.push(alg.digest_parts(&[&chunk[0], chunk.get(1).unwrap_or(&chunk[0])]));
             }
             levels.push(next.clone());
             current = next;
@@ -124,6 +277,31 @@ impl MerkleTree {
         }
         proof
     }
+
+    /// Check a proof produced by `proof(index)` for `leaf` against `root`.
+    pub fn verify_proof(leaf: [u8; 32], index: usize, proof: &[[u8; 32]], root: [u8; 32]) -> bool {
+        Self::verify_proof_with(HashAlgorithm::default(), leaf, index, proof, root)
+    }
+
+    pub fn verify_proof_with(
+        alg: HashAlgorithm,
+        leaf: [u8; 32],
+        index: usize,
+        proof: &[[u8; 32]],
+        root: [u8; 32],
+    ) -> bool {
+        let mut acc = leaf;
+        let mut idx = index;
+        for sibling in proof {
+            acc = if idx % 2 == 0 {
+                alg.digest_parts(&[&acc, sibling])
+            } else {
+                alg.digest_parts(&[sibling, &acc])
+            };
+            idx /= 2;
+        }
+        acc == root
+    }
 }
 
 // ─── Query Filters ────────────────────────────────────────────────────────────
@@ -138,6 +316,18 @@ pub struct EventFilter {
     pub time_to: Option<u128>,
 }
 
+impl EventFilter {
+    /// Whether `event` passes every criterion set on this filter
+    pub fn matches(&self, event: &AuditEvent) -> bool {
+        self.actor.as_ref().map_or(true, |a| &event.actor == a)
+            && self.action.as_ref().map_or(true, |a| &event.action == a)
+            && self.category.as_ref().map_or(true, |c| &event.category == c)
+            && self.severity_min.as_ref().map_or(true, |s| &event.severity >= s)
+            && self.time_from.map_or(true, |t| event.timestamp >= t)
+            && self.time_to.map_or(true, |t| event.timestamp <= t)
+    }
+}
+
 // ─── Anomaly Detection ────────────────────────────────────────────────────────
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -149,82 +339,181 @@ pub struct AnomalyAlert {
     pub severity: Severity,
 }
 
+/// Merkle inclusion proof for one event related to an anomaly alert
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AnomalyProof {
+    pub event_id: u64,
+    /// Sibling hashes from the event's leaf up to the root
+    pub path: Vec<[u8; 32]>,
+}
+
+/// Per-category observation windows and thresholds for anomaly detection.
+/// Per-actor counts saturate at `WINDOWED_COUNTER_CAPACITY`, so count
+/// thresholds must stay below it to ever fire.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AnomalyConfig {
+    pub trade_window_ns: u128,
+    pub admin_window_ns: u128,
+    /// Security signals are sparse, so they are watched over a longer window.
+    pub security_window_ns: u128,
+    pub max_trades_per_window: u64,
+    pub max_admin_per_window: u64,
+    pub max_failed_auth_per_window: u64,
+    pub liquidity_window_ns: u128,
+    /// Share of a pool's TVL (bps) one actor may remove per window before a Critical alert.
+    pub max_liquidity_removal_bps: u32,
+}
+
+/// Action verb marking a liquidity removal; `state_delta.delta` carries the amount
+/// and `target` names the pool.
+pub const LIQUIDITY_REMOVE_ACTION: &str = "LIQUIDITY_REMOVE";
+
+impl Default for AnomalyConfig {
+    fn default() -> Self {
+        Self {
+            trade_window_ns: 60_000_000_000,     // 1 minute
+            admin_window_ns: 60_000_000_000,     // 1 minute
+            security_window_ns: 600_000_000_000, // 10 minutes
+            max_trades_per_window: 50,
+            max_admin_per_window: 5,
+            max_failed_auth_per_window: 3,
+            liquidity_window_ns: 3_600_000_000_000, // 1 hour
+            max_liquidity_removal_bps: 2_000,
+        }
+    }
+}
+
 struct AnomalyDetector {
-    /// (actor, window_start_ns) → trade count
-    trade_window: HashMap<String, (u128, u64)>,
-    admin_window: HashMap<String, (u128, u64)>,
+    config: AnomalyConfig,
+    /// actor → recent event timestamps (ns) per category
+    trade_window: HashMap<String, WindowedCounter>,
+    admin_window: HashMap<String, WindowedCounter>,
+    failed_auth_window: HashMap<String, WindowedCounter>,
+    /// (actor, pool) → (window_start_ns, amount removed, event ids)
+    drain_window: HashMap<(String, String), (u128, u128, Vec<u64>)>,
+    /// Latest known TVL per pool, supplied by the caller
+    pool_tvl: HashMap<String, u128>,
     alert_counter: u64,
 }
 
 impl AnomalyDetector {
-    const TRADE_WINDOW_NS: u128 = 60_000_000_000; // 1 minute
-    const MAX_TRADES_PER_WINDOW: u64 = 50;
-    const MAX_ADMIN_PER_WINDOW: u64 = 5;
-
-    fn new() -> Self {
+    fn new(config: AnomalyConfig) -> Self {
         Self {
+            config,
             trade_window: HashMap::new(),
             admin_window: HashMap::new(),
+            failed_auth_window: HashMap::new(),
+            drain_window: HashMap::new(),
+            pool_tvl: HashMap::new(),
             alert_counter: 0,
         }
     }
 
     fn inspect(&mut self, event: &AuditEvent) -> Option<AnomalyAlert> {
+        if event.action == LIQUIDITY_REMOVE_ACTION
+            && matches!(event.category, EventCategory::System | EventCategory::Trading)
+        {
+            return self.check_liquidity_drain(event);
+        }
         match event.category {
             EventCategory::Trading => self.check_trade_volume(event),
             EventCategory::Administrative => self.check_admin_burst(event),
+            EventCategory::Security => self.check_failed_auth(event),
             _ => None,
         }
     }
 
+    /// Count `event` against `actor` and return how many of their events fall
+    /// in the trailing `window_ns`, this one included.
+    fn bump(window: &mut HashMap<String, WindowedCounter>, event: &AuditEvent, window_ns: u128) -> u64 {
+        // Nanosecond timestamps fit in u64 until the year 2554
+        let now = u64::try_from(event.timestamp).unwrap_or(u64::MAX);
+        let window_ns = u64::try_from(window_ns).unwrap_or(u64::MAX);
+        let counter = window.entry(event.actor.clone()).or_default();
+        counter.record(now);
+        counter.prune(now, window_ns);
+        counter.count_in_window(now, window_ns) as u64
+    }
+
+    fn next_alert(&mut self, event: &AuditEvent, description: String, severity: Severity) -> AnomalyAlert {
+        self.alert_counter += 1;
+        AnomalyAlert {
+            alert_id: self.alert_counter,
+            detected_at: now_ns(),
+            description,
+            related_event_ids: vec![event.id],
+            severity,
+        }
+    }
+
     fn check_trade_volume(&mut self, event: &AuditEvent) -> Option<AnomalyAlert> {
-        let entry = self.trade_window.entry(event.actor.clone()).or_insert((event.timestamp, 0));
-        if event.timestamp - entry.0 > Self::TRADE_WINDOW_NS {
-            *entry = (event.timestamp, 1);
-            None
-        } else {
-            entry.1 += 1;
-            if entry.1 > Self::MAX_TRADES_PER_WINDOW {
-                self.alert_counter += 1;
-                Some(AnomalyAlert {
-                    alert_id: self.alert_counter,
-                    detected_at: now_ns(),
-                    description: format!(
-                        "Actor '{}' exceeded {} trades/min (current: {})",
-                        event.actor, Self::MAX_TRADES_PER_WINDOW, entry.1
-                    ),
-                    related_event_ids: vec![event.id],
-                    severity: Severity::Warning,
-                })
-            } else {
-                None
-            }
+        let count = Self::bump(&mut self.trade_window, event, self.config.trade_window_ns);
+        if count <= self.config.max_trades_per_window {
+            return None;
         }
+        let description = format!(
+            "Actor '{}' exceeded {} trades/min (current: {})",
+            event.actor, self.config.max_trades_per_window, count
+        );
+        Some(self.next_alert(event, description, Severity::Warning))
     }
 
     fn check_admin_burst(&mut self, event: &AuditEvent) -> Option<AnomalyAlert> {
-        let entry = self.admin_window.entry(event.actor.clone()).or_insert((event.timestamp, 0));
-        if event.timestamp - entry.0 > Self::TRADE_WINDOW_NS {
-            *entry = (event.timestamp, 1);
-            None
-        } else {
-            entry.1 += 1;
-            if entry.1 > Self::MAX_ADMIN_PER_WINDOW {
-                self.alert_counter += 1;
-                Some(AnomalyAlert {
-                    alert_id: self.alert_counter,
-                    detected_at: now_ns(),
-                    description: format!(
-                        "Suspicious admin burst from '{}': {} actions/min",
-                        event.actor, entry.1
-                    ),
-                    related_event_ids: vec![event.id],
-                    severity: Severity::Critical,
-                })
-            } else {
-                None
-            }
+        let count = Self::bump(&mut self.admin_window, event, self.config.admin_window_ns);
+        if count <= self.config.max_admin_per_window {
+            return None;
         }
+        let description = format!(
+            "Suspicious admin burst from '{}': {} actions/min",
+            event.actor, count
+        );
+        Some(self.next_alert(event, description, Severity::Critical))
+    }
+
+    /// Security events with a non-"OK" result are treated as failed auth attempts.
+    fn check_failed_auth(&mut self, event: &AuditEvent) -> Option<AnomalyAlert> {
+        if event.result == "OK" {
+            return None;
+        }
+        let count = Self::bump(&mut self.failed_auth_window, event, self.config.security_window_ns);
+        if count <= self.config.max_failed_auth_per_window {
+            return None;
+        }
+        let description = format!(
+            "Repeated failed auth from '{}': {} failures in window",
+            event.actor, count
+        );
+        Some(self.next_alert(event, description, Severity::Critical))
+    }
+
+    /// Sum an actor's removals from one pool over the window and alert once the
+    /// total crosses the configured share of that pool's TVL. Pools with no
+    /// known TVL are not checked.
+    fn check_liquidity_drain(&mut self, event: &AuditEvent) -> Option<AnomalyAlert> {
+        let amount = event.state_delta.as_ref()?.delta.unsigned_abs();
+        let tvl = *self.pool_tvl.get(&event.target)?;
+        let key = (event.actor.clone(), event.target.clone());
+        let entry = self
+            .drain_window
+            .entry(key.clone())
+            .or_insert((event.timestamp, 0, Vec::new()));
+        if event.timestamp.saturating_sub(entry.0) >= self.config.liquidity_window_ns {
+            *entry = (event.timestamp, 0, Vec::new());
+        }
+        entry.1 = entry.1.saturating_add(amount);
+        entry.2.push(event.id);
+        if entry.1.saturating_mul(10_000) <= tvl.saturating_mul(self.config.max_liquidity_removal_bps as u128) {
+            return None;
+        }
+
+        let (_, removed, ids) = self.drain_window.remove(&key)?;
+        let description = format!(
+            "Liquidity drain by '{}' on '{}': removed {} of {} TVL in window",
+            event.actor, event.target, removed, tvl
+        );
+        let mut alert = self.next_alert(event, description, Severity::Critical);
+        alert.related_event_ids = ids;
+        Some(alert)
     }
 }
 
@@ -258,6 +547,9 @@ pub struct SiemRecord {
     pub result: String,
     pub category: String,
     pub severity: String,
+    /// Numeric form of `severity` (see `Severity::level`)
+    #[serde(default)]
+    pub severity_level: u8,
     pub integrity_hash: String,
 }
 
@@ -272,6 +564,7 @@ impl From<&AuditEvent> for SiemRecord {
             result: e.result.clone(),
             category: format!("{:?}", e.category),
             severity: format!("{:?}", e.severity),
+            severity_level: e.severity.level(),
             integrity_hash: hex::encode(e.event_hash),
         }
     }
@@ -279,14 +572,100 @@ impl From<&AuditEvent> for SiemRecord {
 
 // ─── Forensic Export ─────────────────────────────────────────────────────────
 
+/// Version of the `ForensicReport` JSON layout. Bump whenever fields change.
+///
+/// 1 – initial layout
+/// 2 – `anomalies` section
+/// 3 – numeric `severity_level` on SIEM records
+/// 4 – event hashes use the length-prefixed `canonical_encode` layout
+/// 5 – `hash_alg` names the digest behind event hashes and the Merkle root
+/// 6 – `genesis_hash`, `segments` and `retention_anchor` so the chain can be
+///     verified across seals and after retention
+pub const FORENSIC_REPORT_SCHEMA_VERSION: u32 = 6;
+
+/// Reports written before versioning carry no `schema_version`.
+fn legacy_schema_version() -> u32 {
+    1
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct ForensicReport {
+    #[serde(default = "legacy_schema_version")]
+    pub schema_version: u32,
     pub incident_id: String,
     pub generated_at: u128,
     pub events: Vec<AuditEvent>,
     pub merkle_root: Option<String>,
     pub chain_valid: bool,
     pub siem_records: Vec<SiemRecord>,
+    /// Anomalies detected while the events were recorded
+    #[serde(default)]
+    pub anomalies: Vec<AnomalyAlert>,
+    /// Digest used for event hashes and the Merkle root
+    #[serde(default)]
+    pub hash_alg: HashAlgorithm,
+    /// `prev_hash` of the log's very first event
+    #[serde(default)]
+    pub genesis_hash: [u8; 32],
+    /// Seals of every segment closed so far
+    #[serde(default)]
+    pub segments: Vec<SegmentSeal>,
+    /// Hash the first exported event links to when retention evicted its
+    /// predecessors
+    #[serde(default)]
+    pub retention_anchor: Option<[u8; 32]>,
+}
+
+// ─── Chain Segments ───────────────────────────────────────────────────────────
+
+/// Boundary of a sealed sub-chain. The first event after the seal uses `root`
+/// as its `prev_hash`, anchoring the next segment to this one.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct SegmentSeal {
+    pub segment: u32,
+    pub first_event_id: u64,
+    pub last_event_id: u64,
+    /// Merkle root over the segment's event hashes
+    pub merkle_root: [u8; 32],
+    /// `seal_root` of the fields above; never equal to an event hash, so a
+    /// seam cannot be re-linked as if the segment had never been sealed
+    pub root: [u8; 32],
+}
+
+/// Domain-separated seal hash:
+/// `H("seal" ‖ segment ‖ first_event_id ‖ last_event_id ‖ merkle_root)`.
+pub fn seal_root(
+    alg: HashAlgorithm,
+    segment: u32,
+    first_event_id: u64,
+    last_event_id: u64,
+    merkle_root: &[u8; 32],
+) -> [u8; 32] {
+    alg.digest(&canonical_encode(&[
+        Field::Str("seal"),
+        Field::U64(segment as u64),
+        Field::U64(first_event_id),
+        Field::U64(last_event_id),
+        Field::Bytes(merkle_root),
+    ]))
+}
+
+// ─── Signed Checkpoints ───────────────────────────────────────────────────────
+
+/// Merkle root committed by one batch flush, signed by the log's signer.
+#[cfg(feature = "ed25519")]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SignedCheckpoint {
+    /// Id of the last event in the flushed batch
+    pub batch_end_id: u64,
+    pub root: [u8; 32],
+    pub signature: [u8; 64],
+}
+
+/// Bytes signed for a checkpoint: `batch_end_id` (little-endian) then `root`.
+#[cfg(feature = "ed25519")]
+fn checkpoint_message(batch_end_id: u64, root: &[u8; 32]) -> Vec<u8> {
+    [&batch_end_id.to_le_bytes()[..], &root[..]].concat()
 }
 
 // ─── Main AuditLog Contract ───────────────────────────────────────────────────
@@ -303,24 +682,81 @@ pub struct AuditLog {
     anomaly_detector: AnomalyDetector,
     pub anomaly_alerts: Vec<AnomalyAlert>,
     pub retention: RetentionPolicy,
+    /// `prev_hash` of the very first event
+    genesis_hash: [u8; 32],
+    segments: Vec<SegmentSeal>,
+    /// Anchor for the next recorded event after a seal
+    next_prev_hash: Option<[u8; 32]>,
+    /// Hash the oldest retained event links to once retention has evicted
+    /// its predecessors
+    retention_anchor: Option<[u8; 32]>,
+    /// Digest for event hashes and Merkle nodes; fixed for the log's lifetime
+    hash_alg: HashAlgorithm,
+    /// Key that signs the Merkle root on every batch flush, if set
+    #[cfg(feature = "ed25519")]
+    signer: Option<SigningKey>,
+    #[cfg(feature = "ed25519")]
+    checkpoints: Vec<SignedCheckpoint>,
 }
 
 impl AuditLog {
     pub const MAX_BATCH_SIZE: usize = 100;
 
     pub fn new() -> Self {
+        Self::with_genesis([0u8; 32])
+    }
+
+    /// Start a log whose first event links to `genesis_hash` instead of all-zeros,
+    /// e.g. to continue from a previously archived log.
+    pub fn with_genesis(genesis_hash: [u8; 32]) -> Self {
+        Self::with_genesis_and_hash(genesis_hash, HashAlgorithm::default())
+    }
+
+    /// Start an empty log that hashes with `alg` instead of SHA-256.
+    pub fn with_hash_alg(alg: HashAlgorithm) -> Self {
+        Self::with_genesis_and_hash([0u8; 32], alg)
+    }
+
+    pub fn with_genesis_and_hash(genesis_hash: [u8; 32], hash_alg: HashAlgorithm) -> Self {
         Self {
             events: Vec::new(),
             index: HashMap::new(),
             counter: 0,
             merkle: None,
             pending_batch: Vec::new(),
-            anomaly_detector: AnomalyDetector::new(),
+            anomaly_detector: AnomalyDetector::new(AnomalyConfig::default()),
             anomaly_alerts: Vec::new(),
             retention: RetentionPolicy::default(),
+            genesis_hash,
+            segments: Vec::new(),
+            next_prev_hash: None,
+            retention_anchor: None,
+            hash_alg,
+            #[cfg(feature = "ed25519")]
+            signer: None,
+            #[cfg(feature = "ed25519")]
+            checkpoints: Vec::new(),
         }
     }
 
+    pub fn hash_alg(&self) -> HashAlgorithm {
+        self.hash_alg
+    }
+
+    /// Replace the anomaly windows and thresholds; in-flight windows are reset.
+    pub fn set_anomaly_config(&mut self, config: AnomalyConfig) {
+        self.anomaly_detector = AnomalyDetector {
+            alert_counter: self.anomaly_detector.alert_counter,
+            pool_tvl: std::mem::take(&mut self.anomaly_detector.pool_tvl),
+            ..AnomalyDetector::new(config)
+        };
+    }
+
+    /// Record a pool's current TVL for liquidity-drain detection.
+    pub fn set_pool_tvl(&mut self, pool: impl Into<String>, tvl: u128) {
+        self.anomaly_detector.pool_tvl.insert(pool.into(), tvl);
+    }
+
     // ── Recording ────────────────────────────────────────────────────────────
 
     pub fn record(
@@ -334,24 +770,64 @@ impl AuditLog {
         category: EventCategory,
         severity: Severity,
     ) -> u64 {
-        let prev_hash = self.events.last().map(|e| e.event_hash).unwrap_or([0u8; 32]);
+        self.record_event(
+            RawEvent::new()
+                .actor(actor)
+                .action(action)
+                .target(target)
+                .result(result)
+                .gas_used(gas_used)
+                .state_hash(state_hash)
+                .category(category)
+                .severity(severity),
+        )
+    }
+
+    /// Record one event built with `RawEvent`'s named setters.
+    pub fn record_event(&mut self, raw: RawEvent) -> u64 {
+        let id = self.enqueue(raw);
+
+        if self.pending_batch.len() >= Self::MAX_BATCH_SIZE {
+            self.flush_batch();
+        }
+
+        id
+    }
+
+    /// Record a batch of events with a single flush and Merkle rebuild at the
+    /// end. Returns the assigned ids in input order.
+    pub fn record_many(&mut self, events: impl Iterator<Item = RawEvent>) -> Vec<u64> {
+        let ids = events.map(|raw| self.enqueue(raw)).collect();
+        self.flush_batch();
+        ids
+    }
+
+    /// Assign id and chain links to `raw`, run anomaly detection and append it
+    /// to the pending batch.
+    fn enqueue(&mut self, raw: RawEvent) -> u64 {
+        let prev_hash = self
+            .next_prev_hash
+            .take()
+            .or_else(|| self.pending_batch.last().or(self.events.last()).map(|e| e.event_hash))
+            .unwrap_or_else(|| self.head_prev_hash());
         self.counter += 1;
 
         let mut event = AuditEvent {
             id: self.counter,
-            timestamp: now_ns(),
-            actor: actor.into(),
-            action: action.into(),
-            target: target.into(),
-            result: result.into(),
-            gas_used,
-            state_hash,
-            category,
-            severity,
+            timestamp: raw.timestamp.unwrap_or_else(now_ns),
+            actor: raw.actor,
+            action: raw.action,
+            target: raw.target,
+            result: raw.result,
+            gas_used: raw.gas_used,
+            state_hash: raw.state_hash,
+            category: raw.category,
+            severity: raw.severity,
             prev_hash,
             event_hash: [0u8; 32],
+            state_delta: raw.state_delta,
         };
-        event.event_hash = event.compute_hash();
+        event.event_hash = event.compute_hash_with(self.hash_alg);
 
         // Anomaly detection
         if let Some(alert) = self.anomaly_detector.inspect(&event) {
@@ -359,11 +835,6 @@ impl AuditLog {
         }
 
         self.pending_batch.push(event);
-
-        if self.pending_batch.len() >= Self::MAX_BATCH_SIZE {
-            self.flush_batch();
-        }
-
         self.counter
     }
 
@@ -372,6 +843,8 @@ impl AuditLog {
         if self.pending_batch.is_empty() {
             return;
         }
+        #[cfg(feature = "ed25519")]
+        let batch_end_id = self.pending_batch.last().map_or(0, |e| e.id);
         for event in self.pending_batch.drain(..) {
             let idx = self.events.len();
             self.index.insert(event.id, idx);
@@ -379,6 +852,47 @@ impl AuditLog {
         }
         self.rebuild_merkle();
         self.apply_retention();
+
+        #[cfg(feature = "ed25519")]
+        self.sign_checkpoint(batch_end_id);
+    }
+
+    // ── Signed checkpoints ────────────────────────────────────────────────────
+
+    /// Sign `(batch_end_id, merkle_root)` with `key` on every later flush.
+    #[cfg(feature = "ed25519")]
+    pub fn set_signer(&mut self, key: SigningKey) {
+        self.signer = Some(key);
+    }
+
+    #[cfg(feature = "ed25519")]
+    pub fn checkpoints(&self) -> &[SignedCheckpoint] {
+        &self.checkpoints
+    }
+
+    #[cfg(feature = "ed25519")]
+    fn sign_checkpoint(&mut self, batch_end_id: u64) {
+        let (Some(signer), Some(root)) = (&self.signer, self.merkle_root()) else {
+            return;
+        };
+        let signature = signer.sign(&checkpoint_message(batch_end_id, &root));
+        self.checkpoints.push(SignedCheckpoint {
+            batch_end_id,
+            root,
+            signature: signature.to_bytes(),
+        });
+    }
+
+    /// Check every checkpoint signature against `pubkey`.
+    #[cfg(feature = "ed25519")]
+    pub fn verify_checkpoints(&self, pubkey: &VerifyingKey) -> Result<(), String> {
+        for checkpoint in &self.checkpoints {
+            let signature = Signature::from_bytes(&checkpoint.signature);
+            pubkey
+                .verify(&checkpoint_message(checkpoint.batch_end_id, &checkpoint.root), &signature)
+                .map_err(|_| format!("Checkpoint at event {} has an invalid signature", checkpoint.batch_end_id))?;
+        }
+        Ok(())
     }
 
     // ── Query ─────────────────────────────────────────────────────────────────
@@ -387,17 +901,7 @@ impl AuditLog {
         self.events
             .iter()
             .enumerate()
-            .filter(|(_, e)| {
-                filter.actor.as_ref().map_or(true, |a| &e.actor == a)
-                    && filter.action.as_ref().map_or(true, |a| &e.action == a)
-                    && filter.category.as_ref().map_or(true, |c| &e.category == c)
-                    && filter
-                        .severity_min
-                        .as_ref()
-                        .map_or(true, |s| &e.severity >= s)
-                    && filter.time_from.map_or(true, |t| e.timestamp >= t)
-                    && filter.time_to.map_or(true, |t| e.timestamp <= t)
-            })
+            .filter(|(_, e)| filter.matches(e))
             .map(|(idx, e)| {
                 let proof = self
                     .merkle
@@ -419,20 +923,17 @@ impl AuditLog {
         let event = &self.events[idx];
 
         // 1. Self-consistency
-        if !event.is_self_consistent() {
+        if !event.is_self_consistent_with(self.hash_alg) {
             return Err(format!("Event {} hash mismatch – tampered!", event_id));
         }
 
         // 2. Chain linkage
-        if idx > 0 {
-            let prev = &self.events[idx - 1];
-            if event.prev_hash != prev.event_hash {
-                return Err(format!(
-                    "Event {} chain broken at predecessor {}",
-                    event_id,
-                    event_id - 1
-                ));
-            }
+        if event.prev_hash != self.expected_prev_hash(idx) {
+            return Err(format!(
+                "Event {} chain broken at predecessor {}",
+                event_id,
+                event_id - 1
+            ));
         }
 
         // 3. Return Merkle proof root
@@ -445,17 +946,239 @@ impl AuditLog {
 
     /// Verify the entire chain from genesis to tip.
     pub fn verify_chain(&self) -> Result<(), String> {
+        // The oldest retained event must link to the log's recorded origin;
+        // anything else means earlier history was cut away or replaced.
+        if let Some(first) = self.events.first() {
+            if first.prev_hash != self.head_prev_hash() {
+                let origin = if self.retention_anchor.is_some() { "retention anchor" } else { "genesis hash" };
+                return Err(format!(
+                    "Chain broken: first event {} prev_hash does not match the {}",
+                    first.id, origin
+                ));
+            }
+        }
         for (i, event) in self.events.iter().enumerate() {
-            if !event.is_self_consistent() {
+            if !event.is_self_consistent_with(self.hash_alg) {
                 return Err(format!("Chain broken: event {} hash invalid", event.id));
             }
-            if i > 0 && event.prev_hash != self.events[i - 1].event_hash {
+            if event.prev_hash != self.expected_prev_hash(i) {
                 return Err(format!("Chain broken: event {} prev_hash mismatch", event.id));
             }
         }
+        self.verify_seals()
+    }
+
+    /// Every seal must be numbered in order and hash to its recorded root; seals
+    /// whose events are all still retained must also match their Merkle root.
+    fn verify_seals(&self) -> Result<(), String> {
+        for (i, seal) in self.segments.iter().enumerate() {
+            let expected = seal_root(
+                self.hash_alg,
+                i as u32,
+                seal.first_event_id,
+                seal.last_event_id,
+                &seal.merkle_root,
+            );
+            if seal.segment != i as u32 || seal.root != expected {
+                return Err(format!("Chain broken: segment {} seal invalid", i));
+            }
+            let hashes: Vec<[u8; 32]> = self
+                .events
+                .iter()
+                .filter(|e| (seal.first_event_id..=seal.last_event_id).contains(&e.id))
+                .map(|e| e.event_hash)
+                .collect();
+            let retained = hashes.len() as u64 == seal.last_event_id - seal.first_event_id + 1;
+            if retained && MerkleTree::build_with(self.hash_alg, &hashes).root() != Some(seal.merkle_root) {
+                return Err(format!("Chain broken: segment {} Merkle root mismatch", i));
+            }
+        }
+        Ok(())
+    }
+
+    // ── Segmentation ──────────────────────────────────────────────────────────
+
+    /// Seal the current segment and return its seal root (see `seal_root`).
+    /// The next recorded event starts a new segment with that root as its
+    /// `prev_hash`.
+    pub fn seal_segment(&mut self) -> [u8; 32] {
+        self.flush_batch();
+
+        let after_id = self.segments.last().map(|s| s.last_event_id).unwrap_or(0);
+        let segment_events: Vec<&AuditEvent> =
+            self.events.iter().filter(|e| e.id > after_id).collect();
+
+        let (first, last) = match (segment_events.first(), segment_events.last()) {
+            (Some(f), Some(l)) => (f.id, l.id),
+            // Nothing recorded since the last seal: keep the current anchor
+            _ => {
+                return self
+                    .segments
+                    .last()
+                    .map(|s| s.root)
+                    .unwrap_or(self.genesis_hash)
+            }
+        };
+
+        let hashes: Vec<[u8; 32]> = segment_events.iter().map(|e| e.event_hash).collect();
+        let merkle_root = MerkleTree::build_with(self.hash_alg, &hashes).root().unwrap_or([0u8; 32]);
+        let segment = self.segments.len() as u32;
+        let root = seal_root(self.hash_alg, segment, first, last, &merkle_root);
+
+        self.segments.push(SegmentSeal {
+            segment,
+            first_event_id: first,
+            last_event_id: last,
+            merkle_root,
+            root,
+        });
+        self.next_prev_hash = Some(root);
+        root
+    }
+
+    pub fn segments(&self) -> &[SegmentSeal] {
+        &self.segments
+    }
+
+    /// Hash the oldest retained event must link to: the genesis hash, or the
+    /// anchor left behind by the last retention eviction.
+    pub fn head_prev_hash(&self) -> [u8; 32] {
+        self.retention_anchor.unwrap_or(self.genesis_hash)
+    }
+
+    /// `prev_hash` the event at `idx` must carry: the predecessor's hash, the
+    /// sealing root when the predecessor closed a segment, or the head anchor.
+    fn expected_prev_hash(&self, idx: usize) -> [u8; 32] {
+        match idx.checked_sub(1) {
+            Some(prev) => self.successor_link(&self.events[prev]),
+            None => self.head_prev_hash(),
+        }
+    }
+
+    /// `prev_hash` an event following `prev` carries.
+    fn successor_link(&self, prev: &AuditEvent) -> [u8; 32] {
+        self.segments
+            .iter()
+            .find(|s| s.last_event_id == prev.id)
+            .map(|s| s.root)
+            .unwrap_or(prev.event_hash)
+    }
+
+    // ── Replica merge ─────────────────────────────────────────────────────────
+
+    /// Append the part of `other` recorded past this log's tip.
+    ///
+    /// Both chains are verified on their own first; they must use the same hash
+    /// algorithm and genesis hash, and every event id both still hold must carry
+    /// the same hash. `other`'s tail (with any segment seals covering it) is then
+    /// appended, re-inspected for anomalies, and the index and Merkle tree rebuilt.
+    /// On error the log is left unchanged.
+    pub fn merge(&mut self, mut other: AuditLog) -> Result<(), String> {
+        self.flush_batch();
+        other.flush_batch();
+
+        if self.hash_alg != other.hash_alg {
+            return Err(format!(
+                "Cannot merge: hash algorithm {:?} differs from {:?}",
+                other.hash_alg, self.hash_alg
+            ));
+        }
+        if self.genesis_hash != other.genesis_hash {
+            return Err("Cannot merge: genesis hashes differ".into());
+        }
+        self.verify_chain().map_err(|e| format!("Local log invalid: {}", e))?;
+        other.verify_chain().map_err(|e| format!("Merged log invalid: {}", e))?;
+
+        // Shared prefix: ids held by both sides must agree
+        for event in other.events.iter().filter(|e| e.id <= self.counter) {
+            if let Some(&idx) = self.index.get(&event.id) {
+                if self.events[idx].event_hash != event.event_hash {
+                    return Err(format!("Chains diverge at event {}", event.id));
+                }
+            }
+        }
+
+        let tail: Vec<AuditEvent> = other.events.iter().filter(|e| e.id > self.counter).cloned().collect();
+        let first_id = match tail.first() {
+            Some(first) if first.id == self.counter + 1 => first.id,
+            Some(first) => {
+                return Err(format!(
+                    "Cannot merge: events {}..{} are missing from both logs",
+                    self.counter + 1,
+                    first.id - 1
+                ))
+            }
+            // Nothing past our tip
+            None => return Ok(()),
+        };
+
+        // Seals from `other` must pick up where ours stop and not split our tail
+        let sealed_to = self.segments.last().map_or(0, |s| s.last_event_id);
+        let new_seals: Vec<SegmentSeal> =
+            other.segments.iter().filter(|s| s.last_event_id > sealed_to).cloned().collect();
+        if let Some(seal) = new_seals.first() {
+            if seal.first_event_id != sealed_to + 1 || seal.last_event_id < self.counter {
+                return Err(format!("Chains diverge at segment boundary after event {}", seal.last_event_id));
+            }
+        }
+
+        let (events_len, segments_len) = (self.events.len(), self.segments.len());
+        let (counter, next_prev_hash) = (self.counter, self.next_prev_hash);
+        // Seal roots commit to their segment number, so they are kept as-is;
+        // verify_chain below rejects any that do not continue our numbering
+        self.segments.extend(new_seals);
+        self.counter = tail.last().map_or(counter, |e| e.id);
+        self.next_prev_hash = other.next_prev_hash;
+        self.events.extend(tail);
+
+        // The tail's first link is the only one not already checked on `other`
+        if let Err(e) = self.verify_chain() {
+            self.events.truncate(events_len);
+            self.segments.truncate(segments_len);
+            self.counter = counter;
+            self.next_prev_hash = next_prev_hash;
+            return Err(format!("Chains diverge at event {}: {}", first_id, e));
+        }
+
+        for idx in events_len..self.events.len() {
+            self.index.insert(self.events[idx].id, idx);
+            if let Some(alert) = self.anomaly_detector.inspect(&self.events[idx]) {
+                self.anomaly_alerts.push(alert);
+            }
+        }
+        self.rebuild_merkle();
+        self.apply_retention();
+
+        #[cfg(feature = "ed25519")]
+        self.sign_checkpoint(self.counter);
         Ok(())
     }
 
+    /// Pair each anomaly alert with Merkle inclusion proofs for its related events.
+    /// Events that are still pending or already evicted are skipped.
+    pub fn anomaly_with_proofs(&self) -> Vec<(AnomalyAlert, Vec<AnomalyProof>)> {
+        let merkle = match self.merkle.as_ref() {
+            Some(m) => m,
+            None => return self.anomaly_alerts.iter().map(|a| (a.clone(), Vec::new())).collect(),
+        };
+
+        self.anomaly_alerts
+            .iter()
+            .map(|alert| {
+                let proofs = alert
+                    .related_event_ids
+                    .iter()
+                    .filter_map(|id| {
+                        self.index
+                            .get(id)
+                            .map(|&idx| AnomalyProof { event_id: *id, path: merkle.proof(idx) })
+                    })
+                    .collect();
+                (alert.clone(), proofs)
+            })
+            .collect()
+    }
+
     // ── Forensic Export ───────────────────────────────────────────────────────
 
     pub fn forensic_export(&self, incident_id: impl Into<String>) -> ForensicReport {
@@ -469,22 +1192,45 @@ impl AuditLog {
             .map(hex::encode);
 
         ForensicReport {
+            schema_version: FORENSIC_REPORT_SCHEMA_VERSION,
             incident_id: incident_id.into(),
             generated_at: now_ns(),
             events: all_events,
             merkle_root,
             chain_valid,
             siem_records,
+            anomalies: self.anomaly_alerts.clone(),
+            hash_alg: self.hash_alg,
+            genesis_hash: self.genesis_hash,
+            segments: self.segments.clone(),
+            retention_anchor: self.retention_anchor,
         }
     }
 
     /// Export events matching a filter as SIEM-ready JSON strings (NDJSON).
+    /// Convenience wrapper over `siem_export_to_writer` for small logs.
     pub fn siem_export(&self, filter: &EventFilter) -> String {
-        self.query_events(filter)
-            .iter()
-            .map(|(e, _)| serde_json::to_string(&SiemRecord::from(*e)).unwrap_or_default())
-            .collect::<Vec<_>>()
-            .join("\n")
+        let mut buf = Vec::new();
+        // Writing into a Vec cannot fail, and SiemRecord always serializes
+        let _ = self.siem_export_to_writer(filter, &mut buf);
+        let mut ndjson = String::from_utf8(buf).unwrap_or_default();
+        if ndjson.ends_with('\n') {
+            ndjson.pop();
+        }
+        ndjson
+    }
+
+    /// Stream events matching a filter to `writer` as NDJSON, one record per
+    /// line, without buffering the whole export. Returns the records written.
+    pub fn siem_export_to_writer<W: Write>(&self, filter: &EventFilter, mut writer: W) -> io::Result<usize> {
+        let mut written = 0;
+        for event in self.events.iter().filter(|e| filter.matches(e)) {
+            serde_json::to_writer(&mut writer, &SiemRecord::from(event))?;
+            writer.write_all(b"\n")?;
+            written += 1;
+        }
+        writer.flush()?;
+        Ok(written)
     }
 
     // ── State Reconstruction ─────────────────────────────────────────────────
@@ -498,6 +1244,23 @@ impl AuditLog {
             .map(|e| e.state_hash)
     }
 
+    /// Balance of `actor` in `asset` as of `until_id`, obtained by summing the
+    /// recorded state deltas. The chain is verified before replaying.
+    pub fn balance_at(&self, actor: &str, asset: &str, until_id: u64) -> Result<i128, String> {
+        self.verify_chain()?;
+        if until_id > self.counter {
+            return Err(format!("Event {} not recorded", until_id));
+        }
+        Ok(self
+            .events
+            .iter()
+            .chain(self.pending_batch.iter())
+            .take_while(|e| e.id <= until_id)
+            .filter_map(|e| e.state_delta.as_ref())
+            .filter(|d| d.actor == actor && d.asset == asset)
+            .fold(0i128, |acc, d| acc.saturating_add(d.delta)))
+    }
+
     // ── Internal helpers ──────────────────────────────────────────────────────
 
     fn rebuild_merkle(&mut self) {
@@ -505,7 +1268,7 @@ impl AuditLog {
         self.merkle = if hashes.is_empty() {
             None
         } else {
-            Some(MerkleTree::build(&hashes))
+            Some(MerkleTree::build_with(self.hash_alg, &hashes))
         };
     }
 
@@ -522,6 +1285,10 @@ impl AuditLog {
             if let Some(hook) = &self.retention.archive_hook {
                 hook(&expired);
             }
+            // Keep the link the surviving head (or the next event) verifies against
+            if let Some(last_evicted) = expired.last() {
+                self.retention_anchor = Some(self.successor_link(last_evicted));
+            }
             self.events.retain(|e| e.timestamp >= cutoff);
             // Rebuild index
             self.index.clear();
@@ -536,6 +1303,11 @@ impl AuditLog {
         self.events.len()
     }
 
+    /// Merkle root over committed events, if any.
+    pub fn merkle_root(&self) -> Option<[u8; 32]> {
+        self.merkle.as_ref().and_then(|m| m.root())
+    }
+
     pub fn pending_len(&self) -> usize {
         self.pending_batch.len()
     }
@@ -559,4 +1331,7 @@ pub fn now_ns() -> u128 {
 fn format_ns(ns: u128) -> String {
     let secs = ns / 1_000_000_000;
     format!("{}", secs) // simplified; production would use chrono
-}
\ No newline at end of file
+}
+#[cfg(test)]
+#[path = "audit_log_test_.rs"]
+mod audit_log_test_;
@@ -69,6 +69,35 @@ mod tests {
         assert_eq!(log.events[0].prev_hash, [0u8; 32]);
     }
 
+    #[test]
+    fn test_forged_genesis_prev_hash_rejected() {
+        let mut log = AuditLog::new();
+        record_event(&mut log, "root", "INIT", EventCategory::System);
+        log.flush_batch();
+
+        // A self-consistent first event that claims a predecessor
+        log.events[0].prev_hash = [7u8; 32];
+        log.events[0].event_hash = log.events[0].compute_hash_with(log.hash_alg());
+
+        let err = log.verify_chain().unwrap_err();
+        assert!(err.contains("genesis hash"), "unexpected error: {}", err);
+    }
+
+    #[test]
+    fn test_spliced_log_missing_its_beginning_rejected() {
+        let mut log = AuditLog::new();
+        for i in 0..3 {
+            record_event(&mut log, "alice", &format!("ACT_{}", i), EventCategory::System);
+        }
+        log.flush_batch();
+
+        // Every remaining link is intact, but the log no longer starts at genesis
+        log.events.remove(0);
+
+        let err = log.verify_chain().unwrap_err();
+        assert!(err.contains("first event 2"), "unexpected error: {}", err);
+    }
+
     #[test]
     fn test_chained_prev_hash() {
         let mut log = AuditLog::new();
@@ -79,6 +108,63 @@ mod tests {
         assert_eq!(log.events[1].prev_hash, log.events[0].event_hash);
     }
 
+    // ── Segmentation ──────────────────────────────────────────────────────────
+
+    #[test]
+    fn test_seal_segment_anchors_next_segment() {
+        let mut log = AuditLog::new();
+        for i in 0..3 {
+            record_event(&mut log, "alice", &format!("S0_{}", i), EventCategory::System);
+        }
+        let root0 = log.seal_segment();
+
+        for i in 0..2 {
+            record_event(&mut log, "alice", &format!("S1_{}", i), EventCategory::System);
+        }
+        let root1 = log.seal_segment();
+        record_event(&mut log, "alice", "S2_0", EventCategory::System);
+        log.flush_batch();
+
+        assert!(log.verify_chain().is_ok());
+        assert_eq!(log.events[3].prev_hash, root0);
+        assert_eq!(log.events[5].prev_hash, root1);
+
+        let segments = log.segments();
+        assert_eq!(segments.len(), 2);
+        assert_eq!((segments[0].first_event_id, segments[0].last_event_id), (1, 3));
+        assert_eq!((segments[1].first_event_id, segments[1].last_event_id), (4, 5));
+
+        let seg0: Vec<[u8; 32]> = log.events[..3].iter().map(|e| e.event_hash).collect();
+        let seg1: Vec<[u8; 32]> = log.events[3..5].iter().map(|e| e.event_hash).collect();
+        assert_eq!(MerkleTree::build(&seg0).root(), Some(segments[0].merkle_root));
+        assert_eq!(MerkleTree::build(&seg1).root(), Some(segments[1].merkle_root));
+        assert_eq!(segments[0].root, seal_root(HashAlgorithm::Sha256, 0, 1, 3, &segments[0].merkle_root));
+        assert_ne!(segments[0].root, segments[0].merkle_root);
+    }
+
+    #[test]
+    fn test_seal_segment_seam_tamper_detected() {
+        let mut log = AuditLog::new();
+        record_event(&mut log, "alice", "A", EventCategory::System);
+        log.seal_segment();
+        record_event(&mut log, "alice", "B", EventCategory::System);
+        log.flush_batch();
+
+        // Re-link across the seam as if the segment had never been sealed
+        log.events[1].prev_hash = log.events[0].event_hash;
+        log.events[1].event_hash = log.events[1].compute_hash();
+        assert!(log.verify_chain().is_err());
+    }
+
+    #[test]
+    fn test_custom_genesis_hash() {
+        let mut log = AuditLog::with_genesis(state(9));
+        record_event(&mut log, "root", "INIT", EventCategory::System);
+        log.flush_batch();
+        assert_eq!(log.events[0].prev_hash, state(9));
+        assert!(log.verify_chain().is_ok());
+    }
+
     // ── verify_event_integrity ────────────────────────────────────────────────
 
     #[test]
@@ -207,6 +293,128 @@ mod tests {
             .any(|a| matches!(a.severity, Severity::Critical)));
     }
 
+    #[test]
+    fn test_anomaly_security_burst_uses_own_window() {
+        let mut log = AuditLog::new();
+        // Trading window too short to ever accumulate; security window long
+        log.set_anomaly_config(AnomalyConfig {
+            trade_window_ns: 0,
+            security_window_ns: 3_600_000_000_000,
+            max_trades_per_window: 1,
+            max_failed_auth_per_window: 3,
+            ..AnomalyConfig::default()
+        });
+
+        for _ in 0..10 {
+            log.record("bot", "TRADE_EXECUTE", "PAIR_XY", "OK", 21_000, state(1), EventCategory::Trading, Severity::Info);
+        }
+        for _ in 0..4 {
+            log.record("mallory", "AUTH_LOGIN", "ADMIN_PANEL", "BAD_SIGNATURE", 0, state(1), EventCategory::Security, Severity::Warning);
+        }
+
+        assert!(log.anomaly_alerts.iter().all(|a| !a.description.contains("bot")));
+        assert_eq!(log.anomaly_alerts.len(), 1);
+        assert!(log.anomaly_alerts[0].description.contains("mallory"));
+        assert!(matches!(log.anomaly_alerts[0].severity, Severity::Critical));
+    }
+
+    #[test]
+    fn test_anomaly_trading_window_independent_of_security_window() {
+        let mut log = AuditLog::new();
+        // Now the security window is too short, while trading accumulates
+        log.set_anomaly_config(AnomalyConfig {
+            trade_window_ns: 3_600_000_000_000,
+            security_window_ns: 0,
+            max_trades_per_window: 3,
+            max_failed_auth_per_window: 1,
+            ..AnomalyConfig::default()
+        });
+
+        for _ in 0..4 {
+            log.record("mallory", "AUTH_LOGIN", "ADMIN_PANEL", "BAD_SIGNATURE", 0, state(1), EventCategory::Security, Severity::Warning);
+        }
+        for _ in 0..4 {
+            log.record("bot", "TRADE_EXECUTE", "PAIR_XY", "OK", 21_000, state(1), EventCategory::Trading, Severity::Info);
+        }
+
+        assert_eq!(log.anomaly_alerts.len(), 1);
+        assert!(log.anomaly_alerts[0].description.contains("bot"));
+    }
+
+    #[test]
+    fn test_liquidity_drain_alert_lists_removal_events() {
+        let mut log = AuditLog::new();
+        log.set_pool_tvl("POOL_XY", 1_000_000);
+
+        let removal = |amount: i128| {
+            RawEvent::new()
+                .actor("whale")
+                .action(LIQUIDITY_REMOVE_ACTION)
+                .target("POOL_XY")
+                .category(EventCategory::Trading)
+                .state_delta(StateDelta { actor: "whale".into(), asset: "POOL_XY".into(), delta: amount })
+        };
+
+        // 15% alone stays under the 20% default; the second 15% crosses it
+        let first = log.record_event(removal(150_000));
+        assert!(log.anomaly_alerts.is_empty());
+        let second = log.record_event(removal(150_000));
+
+        assert_eq!(log.anomaly_alerts.len(), 1);
+        let alert = &log.anomaly_alerts[0];
+        assert!(matches!(alert.severity, Severity::Critical));
+        assert_eq!(alert.related_event_ids, vec![first, second]);
+        assert!(alert.description.contains("whale"));
+    }
+
+    #[test]
+    fn test_successful_security_events_are_not_failed_auth() {
+        let mut log = AuditLog::new();
+        for _ in 0..10 {
+            log.record("alice", "AUTH_LOGIN", "APP", "OK", 0, state(1), EventCategory::Security, Severity::Info);
+        }
+        assert!(log.anomaly_alerts.is_empty());
+    }
+
+    #[test]
+    fn test_anomaly_with_proofs_verify_against_root() {
+        let mut log = AuditLog::new();
+        for _ in 0..=6 {
+            log.record(
+                "attacker", "ADMIN_ROLE_GRANT", "USER", "OK",
+                50_000, state(2), EventCategory::Administrative, Severity::Warning,
+            );
+        }
+        log.flush_batch();
+        let root = log.verify_event_integrity(1).unwrap();
+
+        let with_proofs = log.anomaly_with_proofs();
+        assert!(!with_proofs.is_empty());
+        for (alert, proofs) in &with_proofs {
+            assert_eq!(proofs.len(), alert.related_event_ids.len());
+            for proof in proofs {
+                let idx = log.events.iter().position(|e| e.id == proof.event_id).unwrap();
+                let leaf = log.events[idx].event_hash;
+                assert!(MerkleTree::verify_proof(leaf, idx, &proof.path, root));
+            }
+        }
+    }
+
+    #[test]
+    fn test_anomaly_with_proofs_skips_unindexed() {
+        let mut log = AuditLog::new();
+        for _ in 0..=6 {
+            log.record(
+                "attacker", "ADMIN_ROLE_GRANT", "USER", "OK",
+                50_000, state(2), EventCategory::Administrative, Severity::Warning,
+            );
+        }
+        // Not flushed: related events are still pending
+        let with_proofs = log.anomaly_with_proofs();
+        assert!(!with_proofs.is_empty());
+        assert!(with_proofs.iter().all(|(_, proofs)| proofs.is_empty()));
+    }
+
     // ── Forensic export ───────────────────────────────────────────────────────
 
     #[test]
@@ -222,6 +430,36 @@ mod tests {
         assert_eq!(report.events.len(), 2);
         assert!(report.merkle_root.is_some());
         assert_eq!(report.siem_records.len(), 2);
+        assert_eq!(report.schema_version, FORENSIC_REPORT_SCHEMA_VERSION);
+    }
+
+    #[test]
+    fn test_forensic_report_legacy_schema_version() {
+        let json = r#"{"incident_id":"OLD","generated_at":0,"events":[],"merkle_root":null,"chain_valid":true,"siem_records":[]}"#;
+        let report: ForensicReport = serde_json::from_str(json).unwrap();
+        assert_eq!(report.schema_version, 1);
+        assert!(report.anomalies.is_empty());
+    }
+
+    #[test]
+    fn test_forensic_export_includes_anomalies() {
+        let mut log = AuditLog::new();
+        for _ in 0..=60 {
+            log.record(
+                "hft_bot", "TRADE_EXECUTE", "PAIR_XY", "OK",
+                21_000, state(1), EventCategory::Trading, Severity::Info,
+            );
+        }
+        log.flush_batch();
+
+        let report = log.forensic_export("INC-BURST");
+        assert!(!report.anomalies.is_empty());
+        assert!(report.anomalies.iter().any(|a| a.description.contains("hft_bot")));
+
+        // Anomalies survive the JSON round-trip consumed by the CLI
+        let json = serde_json::to_string(&report).unwrap();
+        let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(value["anomalies"][0]["severity"], "Warning");
     }
 
     // ── SIEM export ───────────────────────────────────────────────────────────
@@ -240,6 +478,56 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_siem_export_to_writer_streams_one_record_per_line() {
+        let mut log = AuditLog::new();
+        record_event(&mut log, "alice", "TRADE", EventCategory::Trading);
+        record_event(&mut log, "bob", "TRADE", EventCategory::Trading);
+        record_event(&mut log, "admin", "PAUSE", EventCategory::Administrative);
+        log.flush_batch();
+
+        let filter = EventFilter { category: Some(EventCategory::Trading), ..Default::default() };
+        let mut buf: Vec<u8> = Vec::new();
+        let written = log.siem_export_to_writer(&filter, &mut buf).unwrap();
+
+        let output = String::from_utf8(buf).unwrap();
+        let records: Vec<SiemRecord> = output
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(written, 2);
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[0].actor, "alice");
+        assert_eq!(records[1].actor, "bob");
+
+        // The String form is the same stream without the trailing newline
+        assert_eq!(log.siem_export(&filter), output.trim_end());
+    }
+
+    #[test]
+    fn test_siem_severity_level_mapping() {
+        let cases = [
+            (Severity::Info, 2u8),
+            (Severity::Warning, 5),
+            (Severity::Critical, 8),
+            (Severity::Emergency, 10),
+        ];
+        let mut log = AuditLog::new();
+        for (severity, _) in cases.iter() {
+            log.record("alice", "ACT", "target", "OK", 0, state(1), EventCategory::System, severity.clone());
+        }
+        log.flush_batch();
+
+        let lines: Vec<String> = log.siem_export(&EventFilter::default()).lines().map(String::from).collect();
+        assert_eq!(lines.len(), cases.len());
+        for (line, (severity, level)) in lines.iter().zip(cases.iter()) {
+            assert_eq!(severity.level(), *level);
+            let record: SiemRecord = serde_json::from_str(line).unwrap();
+            assert_eq!(record.severity_level, *level);
+            assert_eq!(record.severity, format!("{:?}", severity));
+        }
+    }
+
     // ── State reconstruction ──────────────────────────────────────────────────
 
     #[test]
@@ -256,6 +544,80 @@ mod tests {
         assert_eq!(s2[0], 20);
     }
 
+    #[test]
+    fn test_balance_at_replays_deltas() {
+        let mut log = AuditLog::new();
+        let delta = |actor: &str, asset: &str, delta: i128| StateDelta {
+            actor: actor.into(),
+            asset: asset.into(),
+            delta,
+        };
+        let event = |actor: &str, action: &str, hash: [u8; 32], change: StateDelta| {
+            RawEvent::new()
+                .actor(actor)
+                .action(action)
+                .target("XLM")
+                .state_hash(hash)
+                .category(EventCategory::Trading)
+                .state_delta(change)
+        };
+        let mint = log.record_event(event("alice", "MINT", state(1), delta("alice", "XLM", 1_000)));
+        let other = log.record_event(event("bob", "MINT", state(2), delta("bob", "XLM", 500)));
+        let debit = log.record_event(event("alice", "SWAP", state(3), delta("alice", "XLM", -300)));
+        let no_delta = record_event(&mut log, "alice", "LOGIN", EventCategory::System);
+        log.flush_batch();
+
+        assert_eq!(log.balance_at("alice", "XLM", mint).unwrap(), 1_000);
+        assert_eq!(log.balance_at("alice", "XLM", other).unwrap(), 1_000);
+        assert_eq!(log.balance_at("alice", "XLM", debit).unwrap(), 700);
+        assert_eq!(log.balance_at("alice", "XLM", no_delta).unwrap(), 700);
+        assert_eq!(log.balance_at("alice", "USDC", no_delta).unwrap(), 0);
+        assert_eq!(log.balance_at("bob", "XLM", mint).unwrap(), 0);
+        assert!(log.balance_at("alice", "XLM", 99).is_err());
+    }
+
+    #[test]
+    fn test_state_delta_is_hashed() {
+        let mut log = AuditLog::new();
+        let id = log.record_event(
+            RawEvent::new()
+                .actor("alice")
+                .action("MINT")
+                .target("XLM")
+                .state_hash(state(1))
+                .category(EventCategory::Trading)
+                .state_delta(StateDelta { actor: "alice".into(), asset: "XLM".into(), delta: 10 }),
+        );
+        log.flush_batch();
+
+        let mut event = log.forensic_export("INC-DELTA").events
+            .into_iter()
+            .find(|e| e.id == id)
+            .unwrap();
+        assert!(event.is_self_consistent());
+        event.state_delta.as_mut().unwrap().delta = 10_000;
+        assert!(!event.is_self_consistent(), "tampered delta must break the hash");
+    }
+
+    #[test]
+    fn test_field_boundaries_change_hash() {
+        let mut log = AuditLog::new();
+        record_event(&mut log, "ab", "c", EventCategory::System);
+        log.flush_batch();
+
+        let original = log.events[0].clone();
+        let mut shifted = original.clone();
+        shifted.actor = "a".into();
+        shifted.action = "bc".into();
+
+        // Same concatenated bytes, different field boundaries
+        assert_eq!(
+            format!("{}{}", original.actor, original.action),
+            format!("{}{}", shifted.actor, shifted.action)
+        );
+        assert_ne!(original.compute_hash(), shifted.compute_hash());
+    }
+
     // ── Retention ─────────────────────────────────────────────────────────────
 
     #[test]
@@ -282,4 +644,226 @@ mod tests {
         assert_eq!(log.len(), 0);
         assert!(!archived.lock().unwrap().is_empty());
     }
-}
\ No newline at end of file
+    #[test]
+    fn test_chain_verifies_after_retention_eviction() {
+        let mut log = AuditLog::new();
+        log.retention.hot_retention_ns = 0;
+        for i in 0..3u8 {
+            log.record("alice", "OLD_EVENT", "t", "OK", 0, state(i), EventCategory::System, Severity::Info);
+        }
+        log.flush_batch();
+        assert_eq!(log.len(), 0);
+        let anchor = log.head_prev_hash();
+        assert_ne!(anchor, [0u8; 32]);
+
+        // Stop evicting; the next events must chain onto the evicted tail
+        log.retention.hot_retention_ns = u128::MAX;
+        log.record("bob", "NEW_EVENT", "t", "OK", 0, state(7), EventCategory::System, Severity::Info);
+        log.record("bob", "NEW_EVENT", "t", "OK", 0, state(8), EventCategory::System, Severity::Info);
+        log.flush_batch();
+
+        assert_eq!(log.len(), 2);
+        assert!(log.verify_chain().is_ok());
+        assert!(log.verify_event_integrity(4).is_ok());
+        assert_eq!(log.head_prev_hash(), anchor);
+    }
+    // ── Batch recording ───────────────────────────────────────────────────────
+
+    #[test]
+    fn test_record_many_matches_individual_records() {
+        let mut single = AuditLog::new();
+        for i in 0..250u64 {
+            single.record("alice", "TRADE_EXECUTE", format!("pool{}", i % 7), "OK", i, state(i as u8), EventCategory::Trading, Severity::Info);
+        }
+        single.flush_batch();
+
+        // Replay with the same timestamps so event hashes line up
+        let raws: Vec<RawEvent> = single
+            .forensic_export("replay")
+            .events
+            .into_iter()
+            .map(|e| RawEvent {
+                actor: e.actor,
+                action: e.action,
+                target: e.target,
+                result: e.result,
+                gas_used: e.gas_used,
+                state_hash: e.state_hash,
+                category: e.category,
+                severity: e.severity,
+                state_delta: e.state_delta,
+                timestamp: Some(e.timestamp),
+            })
+            .collect();
+
+        let mut batched = AuditLog::new();
+        let ids = batched.record_many(raws.into_iter());
+
+        assert_eq!(ids, (1..=250).collect::<Vec<u64>>());
+        assert_eq!(batched.len(), 250);
+        assert_eq!(batched.pending_len(), 0);
+        assert!(batched.verify_chain().is_ok());
+        assert!(batched.merkle_root().is_some());
+        assert_eq!(batched.merkle_root(), single.merkle_root());
+    }
+    // ── Hash algorithm ────────────────────────────────────────────────────────
+
+    #[test]
+    fn test_chain_verifies_under_each_hash_alg() {
+        #[allow(unused_mut)]
+        let mut algs = vec![HashAlgorithm::Sha256];
+        #[cfg(feature = "keccak")]
+        algs.push(HashAlgorithm::Keccak256);
+        #[cfg(feature = "blake3")]
+        algs.push(HashAlgorithm::Blake3);
+        for alg in algs {
+            let mut log = AuditLog::with_hash_alg(alg);
+            for i in 0..5u8 {
+                log.record("alice", "TRADE_EXECUTE", "pool", "OK", 0, state(i), EventCategory::Trading, Severity::Info);
+            }
+            log.flush_batch();
+
+            assert!(log.verify_chain().is_ok(), "{:?} chain must verify", alg);
+            assert!(log.events.iter().all(|e| e.is_self_consistent_with(alg)));
+
+            let report = log.forensic_export("alg");
+            assert_eq!(report.hash_alg, alg);
+            let root = log.merkle_root().unwrap();
+            for (event, proof) in log.query_events(&EventFilter::default()) {
+                let idx = (event.id - 1) as usize;
+                assert!(MerkleTree::verify_proof_with(alg, event.event_hash, idx, &proof, root));
+            }
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "keccak")]
+    fn test_hash_alg_mismatch_fails_verification() {
+        let mut log = AuditLog::with_hash_alg(HashAlgorithm::Keccak256);
+        log.record("alice", "TRADE_EXECUTE", "pool", "OK", 0, state(1), EventCategory::Trading, Severity::Info);
+        log.flush_batch();
+        assert!(log.verify_chain().is_ok());
+        assert!(!log.events[0].is_self_consistent_with(HashAlgorithm::Sha256));
+    }
+    // ── RawEvent builder ──────────────────────────────────────────────────────
+
+    #[test]
+    fn test_raw_event_builder_sets_named_fields() {
+        let mut log = AuditLog::new();
+        // Setters are order-independent: category before actor, severity first
+        let id = log.record_event(
+            RawEvent::new()
+                .severity(Severity::Critical)
+                .category(EventCategory::Administrative)
+                .target("fee_bps")
+                .action("ADMIN_SET_FEE")
+                .actor("admin"),
+        );
+        log.flush_batch();
+
+        let event = &log.events[0];
+        assert_eq!(event.id, id);
+        assert_eq!(event.actor, "admin");
+        assert_eq!(event.action, "ADMIN_SET_FEE");
+        assert_eq!(event.target, "fee_bps");
+        assert_eq!(event.result, "OK");
+        assert_eq!(event.category, EventCategory::Administrative);
+        assert_eq!(event.severity, Severity::Critical);
+        // Defaults
+        assert_eq!(event.gas_used, 0);
+        assert_eq!(event.state_hash, [0u8; 32]);
+        assert!(event.state_delta.is_none());
+        assert!(log.verify_chain().is_ok());
+    }
+
+    // ── Signed checkpoints ────────────────────────────────────────────────────
+
+    #[cfg(feature = "ed25519")]
+    #[test]
+    fn test_batch_flush_checkpoints_are_signed() {
+        use ed25519_dalek::SigningKey;
+
+        let key = SigningKey::from_bytes(&[7u8; 32]);
+        let mut log = AuditLog::new();
+        log.set_signer(key.clone());
+
+        record_event(&mut log, "alice", "TRADE_EXECUTE", EventCategory::Trading);
+        log.flush_batch();
+        record_event(&mut log, "bob", "TRADE_EXECUTE", EventCategory::Trading);
+        record_event(&mut log, "carol", "TRADE_EXECUTE", EventCategory::Trading);
+        log.flush_batch();
+
+        let checkpoints = log.checkpoints();
+        assert_eq!(checkpoints.len(), 2);
+        assert_eq!(checkpoints[0].batch_end_id, 1);
+        assert_eq!(checkpoints[1].batch_end_id, 3);
+        assert_eq!(Some(checkpoints[1].root), log.merkle_root());
+        assert!(log.verify_checkpoints(&key.verifying_key()).is_ok());
+
+        // Another key's signatures do not verify
+        let other = SigningKey::from_bytes(&[8u8; 32]);
+        assert!(log.verify_checkpoints(&other.verifying_key()).is_err());
+
+        log.checkpoints[0].root[0] ^= 0xff;
+        let err = log.verify_checkpoints(&key.verifying_key()).unwrap_err();
+        assert!(err.contains("event 1"), "{}", err);
+    }
+
+    // ── Replica merge ─────────────────────────────────────────────────────────
+
+    /// Replay `events` into `log` with fixed timestamps so replicas hash alike.
+    fn replay(log: &mut AuditLog, ts: u128, events: &[(&str, u8)]) {
+        for &(actor, n) in events {
+            log.record_event(
+                RawEvent::new()
+                    .actor(actor)
+                    .action("TRADE_EXECUTE")
+                    .category(EventCategory::Trading)
+                    .state_hash(state(n))
+                    .timestamp(ts),
+            );
+        }
+        log.flush_batch();
+    }
+
+    #[test]
+    fn test_merge_appends_tail_of_extended_replica() {
+        let ts = now_ns();
+        let mut local = AuditLog::new();
+        replay(&mut local, ts, &[("alice", 1), ("bob", 2)]);
+        let mut remote = AuditLog::new();
+        replay(&mut remote, ts, &[("alice", 1), ("bob", 2), ("carol", 3), ("dave", 4)]);
+        let remote_root = remote.merkle_root();
+
+        local.merge(remote).unwrap();
+
+        assert_eq!(local.len(), 4);
+        assert!(local.verify_chain().is_ok());
+        assert!(local.verify_event_integrity(4).is_ok());
+        assert_eq!(local.merkle_root(), remote_root);
+
+        // Recording continues from the merged tip
+        let id = record_event(&mut local, "erin", "TRADE_EXECUTE", EventCategory::Trading);
+        local.flush_batch();
+        assert_eq!(id, 5);
+        assert!(local.verify_chain().is_ok());
+    }
+
+    #[test]
+    fn test_merge_rejects_conflicting_fork() {
+        let ts = now_ns();
+        let mut local = AuditLog::new();
+        replay(&mut local, ts, &[("alice", 1), ("bob", 2)]);
+        let mut remote = AuditLog::new();
+        replay(&mut remote, ts, &[("alice", 1), ("mallory", 9), ("carol", 3)]);
+        let root = local.merkle_root();
+
+        let err = local.merge(remote).unwrap_err();
+        assert!(err.contains("event 2"), "{}", err);
+
+        // Local log untouched
+        assert_eq!(local.len(), 2);
+        assert_eq!(local.merkle_root(), root);
+        assert!(local.verify_chain().is_ok());
+    }
+}
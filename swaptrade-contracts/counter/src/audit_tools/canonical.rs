@@ -0,0 +1,49 @@
+// audit_tools/canonical.rs
+// Platform-independent byte encoding for audit event hashing.
+//
+// Shared by `AuditEvent::compute_hash` (audit_log.rs) and the CLI's
+// `recompute_hash` (main.rs) so both sides hash identical bytes.
+//
+// Layout: u32 field count, then per field a 1-byte type tag, a u32 payload
+// length and the payload. All integers are big-endian, so the encoding does
+// not depend on host endianness and no two field sequences share bytes.
+
+/// A single value fed into `canonical_encode`.
+pub enum Field<'a> {
+    U64(u64),
+    U128(u128),
+    I128(i128),
+    Bytes(&'a [u8]),
+    Str(&'a str),
+}
+
+impl Field<'_> {
+    fn tag(&self) -> u8 {
+        match self {
+            Field::U64(_) => 1,
+            Field::U128(_) => 2,
+            Field::I128(_) => 3,
+            Field::Bytes(_) => 4,
+            Field::Str(_) => 5,
+        }
+    }
+}
+
+/// Length-prefixed, fixed-endian encoding of `fields`.
+pub fn canonical_encode(fields: &[Field]) -> Vec<u8> {
+    let mut out = Vec::new();
+    out.extend_from_slice(&(fields.len() as u32).to_be_bytes());
+    for field in fields {
+        let payload: Vec<u8> = match field {
+            Field::U64(v) => v.to_be_bytes().to_vec(),
+            Field::U128(v) => v.to_be_bytes().to_vec(),
+            Field::I128(v) => v.to_be_bytes().to_vec(),
+            Field::Bytes(b) => b.to_vec(),
+            Field::Str(s) => s.as_bytes().to_vec(),
+        };
+        out.push(field.tag());
+        out.extend_from_slice(&(payload.len() as u32).to_be_bytes());
+        out.extend_from_slice(&payload);
+    }
+    out
+}
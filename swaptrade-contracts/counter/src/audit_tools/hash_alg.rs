@@ -0,0 +1,43 @@
+// src/hash_alg.rs
+// Pluggable 32-byte digest for event hashes, Merkle nodes and governance ids.
+//
+// SHA-256 is always available and is the default. Keccak-256 and BLAKE3 are
+// behind the `keccak` and `blake3` features for chains that verify with them.
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+pub trait HashAlg {
+    fn digest(&self, data: &[u8]) -> [u8; 32];
+
+    /// Hash the concatenation of `parts` as one message.
+    fn digest_parts(&self, parts: &[&[u8]]) -> [u8; 32] {
+        self.digest(&parts.concat())
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HashAlgorithm {
+    #[default]
+    Sha256,
+    #[cfg(feature = "keccak")]
+    Keccak256,
+    #[cfg(feature = "blake3")]
+    Blake3,
+}
+
+impl HashAlg for HashAlgorithm {
+    fn digest(&self, data: &[u8]) -> [u8; 32] {
+        match self {
+            HashAlgorithm::Sha256 => Sha256::digest(data).into(),
+            #[cfg(feature = "keccak")]
+            HashAlgorithm::Keccak256 => {
+                use sha3::Keccak256;
+                Keccak256::digest(data).into()
+            }
+            #[cfg(feature = "blake3")]
+            HashAlgorithm::Blake3 => *blake3::hash(data).as_bytes(),
+        }
+    }
+}
@@ -3,8 +3,13 @@
 
 use std::fs;
 use std::path::PathBuf;
-use clap::{Parser, Subcommand};
-use sha2::{Sha256, Digest};
+use clap::{Parser, Subcommand, ValueEnum};
+
+mod canonical;
+use canonical::{canonical_encode, Field};
+
+mod hash_alg;
+use hash_alg::{HashAlg, HashAlgorithm};
 
 // ─── CLI Definition ───────────────────────────────────────────────────────────
 
@@ -43,24 +48,64 @@ enum Command {
         #[arg(help = "Path to forensic_report.json")]
         file: PathBuf,
     },
-    /// Export events to NDJSON for SIEM ingestion
+    /// Export events for SIEM ingestion (NDJSON, ArcSight CEF or QRadar LEEF)
     SiemExport {
         #[arg(help = "Path to forensic_report.json")]
         file: PathBuf,
         #[arg(short, long, help = "Output file (stdout if omitted)")]
         output: Option<PathBuf>,
+        #[arg(long, value_enum, default_value_t = SiemFormat::Ndjson, help = "Output format")]
+        format: SiemFormat,
+    },
+    /// Run verify and merkle-check on every *.json report in a directory
+    VerifyDir {
+        #[arg(help = "Directory of forensic reports")]
+        dir: PathBuf,
     },
     /// Re-derive Merkle root from event hashes to confirm report root
     MerkleCheck {
         #[arg(help = "Path to forensic_report.json")]
         file: PathBuf,
     },
+    /// List anomaly alerts captured in an exported report
+    Alerts {
+        #[arg(help = "Path to forensic_report.json")]
+        file: PathBuf,
+        #[arg(long, help = "Minimum severity (Info|Warning|Critical|Emergency)")]
+        min_severity: Option<String>,
+    },
+    /// Reconstruct an actor's balance as of a given event by replaying state deltas
+    BalanceAt {
+        #[arg(help = "Path to forensic_report.json")]
+        file: PathBuf,
+        #[arg(long, help = "Actor whose balance to reconstruct")]
+        actor: String,
+        #[arg(long, help = "Asset symbol")]
+        asset: String,
+        #[arg(long, help = "Replay up to and including this event id")]
+        event_id: u64,
+    },
+}
+
+/// Line format for `siem-export`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum SiemFormat {
+    Ndjson,
+    Cef,
+    Leef,
 }
 
 // ─── Shared data structures (mirrors audit_log.rs – kept minimal for the tool) ──
 
 use serde::{Deserialize, Serialize};
 
+#[derive(Debug, Deserialize, Serialize)]
+struct StateDelta {
+    actor: String,
+    asset: String,
+    delta: i128,
+}
+
 #[derive(Debug, Deserialize, Serialize)]
 struct AuditEvent {
     id: u64,
@@ -75,21 +120,29 @@ struct AuditEvent {
     severity: String,
     prev_hash: [u8; 32],
     event_hash: [u8; 32],
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    state_delta: Option<StateDelta>,
 }
 
 impl AuditEvent {
-    fn recompute_hash(&self) -> [u8; 32] {
-        let mut h = Sha256::new();
-        h.update(self.id.to_le_bytes());
-        h.update(self.timestamp.to_le_bytes());
-        h.update(self.actor.as_bytes());
-        h.update(self.action.as_bytes());
-        h.update(self.target.as_bytes());
-        h.update(self.result.as_bytes());
-        h.update(self.gas_used.to_le_bytes());
-        h.update(self.state_hash);
-        h.update(self.prev_hash);
-        h.finalize().into()
+    fn recompute_hash(&self, alg: HashAlgorithm) -> [u8; 32] {
+        let mut fields = vec![
+            Field::U64(self.id),
+            Field::U128(self.timestamp),
+            Field::Str(&self.actor),
+            Field::Str(&self.action),
+            Field::Str(&self.target),
+            Field::Str(&self.result),
+            Field::U64(self.gas_used),
+            Field::Bytes(&self.state_hash),
+            Field::Bytes(&self.prev_hash),
+        ];
+        if let Some(d) = &self.state_delta {
+            fields.push(Field::Str(&d.actor));
+            fields.push(Field::Str(&d.asset));
+            fields.push(Field::I128(d.delta));
+        }
+        alg.digest(&canonical_encode(&fields))
     }
 }
 
@@ -103,22 +156,72 @@ struct SiemRecord {
     result: String,
     category: String,
     severity: String,
+    #[serde(default)]
+    severity_level: u8,
     integrity_hash: String,
 }
 
+#[derive(Debug, Deserialize, Serialize)]
+struct AnomalyAlert {
+    alert_id: u64,
+    detected_at: u128,
+    description: String,
+    related_event_ids: Vec<u64>,
+    severity: String,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+struct SegmentSeal {
+    segment: u32,
+    first_event_id: u64,
+    last_event_id: u64,
+    merkle_root: [u8; 32],
+    root: [u8; 32],
+}
+
+/// Report layout this tool was written against (see `FORENSIC_REPORT_SCHEMA_VERSION`).
+const SUPPORTED_SCHEMA_VERSION: u32 = 6;
+
+fn legacy_schema_version() -> u32 {
+    1
+}
+
 #[derive(Debug, Deserialize, Serialize)]
 struct ForensicReport {
+    #[serde(default = "legacy_schema_version")]
+    schema_version: u32,
     incident_id: String,
     generated_at: u128,
     events: Vec<AuditEvent>,
     merkle_root: Option<String>,
     chain_valid: bool,
     siem_records: Vec<SiemRecord>,
+    #[serde(default)]
+    anomalies: Vec<AnomalyAlert>,
+    #[serde(default)]
+    hash_alg: HashAlgorithm,
+    #[serde(default)]
+    genesis_hash: [u8; 32],
+    #[serde(default)]
+    segments: Vec<SegmentSeal>,
+    #[serde(default)]
+    retention_anchor: Option<[u8; 32]>,
+}
+
+/// Ordering of severity names as emitted by `Severity`'s Debug impl.
+fn severity_rank(severity: &str) -> Option<u8> {
+    match severity.to_ascii_lowercase().as_str() {
+        "info" => Some(0),
+        "warning" => Some(1),
+        "critical" => Some(2),
+        "emergency" => Some(3),
+        _ => None,
+    }
 }
 
 // ─── Merkle helper ────────────────────────────────────────────────────────────
 
-fn merkle_root(hashes: &[[u8; 32]]) -> Option<[u8; 32]> {
+fn merkle_root(alg: HashAlgorithm, hashes: &[[u8; 32]]) -> Option<[u8; 32]> {
     if hashes.is_empty() {
         return None;
     }
@@ -126,52 +229,137 @@ fn merkle_root(hashes: &[[u8; 32]]) -> Option<[u8; 32]> {
     while current.len() > 1 {
         let mut next = Vec::new();
         for chunk in current.chunks(2) {
-            let mut h = Sha256::new();
-            h.update(chunk[0]);
-            h.update(chunk.get(1).unwrap_or(&chunk[0]));
-            next.push(h.finalize().into());
+            next.push(alg.digest_parts(&[&chunk[0], chunk.get(1).unwrap_or(&chunk[0])]));
         }
         current = next;
     }
     current.into_iter().next()
 }
 
+/// Same as `audit_log::seal_root`.
+fn seal_root(alg: HashAlgorithm, segment: u32, first_event_id: u64, last_event_id: u64, merkle_root: &[u8; 32]) -> [u8; 32] {
+    alg.digest(&canonical_encode(&[
+        Field::Str("seal"),
+        Field::U64(segment as u64),
+        Field::U64(first_event_id),
+        Field::U64(last_event_id),
+        Field::Bytes(merkle_root),
+    ]))
+}
+
+// ─── Chain checks ─────────────────────────────────────────────────────────────
+
+/// `prev_hash` the event at `idx` must carry: the retention anchor or genesis
+/// hash for the first event, the sealing root when the predecessor closed a
+/// segment, otherwise the predecessor's hash.
+fn expected_prev_hash(report: &ForensicReport, idx: usize) -> [u8; 32] {
+    match idx.checked_sub(1).map(|prev| &report.events[prev]) {
+        Some(prev) => report
+            .segments
+            .iter()
+            .find(|s| s.last_event_id == prev.id)
+            .map_or(prev.event_hash, |s| s.root),
+        None => report.retention_anchor.unwrap_or(report.genesis_hash),
+    }
+}
+
+/// Seals must be numbered in order and hash to their root; seals whose events
+/// are all in the report must also match their Merkle root.
+fn seal_errors(report: &ForensicReport) -> Vec<String> {
+    let mut errors = Vec::new();
+    for (i, seal) in report.segments.iter().enumerate() {
+        let expected = seal_root(report.hash_alg, i as u32, seal.first_event_id, seal.last_event_id, &seal.merkle_root);
+        if seal.segment != i as u32 || seal.root != expected {
+            errors.push(format!("Segment {} seal invalid", i));
+            continue;
+        }
+        let hashes: Vec<[u8; 32]> = report
+            .events
+            .iter()
+            .filter(|e| (seal.first_event_id..=seal.last_event_id).contains(&e.id))
+            .map(|e| e.event_hash)
+            .collect();
+        let complete = hashes.len() as u64 == seal.last_event_id - seal.first_event_id + 1;
+        if complete && merkle_root(report.hash_alg, &hashes) != Some(seal.merkle_root) {
+            errors.push(format!("Segment {} Merkle root mismatch", i));
+        }
+    }
+    errors
+}
+
 // ─── Command implementations ──────────────────────────────────────────────────
 
+/// Parse a report, returning a warning alongside it when the schema version
+/// differs from the one this tool understands. Missing fields fall back to
+/// their defaults so older reports still load.
+fn parse_report(json: &str) -> Result<(ForensicReport, Option<String>), String> {
+    let report: ForensicReport = serde_json::from_str(json)
+        .map_err(|e| format!("Invalid report JSON: {}", e))?;
+    let warning = if report.schema_version != SUPPORTED_SCHEMA_VERSION {
+        Some(format!(
+            "Warning: report schema version {} (tool expects {}); some fields may be missing or ignored",
+            report.schema_version, SUPPORTED_SCHEMA_VERSION
+        ))
+    } else {
+        None
+    };
+    Ok((report, warning))
+}
+
 fn load_report(path: &PathBuf) -> ForensicReport {
     let json = fs::read_to_string(path)
         .unwrap_or_else(|e| { eprintln!("Cannot read {}: {}", path.display(), e); std::process::exit(1); });
-    serde_json::from_str(&json)
-        .unwrap_or_else(|e| { eprintln!("Invalid report JSON: {}", e); std::process::exit(1); })
+    let (report, warning) = parse_report(&json)
+        .unwrap_or_else(|e| { eprintln!("{}", e); std::process::exit(1); });
+    if let Some(w) = warning {
+        eprintln!("{}", w);
+    }
+    report
 }
 
-fn cmd_verify(file: &PathBuf) {
-    let report = load_report(file);
-    println!("=== Verifying report: {} ===", report.incident_id);
-    println!("Events: {}", report.events.len());
-
-    let mut errors = 0usize;
+/// One line per self-hash, linkage or seal failure; empty when the chain is
+/// intact.
+fn chain_errors(report: &ForensicReport) -> Vec<String> {
+    let mut errors = Vec::new();
 
     for (i, event) in report.events.iter().enumerate() {
         // Self-hash
-        let expected = event.recompute_hash();
+        let expected = event.recompute_hash(report.hash_alg);
         if expected != event.event_hash {
-            println!("  ✗ Event {} (id={}) – self-hash MISMATCH", i, event.id);
-            errors += 1;
+            errors.push(format!("  ✗ Event {} (id={}) – self-hash MISMATCH", i, event.id));
         }
 
-        // Chain linkage
-        if i > 0 {
-            let prev_hash = report.events[i - 1].event_hash;
-            if event.prev_hash != prev_hash {
-                println!("  ✗ Event {} (id={}) – prev_hash MISMATCH", i, event.id);
-                errors += 1;
+        // Chain linkage, across seals and back to the genesis hash or retention anchor
+        if event.prev_hash != expected_prev_hash(report, i) {
+            if i == 0 {
+                let origin = if report.retention_anchor.is_some() { "retention anchor" } else { "genesis hash" };
+                errors.push(format!("  ✗ First event (id={}) prev_hash does not match the {}", event.id, origin));
+            } else {
+                errors.push(format!("  ✗ Event {} (id={}) – prev_hash MISMATCH", i, event.id));
             }
-        } else if event.prev_hash != [0u8; 32] {
-            println!("  ✗ Genesis event has non-zero prev_hash");
-            errors += 1;
         }
     }
+    errors.extend(seal_errors(report).into_iter().map(|e| format!("  ✗ {}", e)));
+
+    errors
+}
+
+/// Root derived from the report's event hashes, hex-encoded.
+fn derived_merkle_root(report: &ForensicReport) -> Option<String> {
+    let hashes: Vec<[u8; 32]> = report.events.iter().map(|e| e.event_hash).collect();
+    merkle_root(report.hash_alg, &hashes).map(hex::encode)
+}
+
+fn cmd_verify(file: &PathBuf) {
+    let report = load_report(file);
+    println!("=== Verifying report: {} ===", report.incident_id);
+    println!("Events: {} (hash: {:?})", report.events.len(), report.hash_alg);
+
+    let lines = chain_errors(&report);
+    for line in &lines {
+        println!("{}", line);
+    }
+    let errors = lines.len();
 
     if errors == 0 {
         println!("✓ All {} events verified. Chain intact.", report.events.len());
@@ -244,27 +432,145 @@ fn cmd_stats(file: &PathBuf) {
     }
 }
 
-fn cmd_siem_export(file: &PathBuf, output: Option<PathBuf>) {
+const SIEM_VENDOR: &str = "SwapTrade";
+const SIEM_PRODUCT: &str = "AuditLog";
+const SIEM_PRODUCT_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// Numeric 0–10 severity for CEF and LEEF. Reports older than schema 3 lack
+/// `severity_level`, so fall back to mapping the severity name.
+fn siem_severity_score(record: &SiemRecord) -> u8 {
+    if record.severity_level > 0 {
+        return record.severity_level;
+    }
+    match severity_rank(&record.severity) {
+        Some(0) => 2,
+        Some(1) => 5,
+        Some(2) => 8,
+        Some(3) => 10,
+        _ => 0,
+    }
+}
+
+/// CEF header fields escape `\` and `|`.
+fn cef_escape_header(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('|', "\\|")
+}
+
+/// CEF extension values escape `\`, `=` and line breaks.
+fn cef_escape_value(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('=', "\\=")
+        .replace('\n', "\\n")
+        .replace('\r', "\\r")
+}
+
+/// LEEF header fields escape `\` and `|`; attribute values escape `\`,
+/// the tab delimiter and line breaks.
+fn leef_escape_header(value: &str) -> String {
+    cef_escape_header(value)
+}
+
+fn leef_escape_value(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('\t', "\\t")
+        .replace('\n', "\\n")
+        .replace('\r', "\\r")
+}
+
+fn format_ndjson(record: &SiemRecord) -> String {
+    serde_json::to_string(record).unwrap_or_default()
+}
+
+/// `CEF:0|Vendor|Product|Version|SignatureID|Name|Severity|Extension`
+fn format_cef(record: &SiemRecord) -> String {
+    let header = [
+        SIEM_VENDOR,
+        SIEM_PRODUCT,
+        SIEM_PRODUCT_VERSION,
+        &record.action,
+        &format!("{} {}", record.category, record.action),
+    ]
+    .iter()
+    .map(|f| cef_escape_header(f))
+    .collect::<Vec<_>>()
+    .join("|");
+
+    let extension = [
+        ("externalId", record.event_id.to_string()),
+        ("rt", record.timestamp_iso.clone()),
+        ("suser", record.actor.clone()),
+        ("act", record.action.clone()),
+        ("outcome", record.result.clone()),
+        ("cat", record.category.clone()),
+        ("cs1Label", "target".to_string()),
+        ("cs1", record.target.clone()),
+        ("cs2Label", "integrityHash".to_string()),
+        ("cs2", record.integrity_hash.clone()),
+    ]
+    .iter()
+    .map(|(k, v)| format!("{}={}", k, cef_escape_value(v)))
+    .collect::<Vec<_>>()
+    .join(" ");
+
+    format!("CEF:0|{}|{}|{}", header, siem_severity_score(record), extension)
+}
+
+/// `LEEF:1.0|Vendor|Product|Version|EventID|` followed by tab-separated attributes
+fn format_leef(record: &SiemRecord) -> String {
+    let header = [SIEM_VENDOR, SIEM_PRODUCT, SIEM_PRODUCT_VERSION, &record.action]
+        .iter()
+        .map(|f| leef_escape_header(f))
+        .collect::<Vec<_>>()
+        .join("|");
+
+    let attributes = [
+        ("devTime", record.timestamp_iso.clone()),
+        ("sev", siem_severity_score(record).to_string()),
+        ("cat", record.category.clone()),
+        ("usrName", record.actor.clone()),
+        ("eventId", record.event_id.to_string()),
+        ("target", record.target.clone()),
+        ("result", record.result.clone()),
+        ("integrityHash", record.integrity_hash.clone()),
+    ]
+    .iter()
+    .map(|(k, v)| format!("{}={}", k, leef_escape_value(v)))
+    .collect::<Vec<_>>()
+    .join("\t");
+
+    format!("LEEF:1.0|{}|{}", header, attributes)
+}
+
+fn format_siem_record(record: &SiemRecord, format: SiemFormat) -> String {
+    match format {
+        SiemFormat::Ndjson => format_ndjson(record),
+        SiemFormat::Cef => format_cef(record),
+        SiemFormat::Leef => format_leef(record),
+    }
+}
+
+fn cmd_siem_export(file: &PathBuf, output: Option<PathBuf>, format: SiemFormat) {
     let report = load_report(file);
-    let ndjson = report.siem_records.iter()
-        .map(|r| serde_json::to_string(r).unwrap_or_default())
+    let lines = report.siem_records.iter()
+        .map(|r| format_siem_record(r, format))
         .collect::<Vec<_>>()
         .join("\n");
 
     match output {
         Some(path) => {
-            fs::write(&path, &ndjson)
+            fs::write(&path, &lines)
                 .unwrap_or_else(|e| { eprintln!("Write error: {}", e); std::process::exit(1); });
             println!("Wrote {} SIEM records to {}", report.siem_records.len(), path.display());
         }
-        None => println!("{}", ndjson),
+        None => println!("{}", lines),
     }
 }
 
 fn cmd_merkle_check(file: &PathBuf) {
     let report = load_report(file);
-    let hashes: Vec<[u8; 32]> = report.events.iter().map(|e| e.event_hash).collect();
-    let derived = merkle_root(&hashes).map(hex::encode);
+    let derived = derived_merkle_root(&report);
 
     println!("Claimed  root: {}", report.merkle_root.as_deref().unwrap_or("(none)"));
     println!("Computed root: {}", derived.as_deref().unwrap_or("(none)"));
@@ -277,6 +583,142 @@ fn cmd_merkle_check(file: &PathBuf) {
     }
 }
 
+/// Outcome of checking one file in `verify-dir`.
+#[derive(Debug)]
+struct VerifyDirRow {
+    file: String,
+    events: usize,
+    chain_valid: bool,
+    merkle_ok: bool,
+    /// Set when the file could not be read or parsed
+    error: Option<String>,
+}
+
+impl VerifyDirRow {
+    fn passed(&self) -> bool {
+        self.error.is_none() && self.chain_valid && self.merkle_ok
+    }
+}
+
+fn verify_report_file(path: &std::path::Path) -> VerifyDirRow {
+    let file = path.file_name().map(|n| n.to_string_lossy().into_owned()).unwrap_or_default();
+    let parsed = fs::read_to_string(path)
+        .map_err(|e| format!("Cannot read: {}", e))
+        .and_then(|json| parse_report(&json));
+    match parsed {
+        Ok((report, _)) => VerifyDirRow {
+            file,
+            events: report.events.len(),
+            chain_valid: chain_errors(&report).is_empty(),
+            merkle_ok: report.merkle_root == derived_merkle_root(&report),
+            error: None,
+        },
+        Err(e) => VerifyDirRow { file, events: 0, chain_valid: false, merkle_ok: false, error: Some(e) },
+    }
+}
+
+/// Check every `*.json` file in `dir`, sorted by name. A bad file becomes a
+/// failing row instead of stopping the run.
+fn verify_dir(dir: &PathBuf) -> Result<Vec<VerifyDirRow>, String> {
+    let mut paths: Vec<PathBuf> = fs::read_dir(dir)
+        .map_err(|e| format!("Cannot read {}: {}", dir.display(), e))?
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|p| p.is_file() && p.extension().map_or(false, |ext| ext == "json"))
+        .collect();
+    paths.sort();
+    Ok(paths.iter().map(|p| verify_report_file(p)).collect())
+}
+
+/// Summary table lines and the process exit code (2 if any report failed).
+fn render_verify_summary(rows: &[VerifyDirRow]) -> (Vec<String>, i32) {
+    let mark = |ok: bool| if ok { "✓" } else { "✗" };
+    let mut lines = vec![format!("  {:40} {:>8}  {:11}  {:9}", "file", "events", "chain_valid", "merkle_ok")];
+    for row in rows {
+        let mut line = format!(
+            "  {:40} {:>8}  {:11}  {:9}",
+            row.file, row.events, mark(row.chain_valid), mark(row.merkle_ok)
+        );
+        if let Some(e) = &row.error {
+            line.push_str(&format!("  ({})", e));
+        }
+        lines.push(line);
+    }
+    let failed = rows.iter().filter(|r| !r.passed()).count();
+    lines.push(format!("{} report(s) checked, {} failed", rows.len(), failed));
+    (lines, if failed == 0 { 0 } else { 2 })
+}
+
+fn cmd_verify_dir(dir: &PathBuf) {
+    let rows = verify_dir(dir).unwrap_or_else(|e| { eprintln!("{}", e); std::process::exit(1); });
+    let (lines, code) = render_verify_summary(&rows);
+    for line in lines {
+        println!("{}", line);
+    }
+    std::process::exit(code);
+}
+
+fn render_alerts(report: &ForensicReport, min_severity: Option<&str>) -> Result<Vec<String>, String> {
+    let min_rank = match min_severity {
+        Some(s) => severity_rank(s).ok_or_else(|| format!("Unknown severity '{}'", s))?,
+        None => 0,
+    };
+
+    Ok(report.anomalies.iter()
+        .filter(|a| severity_rank(&a.severity).map_or(true, |r| r >= min_rank))
+        .map(|a| {
+            let ids = a.related_event_ids.iter().map(|id| id.to_string()).collect::<Vec<_>>().join(",");
+            format!("  [{:>4}] {:9}  events=[{}]  {}", a.alert_id, a.severity, ids, a.description)
+        })
+        .collect())
+}
+
+fn cmd_alerts(file: &PathBuf, min_severity: Option<String>) {
+    let report = load_report(file);
+    let lines = render_alerts(&report, min_severity.as_deref())
+        .unwrap_or_else(|e| { eprintln!("{}", e); std::process::exit(1); });
+
+    println!("{} anomaly alert(s):", lines.len());
+    for line in lines {
+        println!("{}", line);
+    }
+}
+
+/// Replay state deltas up to `event_id` after checking each event's hash, its
+/// link to the previous event (or the report's anchor) and the segment seals.
+fn replay_balance(report: &ForensicReport, actor: &str, asset: &str, event_id: u64) -> Result<i128, String> {
+    for (i, event) in report.events.iter().enumerate() {
+        if event.recompute_hash(report.hash_alg) != event.event_hash {
+            return Err(format!("Chain broken: event {} hash invalid", event.id));
+        }
+        if event.prev_hash != expected_prev_hash(report, i) {
+            return Err(format!("Chain broken: event {} prev_hash mismatch", event.id));
+        }
+    }
+    if let Some(e) = seal_errors(report).into_iter().next() {
+        return Err(format!("Chain broken: {}", e));
+    }
+    if !report.events.iter().any(|e| e.id == event_id) {
+        return Err(format!("Event {} not found in report", event_id));
+    }
+
+    Ok(report.events.iter()
+        .take_while(|e| e.id <= event_id)
+        .filter_map(|e| e.state_delta.as_ref())
+        .filter(|d| d.actor == actor && d.asset == asset)
+        .fold(0i128, |acc, d| acc.saturating_add(d.delta)))
+}
+
+fn cmd_balance_at(file: &PathBuf, actor: &str, asset: &str, event_id: u64) {
+    let report = load_report(file);
+    match replay_balance(&report, actor, asset, event_id) {
+        Ok(balance) => println!("{} {} balance at event {}: {}", actor, asset, event_id, balance),
+        Err(e) => {
+            println!("✗ {}", e);
+            std::process::exit(2);
+        }
+    }
+}
+
 // ─── Entry point ─────────────────────────────────────────────────────────────
 
 fn main() {
@@ -286,7 +728,329 @@ fn main() {
         Command::Query { file, actor, action, category, from, to } =>
             cmd_query(&file, actor, action, category, from, to),
         Command::Stats { file } => cmd_stats(&file),
-        Command::SiemExport { file, output } => cmd_siem_export(&file, output),
+        Command::SiemExport { file, output, format } => cmd_siem_export(&file, output, format),
+        Command::VerifyDir { dir } => cmd_verify_dir(&dir),
         Command::MerkleCheck { file } => cmd_merkle_check(&file),
+        Command::Alerts { file, min_severity } => cmd_alerts(&file, min_severity),
+        Command::BalanceAt { file, actor, asset, event_id } =>
+            cmd_balance_at(&file, &actor, &asset, event_id),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn burst_report() -> ForensicReport {
+        let json = r#"{
+            "schema_version": 6,
+            "incident_id": "INC-BURST",
+            "generated_at": 0,
+            "events": [],
+            "merkle_root": null,
+            "chain_valid": true,
+            "siem_records": [],
+            "anomalies": [
+                { "alert_id": 1, "detected_at": 0, "description": "Actor 'hft_bot' exceeded 50 trades/min (current: 51)",
+                  "related_event_ids": [51], "severity": "Warning" },
+                { "alert_id": 2, "detected_at": 0, "description": "Suspicious admin burst from 'attacker': 6 actions/min",
+                  "related_event_ids": [58], "severity": "Critical" }
+            ]
+        }"#;
+        serde_json::from_str(json).unwrap()
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_render_alerts_lists_all() {
+        let lines = render_alerts(&burst_report(), None).unwrap();
+        assert_eq!(lines.len(), 2);
+        assert!(lines[0].contains("hft_bot"));
+        assert!(lines[0].contains("events=[51]"));
+    }
+
+    #[test]
+    fn test_render_alerts_min_severity() {
+        let lines = render_alerts(&burst_report(), Some("critical")).unwrap();
+        assert_eq!(lines.len(), 1);
+        assert!(lines[0].contains("Critical"));
+        assert!(render_alerts(&burst_report(), Some("bogus")).is_err());
+    }
+
+    #[test]
+    fn test_report_without_anomalies_section() {
+        let json = r#"{"incident_id":"OLD","generated_at":0,"events":[],"merkle_root":null,"chain_valid":true,"siem_records":[]}"#;
+        let report: ForensicReport = serde_json::from_str(json).unwrap();
+        assert!(render_alerts(&report, None).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_parse_older_schema_warns_but_loads() {
+        let json = r#"{"incident_id":"OLD","generated_at":0,"events":[],"merkle_root":null,"chain_valid":true,"siem_records":[]}"#;
+        let (report, warning) = parse_report(json).unwrap();
+        assert_eq!(report.schema_version, 1);
+        assert_eq!(report.incident_id, "OLD");
+        assert!(warning.unwrap().contains("schema version 1"));
+    }
+
+    #[test]
+    fn test_parse_current_schema_no_warning() {
+        let (report, warning) = parse_report(&serde_json::to_string(&burst_report()).unwrap()).unwrap();
+        assert_eq!(report.schema_version, SUPPORTED_SCHEMA_VERSION);
+        assert!(warning.is_none());
+    }
+
+    fn chained_report(deltas: &[(&str, &str, i128)]) -> ForensicReport {
+        let mut events: Vec<AuditEvent> = Vec::new();
+        for (i, (actor, asset, delta)) in deltas.iter().enumerate() {
+            let mut event = AuditEvent {
+                id: i as u64 + 1,
+                timestamp: 0,
+                actor: actor.to_string(),
+                action: if *delta >= 0 { "MINT".into() } else { "DEBIT".into() },
+                target: asset.to_string(),
+                result: "OK".into(),
+                gas_used: 0,
+                state_hash: [0u8; 32],
+                category: "Trading".into(),
+                severity: "Info".into(),
+                prev_hash: events.last().map(|e| e.event_hash).unwrap_or([0u8; 32]),
+                event_hash: [0u8; 32],
+                state_delta: Some(StateDelta { actor: actor.to_string(), asset: asset.to_string(), delta: *delta }),
+            };
+            event.event_hash = event.recompute_hash(HashAlgorithm::default());
+            events.push(event);
+        }
+        ForensicReport {
+            schema_version: SUPPORTED_SCHEMA_VERSION,
+            incident_id: "INC-BAL".into(),
+            generated_at: 0,
+            events,
+            merkle_root: None,
+            chain_valid: true,
+            siem_records: vec![],
+            anomalies: vec![],
+            hash_alg: HashAlgorithm::default(),
+            genesis_hash: [0u8; 32],
+            segments: vec![],
+            retention_anchor: None,
+        }
+    }
+
+    #[test]
+    fn test_verify_dir_reports_each_file_and_fails_on_tampering() {
+        let dir = std::env::temp_dir().join(format!("audit-verify-dir-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+
+        let mut good = chained_report(&[("alice", "XLM", 1_000), ("alice", "XLM", -250)]);
+        good.merkle_root = derived_merkle_root(&good);
+        let mut bad = chained_report(&[("alice", "XLM", 1_000), ("alice", "XLM", -250)]);
+        bad.merkle_root = derived_merkle_root(&bad);
+        bad.events[1].state_delta.as_mut().unwrap().delta = -1;
+
+        fs::write(dir.join("a_good.json"), serde_json::to_string(&good).unwrap()).unwrap();
+        fs::write(dir.join("b_tampered.json"), serde_json::to_string(&bad).unwrap()).unwrap();
+        fs::write(dir.join("notes.txt"), "ignored").unwrap();
+
+        let rows = verify_dir(&dir).unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[0].file, "a_good.json");
+        assert!(rows[0].passed());
+        assert_eq!(rows[1].file, "b_tampered.json");
+        assert_eq!(rows[1].events, 2);
+        assert!(!rows[1].chain_valid);
+        assert!(rows[1].merkle_ok, "event_hash was left untouched, so the root still matches");
+
+        let (lines, code) = render_verify_summary(&rows);
+        assert_eq!(code, 2);
+        assert!(lines[1].contains("a_good.json"));
+        assert!(lines[2].contains("b_tampered.json") && lines[2].contains("✗"));
+        assert_eq!(lines.last().unwrap(), "2 report(s) checked, 1 failed");
+
+        let (_, code) = render_verify_summary(&rows[..1]);
+        assert_eq!(code, 0);
+    }
+
+    #[test]
+    fn test_replay_balance_at_cut_points() {
+        let report = chained_report(&[
+            ("alice", "XLM", 1_000),
+            ("bob", "XLM", 400),
+            ("alice", "XLM", -250),
+            ("alice", "USDC", 75),
+            ("alice", "XLM", -50),
+        ]);
+        assert_eq!(replay_balance(&report, "alice", "XLM", 1).unwrap(), 1_000);
+        assert_eq!(replay_balance(&report, "alice", "XLM", 2).unwrap(), 1_000);
+        assert_eq!(replay_balance(&report, "alice", "XLM", 3).unwrap(), 750);
+        assert_eq!(replay_balance(&report, "alice", "XLM", 5).unwrap(), 700);
+        assert_eq!(replay_balance(&report, "alice", "USDC", 5).unwrap(), 75);
+        assert!(replay_balance(&report, "alice", "XLM", 9).is_err());
+    }
+
+    #[test]
+    fn test_replay_balance_rejects_tampered_chain() {
+        let mut report = chained_report(&[("alice", "XLM", 1_000), ("alice", "XLM", -250)]);
+        report.events[1].state_delta.as_mut().unwrap().delta = -1;
+        let err = replay_balance(&report, "alice", "XLM", 2).unwrap_err();
+        assert!(err.contains("hash invalid"));
+    }
+
+    #[test]
+    fn test_chain_checks_follow_seals_and_anchor() {
+        let mut report = chained_report(&[("alice", "XLM", 1_000), ("alice", "XLM", -250)]);
+        let first = &report.events[0];
+        let merkle_root = first.event_hash;
+        let root = seal_root(HashAlgorithm::default(), 0, 1, 1, &merkle_root);
+        report.segments.push(SegmentSeal { segment: 0, first_event_id: 1, last_event_id: 1, merkle_root, root });
+
+        // Event 2 still links to event 1's hash rather than the seal
+        assert!(replay_balance(&report, "alice", "XLM", 2).unwrap_err().contains("prev_hash mismatch"));
+
+        report.events[1].prev_hash = root;
+        report.events[1].event_hash = report.events[1].recompute_hash(HashAlgorithm::default());
+        assert!(chain_errors(&report).is_empty());
+        assert_eq!(replay_balance(&report, "alice", "XLM", 2).unwrap(), 750);
+
+        report.segments[0].merkle_root = [7u8; 32];
+        assert!(chain_errors(&report).iter().any(|l| l.contains("seal invalid")));
+
+        // With the first event evicted, event 2 must link to the retention anchor
+        report.segments.clear();
+        report.events.remove(0);
+        assert!(!chain_errors(&report).is_empty());
+        report.retention_anchor = Some(root);
+        assert!(chain_errors(&report).is_empty());
+    }
+
+    fn tricky_record() -> SiemRecord {
+        SiemRecord {
+            event_id: 42,
+            timestamp_iso: "1700000000.000000000Z".into(),
+            actor: "eve|ops=root".into(),
+            action: "ADMIN_ROLE_GRANT".into(),
+            target: "line1\nline2\tcol\\x".into(),
+            result: "OK".into(),
+            category: "Administrative".into(),
+            severity: "Critical".into(),
+            severity_level: 0,
+            integrity_hash: "ab".repeat(32),
+        }
+    }
+
+    /// Undo `\x` escapes (`n`/`t`/`r` map back to control characters).
+    fn unescape(input: &str) -> String {
+        let mut out = String::new();
+        let mut chars = input.chars();
+        while let Some(c) = chars.next() {
+            if c == '\\' {
+                match chars.next() {
+                    Some('n') => out.push('\n'),
+                    Some('t') => out.push('\t'),
+                    Some('r') => out.push('\r'),
+                    Some(other) => out.push(other),
+                    None => {}
+                }
+            } else {
+                out.push(c);
+            }
+        }
+        out
+    }
+
+    /// Split off the first `count` header fields on unescaped `|`, returning
+    /// them unescaped followed by the raw remainder.
+    fn split_header(line: &str, count: usize) -> Vec<String> {
+        let mut fields = Vec::new();
+        let mut start = 0;
+        let mut escaped = false;
+        for (i, c) in line.char_indices() {
+            if fields.len() == count {
+                break;
+            }
+            if escaped {
+                escaped = false;
+            } else if c == '\\' {
+                escaped = true;
+            } else if c == '|' {
+                fields.push(unescape(&line[start..i]));
+                start = i + 1;
+            }
+        }
+        fields.push(line[start..].to_string());
+        fields
+    }
+
+    #[test]
+    fn test_siem_ndjson_round_trip() {
+        let line = format_siem_record(&tricky_record(), SiemFormat::Ndjson);
+        let parsed: SiemRecord = serde_json::from_str(&line).unwrap();
+        assert_eq!(parsed.actor, "eve|ops=root");
+        assert_eq!(parsed.target, tricky_record().target);
+    }
+
+    #[test]
+    fn test_siem_cef_header_and_escaping() {
+        let line = format_siem_record(&tricky_record(), SiemFormat::Cef);
+        assert!(line.starts_with("CEF:0|SwapTrade|AuditLog|"));
+        assert!(!line.contains('\n'));
+
+        let fields = split_header(&line, 7);
+        assert_eq!(fields[0], "CEF:0");
+        assert_eq!(fields[4], "ADMIN_ROLE_GRANT");
+        assert_eq!(fields[6], "8", "Critical maps to CEF severity 8");
+
+        // Extension pairs are space-separated; `=` inside values is escaped
+        let extension = &fields[7];
+        let suser = extension.split(' ').find_map(|kv| kv.strip_prefix("suser=")).unwrap();
+        assert_eq!(suser, "eve|ops\\=root");
+        assert_eq!(unescape(suser), "eve|ops=root");
+        assert!(extension.contains("externalId=42"));
+        let cs1 = extension.split(' ').find_map(|kv| kv.strip_prefix("cs1=")).unwrap();
+        assert_eq!(unescape(cs1), tricky_record().target);
+    }
+
+    #[test]
+    fn test_siem_leef_header_and_escaping() {
+        let line = format_siem_record(&tricky_record(), SiemFormat::Leef);
+        assert!(line.starts_with("LEEF:1.0|SwapTrade|AuditLog|"));
+        assert!(!line.contains('\n'));
+
+        let fields = split_header(&line, 5);
+        assert_eq!(fields[0], "LEEF:1.0");
+        assert_eq!(fields[4], "ADMIN_ROLE_GRANT");
+
+        // Attributes are tab-delimited; tabs inside values are escaped
+        let attrs: std::collections::HashMap<String, String> = fields[5]
+            .split('\t')
+            .map(|kv| {
+                let (k, v) = kv.split_once('=').unwrap();
+                (k.to_string(), unescape(v))
+            })
+            .collect();
+        assert_eq!(attrs["usrName"], "eve|ops=root");
+        assert_eq!(attrs["target"], tricky_record().target);
+        assert_eq!(attrs["sev"], "8");
+        assert_eq!(attrs["eventId"], "42");
+    }
+
+    #[test]
+    fn test_recompute_hash_distinguishes_field_boundaries() {
+        let original = chained_report(&[("alice", "XLM", 10)]).events.remove(0);
+        let base = chained_report(&[("alice", "XLM", 10)]).events.remove(0);
+        let shifted = AuditEvent {
+            actor: "alic".into(),
+            action: format!("e{}", base.action),
+            ..base
+        };
+        assert_eq!(
+            format!("{}{}", original.actor, original.action),
+            format!("{}{}", shifted.actor, shifted.action)
+        );
+        assert_ne!(
+            original.recompute_hash(HashAlgorithm::default()),
+            shifted.recompute_hash(HashAlgorithm::default())
+        );
+    }
+}
@@ -208,6 +208,7 @@ fn test_get_balance_persistence_across_calls() {
 #[test]
 fn test_metrics_increment_on_mint_and_swap() {
     let env = Env::default();
+    env.mock_all_auths();
     let contract_id = env.register(CounterContract, ());
     let client = CounterContractClient::new(&env, &contract_id);
 
@@ -232,6 +233,7 @@ fn test_metrics_increment_on_mint_and_swap() {
 #[test]
 fn test_try_swap_counts_failed_orders_without_panic() {
     let env = Env::default();
+    env.mock_all_auths();
     let contract_id = env.register(CounterContract, ());
     let client = CounterContractClient::new(&env, &contract_id);
 
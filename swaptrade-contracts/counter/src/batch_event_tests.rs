@@ -4,7 +4,7 @@ mod batch_event_tests {
     use crate::portfolio::{Asset, Badge, Portfolio};
     use soroban_sdk::{
         testutils::{Address as _, Events as _},
-        Address, Env, Symbol,
+        Address, Env, Symbol, TryFromVal,
     };
 
     #[test]
@@ -33,12 +33,11 @@ mod batch_event_tests {
         let events = env.events().all();
         let badge_events: Vec<_> = events
             .iter()
-            .filter(|e| {
-                if let Ok((topics, _)) = e {
-                    topics.len() > 0 && topics.get(0).unwrap() == Symbol::new(&env, "BadgesAwarded")
-                } else {
-                    false
-                }
+            .filter(|(_, topics, _)| {
+                topics
+                    .get(0)
+                    .and_then(|topic| Symbol::try_from_val(&env, &topic).ok())
+                    .map_or(false, |name| name == Symbol::new(&env, "BadgesAwarded"))
             })
             .collect();
 
@@ -67,12 +66,11 @@ mod batch_event_tests {
         let events = env.events().all();
         let badge_events: Vec<_> = events
             .iter()
-            .filter(|e| {
-                if let Ok((topics, _)) = e {
-                    topics.len() > 0 && topics.get(0).unwrap() == Symbol::new(&env, "BadgesAwarded")
-                } else {
-                    false
-                }
+            .filter(|(_, topics, _)| {
+                topics
+                    .get(0)
+                    .and_then(|topic| Symbol::try_from_val(&env, &topic).ok())
+                    .map_or(false, |name| name == Symbol::new(&env, "BadgesAwarded"))
             })
             .collect();
 
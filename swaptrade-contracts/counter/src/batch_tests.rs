@@ -10,6 +10,7 @@ use soroban_sdk::{symbol_short, testutils::Address as TestAddress, Env, Symbol,
 #[test]
 fn test_single_leg_batch_identical_to_direct() {
     let env = Env::default();
+    env.mock_all_auths();
     let contract_id = env.register(CounterContract, ());
     let client = CounterContractClient::new(&env, &contract_id);
 
@@ -583,3 +584,39 @@ fn test_clear_error_messages() {
         assert!(!err_sym.to_string().is_empty());
     }
 }
+
+/// Test batches validate and settle against the configured quote asset
+#[test]
+fn test_batch_uses_configured_quote_symbol() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(CounterContract, ());
+    let client = CounterContractClient::new(&env, &contract_id);
+
+    let admin = Address::generate(&env);
+    let user = Address::generate(&env);
+    env.as_contract(&contract_id, || {
+        env.storage().persistent().set(&crate::storage::ADMIN_KEY, &admin);
+    });
+    let xlm = symbol_short!("XLM");
+    let usdc = symbol_short!("USDC");
+    client.set_quote_asset(&admin, &usdc);
+
+    let mut batch_ops = Vec::new(&env);
+    batch_ops.push_back(BatchOperation::MintToken(usdc.clone(), user.clone(), 500));
+    batch_ops.push_back(BatchOperation::MintToken(xlm.clone(), user.clone(), 1000));
+    batch_ops.push_back(BatchOperation::Swap(xlm.clone(), usdc.clone(), 200, user.clone()));
+    batch_ops.push_back(BatchOperation::RemoveLiquidity(0, 100, user.clone()));
+
+    let batch_result = client.execute_batch(&batch_ops);
+    assert_eq!(batch_result.operations_executed, 4);
+    assert_eq!(batch_result.operations_failed, 0);
+    assert_eq!(client.get_balance(&usdc, &user), 800);
+
+    // The previous quote symbol no longer passes validation
+    let mut stale_ops = Vec::new(&env);
+    stale_ops.push_back(BatchOperation::Swap(xlm.clone(), symbol_short!("USDCSIM"), 100, user.clone()));
+    let stale_result = client.execute_batch(&stale_ops);
+    assert_eq!(stale_result.operations_executed, 0);
+    assert_eq!(stale_result.operations_failed, 1);
+}
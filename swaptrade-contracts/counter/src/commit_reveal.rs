@@ -0,0 +1,82 @@
+//! Commit-reveal flow for swaps.
+//!
+//! A public swap exposes its size and direction before it executes. Instead a
+//! user first commits to a hash of the order plus a secret salt, then reveals
+//! the order in a later ledger. Observers of the commitment learn nothing
+//! they can trade ahead of, and the reveal must match what was committed.
+
+use soroban_sdk::{contracttype, xdr::ToXdr, Address, BytesN, Env, Symbol};
+
+use crate::errors::ContractError;
+use crate::storage::SWAP_COMMIT_KEY;
+
+/// Earliest a commitment can be revealed, so commit and reveal never share a ledger
+pub const REVEAL_MIN_DELAY_SECS: u64 = 5;
+
+/// Longest a commitment may stay open before its deadline
+pub const REVEAL_MAX_WINDOW_SECS: u64 = 3_600;
+
+/// Swap order a commitment hides until reveal
+#[derive(Clone, Debug, PartialEq)]
+#[contracttype]
+pub struct SwapParams {
+    pub token_in: Symbol,
+    pub token_out: Symbol,
+    pub amount: i128,
+    pub min_out: i128,
+}
+
+/// A user's pending commitment
+#[derive(Clone, Debug, PartialEq)]
+#[contracttype]
+pub struct SwapCommitment {
+    pub commitment: BytesN<32>,
+    pub committed_at: u64,
+    pub deadline: u64,
+}
+
+/// Hash a client must commit to for `params` and `salt`
+pub fn commitment_hash(env: &Env, params: &SwapParams, salt: &BytesN<32>) -> BytesN<32> {
+    let encoded = (params.clone(), salt.clone()).to_xdr(env);
+    env.crypto().sha256(&encoded).into()
+}
+
+fn key(user: &Address) -> (Symbol, Address) {
+    (SWAP_COMMIT_KEY, user.clone())
+}
+
+pub fn get_commitment(env: &Env, user: &Address) -> Option<SwapCommitment> {
+    env.storage().persistent().get(&key(user))
+}
+
+/// Record `commitment` for `user`, replacing any pending one
+pub fn commit(env: &Env, user: Address, commitment: BytesN<32>, deadline: u64) -> Result<(), ContractError> {
+    let now = env.ledger().timestamp();
+    let earliest = now.saturating_add(REVEAL_MIN_DELAY_SECS);
+    if deadline < earliest || deadline > now.saturating_add(REVEAL_MAX_WINDOW_SECS) {
+        return Err(ContractError::InvalidRevealDeadline);
+    }
+    env.storage().persistent().set(
+        &key(&user),
+        &SwapCommitment { commitment, committed_at: now, deadline },
+    );
+    Ok(())
+}
+
+/// Check a reveal against `user`'s pending commitment and clear it on success.
+/// An expired commitment stays in place until the user's next commit replaces it.
+pub fn consume(env: &Env, user: &Address, params: &SwapParams, salt: &BytesN<32>) -> Result<(), ContractError> {
+    let pending = get_commitment(env, user).ok_or(ContractError::CommitmentNotFound)?;
+    let now = env.ledger().timestamp();
+    if now > pending.deadline {
+        return Err(ContractError::RevealExpired);
+    }
+    if now < pending.committed_at.saturating_add(REVEAL_MIN_DELAY_SECS) {
+        return Err(ContractError::RevealTooEarly);
+    }
+    if commitment_hash(env, params, salt) != pending.commitment {
+        return Err(ContractError::CommitmentMismatch);
+    }
+    env.storage().persistent().remove(&key(user));
+    Ok(())
+}
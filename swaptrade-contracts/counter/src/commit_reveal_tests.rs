@@ -0,0 +1,100 @@
+#![cfg(test)]
+
+use super::*;
+use crate::commit_reveal::{commitment_hash, REVEAL_MIN_DELAY_SECS};
+use soroban_sdk::{symbol_short, testutils::{Address as _, Ledger}, Address, BytesN, Env};
+
+fn setup(env: &Env) -> (CounterContractClient<'_>, Address) {
+    env.mock_all_auths();
+    env.ledger().with_mut(|li| li.timestamp = 1_000);
+    let contract_id = env.register(CounterContract, ());
+    let client = CounterContractClient::new(env, &contract_id);
+    let user = Address::generate(env);
+    client.mint(&symbol_short!("XLM"), &user, &1_000);
+    (client, user)
+}
+
+fn order(amount: i128, min_out: i128) -> SwapParams {
+    SwapParams {
+        token_in: symbol_short!("XLM"),
+        token_out: symbol_short!("USDCSIM"),
+        amount,
+        min_out,
+    }
+}
+
+#[test]
+fn test_commit_reveal_executes_swap() {
+    let env = Env::default();
+    let (client, user) = setup(&env);
+    let params = order(500, 1);
+    let salt = BytesN::from_array(&env, &[7u8; 32]);
+    let hash = env.as_contract(&client.address, || commitment_hash(&env, &params, &salt));
+
+    client.commit_swap(&user, &hash, &1_100);
+    // Same ledger as the commit
+    assert_eq!(client.try_reveal_swap(&user, &params, &salt), Err(Ok(ContractError::RevealTooEarly)));
+
+    env.ledger().with_mut(|li| li.timestamp = 1_000 + REVEAL_MIN_DELAY_SECS);
+    let out = client.reveal_swap(&user, &params, &salt);
+    assert!(out > 0);
+    assert_eq!(client.get_balance(&symbol_short!("XLM"), &user), 500);
+    assert_eq!(client.get_balance(&symbol_short!("USDCSIM"), &user), out);
+
+    // The commitment is spent
+    assert_eq!(client.try_reveal_swap(&user, &params, &salt), Err(Ok(ContractError::CommitmentNotFound)));
+}
+
+#[test]
+fn test_mismatched_or_late_reveal_fails() {
+    let env = Env::default();
+    let (client, user) = setup(&env);
+    let params = order(500, 1);
+    let salt = BytesN::from_array(&env, &[7u8; 32]);
+    let hash = env.as_contract(&client.address, || commitment_hash(&env, &params, &salt));
+
+    assert_eq!(client.try_commit_swap(&user, &hash, &1_000), Err(Ok(ContractError::InvalidRevealDeadline)));
+    client.commit_swap(&user, &hash, &1_100);
+    env.ledger().with_mut(|li| li.timestamp = 1_050);
+
+    // A larger order or a different salt does not match what was committed
+    let other_salt = BytesN::from_array(&env, &[8u8; 32]);
+    assert_eq!(client.try_reveal_swap(&user, &order(900, 1), &salt), Err(Ok(ContractError::CommitmentMismatch)));
+    assert_eq!(client.try_reveal_swap(&user, &params, &other_salt), Err(Ok(ContractError::CommitmentMismatch)));
+    assert_eq!(client.get_balance(&symbol_short!("XLM"), &user), 1_000);
+
+    env.ledger().with_mut(|li| li.timestamp = 1_101);
+    assert_eq!(client.try_reveal_swap(&user, &params, &salt), Err(Ok(ContractError::RevealExpired)));
+    assert_eq!(client.get_balance(&symbol_short!("XLM"), &user), 1_000);
+
+    // The failed reveal rolls back, so the expired commitment is still there
+    // until the next commit replaces it
+    let pending = env.as_contract(&client.address, || crate::commit_reveal::get_commitment(&env, &user));
+    assert_eq!(pending.map(|p| p.commitment), Some(hash));
+}
+
+#[test]
+fn test_rate_limited_reveal_fails_and_keeps_commitment() {
+    let env = Env::default();
+    let (client, user) = setup(&env);
+    let params = order(100, 0);
+    let salt = BytesN::from_array(&env, &[7u8; 32]);
+    let hash = env.as_contract(&client.address, || commitment_hash(&env, &params, &salt));
+
+    client.commit_swap(&user, &hash, &1_100);
+    env.ledger().with_mut(|li| li.timestamp = 1_000 + REVEAL_MIN_DELAY_SECS);
+
+    // Use up the user's hourly swap allowance
+    let limit = client.get_swap_rate_limit(&user).limit;
+    env.as_contract(&client.address, || {
+        for _ in 0..limit {
+            RateLimiter::record_swap(&env, &user, env.ledger().timestamp());
+        }
+    });
+
+    // Even with min_out 0 the declined swap is an error, not a 0-out success
+    assert_eq!(client.try_reveal_swap(&user, &params, &salt), Err(Ok(ContractError::RateLimitExceeded)));
+    assert_eq!(client.get_balance(&symbol_short!("XLM"), &user), 1_000);
+    let pending = env.as_contract(&client.address, || crate::commit_reveal::get_commitment(&env, &user));
+    assert!(pending.is_some());
+}
@@ -38,6 +38,7 @@ fn test_insufficient_balance_detailed_handling() {
 #[test]
 fn test_concurrent_order_placement_simulation() {
     let env = Env::default();
+    env.mock_all_auths();
     let contract_id = env.register(CounterContract, ());
     let client = CounterContractClient::new(&env, &contract_id);
 
@@ -83,6 +84,7 @@ fn test_concurrent_order_placement_simulation() {
 #[test]
 fn test_amm_precision_and_rounding_edge_cases() {
     let env = Env::default();
+    env.mock_all_auths();
     let contract_id = env.register(CounterContract, ());
     let client = CounterContractClient::new(&env, &contract_id);
 
@@ -116,6 +118,7 @@ fn test_amm_precision_and_rounding_edge_cases() {
 #[test]
 fn test_amm_behavior_with_liquidity_changes() {
     let env = Env::default();
+    env.mock_all_auths();
     let contract_id = env.register(CounterContract, ());
     let client = CounterContractClient::new(&env, &contract_id);
 
@@ -246,6 +249,7 @@ fn test_slippage_protection_enforcement() {
 #[test]
 fn test_rate_limiting_integration_with_trading() {
     let env = Env::default();
+    env.mock_all_auths();
     let contract_id = env.register(CounterContract, ());
     let client = CounterContractClient::new(&env, &contract_id);
 
@@ -281,6 +285,7 @@ fn test_rate_limiting_integration_with_trading() {
 #[test]
 fn test_transaction_history_tracking() {
     let env = Env::default();
+    env.mock_all_auths();
     let contract_id = env.register(CounterContract, ());
     let client = CounterContractClient::new(&env, &contract_id);
 
@@ -314,6 +319,7 @@ fn test_transaction_history_tracking() {
 #[test]
 fn test_fee_calculation_and_collection() {
     let env = Env::default();
+    env.mock_all_auths();
     let contract_id = env.register(CounterContract, ());
     let client = CounterContractClient::new(&env, &contract_id);
 
@@ -345,6 +351,7 @@ fn test_fee_calculation_and_collection() {
 #[test]
 fn test_portfolio_statistics_updates() {
     let env = Env::default();
+    env.mock_all_auths();
     let contract_id = env.register(CounterContract, ());
     let client = CounterContractClient::new(&env, &contract_id);
 
@@ -375,6 +382,7 @@ fn test_portfolio_statistics_updates() {
 #[test]
 fn test_badge_system_integration_with_trading() {
     let env = Env::default();
+    env.mock_all_auths();
     let contract_id = env.register(CounterContract, ());
     let client = CounterContractClient::new(&env, &contract_id);
 
@@ -407,3 +415,34 @@ fn test_badge_system_integration_with_trading() {
     let final_badges = client.get_user_badges(&user);
     assert!(final_badges.len() >= 1);
 }
+
+/// Test: Failure Breakdown by Reason
+/// Tests that safe_swap attributes each declined order to its cause
+#[test]
+fn test_failure_breakdown_attributes_reasons() {
+    let env = Env::default();
+    let contract_id = env.register(CounterContract, ());
+    let client = CounterContractClient::new(&env, &contract_id);
+
+    let user = Address::generate(&env);
+    let xlm = symbol_short!("XLM");
+    let usdc = symbol_short!("USDCSIM");
+
+    client.mint(&xlm, &user, &20000);
+    client.set_pool_liquidity(&xlm, &10000);
+    client.set_pool_liquidity(&usdc, &10000);
+    // The 0.3% LP fee alone costs ~21 bps on a 5000 XLM swap
+    client.set_max_slippage_bps(&10);
+
+    // Slippage failure
+    assert_eq!(client.safe_swap(&xlm, &usdc, &5000, &user), 0);
+
+    // Balance failure: the user holds no USDCSIM
+    assert_eq!(client.safe_swap(&usdc, &xlm, &500, &user), 0);
+
+    let breakdown = client.get_failure_breakdown();
+    assert_eq!(breakdown.get(FailureReason::SlippageExceeded), Some(1));
+    assert_eq!(breakdown.get(FailureReason::InsufficientBalance), Some(1));
+    assert_eq!(breakdown.get(FailureReason::InvalidInput), None);
+    assert_eq!(client.get_metrics().failed_orders, 2);
+}
@@ -6,6 +6,14 @@ use soroban_sdk::contracterror;
 pub enum SwapTradeError {
     NotAdmin = 1,
     TradingPaused = 2,
+    /// Requested version is lower than the stored one or has no upgrade path
+    InvalidMigrationTarget = 3,
+    /// Contract invariants failed after running migration steps
+    MigrationInvariantViolation = 4,
+    /// Trade bounds are empty or below the minimum unit (min < 1 or min > max)
+    InvalidTradeBounds = 5,
+    /// An admin is already configured and cannot be initialized again
+    AdminAlreadySet = 6,
 }
 
 /// Extended errors including security/validation errors
@@ -24,6 +32,8 @@ pub enum ContractError {
     InsufficientBalance = 104,
     /// Zero amount swap not allowed
     ZeroAmountSwap = 105,
+    /// Spender's approved allowance does not cover the amount
+    InsufficientAllowance = 106,
     /// Contract invariant violation - security issue
     InvariantViolation = 200,
     /// Price oracle data is stale
@@ -36,8 +46,39 @@ pub enum ContractError {
     RateLimitExceeded = 300,
     /// Slippage tolerance exceeded
     SlippageExceeded = 301,
+    /// Swap would move the pool price beyond its configured cap
+    PriceImpactTooHigh = 302,
+    /// No swap commitment is pending for the user
+    CommitmentNotFound = 303,
+    /// Revealed parameters and salt do not hash to the pending commitment
+    CommitmentMismatch = 304,
+    /// Reveal arrived before the commitment's minimum delay elapsed
+    RevealTooEarly = 305,
+    /// Reveal arrived after the commitment's deadline
+    RevealExpired = 306,
+    /// Commitment deadline leaves no valid reveal time or is too far out
+    InvalidRevealDeadline = 307,
     /// LP position not found
     LPPositionNotFound = 400,
     /// Insufficient LP tokens
     InsufficientLPTokens = 401,
+    /// No pool for the pair holds any reserve of the input token
+    InsufficientLiquidity = 402,
+    /// Contract is paused; only exits are allowed
+    ContractPaused = 500,
+    /// Caller is not the configured admin
+    NotAdmin = 501,
+}
+
+impl From<SwapTradeError> for ContractError {
+    fn from(err: SwapTradeError) -> Self {
+        match err {
+            SwapTradeError::NotAdmin | SwapTradeError::AdminAlreadySet => ContractError::NotAdmin,
+            SwapTradeError::TradingPaused => ContractError::ContractPaused,
+            SwapTradeError::InvalidTradeBounds => ContractError::InvalidAmount,
+            SwapTradeError::InvalidMigrationTarget | SwapTradeError::MigrationInvariantViolation => {
+                ContractError::InvariantViolation
+            }
+        }
+    }
 }
@@ -104,6 +104,110 @@ impl Events {
             .publish((Symbol::new(env, "AdminResumed"), admin), (timestamp,));
     }
 
+    pub fn contract_migrated(
+        env: &Env,
+        admin: Address,
+        from_version: u32,
+        to_version: u32,
+        timestamp: i64,
+    ) {
+        env.events().publish(
+            (Symbol::new(env, "ContractMigrated"), admin),
+            (from_version, to_version, timestamp),
+        );
+    }
+
+    pub fn rate_limit_allowlist_changed(
+        env: &Env,
+        admin: Address,
+        user: Address,
+        allowed: bool,
+        timestamp: i64,
+    ) {
+        env.events().publish(
+            (Symbol::new(env, "RateLimitAllowlistChanged"), user),
+            (admin, allowed, timestamp),
+        );
+    }
+
+    /// Security audit record for an operation refused by the rate limiter.
+    /// Off-chain tooling maps this onto an `AuditEvent` with
+    /// `EventCategory::Security`.
+    ///
+    /// Topic  : ("AuditEvent", "Security", user)
+    /// Payload: (action, used, limit, cooldown_ms, timestamp)
+    pub fn rate_limit_blocked(
+        env: &Env,
+        user: Address,
+        action: &str,
+        status: &crate::rate_limit::RateLimitStatus,
+        timestamp: i64,
+    ) {
+        env.events().publish(
+            (Symbol::new(env, "AuditEvent"), Symbol::new(env, "Security"), user),
+            (
+                Symbol::new(env, action),
+                status.used,
+                status.limit,
+                status.cooldown_ms,
+                timestamp,
+            ),
+        );
+    }
+
+    /// Administrative audit record for a completed contract migration.
+    ///
+    /// Topic  : ("AuditEvent", "Administrative", admin)
+    /// Payload: ("MIGRATION", from_version, to_version, timestamp)
+    pub fn migration_audited(
+        env: &Env,
+        admin: Address,
+        from_version: u32,
+        to_version: u32,
+        timestamp: i64,
+    ) {
+        env.events().publish(
+            (Symbol::new(env, "AuditEvent"), Symbol::new(env, "Administrative"), admin),
+            (Symbol::new(env, "MIGRATION"), from_version, to_version, timestamp),
+        );
+    }
+
+    /// Trading audit record for one referral commission credited to a referrer.
+    ///
+    /// Topic  : ("AuditEvent", "Trading", trader)
+    /// Payload: ("COMMISSION_PAID", referrer, tier, amount, timestamp)
+    pub fn commission_distributed(
+        env: &Env,
+        trader: Address,
+        referrer: Address,
+        tier: crate::referral::CommissionTier,
+        amount: i128,
+        timestamp: i64,
+    ) {
+        env.events().publish(
+            (Symbol::new(env, "AuditEvent"), Symbol::new(env, "Trading"), trader),
+            (Symbol::new(env, "COMMISSION_PAID"), referrer, tier, amount, timestamp),
+        );
+    }
+
+    /// Trading audit record closing out one `distribute_commission` call.
+    ///
+    /// Topic  : ("AuditEvent", "Trading", trader)
+    /// Payload: ("COMMISSION_TOTAL", recipients, total_amount, timestamp)
+    pub fn commission_distribution_summary(
+        env: &Env,
+        trader: Address,
+        recipients: u32,
+        total_amount: i128,
+        timestamp: i64,
+    ) {
+        env.events().publish(
+            (Symbol::new(env, "AuditEvent"), Symbol::new(env, "Trading"), trader),
+            (Symbol::new(env, "COMMISSION_TOTAL"), recipients, total_amount, timestamp),
+        );
+    }
+}
+
 impl Events {
     /// Emitted whenever an alert fires. Carries enough metadata for an
     /// off-chain indexer to route a push notification or webhook call.
@@ -148,7 +252,7 @@ impl Events {
             (kind_tag, expires_at),
         );
     }
-}
+
     pub fn performance_metrics_calculated(
         env: &Env,
         user: Address,
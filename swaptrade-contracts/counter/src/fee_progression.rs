@@ -1,4 +1,5 @@
-use soroban_sdk::{contracttype, Address, Env, Symbol, Map, Vec};
+use soroban_sdk::{contracttype, symbol_short, Address, Env, Map, Vec};
+use crate::referral::ReferralSystem;
 use crate::tiers::UserTier;
 
 /// Achievement categories for fee discounts
@@ -37,7 +38,8 @@ pub struct AchievementStatus {
     pub achievements: Vec<Achievement>,
     /// Current trading streak (days)
     pub current_streak: u32,
-    /// Last trading day timestamp
+    /// Day index (timestamp / 86400) of the most recent trade; unset while
+    /// `current_streak` is 0
     pub last_trade_day: u64,
     /// Maximum loss percentage (for risk management)
     pub max_loss_percentage: u32,
@@ -51,6 +53,33 @@ pub struct AchievementStatus {
     pub last_recalculation: u64,
 }
 
+/// What happened to an achievement in a user's history
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[contracttype]
+pub enum AchievementEventKind {
+    /// Newly granted
+    Earned,
+    /// Re-earned while still active, replacing the previous grant
+    Stacked,
+    /// Removed after reaching `expires_at`
+    Expired,
+    /// Removed because the user no longer meets the criteria
+    Revoked,
+}
+
+/// One entry in a user's achievement history
+#[derive(Clone, Debug, PartialEq)]
+#[contracttype]
+pub struct AchievementEvent {
+    pub kind: AchievementEventKind,
+    pub category: AchievementCategory,
+    pub discount_bps: u32,
+    pub timestamp: u64,
+}
+
+/// Achievement history entries kept per user; the oldest are dropped beyond this
+pub const ACHIEVEMENT_HISTORY_CAP: u32 = 100;
+
 /// Fee progression result with breakdown
 #[derive(Clone, Debug)]
 #[contracttype]
@@ -59,23 +88,55 @@ pub struct FeeCalculationResult {
     pub base_fee_bps: u32,
     /// Total achievement discount
     pub achievement_discount_bps: u32,
+    /// Referee rebate applied on top of the achievement discount
+    pub referee_rebate_bps: u32,
     /// Final effective fee after discounts
     pub effective_fee_bps: u32,
-    /// Maximum allowed discount (30% of base fee)
+    /// Maximum allowed discount (`max_discount_pct` of base fee)
     pub max_discount_bps: u32,
     /// Applied discounts breakdown
     pub applied_discounts: Vec<AchievementCategory>,
 }
 
+/// Lifetime fee totals for a user, accumulated by `charge_trade_fee`
+#[derive(Clone, Debug, Default, PartialEq)]
+#[contracttype]
+pub struct UserFeeStats {
+    /// Fees charged across all trades (same unit as the trade amounts)
+    pub total_fees_paid: i128,
+    /// Achievement discount summed over every charged trade, in bps
+    /// (contract field names are capped at 30 characters)
+    pub total_discount_bps_applied: u64,
+    /// Number of trades charged
+    pub trades_charged: u32,
+}
+
 /// Fee progression engine for dynamic fee calculation
+#[derive(Clone)]
+#[contracttype]
 pub struct FeeProgression {
     /// User achievement status mapping
-    user_achievements: Map<Address, AchievementStatus>,
+    pub(crate) user_achievements: Map<Address, AchievementStatus>,
+
+    /// Per-user fee totals
+    user_fee_stats: Map<Address, UserFeeStats>,
     
     /// Global achievement definitions
     achievement_definitions: Map<AchievementCategory, AchievementDefinition>,
+
+    /// Per-user traded volume keyed by day number, for time-weighted volume checks
+    daily_volumes: Map<(Address, u64), i128>,
+
+    /// Cap on the total achievement discount, as a percentage of the base fee
+    max_discount_pct: u32,
+
+    /// Per-user log of earned, stacked, expired and revoked achievements
+    achievement_events: Map<Address, Vec<AchievementEvent>>,
 }
 
+/// Achievement discounts may remove at most this share of the base fee by default
+pub const DEFAULT_MAX_DISCOUNT_PCT: u32 = 30;
+
 /// Achievement definition with criteria and rewards
 #[derive(Clone, Debug)]
 #[contracttype]
@@ -90,6 +151,8 @@ pub struct AchievementDefinition {
     pub max_stackable_bps: u32,
     /// Achievement criteria
     pub criteria: AchievementCriteria,
+    /// How volume is measured against `criteria` (only used by Volume)
+    pub volume_measure: VolumeMeasure,
 }
 
 /// Criteria for earning achievements
@@ -114,6 +177,18 @@ pub enum MeasurementType {
     Volume,
 }
 
+/// How a Volume achievement's window is evaluated
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[contracttype]
+pub enum VolumeMeasure {
+    /// Raw sum over the window must reach the minimum
+    WindowSum,
+    /// Average daily volume over the window, with each day capped at the
+    /// daily target (minimum / window days), must reach that target. A single
+    /// spike can only ever fill one day's share.
+    TimeWeightedAverage,
+}
+
 impl FeeProgression {
     pub fn new(env: &Env) -> Self {
         let mut definitions = Map::new(env);
@@ -131,6 +206,7 @@ impl FeeProgression {
                     measurement_type: MeasurementType::Days,
                     time_window_days: Some(7),
                 },
+                volume_measure: VolumeMeasure::WindowSum,
             },
         );
         
@@ -147,6 +223,7 @@ impl FeeProgression {
                     measurement_type: MeasurementType::Percentage,
                     time_window_days: None,
                 },
+                volume_measure: VolumeMeasure::WindowSum,
             },
         );
         
@@ -163,6 +240,7 @@ impl FeeProgression {
                     measurement_type: MeasurementType::Rank,
                     time_window_days: None,
                 },
+                volume_measure: VolumeMeasure::WindowSum,
             },
         );
         
@@ -179,19 +257,24 @@ impl FeeProgression {
                     measurement_type: MeasurementType::Volume,
                     time_window_days: Some(30),
                 },
+                volume_measure: VolumeMeasure::WindowSum,
             },
         );
         
         Self {
             user_achievements: Map::new(env),
+            user_fee_stats: Map::new(env),
             achievement_definitions: definitions,
+            daily_volumes: Map::new(env),
+            max_discount_pct: DEFAULT_MAX_DISCOUNT_PCT,
+            achievement_events: Map::new(env),
         }
     }
 
     /// Calculate effective fee with achievement bonuses
     pub fn calculate_effective_fee(&mut self, env: &Env, user: &Address, user_tier: &UserTier) -> FeeCalculationResult {
         let base_fee_bps = user_tier.effective_fee_bps();
-        let max_discount_bps = (base_fee_bps * 30) / 100; // Max 30% reduction
+        let max_discount_bps = (base_fee_bps * self.max_discount_pct) / 100;
         
         // Get or create user achievement status
         let mut status = self.user_achievements.get(user.clone()).unwrap_or_else(|| {
@@ -246,12 +329,60 @@ impl FeeProgression {
         FeeCalculationResult {
             base_fee_bps,
             achievement_discount_bps: final_discount,
+            referee_rebate_bps: 0,
             effective_fee_bps,
             max_discount_bps,
             applied_discounts,
         }
     }
 
+    /// Charge the fee for a trade of `amount`: computes the effective fee and
+    /// adds it, with the discount applied, to the user's fee statistics.
+    pub fn charge_trade_fee(
+        &mut self,
+        env: &Env,
+        user: &Address,
+        user_tier: &UserTier,
+        amount: i128,
+    ) -> (FeeCalculationResult, i128) {
+        let result = self.calculate_effective_fee(env, user, user_tier);
+        self.record_fee_charge(user, result, amount)
+    }
+
+    /// `charge_trade_fee`, with `referrals` rebating a referee's fee after the
+    /// achievement discount is applied.
+    pub fn charge_trade_fee_with_referrals(
+        &mut self,
+        env: &Env,
+        user: &Address,
+        user_tier: &UserTier,
+        amount: i128,
+        referrals: &mut ReferralSystem,
+    ) -> (FeeCalculationResult, i128) {
+        let mut result = self.calculate_effective_fee(env, user, user_tier);
+        referrals.apply_referee_rebate(user, &mut result);
+        self.record_fee_charge(user, result, amount)
+    }
+
+    fn record_fee_charge(&mut self, user: &Address, result: FeeCalculationResult, amount: i128) -> (FeeCalculationResult, i128) {
+        let fee_amount = (amount * result.effective_fee_bps as i128) / 10000;
+
+        let mut stats = self.user_fee_stats.get(user.clone()).unwrap_or_default();
+        stats.total_fees_paid = stats.total_fees_paid.saturating_add(fee_amount);
+        stats.total_discount_bps_applied = stats
+            .total_discount_bps_applied
+            .saturating_add(result.achievement_discount_bps as u64);
+        stats.trades_charged = stats.trades_charged.saturating_add(1);
+        self.user_fee_stats.set(user.clone(), stats);
+
+        (result, fee_amount)
+    }
+
+    /// Lifetime fee totals for `user`; all zero before their first charged trade
+    pub fn get_user_fee_stats(&self, _env: &Env, user: &Address) -> UserFeeStats {
+        self.user_fee_stats.get(user.clone()).unwrap_or_default()
+    }
+
     /// Check user's progression toward next tier
     pub fn check_tier_progression(&self, env: &Env, user: &Address) -> TierProgressionInfo {
         let status = self.user_achievements.get(user.clone()).unwrap_or_else(|| {
@@ -307,10 +438,15 @@ impl FeeProgression {
         // Add new achievement
         status.achievements.push_back(achievement.clone());
         self.user_achievements.set(user.clone(), status);
+
+        let mut events = Vec::new(env);
+        push_event(&mut events, AchievementEventKind::Earned, &achievement, achievement.earned_at);
+        self.append_history(env, user, events);
         
         // Emit achievement event
         env.events().publish(
-            (symbol_short!("achievement_earned"), user.clone(), achievement.category, achievement.discount_bps),
+            (symbol_short!("ach_earn"), user.clone()),
+            (achievement.category, achievement.discount_bps),
         );
         
         Ok(())
@@ -319,44 +455,62 @@ impl FeeProgression {
     /// Update achievement status based on current user data
     fn update_achievement_status(&mut self, env: &Env, user: &Address, status: &mut AchievementStatus) {
         let current_timestamp = env.ledger().timestamp();
+        let mut events = Vec::new(env);
         
         // Check consistency achievement (7-day streak)
-        self.check_consistency_achievement(env, status, current_timestamp);
+        self.check_consistency_achievement(env, status, current_timestamp, &mut events);
         
         // Check risk management achievement
-        self.check_risk_management_achievement(env, status);
+        self.check_risk_management_achievement(env, status, &mut events);
         
         // Check community achievement (leaderboard)
-        self.check_community_achievement(env, status);
+        self.check_community_achievement(env, status, &mut events);
         
         // Check volume achievement
-        self.check_volume_achievement(env, status, current_timestamp);
+        self.check_volume_achievement(env, user, status, current_timestamp, &mut events);
         
         // Clean up expired achievements
-        self.cleanup_expired_achievements(env, status, current_timestamp);
+        self.cleanup_expired_achievements(env, status, current_timestamp, &mut events);
+
+        self.append_history(env, user, events);
+    }
+
+    /// Append `events` to the user's achievement history, keeping the newest
+    /// `ACHIEVEMENT_HISTORY_CAP` entries
+    fn append_history(&mut self, env: &Env, user: &Address, events: Vec<AchievementEvent>) {
+        if events.is_empty() {
+            return;
+        }
+        let mut history = self.achievement_events.get(user.clone()).unwrap_or_else(|| Vec::new(env));
+        for event in events.iter() {
+            history.push_back(event);
+        }
+        while history.len() > ACHIEVEMENT_HISTORY_CAP {
+            history.pop_front();
+        }
+        self.achievement_events.set(user.clone(), history);
+    }
+
+    /// Up to `limit` of the user's most recent achievement events, oldest first
+    pub fn get_achievement_history(&self, env: &Env, user: &Address, limit: u32) -> Vec<AchievementEvent> {
+        let history = self.achievement_events.get(user.clone()).unwrap_or_else(|| Vec::new(env));
+        let skip = history.len().saturating_sub(limit);
+        history.slice(skip..)
     }
 
     /// Check and update consistency achievement
-    fn check_consistency_achievement(&self, env: &Env, status: &mut AchievementStatus, current_timestamp: u64) {
+    fn check_consistency_achievement(&self, env: &Env, status: &mut AchievementStatus, current_timestamp: u64, events: &mut Vec<AchievementEvent>) {
         if let Some(definition) = self.achievement_definitions.get(AchievementCategory::Consistency) {
             let current_day = current_timestamp / (24 * 60 * 60); // Convert to days
             
-            // Check if user traded today (this would be updated by trading system)
-            let traded_today = current_day == status.last_trade_day;
-            
-            if traded_today {
-                status.current_streak += 1;
-            } else {
-                // Check if it's been more than 1 day since last trade
-                if current_day > status.last_trade_day + 1 {
-                    status.current_streak = 1; // Reset streak
-                }
+            // Trades advance the streak in `record_trade_day`; here we only
+            // notice a whole day passing without one. Today may still extend it.
+            if status.current_streak > 0 && current_day > status.last_trade_day.saturating_add(1) {
+                status.current_streak = 0;
             }
             
-            status.last_trade_day = current_day;
-            
             // Check if streak qualifies for achievement
-            if status.current_streak >= definition.criteria.minimum_value {
+            if u64::from(status.current_streak) >= definition.criteria.minimum_value {
                 let new_achievement = Achievement {
                     category: AchievementCategory::Consistency,
                     discount_bps: definition.discount_bps,
@@ -367,25 +521,35 @@ impl FeeProgression {
                 };
                 
                 // Remove existing consistency achievement if any
-                status.achievements.retain(|achievement| achievement.category != AchievementCategory::Consistency);
+                let stacked = status.achievements.iter().any(|achievement| achievement.category == AchievementCategory::Consistency);
+                let mut kept = Vec::new(env);
+                for achievement in status.achievements.iter() {
+                    if achievement.category != AchievementCategory::Consistency {
+                        kept.push_back(achievement);
+                    }
+                }
+                status.achievements = kept;
                 
                 // Add new achievement
+                let kind = if stacked { AchievementEventKind::Stacked } else { AchievementEventKind::Earned };
+                push_event(events, kind, &new_achievement, current_timestamp);
                 status.achievements.push_back(new_achievement);
                 
                 // Emit event
                 env.events().publish(
-                    (symbol_short!("streak_achievement"), status.current_streak, definition.discount_bps),
+                    (symbol_short!("streak"),),
+                    (status.current_streak, definition.discount_bps),
                 );
             }
         }
     }
 
     /// Check and update risk management achievement
-    fn check_risk_management_achievement(&self, env: &Env, status: &mut AchievementStatus) {
+    fn check_risk_management_achievement(&self, env: &Env, status: &mut AchievementStatus, events: &mut Vec<AchievementEvent>) {
         if let Some(definition) = self.achievement_definitions.get(AchievementCategory::RiskManagement) {
             // This would be updated by trading system to track maximum loss
             // For now, assume user meets criteria if max_loss_percentage <= 5
-            if status.max_loss_percentage <= definition.criteria.minimum_value {
+            if u64::from(status.max_loss_percentage) <= definition.criteria.minimum_value {
                 let current_timestamp = env.ledger().timestamp();
                 
                 // Check if user already has this achievement
@@ -403,11 +567,13 @@ impl FeeProgression {
                         is_active: true,
                     };
                     
+                    push_event(events, AchievementEventKind::Earned, &new_achievement, current_timestamp);
                     status.achievements.push_back(new_achievement);
                     
                     // Emit event
                     env.events().publish(
-                        (symbol_short!("risk_achievement"), status.max_loss_percentage, definition.discount_bps),
+                        (symbol_short!("risk_ach"),),
+                        (status.max_loss_percentage, definition.discount_bps),
                     );
                 }
             }
@@ -415,46 +581,76 @@ impl FeeProgression {
     }
 
     /// Check and update community achievement
-    fn check_community_achievement(&self, env: &Env, status: &mut AchievementStatus) {
+    fn check_community_achievement(&self, env: &Env, status: &mut AchievementStatus, events: &mut Vec<AchievementEvent>) {
         if let Some(definition) = self.achievement_definitions.get(AchievementCategory::Community) {
-            // This would be updated by leaderboard system
-            // For now, assume user is in top 100 if rank <= 100
-            if let Some(rank) = status.leaderboard_rank {
-                if rank <= definition.criteria.minimum_value {
-                    let current_timestamp = env.ledger().timestamp();
-                    
-                    // Check if user already has this achievement
-                    let has_achievement = status.achievements.iter().any(|achievement| {
-                        achievement.category == AchievementCategory::Community && achievement.is_active
-                    });
-                    
-                    if !has_achievement {
-                        let new_achievement = Achievement {
-                            category: AchievementCategory::Community,
-                            discount_bps: definition.discount_bps,
-                            earned_at: current_timestamp,
-                            expires_at: current_timestamp + (90 * 24 * 60 * 60),
-                            metadata: rank as u64,
-                            is_active: true,
-                        };
-                        
-                        status.achievements.push_back(new_achievement);
-                        
-                        // Emit event
-                        env.events().publish(
-                            (symbol_short!("community_achievement"), rank, definition.discount_bps),
-                        );
+            // Rank is fed by the leaderboard via update_leaderboard_rank
+            let in_top = matches!(status.leaderboard_rank, Some(rank) if rank as u64 <= definition.criteria.minimum_value);
+            let has_achievement = status.achievements.iter().any(|achievement| {
+                achievement.category == AchievementCategory::Community && achievement.is_active
+            });
+
+            if in_top && !has_achievement {
+                let rank = status.leaderboard_rank.unwrap_or(0);
+                let current_timestamp = env.ledger().timestamp();
+                let new_achievement = Achievement {
+                    category: AchievementCategory::Community,
+                    discount_bps: definition.discount_bps,
+                    earned_at: current_timestamp,
+                    expires_at: current_timestamp + (90 * 24 * 60 * 60),
+                    metadata: rank as u64,
+                    is_active: true,
+                };
+
+                push_event(events, AchievementEventKind::Earned, &new_achievement, current_timestamp);
+                status.achievements.push_back(new_achievement);
+
+                // Emit event
+                env.events().publish(
+                    (symbol_short!("comm_ach"),),
+                    (rank, definition.discount_bps),
+                );
+            } else if !in_top && has_achievement {
+                // Trader fell off the leaderboard: the discount no longer applies
+                let current_timestamp = env.ledger().timestamp();
+                let mut kept = Vec::new(env);
+                for achievement in status.achievements.iter() {
+                    if achievement.category != AchievementCategory::Community {
+                        kept.push_back(achievement);
+                    } else {
+                        push_event(events, AchievementEventKind::Revoked, &achievement, current_timestamp);
                     }
                 }
+                status.achievements = kept;
+
+                env.events().publish(
+                    (symbol_short!("comm_lost"),),
+                    definition.discount_bps,
+                );
             }
         }
     }
 
     /// Check and update volume achievement
-    fn check_volume_achievement(&self, env: &Env, status: &mut AchievementStatus, current_timestamp: u64) {
+    fn check_volume_achievement(&self, env: &Env, user: &Address, status: &mut AchievementStatus, current_timestamp: u64, events: &mut Vec<AchievementEvent>) {
         if let Some(definition) = self.achievement_definitions.get(AchievementCategory::Volume) {
-            // Check if 30-day volume meets criteria
-            if status.volume_30_days >= definition.criteria.minimum_value.into() {
+            // Accumulated volume only grows; a negative total means a bad
+            // decrement upstream, so treat it as no volume rather than compare it
+            if status.volume_30_days < 0 {
+                env.events().publish(
+                    (symbol_short!("vol_clamp"), user.clone()),
+                    status.volume_30_days,
+                );
+                status.volume_30_days = 0;
+            }
+            let qualifies = match definition.volume_measure {
+                VolumeMeasure::WindowSum => status.volume_30_days >= i128::from(definition.criteria.minimum_value),
+                VolumeMeasure::TimeWeightedAverage => {
+                    let window_days = definition.criteria.time_window_days.unwrap_or(30).max(1);
+                    let daily_target = (definition.criteria.minimum_value / window_days as u64) as i128;
+                    self.time_weighted_daily_volume(user, current_timestamp, window_days, daily_target) >= daily_target
+                }
+            };
+            if qualifies {
                 // Check if user already has this achievement
                 let has_achievement = status.achievements.iter().any(|achievement| {
                     achievement.category == AchievementCategory::Volume && achievement.is_active
@@ -466,32 +662,76 @@ impl FeeProgression {
                         discount_bps: definition.discount_bps,
                         earned_at: current_timestamp,
                         expires_at: current_timestamp + (90 * 24 * 60 * 60),
-                        metadata: status.volume_30_days,
+                        metadata: u64::try_from(status.volume_30_days).unwrap_or(u64::MAX),
                         is_active: true,
                     };
                     
+                    push_event(events, AchievementEventKind::Earned, &new_achievement, current_timestamp);
                     status.achievements.push_back(new_achievement);
                     
                     // Emit event
                     env.events().publish(
-                        (symbol_short!("volume_achievement"), status.volume_30_days, definition.discount_bps),
+                        (symbol_short!("vol_ach"),),
+                        (status.volume_30_days, definition.discount_bps),
                     );
                 }
             }
         }
     }
 
+    /// Average daily volume over the `window_days` ending today, with each day
+    /// capped at `daily_cap` so one large day can't stand in for the rest
+    fn time_weighted_daily_volume(&self, user: &Address, current_timestamp: u64, window_days: u32, daily_cap: i128) -> i128 {
+        let today = current_timestamp / (24 * 60 * 60);
+        let mut total: i128 = 0;
+        for offset in 0..window_days as u64 {
+            let Some(day) = today.checked_sub(offset) else { break };
+            let volume = self.daily_volumes.get((user.clone(), day)).unwrap_or(0);
+            total = total.saturating_add(volume.min(daily_cap));
+        }
+        total / window_days as i128
+    }
+
+    /// Choose how the Volume achievement's window is evaluated
+    pub fn set_volume_measure(&mut self, measure: VolumeMeasure) {
+        if let Some(mut definition) = self.achievement_definitions.get(AchievementCategory::Volume) {
+            definition.volume_measure = measure;
+            self.achievement_definitions.set(AchievementCategory::Volume, definition);
+        }
+    }
+
+    /// Set the cap on achievement discounts as a percentage of the base fee (0-100)
+    pub fn set_max_discount_pct(&mut self, max_discount_pct: u32) -> Result<(), &'static str> {
+        if max_discount_pct > 100 {
+            return Err("Max discount exceeds 100%");
+        }
+        self.max_discount_pct = max_discount_pct;
+        Ok(())
+    }
+
+    pub fn get_max_discount_pct(&self) -> u32 {
+        self.max_discount_pct
+    }
+
+    /// Current definition for `category`, including the volume measure in use
+    pub fn get_achievement_definition(&self, category: AchievementCategory) -> Option<AchievementDefinition> {
+        self.achievement_definitions.get(category)
+    }
+
     /// Remove expired achievements
-    fn cleanup_expired_achievements(&self, env: &Env, status: &mut AchievementStatus, current_timestamp: u64) {
+    fn cleanup_expired_achievements(&self, env: &Env, status: &mut AchievementStatus, current_timestamp: u64, events: &mut Vec<AchievementEvent>) {
         let mut active_achievements = Vec::new(env);
         
         for achievement in status.achievements.iter() {
             if current_timestamp < achievement.expires_at {
                 active_achievements.push_back(achievement.clone());
             } else {
+                push_event(events, AchievementEventKind::Expired, &achievement, current_timestamp);
+
                 // Emit expiration event
                 env.events().publish(
-                    (symbol_short!("achievement_expired"), achievement.category, achievement.discount_bps),
+                    (symbol_short!("ach_exp"),),
+                    (achievement.category, achievement.discount_bps),
                 );
             }
         }
@@ -504,6 +744,25 @@ impl FeeProgression {
         self.user_achievements.get(user.clone())
     }
 
+    /// Record a user's current leaderboard position (`None` when off the board).
+    /// The Community achievement is granted or revoked on the next fee calculation.
+    pub fn update_leaderboard_rank(&mut self, env: &Env, user: &Address, rank: Option<u32>) {
+        let mut status = self.user_achievements.get(user.clone()).unwrap_or_else(|| {
+            AchievementStatus {
+                achievements: Vec::new(env),
+                current_streak: 0,
+                last_trade_day: 0,
+                max_loss_percentage: 0,
+                leaderboard_rank: None,
+                volume_30_days: 0,
+                total_discount_bps: 0,
+                last_recalculation: 0,
+            }
+        });
+        status.leaderboard_rank = rank;
+        self.user_achievements.set(user.clone(), status);
+    }
+
     /// Update user trading data (called by trading system)
     pub fn update_trading_activity(&mut self, env: &Env, user: &Address, trade_volume: i128, loss_percentage: Option<u32>) {
         let mut status = self.user_achievements.get(user.clone()).unwrap_or_else(|| {
@@ -521,7 +780,14 @@ impl FeeProgression {
         
         // Update volume (simplified - would use proper rolling window in production)
         status.volume_30_days += trade_volume;
+
+        let day = env.ledger().timestamp() / (24 * 60 * 60);
+        let key = (user.clone(), day);
+        let day_volume = self.daily_volumes.get(key.clone()).unwrap_or(0);
+        self.daily_volumes.set(key, day_volume.saturating_add(trade_volume));
         
+        record_trade_day(&mut status, day);
+
         // Update max loss percentage
         if let Some(loss_pct) = loss_percentage {
             status.max_loss_percentage = status.max_loss_percentage.max(loss_pct);
@@ -534,6 +800,31 @@ impl FeeProgression {
     }
 }
 
+/// Advance the streak for a trade on `day`. The first trade of a day extends
+/// a streak whose last trade was the day before; after a missed day it starts
+/// over at 1. Further trades on the same day leave it unchanged.
+fn record_trade_day(status: &mut AchievementStatus, day: u64) {
+    let has_streak = status.current_streak > 0;
+    if has_streak && day == status.last_trade_day {
+        return;
+    }
+    status.current_streak = if has_streak && day == status.last_trade_day.saturating_add(1) {
+        status.current_streak.saturating_add(1)
+    } else {
+        1
+    };
+    status.last_trade_day = day;
+}
+
+fn push_event(events: &mut Vec<AchievementEvent>, kind: AchievementEventKind, achievement: &Achievement, timestamp: u64) {
+    events.push_back(AchievementEvent {
+        kind,
+        category: achievement.category.clone(),
+        discount_bps: achievement.discount_bps,
+        timestamp,
+    });
+}
+
 /// Information about tier progression
 #[derive(Clone, Debug)]
 #[contracttype]
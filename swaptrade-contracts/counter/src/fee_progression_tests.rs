@@ -1,7 +1,7 @@
-use soroban_sdk::{Env, Symbol, Address};
+use soroban_sdk::{testutils::Address as _, Address, Env, Vec};
 use crate::fee_progression::{
     FeeProgression, AchievementCategory, Achievement, AchievementStatus, 
-    FeeCalculationResult, TierProgressionInfo
+    UserFeeStats, VolumeMeasure
 };
 use crate::tiers::UserTier;
 
@@ -12,6 +12,18 @@ fn test_fee_calculation_without_achievements() {
     let user = Address::generate(&env);
     let user_tier = UserTier::Novice;
 
+    // A loss above the 5% threshold rules out the default risk-management discount
+    fee_progression.user_achievements.set(user.clone(), AchievementStatus {
+        achievements: Vec::new(&env),
+        current_streak: 0,
+        last_trade_day: 0,
+        max_loss_percentage: 10,
+        leaderboard_rank: None,
+        volume_30_days: 0,
+        total_discount_bps: 0,
+        last_recalculation: 0,
+    });
+
     // Calculate fee without any achievements
     let result = fee_progression.calculate_effective_fee(&env, &user, &user_tier);
 
@@ -34,7 +46,7 @@ fn test_consistency_achievement_7_day_streak() {
         achievements: Vec::new(&env),
         current_streak: 6,
         last_trade_day: 0,
-        max_loss_percentage: 0,
+        max_loss_percentage: 10, // above the risk-management threshold
         leaderboard_rank: None,
         volume_30_days: 0,
         total_discount_bps: 0,
@@ -64,7 +76,7 @@ fn test_risk_management_achievement() {
     let user_tier = UserTier::Expert;
 
     // Simulate user with good risk management (max 5% loss)
-    let mut status = AchievementStatus {
+    let status = AchievementStatus {
         achievements: Vec::new(&env),
         current_streak: 0,
         last_trade_day: 0,
@@ -94,11 +106,11 @@ fn test_community_achievement_top_100() {
     let user_tier = UserTier::Whale;
 
     // Simulate user in top 100 leaderboard
-    let mut status = AchievementStatus {
+    let status = AchievementStatus {
         achievements: Vec::new(&env),
         current_streak: 0,
         last_trade_day: 0,
-        max_loss_percentage: 0,
+        max_loss_percentage: 10, // above the risk-management threshold
         leaderboard_rank: Some(50), // Top 100
         volume_30_days: 0,
         total_discount_bps: 0,
@@ -107,6 +119,9 @@ fn test_community_achievement_top_100() {
 
     fee_progression.user_achievements.set(user.clone(), status);
 
+    // Whale's default 30% cap (4 bps) would clip the 5 bps discount
+    fee_progression.set_max_discount_pct(50).unwrap();
+
     // Calculate fee - should include community discount
     let result = fee_progression.calculate_effective_fee(&env, &user, &user_tier);
 
@@ -124,11 +139,11 @@ fn test_volume_achievement_50k_xlm() {
     let user_tier = UserTier::Trader;
 
     // Simulate user with high volume
-    let mut status = AchievementStatus {
+    let status = AchievementStatus {
         achievements: Vec::new(&env),
         current_streak: 0,
         last_trade_day: 0,
-        max_loss_percentage: 0,
+        max_loss_percentage: 10, // above the risk-management threshold
         leaderboard_rank: None,
         volume_30_days: 60000, // 60k XLM volume
         total_discount_bps: 0,
@@ -146,6 +161,45 @@ fn test_volume_achievement_50k_xlm() {
     assert!(result.applied_discounts.contains(&AchievementCategory::Volume));
 }
 
+#[test]
+fn test_time_weighted_volume_rejects_single_spike() {
+    use soroban_sdk::testutils::Ledger;
+
+    let env = Env::default();
+    let mut fee_progression = FeeProgression::new(&env);
+    fee_progression.set_volume_measure(VolumeMeasure::TimeWeightedAverage);
+    assert_eq!(
+        fee_progression.get_achievement_definition(AchievementCategory::Volume).unwrap().volume_measure,
+        VolumeMeasure::TimeWeightedAverage
+    );
+
+    let steady = Address::generate(&env);
+    let spiky = Address::generate(&env);
+    let day = 24 * 60 * 60;
+
+    // Both trade 60k over the window: 2k every day vs. 60k on the last day
+    for d in 0..30u64 {
+        env.ledger().with_mut(|li| li.timestamp = d * day);
+        fee_progression.update_trading_activity(&env, &steady, 2_000, None);
+    }
+    fee_progression.update_trading_activity(&env, &spiky, 60_000, None);
+
+    let has_volume = |fp: &FeeProgression, user: &Address| {
+        fp.get_achievement_status(user)
+            .unwrap()
+            .achievements
+            .iter()
+            .any(|a| a.category == AchievementCategory::Volume)
+    };
+    assert!(has_volume(&fee_progression, &steady));
+    assert!(!has_volume(&fee_progression, &spiky));
+
+    // Under the raw window sum both qualify
+    fee_progression.set_volume_measure(VolumeMeasure::WindowSum);
+    fee_progression.update_trading_activity(&env, &spiky, 1, None);
+    assert!(has_volume(&fee_progression, &spiky));
+}
+
 #[test]
 fn test_achievement_stacking_consistency() {
     let env = Env::default();
@@ -158,7 +212,7 @@ fn test_achievement_stacking_consistency() {
         achievements: Vec::new(&env),
         current_streak: 0,
         last_trade_day: 0,
-        max_loss_percentage: 0,
+        max_loss_percentage: 10, // above the risk-management threshold
         leaderboard_rank: None,
         volume_30_days: 0,
         total_discount_bps: 0,
@@ -180,6 +234,9 @@ fn test_achievement_stacking_consistency() {
 
     fee_progression.user_achievements.set(user.clone(), status);
 
+    // Lift the overall cap above 10 bps so the stacking limit is what binds
+    fee_progression.set_max_discount_pct(50).unwrap();
+
     // Calculate fee - should cap consistency discount at 10 bps
     let result = fee_progression.calculate_effective_fee(&env, &user, &user_tier);
 
@@ -258,6 +315,57 @@ fn test_discount_capping_30_percent_max() {
     assert_eq!(result.effective_fee_bps, 14); // 20 - 6 = 14
 }
 
+#[test]
+fn test_discount_cap_is_configurable() {
+    let env = Env::default();
+    let user = Address::generate(&env);
+    let user_tier = UserTier::Expert;
+
+    let mut status = AchievementStatus {
+        achievements: Vec::new(&env),
+        current_streak: 0,
+        last_trade_day: 0,
+        max_loss_percentage: 0,
+        leaderboard_rank: Some(1),
+        volume_30_days: 100000,
+        total_discount_bps: 0,
+        last_recalculation: 0,
+    };
+    for (category, discount_bps, metadata) in [
+        (AchievementCategory::Consistency, 10, 14),
+        (AchievementCategory::RiskManagement, 3, 4),
+        (AchievementCategory::Community, 5, 1),
+        (AchievementCategory::Volume, 4, 100000),
+    ] {
+        status.achievements.push_back(Achievement {
+            category,
+            discount_bps,
+            earned_at: env.ledger().timestamp(),
+            expires_at: env.ledger().timestamp() + (90 * 24 * 60 * 60),
+            metadata,
+            is_active: true,
+        });
+    }
+
+    let mut capped_30 = FeeProgression::new(&env);
+    assert_eq!(capped_30.get_max_discount_pct(), 30);
+    capped_30.user_achievements.set(user.clone(), status.clone());
+    let at_30 = capped_30.calculate_effective_fee(&env, &user, &user_tier);
+
+    let mut capped_50 = FeeProgression::new(&env);
+    capped_50.set_max_discount_pct(50).unwrap();
+    capped_50.user_achievements.set(user.clone(), status);
+    let at_50 = capped_50.calculate_effective_fee(&env, &user, &user_tier);
+
+    assert_eq!(at_30.max_discount_bps, 6); // 30% of 20
+    assert_eq!(at_50.max_discount_bps, 10); // 50% of 20
+    assert!(at_50.achievement_discount_bps > at_30.achievement_discount_bps);
+    assert!(at_50.effective_fee_bps < at_30.effective_fee_bps);
+
+    assert_eq!(capped_50.set_max_discount_pct(101), Err("Max discount exceeds 100%"));
+    assert_eq!(capped_50.get_max_discount_pct(), 50);
+}
+
 #[test]
 fn test_achievement_expiration() {
     let env = Env::default();
@@ -265,6 +373,9 @@ fn test_achievement_expiration() {
     let user = Address::generate(&env);
     let user_tier = UserTier::Trader;
 
+    use soroban_sdk::testutils::Ledger;
+    env.ledger().with_mut(|li| li.timestamp = 200 * 24 * 60 * 60);
+
     // Create expired achievement
     let past_timestamp = env.ledger().timestamp() - (100 * 24 * 60 * 60); // 100 days ago
     let expired_achievement = Achievement {
@@ -280,7 +391,7 @@ fn test_achievement_expiration() {
         achievements: Vec::new(&env),
         current_streak: 0,
         last_trade_day: 0,
-        max_loss_percentage: 0,
+        max_loss_percentage: 10, // above the risk-management threshold
         leaderboard_rank: None,
         volume_30_days: 0,
         total_discount_bps: 0,
@@ -352,7 +463,7 @@ fn test_duplicate_achievement_prevention() {
 #[test]
 fn test_tier_progression_info() {
     let env = Env::default();
-    let mut fee_progression = FeeProgression::new(&env);
+    let fee_progression = FeeProgression::new(&env);
     let user = Address::generate(&env);
 
     // Get tier progression info
@@ -394,7 +505,7 @@ fn test_non_stackable_achievement_combination() {
         achievements: Vec::new(&env),
         current_streak: 14, // 14-day streak (2 achievements worth)
         last_trade_day: 0,
-        max_loss_percentage: 0,
+        max_loss_percentage: 10, // above the risk-management threshold
         leaderboard_rank: Some(50), // Community achievement
         volume_30_days: 0,
         total_discount_bps: 0,
@@ -424,12 +535,13 @@ fn test_non_stackable_achievement_combination() {
     status.achievements.push_back(community_achievement);
     fee_progression.user_achievements.set(user.clone(), status);
 
-    // Calculate fee - should get 4 (consistency) + 5 (community) = 9 bps
+    // Calculate fee - the live 14-day streak re-issues consistency at its
+    // definition's 2 bps, so 2 (consistency) + 5 (community) = 7 bps
     let result = fee_progression.calculate_effective_fee(&env, &user, &user_tier);
 
     assert_eq!(result.base_fee_bps, 30); // Novice base fee
-    assert_eq!(result.achievement_discount_bps, 9); // 4 + 5 = 9
-    assert_eq!(result.effective_fee_bps, 21); // 30 - 9 = 21
+    assert_eq!(result.achievement_discount_bps, 7); // 2 + 5 = 7
+    assert_eq!(result.effective_fee_bps, 23); // 30 - 7 = 23
     assert_eq!(result.applied_discounts.len(), 2); // Both discounts applied
 }
 
@@ -446,7 +558,7 @@ fn test_fee_calculation_accuracy() {
         current_streak: 0,
         last_trade_day: 0,
         max_loss_percentage: 4,
-        leaderboard_rank: Some(25),
+        leaderboard_rank: None,
         volume_30_days: 75000,
         total_discount_bps: 0,
         last_recalculation: 0,
@@ -475,6 +587,9 @@ fn test_fee_calculation_accuracy() {
     status.achievements.push_back(volume_achievement);
     fee_progression.user_achievements.set(user.clone(), status);
 
+    // Expert's default 30% cap (6 bps) would clip the 7 bps discount
+    fee_progression.set_max_discount_pct(50).unwrap();
+
     // Test with specific swap amount
     let swap_amount = 10000i128; // 100.00 tokens
     let result = fee_progression.calculate_effective_fee(&env, &user, &user_tier);
@@ -489,3 +604,241 @@ fn test_fee_calculation_accuracy() {
     let actual_fee = (swap_amount * result.effective_fee_bps as i128) / 10000;
     assert_eq!(actual_fee, expected_fee);
 }
+
+#[test]
+fn test_leaderboard_ties_break_by_first_seen_then_address() {
+    use crate::portfolio::{Asset, Portfolio};
+    use soroban_sdk::testutils::Ledger;
+
+    let env = Env::default();
+    let mut portfolio = Portfolio::new(&env);
+    let veteran = Address::generate(&env);
+    let newcomer = Address::generate(&env);
+
+    // Veteran joins the board first, then both post the same volume later on
+    env.ledger().with_mut(|li| li.timestamp = 0);
+    portfolio.mint(&env, Asset::XLM, veteran.clone(), 0);
+    env.ledger().with_mut(|li| li.timestamp = 1_000);
+    portfolio.mint(&env, Asset::XLM, newcomer.clone(), 10_000);
+    portfolio.mint(&env, Asset::XLM, veteran.clone(), 10_000);
+
+    // Same volume and same entry time: the lower address ranks first
+    let a = Address::generate(&env);
+    let b = Address::generate(&env);
+    portfolio.mint(&env, Asset::XLM, b.clone(), 5_000);
+    portfolio.mint(&env, Asset::XLM, a.clone(), 5_000);
+    let (first, second) = if a < b { (a, b) } else { (b, a) };
+
+    for _ in 0..3 {
+        // Unrelated activity re-sorts the board without reordering the ties
+        portfolio.mint(&env, Asset::XLM, Address::generate(&env), 1);
+        let top = portfolio.get_top_traders(&env, 4);
+        assert_eq!(top.get(0).unwrap().0, veteran);
+        assert_eq!(top.get(1).unwrap().0, newcomer);
+        assert_eq!(top.get(2).unwrap().0, first);
+        assert_eq!(top.get(3).unwrap().0, second);
+    }
+}
+
+#[test]
+fn test_inactive_top_trader_decays_off_leaderboard_and_loses_community_discount() {
+    use crate::portfolio::{Asset, Portfolio, LEADERBOARD_HALF_LIFE_SECS};
+    use soroban_sdk::testutils::Ledger;
+
+    let env = Env::default();
+    // Over a hundred leaderboard updates exceed the default test budget
+    env.cost_estimate().budget().reset_unlimited();
+    let mut portfolio = Portfolio::new(&env);
+    let mut fee_progression = FeeProgression::new(&env);
+    let star = Address::generate(&env);
+    let user_tier = UserTier::Whale;
+
+    // Star trader tops the board and earns the Community discount
+    portfolio.mint(&env, Asset::XLM, star.clone(), 1_000_000);
+    assert_eq!(portfolio.get_leaderboard_rank(&star), Some(1));
+    fee_progression.update_leaderboard_rank(&env, &star, portfolio.get_leaderboard_rank(&star));
+    let result = fee_progression.calculate_effective_fee(&env, &star, &user_tier);
+    assert!(result.applied_discounts.contains(&AchievementCategory::Community));
+
+    // One half-life later, a fresh trader with less raw volume overtakes them
+    env.ledger().with_mut(|li| li.timestamp += LEADERBOARD_HALF_LIFE_SECS);
+    let challenger = Address::generate(&env);
+    portfolio.mint(&env, Asset::XLM, challenger.clone(), 600_000);
+    assert_eq!(portfolio.get_leaderboard_rank(&challenger), Some(1));
+    assert_eq!(portfolio.get_leaderboard_rank(&star), Some(2));
+
+    // After ten half-lives of inactivity, 100 modest traders push them off the top 100
+    env.ledger().with_mut(|li| li.timestamp += 9 * LEADERBOARD_HALF_LIFE_SECS);
+    for _ in 0..100 {
+        let trader = Address::generate(&env);
+        portfolio.mint(&env, Asset::XLM, trader, 5_000);
+    }
+    assert_eq!(portfolio.get_top_traders(&env, 200).len(), 100);
+    assert_eq!(portfolio.get_leaderboard_rank(&star), None);
+
+    fee_progression.update_leaderboard_rank(&env, &star, portfolio.get_leaderboard_rank(&star));
+    let result = fee_progression.calculate_effective_fee(&env, &star, &user_tier);
+    assert!(!result.applied_discounts.contains(&AchievementCategory::Community));
+    // Only the default risk-management discount is left
+    assert_eq!(result.effective_fee_bps, result.base_fee_bps - 3);
+}
+
+#[test]
+fn test_user_fee_stats_accumulate_across_trades() {
+    let env = Env::default();
+    let mut fee_progression = FeeProgression::new(&env);
+    let user = Address::generate(&env);
+
+    assert_eq!(fee_progression.get_user_fee_stats(&env, &user), UserFeeStats::default());
+
+    // Novice, only the default risk-management discount: 30 - 3 = 27 bps
+    let (first, fee1) = fee_progression.charge_trade_fee(&env, &user, &UserTier::Novice, 10_000);
+    assert_eq!((first.achievement_discount_bps, fee1), (3, 27));
+
+    // Expert keeps the risk discount: 20 - 3 = 17 bps
+    let (second, fee2) = fee_progression.charge_trade_fee(&env, &user, &UserTier::Expert, 10_000);
+    assert_eq!((second.achievement_discount_bps, fee2), (3, 17));
+
+    // Whale with community + risk (8 bps) is capped at 30% of 15 = 4 bps
+    fee_progression.update_leaderboard_rank(&env, &user, Some(5));
+    let (third, fee3) = fee_progression.charge_trade_fee(&env, &user, &UserTier::Whale, 20_000);
+    assert_eq!((third.achievement_discount_bps, fee3), (4, 22));
+
+    let stats = fee_progression.get_user_fee_stats(&env, &user);
+    assert_eq!(stats.total_fees_paid, 27 + 17 + 22);
+    assert_eq!(stats.total_discount_bps_applied, 3 + 3 + 4);
+    assert_eq!(stats.trades_charged, 3);
+}
+
+#[test]
+fn test_achievement_history_records_earn_then_expire() {
+    use crate::fee_progression::AchievementEventKind;
+    use soroban_sdk::testutils::Ledger;
+
+    let env = Env::default();
+    let mut fee_progression = FeeProgression::new(&env);
+    let user = Address::generate(&env);
+
+    env.ledger().with_mut(|li| li.timestamp = 1_000);
+    let achievement = Achievement {
+        category: AchievementCategory::Volume,
+        discount_bps: 5,
+        earned_at: 1_000,
+        expires_at: 1_000 + (30 * 24 * 60 * 60),
+        metadata: 50_000,
+        is_active: true,
+    };
+    fee_progression.apply_achievement_bonus(&env, &user, achievement).unwrap();
+
+    // Past expiry, the next fee calculation cleans the achievement up
+    let expired_at = 1_000 + (31 * 24 * 60 * 60);
+    env.ledger().with_mut(|li| li.timestamp = expired_at);
+    fee_progression.calculate_effective_fee(&env, &user, &UserTier::Novice);
+
+    let history = fee_progression.get_achievement_history(&env, &user, 10);
+    let volume_events: std::vec::Vec<_> = history
+        .iter()
+        .filter(|e| e.category == AchievementCategory::Volume)
+        .collect();
+    assert_eq!(volume_events.len(), 2);
+    assert_eq!(volume_events[0].kind, AchievementEventKind::Earned);
+    assert_eq!(volume_events[0].timestamp, 1_000);
+    assert_eq!(volume_events[1].kind, AchievementEventKind::Expired);
+    assert_eq!(volume_events[1].timestamp, expired_at);
+
+    // The limit keeps only the most recent entries
+    let latest = fee_progression.get_achievement_history(&env, &user, 1);
+    assert_eq!(latest.len(), 1);
+    assert_eq!(latest.get(0).unwrap(), history.get(history.len() - 1).unwrap());
+}
+
+#[test]
+fn test_negative_volume_is_clamped_and_not_rewarded() {
+    let env = Env::default();
+    let mut fee_progression = FeeProgression::new(&env);
+    let user = Address::generate(&env);
+
+    let status = AchievementStatus {
+        achievements: Vec::new(&env),
+        current_streak: 0,
+        last_trade_day: 0,
+        max_loss_percentage: 0,
+        leaderboard_rank: None,
+        volume_30_days: -75_000,
+        total_discount_bps: 0,
+        last_recalculation: 0,
+    };
+    fee_progression.user_achievements.set(user.clone(), status);
+
+    let result = fee_progression.calculate_effective_fee(&env, &user, &UserTier::Novice);
+
+    assert!(!result.applied_discounts.contains(&AchievementCategory::Volume));
+    let status = fee_progression.get_achievement_status(&user).unwrap();
+    assert_eq!(status.volume_30_days, 0);
+    assert!(!status.achievements.iter().any(|a| a.category == AchievementCategory::Volume));
+}
+
+#[test]
+fn test_consistency_streak_over_seven_trading_days() {
+    use soroban_sdk::testutils::Ledger;
+
+    let env = Env::default();
+    let mut fee_progression = FeeProgression::new(&env);
+    let user = Address::generate(&env);
+    let day = 24 * 60 * 60;
+
+    for d in 1..=7u64 {
+        env.ledger().with_mut(|li| li.timestamp = d * day + 3_600);
+        fee_progression.update_trading_activity(&env, &user, 100, None);
+        assert_eq!(fee_progression.get_achievement_status(&user).unwrap().current_streak, d as u32);
+    }
+
+    let result = fee_progression.calculate_effective_fee(&env, &user, &UserTier::Novice);
+    assert!(result.applied_discounts.contains(&AchievementCategory::Consistency));
+}
+
+#[test]
+fn test_consistency_streak_breaks_on_missed_day() {
+    use soroban_sdk::testutils::Ledger;
+
+    let env = Env::default();
+    let mut fee_progression = FeeProgression::new(&env);
+    let user = Address::generate(&env);
+    let day = 24 * 60 * 60;
+
+    for d in 1..=3u64 {
+        env.ledger().with_mut(|li| li.timestamp = d * day);
+        fee_progression.update_trading_activity(&env, &user, 100, None);
+    }
+    assert_eq!(fee_progression.get_achievement_status(&user).unwrap().current_streak, 3);
+
+    // Day 4 has no trade; a recalculation on day 5 sees the gap
+    env.ledger().with_mut(|li| li.timestamp = 5 * day);
+    fee_progression.calculate_effective_fee(&env, &user, &UserTier::Novice);
+    assert_eq!(fee_progression.get_achievement_status(&user).unwrap().current_streak, 0);
+
+    fee_progression.update_trading_activity(&env, &user, 100, None);
+    assert_eq!(fee_progression.get_achievement_status(&user).unwrap().current_streak, 1);
+}
+
+#[test]
+fn test_consistency_streak_counts_one_per_day() {
+    use soroban_sdk::testutils::Ledger;
+
+    let env = Env::default();
+    let mut fee_progression = FeeProgression::new(&env);
+    let user = Address::generate(&env);
+    let day = 24 * 60 * 60;
+
+    for offset in 0..4 {
+        env.ledger().with_mut(|li| li.timestamp = day + offset * 600);
+        fee_progression.update_trading_activity(&env, &user, 100, None);
+        fee_progression.calculate_effective_fee(&env, &user, &UserTier::Novice);
+    }
+    assert_eq!(fee_progression.get_achievement_status(&user).unwrap().current_streak, 1);
+
+    env.ledger().with_mut(|li| li.timestamp = 2 * day + 60);
+    fee_progression.update_trading_activity(&env, &user, 100, None);
+    fee_progression.update_trading_activity(&env, &user, 100, None);
+    assert_eq!(fee_progression.get_achievement_status(&user).unwrap().current_streak, 2);
+}
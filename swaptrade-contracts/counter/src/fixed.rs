@@ -0,0 +1,264 @@
+//! Overflow-safe fixed-point helpers.
+//!
+//! All helpers compute `a * b / d` through a 256-bit intermediate so the
+//! product never overflows, then saturate if the quotient itself does not fit.
+//! Rounding rule: results are truncated toward zero, matching plain integer
+//! division so call sites keep their existing semantics. A zero divisor
+//! yields 0 rather than panicking.
+
+/// `a * b / precision` for signed fixed-point values.
+pub fn mul_fp(a: i128, b: i128, precision: i128) -> i128 {
+    mul_div(a, b, precision)
+}
+
+/// `a * precision / b` for signed fixed-point values.
+pub fn div_fp(a: i128, b: i128, precision: i128) -> i128 {
+    mul_div(a, precision, b)
+}
+
+/// `a * b / precision` for unsigned fixed-point values.
+pub fn mul_fp_u128(a: u128, b: u128, precision: u128) -> u128 {
+    mul_div_u128(a, b, precision).unwrap_or(u128::MAX)
+}
+
+/// `a * precision / b` for unsigned fixed-point values.
+pub fn div_fp_u128(a: u128, b: u128, precision: u128) -> u128 {
+    mul_div_u128(a, precision, b).unwrap_or(u128::MAX)
+}
+
+/// One in the crate's 7-decimal fixed-point convention.
+pub const FP_ONE: i128 = 10_000_000;
+
+/// Inputs above this (80.0) saturate `exp_fp` to `i128::MAX`; the result
+/// already exceeds `i128::MAX` from about 71.9.
+pub const EXP_FP_MAX_INPUT: i128 = 80 * FP_ONE;
+
+/// Inputs below this (-17.0) make `exp_fp` return 0, as e^x < 1e-7.
+pub const EXP_FP_MIN_INPUT: i128 = -17 * FP_ONE;
+
+/// Internal precision (18 decimals) for the series in `exp_fp` and `ln_fp`.
+const WIDE_ONE: i128 = 1_000_000_000_000_000_000;
+
+/// ln(2) at 18 decimals.
+const LN_2_WIDE: i128 = 693_147_180_559_945_309;
+
+/// Series terms evaluated at most; both series converge well before this.
+const SERIES_TERMS: i128 = 40;
+
+/// e^x for a 7-decimal fixed-point `x`.
+///
+/// Reduces `x = k·ln 2 + r` with |r| <= ln(2)/2, sums the Taylor series of
+/// e^r at 18 decimals and scales by 2^k. The result is truncated to 7
+/// decimals, so it is within 1e-7 plus a relative 1e-15 of the true value.
+/// Inputs above `EXP_FP_MAX_INPUT` saturate, inputs below `EXP_FP_MIN_INPUT`
+/// return 0.
+pub fn exp_fp(x: i128) -> i128 {
+    if x > EXP_FP_MAX_INPUT {
+        return i128::MAX;
+    }
+    if x < EXP_FP_MIN_INPUT {
+        return 0;
+    }
+    let x_wide = x * (WIDE_ONE / FP_ONE);
+    let half = if x_wide < 0 { -LN_2_WIDE / 2 } else { LN_2_WIDE / 2 };
+    let k = (x_wide + half) / LN_2_WIDE;
+    let r = x_wide - k * LN_2_WIDE;
+
+    let mut sum = WIDE_ONE;
+    let mut term = WIDE_ONE;
+    for n in 1..SERIES_TERMS {
+        term = mul_div(term, r, WIDE_ONE * n);
+        if term == 0 {
+            break;
+        }
+        sum += term;
+    }
+
+    if k >= 0 {
+        mul_div(sum, 1i128 << k, WIDE_ONE / FP_ONE)
+    } else {
+        sum / ((WIDE_ONE / FP_ONE) << -k)
+    }
+}
+
+/// Natural logarithm of a 7-decimal fixed-point `x`; `None` unless `x > 0`.
+///
+/// Reduces `x = m·2^k` with m in [0.83, 1.68), evaluates
+/// ln(m) = 2·atanh((m - 1) / (m + 1)) by its series at 18 decimals, and
+/// rounds to 7 decimals: the result is within 1e-7 of the true value over
+/// the whole positive i128 range.
+pub fn ln_fp(x: i128) -> Option<i128> {
+    if x <= 0 {
+        return None;
+    }
+    // 2^23 <= FP_ONE < 2^24, so m = x / 2^k lands in [2^23, 2^24) / FP_ONE
+    let k = (127 - x.leading_zeros() as i128) - 23;
+    let scale = WIDE_ONE / FP_ONE;
+    let m = if k >= 0 {
+        mul_div(x, scale, 1i128 << k)
+    } else {
+        x * (scale << -k)
+    };
+
+    let z = mul_div(m - WIDE_ONE, WIDE_ONE, m + WIDE_ONE);
+    let z_squared = mul_div(z, z, WIDE_ONE);
+    let mut sum = 0i128;
+    let mut power = z;
+    for n in 0..SERIES_TERMS {
+        if power == 0 {
+            break;
+        }
+        sum += power / (2 * n + 1);
+        power = mul_div(power, z_squared, WIDE_ONE);
+    }
+
+    let ln_wide = k * LN_2_WIDE + 2 * sum;
+    let half = if ln_wide < 0 { -scale / 2 } else { scale / 2 };
+    Some((ln_wide + half) / scale)
+}
+
+/// Signed `a * b / d`, truncated toward zero and saturated to the i128 range.
+pub fn mul_div(a: i128, b: i128, d: i128) -> i128 {
+    if d == 0 {
+        return 0;
+    }
+    let negative = (a < 0) ^ (b < 0) ^ (d < 0);
+    let magnitude = mul_div_u128(a.unsigned_abs(), b.unsigned_abs(), d.unsigned_abs());
+    match (magnitude, negative) {
+        (Some(m), false) if m <= i128::MAX as u128 => m as i128,
+        (Some(m), true) if m <= i128::MIN.unsigned_abs() => (m as i128).wrapping_neg(),
+        (_, false) => i128::MAX,
+        (_, true) => i128::MIN,
+    }
+}
+
+/// Unsigned `a * b / d`, truncated. A zero divisor yields `Some(0)`;
+/// `None` means the quotient exceeds `u128::MAX`.
+fn mul_div_u128(a: u128, b: u128, d: u128) -> Option<u128> {
+    if d == 0 {
+        return Some(0);
+    }
+    let (hi, lo) = mul_wide(a, b);
+    div_wide(hi, lo, d)
+}
+
+/// Full 256-bit product of two u128 values as `(high, low)` halves.
+fn mul_wide(a: u128, b: u128) -> (u128, u128) {
+    const MASK: u128 = u64::MAX as u128;
+    let (a_hi, a_lo) = (a >> 64, a & MASK);
+    let (b_hi, b_lo) = (b >> 64, b & MASK);
+
+    let lo_lo = a_lo * b_lo;
+    let hi_lo = a_hi * b_lo;
+    let lo_hi = a_lo * b_hi;
+    let hi_hi = a_hi * b_hi;
+
+    let cross = (lo_lo >> 64) + (hi_lo & MASK) + (lo_hi & MASK);
+    let lo = (cross << 64) | (lo_lo & MASK);
+    let hi = hi_hi + (hi_lo >> 64) + (lo_hi >> 64) + (cross >> 64);
+    (hi, lo)
+}
+
+/// Divide the 256-bit value `(hi, lo)` by `d`; `None` if the quotient
+/// does not fit in 128 bits.
+fn div_wide(hi: u128, lo: u128, d: u128) -> Option<u128> {
+    if hi == 0 {
+        return Some(lo / d);
+    }
+    if hi >= d {
+        return None;
+    }
+    // Restoring long division over the low 128 bits; the running remainder
+    // stays below `d`, with `carry` tracking the bit shifted out of it.
+    let mut rem = hi;
+    let mut quot: u128 = 0;
+    for i in (0..128).rev() {
+        let carry = rem >> 127;
+        rem = (rem << 1) | ((lo >> i) & 1);
+        quot <<= 1;
+        if carry == 1 || rem >= d {
+            rem = rem.wrapping_sub(d);
+            quot |= 1;
+        }
+    }
+    Some(quot)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const P: i128 = 10_000_000;
+
+    #[test]
+    fn test_mul_fp_matches_plain_math_for_small_values() {
+        assert_eq!(mul_fp(3 * P, 2 * P, P), 6 * P);
+        assert_eq!(mul_fp(-3 * P, P / 2, P), -15_000_000);
+        assert_eq!(div_fp(P, 3 * P, P), 3_333_333);
+        assert_eq!(div_fp(-P, 3 * P, P), -3_333_333, "truncates toward zero");
+    }
+
+    #[test]
+    fn test_mul_fp_near_max_does_not_panic() {
+        let big = i128::MAX / P;
+        // big * 2.0 fits exactly even though big * (2 * P) overflows i128.
+        assert_eq!(mul_fp(big, 2 * P, P), big * 2);
+        assert_eq!(mul_fp(-big, 2 * P, P), -big * 2);
+        // Reference: (big * P) / P == big.
+        assert_eq!(div_fp(big, P, P), big);
+        assert_eq!(mul_fp(big, big, P), i128::MAX, "saturates when the quotient overflows");
+        assert_eq!(mul_fp(-big, big, P), i128::MIN);
+    }
+
+    #[test]
+    fn test_u128_helpers_near_max() {
+        let p = P as u128;
+        let big = u128::MAX / p;
+        assert_eq!(mul_fp_u128(big, 3 * p, p), big * 3);
+        assert_eq!(div_fp_u128(big, p, p), big);
+        assert_eq!(div_fp_u128(u128::MAX, 1, p), u128::MAX);
+    }
+
+    #[test]
+    fn test_zero_divisor_yields_zero() {
+        assert_eq!(div_fp(5 * P, 0, P), 0);
+        assert_eq!(mul_fp(5, 5, 0), 0);
+        assert_eq!(div_fp_u128(5, 0, 10), 0);
+    }
+
+    #[test]
+    fn test_wide_multiply_matches_reference() {
+        // (2^127 - 1) * 4 / 8 == (2^127 - 1) / 2
+        assert_eq!(mul_div(i128::MAX, 4, 8), i128::MAX / 2);
+        assert_eq!(mul_div_u128(u128::MAX, u128::MAX, u128::MAX), Some(u128::MAX));
+    }
+
+    #[test]
+    fn test_exp_ln_match_reference_values() {
+        assert_eq!(exp_fp(0), P);
+        assert_eq!(exp_fp(P), 27_182_818); // e = 2.71828182...
+        assert_eq!(exp_fp(-P), 3_678_794); // 1/e = 0.36787944...
+        assert_eq!(exp_fp(3 * P), 200_855_369); // e^3 = 20.0855369...
+        assert_eq!(exp_fp(EXP_FP_MAX_INPUT + 1), i128::MAX);
+        assert_eq!(exp_fp(EXP_FP_MIN_INPUT - 1), 0);
+
+        assert_eq!(ln_fp(P), Some(0));
+        assert_eq!(ln_fp(2 * P), Some(6_931_472)); // ln 2 = 0.69314718...
+        assert_eq!(ln_fp(P / 2), Some(-6_931_472));
+        assert_eq!(ln_fp(27_182_818), Some(P));
+        assert_eq!(ln_fp(i128::MAX), Some(719_115_963)); // ln(i128::MAX / 1e7) = 71.9115962...
+        assert_eq!(ln_fp(0), None);
+        assert_eq!(ln_fp(-P), None);
+    }
+
+    #[test]
+    fn test_exp_inverts_ln_across_range() {
+        let mut x = 1_000i128; // 0.0001
+        while x <= 1_000_000 * P {
+            let round_trip = exp_fp(ln_fp(x).unwrap());
+            // ln is rounded to 1e-7, which exp turns into a relative 1e-7 error
+            assert!((round_trip - x).abs() <= x / P + 1, "x = {}, exp(ln(x)) = {}", x, round_trip);
+            x = x * 3 + 7;
+        }
+    }
+}
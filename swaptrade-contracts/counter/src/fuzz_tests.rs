@@ -8,7 +8,7 @@ use soroban_sdk::{symbol_short, Address, Env, Symbol, Vec};
 
 use crate::errors::ContractError;
 use crate::invariants::*;
-use crate::portfolio::{Asset, LPPosition, Portfolio};
+use crate::portfolio::{Asset, FailureReason, LPPosition, Portfolio};
 
 /// Maximum amount for fuzz testing (prevents unrealistic values)
 const FUZZ_MAX_AMOUNT: i128 = 1_000_000_000_000;
@@ -177,7 +177,7 @@ fn fuzz_lp_token_calculations() {
     let env = Env::default();
 
     // Test various deposit ratios
-    let test_cases: Vec<(i128, i128, i128, i128)> = vec![
+    let test_cases: std::vec::Vec<(i128, i128, i128, i128)> = vec![
         (1000, 1000, 0, 1000),             // First deposit, equal amounts
         (1000, 2000, 1000, 1414),          // Unequal pool, proportional
         (1, 1, 1000000, 1),                // Minimum deposit
@@ -226,7 +226,7 @@ fn integer_sqrt(n: u128) -> u128 {
 /// Fuzz test: AMM constant product with random swap scenarios
 #[test]
 fn fuzz_amm_constant_product() {
-    let test_cases: Vec<(i128, i128, i128, i128)> = vec![
+    let test_cases: std::vec::Vec<(i128, i128, i128, i128)> = vec![
         // (xlm_before, usdc_before, xlm_after, usdc_after)
         (10000, 10000, 11000, 9090),         // Normal swap with fees
         (50000, 20000, 51000, 19607),        // Different pool ratio
@@ -250,7 +250,7 @@ fn fuzz_amm_constant_product() {
 /// Fuzz test: AMM should reject impossible scenarios
 #[test]
 fn fuzz_amm_reject_impossible() {
-    let impossible_cases: Vec<(i128, i128, i128, i128)> = vec![
+    let impossible_cases: std::vec::Vec<(i128, i128, i128, i128)> = vec![
         // Cases where k increases (value created from nothing)
         (10000, 10000, 9000, 12000), // k_before=100M, k_after=108M
         (10000, 10000, 8000, 13000), // k_before=100M, k_after=104M
@@ -276,7 +276,7 @@ fn fuzz_amm_reject_impossible() {
 /// Fuzz test: Fee calculations within bounds
 #[test]
 fn fuzz_fee_calculations() {
-    let test_amounts: Vec<i128> = vec![100, 1000, 10000, 100000, 1000000, 10000000, 100000000];
+    let test_amounts: std::vec::Vec<i128> = vec![100, 1000, 10000, 100000, 1000000, 10000000, 100000000];
 
     for amount in test_amounts {
         // Calculate 0.3% fee
@@ -306,7 +306,7 @@ fn fuzz_fee_calculations() {
 #[test]
 fn fuzz_fee_edge_cases() {
     // Very small amounts
-    let small_amounts: Vec<i128> = vec![1, 10, 33, 100];
+    let small_amounts: std::vec::Vec<i128> = vec![1, 10, 33, 100];
     for amount in small_amounts {
         let fee = (amount * 30) / 10000; // 0.3%
                                          // Due to integer division, small amounts may have 0 fee
@@ -315,7 +315,7 @@ fn fuzz_fee_edge_cases() {
     }
 
     // Very large amounts
-    let large_amounts: Vec<i128> = vec![1_000_000_000, 10_000_000_000, 100_000_000_000];
+    let large_amounts: std::vec::Vec<i128> = vec![1_000_000_000, 10_000_000_000, 100_000_000_000];
     for amount in large_amounts {
         let fee = (amount * 30) / 10000;
         assert!(fee > 0);
@@ -330,7 +330,7 @@ fn fuzz_fee_edge_cases() {
 fn fuzz_batch_operation_counts() {
     let env = Env::default();
 
-    let test_cases: Vec<(u32, u32, u32, bool, bool)> = vec![
+    let test_cases: std::vec::Vec<(u32, u32, u32, bool, bool)> = vec![
         // (total, success, failure, is_atomic, should_pass)
         (10, 10, 0, true, true), // All succeed, atomic
         (10, 0, 10, true, true), // All fail, atomic
@@ -378,7 +378,7 @@ fn fuzz_metrics_monotonicity() {
     for i in 1..=20 {
         if i % 3 == 0 {
             // Simulate failed order
-            portfolio.inc_failed_order();
+            portfolio.inc_failed_order(FailureReason::InvalidInput);
         } else {
             // Simulate successful trade
             portfolio.record_trade(&env, user.clone());
@@ -431,7 +431,7 @@ fn fuzz_user_count_consistency() {
 /// Fuzz test: Slippage calculations
 #[test]
 fn fuzz_slippage_calculations() {
-    let test_cases: Vec<(u128, u128, u32, bool)> = vec![
+    let test_cases: std::vec::Vec<(u128, u128, u32, bool)> = vec![
         // (expected, actual, max_slippage_bps, should_pass)
         (10000, 10000, 100, true), // No slippage
         (10000, 9900, 100, true),  // 1% slippage, max 1%
@@ -465,7 +465,7 @@ fn fuzz_slippage_calculations() {
 /// Fuzz test: Balance update consistency
 #[test]
 fn fuzz_balance_update_consistency() {
-    let test_cases: Vec<(i128, i128, i128, i128, bool)> = vec![
+    let test_cases: std::vec::Vec<(i128, i128, i128, i128, bool)> = vec![
         // (balance_before, debit, credit, balance_after, should_pass)
         (1000, 200, 300, 1100, true),                     // Normal case
         (1000, 0, 0, 1000, true),                         // No change
@@ -520,7 +520,7 @@ fn fuzz_saturating_arithmetic() {
 /// Fuzz test: Large number operations
 #[test]
 fn fuzz_large_number_operations() {
-    let large_values: Vec<i128> = vec![
+    let large_values: std::vec::Vec<i128> = vec![
         1_000_000_000_000,
         10_000_000_000_000,
         100_000_000_000_000,
@@ -650,7 +650,7 @@ fn fuzz_tier_calculations() {
     let env = Env::default();
     let portfolio = Portfolio::new(&env);
 
-    let trade_counts: Vec<u32> = vec![0, 1, 5, 9, 10, 25, 49, 50, 75, 99, 100, 200];
+    let trade_counts: std::vec::Vec<u32> = vec![0, 1, 5, 9, 10, 25, 49, 50, 75, 99, 100, 200];
 
     for trades in trade_counts {
         let user = fuzz_user(&env);
@@ -676,7 +676,7 @@ fn fuzz_tier_calculations() {
 /// Fuzz test: Rate limit with various timestamps
 #[test]
 fn fuzz_rate_limit_monotonicity() {
-    let timestamps: Vec<u64> = vec![1000, 2000, 3000, 5000, 10000, 50000, 100000];
+    let timestamps: std::vec::Vec<u64> = vec![1000, 2000, 3000, 5000, 10000, 50000, 100000];
 
     for window in 0..timestamps.len().saturating_sub(1) {
         let prev = timestamps[window];
@@ -700,7 +700,7 @@ fn fuzz_transaction_history_limits() {
     let user = fuzz_user(&env);
 
     // Request various limits
-    let limits: Vec<u32> = vec![0, 1, 5, 10, 100, 1000];
+    let limits: std::vec::Vec<u32> = vec![0, 1, 5, 10, 100, 1000];
 
     for limit in limits {
         let txs = portfolio.get_user_transactions(&env, user.clone(), limit);
@@ -732,7 +732,7 @@ fn fuzz_top_traders_consistency() {
     }
 
     // Get top traders with various limits
-    let limits: Vec<u32> = vec![1, 5, 10, 50, 100];
+    let limits: std::vec::Vec<u32> = vec![1, 5, 10, 50, 100];
 
     for limit in limits {
         let top = portfolio.get_top_traders(&env, limit);
@@ -770,7 +770,7 @@ fn fuzz_pool_stats_consistency() {
 /// Fuzz test: Version monotonicity
 #[test]
 fn fuzz_version_monotonicity() {
-    let versions: Vec<(u32, u32, bool)> = vec![
+    let versions: std::vec::Vec<(u32, u32, bool)> = vec![
         (1, 1, true),  // Same version is ok
         (1, 2, true),  // Upgrade is ok
         (2, 1, false), // Downgrade is not ok
@@ -825,3 +825,266 @@ fn fuzz_state_corruption_detection() {
         }
     }
 }
+
+// ==================== ROUTING FUZZ TESTS ====================
+
+use crate::liquidity_pool::{PoolRegistry, Route, DEFAULT_FEE_TIERS};
+use crate::lp_tests::{register_pools, PoolSpec};
+use crate::CounterContract;
+
+const ROUTE_TOKENS: [&str; 5] = ["TKA", "TKB", "TKC", "TKD", "TKE"];
+const ROUTE_MIN_RESERVE: i128 = 50_000;
+const ROUTE_MAX_RESERVE: i128 = 200_000;
+/// Trades are 5-10% of the smallest reserve, so price impact always outweighs rounding
+const ROUTE_MIN_TRADE: i128 = 10_000;
+const ROUTE_MAX_TRADE: i128 = 20_000;
+const ROUTE_GRAPH_SEEDS: u64 = 40;
+
+/// Xorshift generator; a failing graph can be replayed from its seed
+struct FuzzRng(u64);
+
+impl FuzzRng {
+    fn new(seed: u64) -> Self {
+        FuzzRng(seed.wrapping_mul(0x9E37_79B9_7F4A_7C15) | 1)
+    }
+
+    fn next(&mut self) -> u64 {
+        let mut x = self.0;
+        x ^= x << 13;
+        x ^= x >> 7;
+        x ^= x << 17;
+        self.0 = x;
+        x
+    }
+
+    fn range(&mut self, lo: i128, hi: i128) -> i128 {
+        lo + (self.next() % (hi - lo + 1) as u64) as i128
+    }
+}
+
+/// Random pool graph over `ROUTE_TOKENS` with at most one pool per pair
+fn fuzz_pool_graph(env: &Env, contract_id: &Address, admin: &Address, seed: u64) -> (PoolRegistry, Vec<Symbol>) {
+    let mut rng = FuzzRng::new(seed);
+    let mut tokens = Vec::new(env);
+    for name in ROUTE_TOKENS {
+        tokens.push_back(Symbol::new(env, name));
+    }
+    let mut specs: std::vec::Vec<PoolSpec> = std::vec::Vec::new();
+    for i in 0..tokens.len() {
+        for j in (i + 1)..tokens.len() {
+            if rng.next() % 2 == 0 {
+                continue;
+            }
+            let fee_tier = DEFAULT_FEE_TIERS[(rng.next() % DEFAULT_FEE_TIERS.len() as u64) as usize];
+            let reserve_a = rng.range(ROUTE_MIN_RESERVE, ROUTE_MAX_RESERVE);
+            let reserve_b = rng.range(ROUTE_MIN_RESERVE, ROUTE_MAX_RESERVE);
+            specs.push((tokens.get(i).unwrap(), tokens.get(j).unwrap(), reserve_a, reserve_b, fee_tier));
+        }
+    }
+    let mut registry = env.as_contract(contract_id, || PoolRegistry::new(env));
+    register_pools(env, contract_id, &mut registry, admin, &specs);
+    (registry, tokens)
+}
+
+/// Output of actually executing `amount_in` along a path on a scratch copy of the registry
+fn execute_path(env: &Env, registry: &PoolRegistry, pools: &Vec<u64>, tokens: &Vec<Symbol>, amount_in: i128) -> i128 {
+    let mut scratch = registry.clone();
+    let mut amount = amount_in;
+    for hop in 0..pools.len() {
+        amount = scratch.swap(env, pools.get(hop).unwrap(), tokens.get(hop).unwrap(), amount, 0).unwrap();
+    }
+    amount
+}
+
+/// Every direct and 2-hop path from `token_in` to `token_out` with its executed output
+fn alternative_outputs(env: &Env, registry: &PoolRegistry, token_in: Symbol, token_out: Symbol, amount_in: i128) -> std::vec::Vec<i128> {
+    let mut outputs = std::vec::Vec::new();
+    for pool_id in registry.pools_for_pair(env, token_in.clone(), token_out.clone()).iter() {
+        let pools = Vec::from_array(env, [pool_id]);
+        let tokens = Vec::from_array(env, [token_in.clone(), token_out.clone()]);
+        outputs.push(execute_path(env, registry, &pools, &tokens, amount_in));
+    }
+    for first in registry.candidate_pools(env, token_in.clone()).iter() {
+        let pool = registry.get_pool(first).unwrap();
+        let mid = if pool.token_a == token_in { pool.token_b } else { pool.token_a };
+        if mid == token_out {
+            continue;
+        }
+        for second in registry.pools_for_pair(env, mid.clone(), token_out.clone()).iter() {
+            let pools = Vec::from_array(env, [first, second]);
+            let tokens = Vec::from_array(env, [token_in.clone(), mid.clone(), token_out.clone()]);
+            outputs.push(execute_path(env, registry, &pools, &tokens, amount_in));
+        }
+    }
+    outputs
+}
+
+/// Router under test: maps (registry, token_in, token_out, amount_in) to a quoted output
+type RouterQuote<'a> = &'a dyn Fn(&PoolRegistry, Symbol, Symbol, i128) -> Option<i128>;
+
+/// Whether doubling the trade size always yields strictly less output per unit
+fn price_impact_is_monotonic(env: &Env, contract_id: &Address, admin: &Address, router: RouterQuote) -> bool {
+    for seed in 1..=ROUTE_GRAPH_SEEDS {
+        let (registry, tokens) = fuzz_pool_graph(env, contract_id, admin, seed);
+        let mut rng = FuzzRng::new(seed + ROUTE_GRAPH_SEEDS);
+        let monotonic = env.as_contract(contract_id, || {
+            for token_in in tokens.iter() {
+                for token_out in tokens.iter() {
+                    if token_in == token_out {
+                        continue;
+                    }
+                    let amount = rng.range(ROUTE_MIN_TRADE, ROUTE_MAX_TRADE);
+                    let small = router(&registry, token_in.clone(), token_out.clone(), amount);
+                    let large = router(&registry, token_in.clone(), token_out.clone(), amount * 2);
+                    if let (Some(small), Some(large)) = (small, large) {
+                        // Per-unit output of 2x must be below that of 1x: large / 2a < small / a
+                        if large >= small * 2 {
+                            return false;
+                        }
+                    }
+                }
+            }
+            true
+        });
+        if !monotonic {
+            return false;
+        }
+    }
+    true
+}
+
+/// Quotes each hop of the real best route at its spot price, ignoring price impact
+fn spot_price_quote(registry: &PoolRegistry, route: &Route, amount_in: i128) -> i128 {
+    let mut amount = amount_in;
+    for hop in 0..route.pools.len() {
+        let pool = registry.get_pool(route.pools.get(hop).unwrap()).unwrap();
+        let (reserve_in, reserve_out) = if route.tokens.get(hop).unwrap() == pool.token_a {
+            (pool.reserve_a, pool.reserve_b)
+        } else {
+            (pool.reserve_b, pool.reserve_a)
+        };
+        let after_fee = amount - amount * pool.fee_tier as i128 / 10000;
+        amount = after_fee * reserve_out / reserve_in;
+    }
+    amount
+}
+
+/// Fuzz test: larger trades never receive a better per-unit output
+#[test]
+fn fuzz_route_price_impact_monotonic() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(CounterContract, ());
+    let admin = fuzz_user(&env);
+
+    let router = |registry: &PoolRegistry, token_in: Symbol, token_out: Symbol, amount: i128| {
+        registry.find_best_route(&env, token_in, token_out, amount).map(|r| r.expected_output)
+    };
+    assert!(price_impact_is_monotonic(&env, &contract_id, &admin, &router));
+}
+
+/// Fuzz test: the monotonicity check rejects a router that ignores price impact
+#[test]
+fn fuzz_route_check_catches_impact_free_router() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(CounterContract, ());
+    let admin = fuzz_user(&env);
+
+    let broken = |registry: &PoolRegistry, token_in: Symbol, token_out: Symbol, amount: i128| {
+        registry
+            .find_best_route(&env, token_in, token_out, amount)
+            .map(|r| spot_price_quote(registry, &r, amount))
+    };
+    assert!(
+        !price_impact_is_monotonic(&env, &contract_id, &admin, &broken),
+        "Router without price impact must fail the monotonicity check"
+    );
+}
+
+/// Fuzz test: the best route is never beaten by another path and matches execution
+#[test]
+fn fuzz_route_best_output_dominates_alternatives() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(CounterContract, ());
+    let admin = fuzz_user(&env);
+
+    for seed in 1..=ROUTE_GRAPH_SEEDS {
+        let (registry, tokens) = fuzz_pool_graph(&env, &contract_id, &admin, seed);
+        let mut rng = FuzzRng::new(seed);
+        env.as_contract(&contract_id, || {
+            for token_in in tokens.iter() {
+                for token_out in tokens.iter() {
+                    if token_in == token_out {
+                        continue;
+                    }
+                    let amount = rng.range(ROUTE_MIN_TRADE, ROUTE_MAX_TRADE);
+                    let alternatives = alternative_outputs(&env, &registry, token_in.clone(), token_out.clone(), amount);
+                    let route = registry.find_best_route(&env, token_in.clone(), token_out.clone(), amount);
+                    let best_alternative = alternatives.iter().copied().max();
+
+                    match (route, best_alternative) {
+                        (Some(route), Some(best_alternative)) => {
+                            assert!(
+                                route.expected_output >= best_alternative,
+                                "seed {}: route output {} beaten by alternative {}",
+                                seed, route.expected_output, best_alternative
+                            );
+                            let executed = execute_path(&env, &registry, &route.pools, &route.tokens, amount);
+                            assert_eq!(executed, route.expected_output, "seed {}: quote differs from execution", seed);
+                        }
+                        (None, None) => {}
+                        (route, alternative) => panic!(
+                            "seed {}: router returned {:?} but best alternative is {:?}",
+                            seed, route.map(|r| r.expected_output), alternative
+                        ),
+                    }
+                }
+            }
+        });
+    }
+}
+
+/// Fuzz test: routes are simple paths that start and end at the requested tokens
+#[test]
+fn fuzz_route_never_revisits_token() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(CounterContract, ());
+    let admin = fuzz_user(&env);
+
+    for seed in 1..=ROUTE_GRAPH_SEEDS {
+        let (registry, tokens) = fuzz_pool_graph(&env, &contract_id, &admin, seed);
+        let mut rng = FuzzRng::new(seed);
+        env.as_contract(&contract_id, || {
+            for token_in in tokens.iter() {
+                for token_out in tokens.iter() {
+                    if token_in == token_out {
+                        continue;
+                    }
+                    let amount = rng.range(ROUTE_MIN_TRADE, ROUTE_MAX_TRADE);
+                    let Some(route) = registry.find_best_route(&env, token_in.clone(), token_out.clone(), amount) else {
+                        continue;
+                    };
+                    assert_eq!(route.tokens.len(), route.pools.len() + 1);
+                    assert_eq!(route.tokens.first().unwrap(), token_in);
+                    assert_eq!(route.tokens.last().unwrap(), token_out);
+                    for i in 0..route.tokens.len() {
+                        for j in (i + 1)..route.tokens.len() {
+                            assert_ne!(route.tokens.get(i), route.tokens.get(j), "seed {}: route revisits a token", seed);
+                        }
+                    }
+                    for hop in 0..route.pools.len() {
+                        let pool = registry.get_pool(route.pools.get(hop).unwrap()).unwrap();
+                        let (from, to) = (route.tokens.get(hop).unwrap(), route.tokens.get(hop + 1).unwrap());
+                        assert!(
+                            (pool.token_a == from && pool.token_b == to) || (pool.token_a == to && pool.token_b == from),
+                            "seed {}: hop {} does not connect its tokens", seed, hop
+                        );
+                    }
+                }
+            }
+        });
+    }
+}
@@ -3,7 +3,7 @@
 // GovernanceContract so the phase enforcement is a single source of truth.
 
 use crate::governance::{
-    GovernanceContract, GovernancePhase, SchnorrProof,
+    GovernanceContract, GovernanceError, GovernancePhase, OverrideProgress, SchnorrProof,
     make_schnorr_proof, TIMELOCK_DELAY_SECS,
 };
 
@@ -40,14 +40,14 @@ impl AdminController {
     // ── Pause / Unpause ───────────────────────────────────────────────────────
     // Allowed in Phase 1 and Phase 2.
 
-    pub fn pause_trading(&mut self, caller: &str) -> Result<(), String> {
+    pub fn pause_trading(&mut self, caller: &str) -> Result<(), GovernanceError> {
         self.assert_admin(caller)?;
         self.governance.assert_can_pause()?;
         self.state.trading_paused = true;
         Ok(())
     }
 
-    pub fn resume_trading(&mut self, caller: &str) -> Result<(), String> {
+    pub fn resume_trading(&mut self, caller: &str) -> Result<(), GovernanceError> {
         self.assert_admin(caller)?;
         self.governance.assert_can_pause()?;
         self.state.trading_paused = false;
@@ -61,7 +61,7 @@ impl AdminController {
     // Phase 4: blocked entirely
 
     /// Set trading fee (basis points). Phase 1 only for direct execution.
-    pub fn set_fee_bps_direct(&mut self, caller: &str, fee_bps: u16) -> Result<(), String> {
+    pub fn set_fee_bps_direct(&mut self, caller: &str, fee_bps: u16) -> Result<(), GovernanceError> {
         self.assert_admin(caller)?;
         self.governance.assert_can_modify_state(caller)?;
         self.state.fee_bps = fee_bps;
@@ -73,11 +73,11 @@ impl AdminController {
         &mut self,
         caller: &str,
         fee_bps: u16,
-    ) -> Result<[u8; 32], String> {
+    ) -> Result<[u8; 32], GovernanceError> {
         self.assert_admin(caller)?;
         // Phase 3+ must use multi-sig; Phase 1-2 may use timelock as best practice
         match self.governance.current_phase() {
-            GovernancePhase::DaoOnly => return Err("Phase 4: use DAO proposal".into()),
+            GovernancePhase::DaoOnly => return Err(GovernanceError::PhaseRestricted("Phase 4: use DAO proposal".into())),
             _ => {}
         }
         let payload = fee_bps.to_le_bytes();
@@ -89,7 +89,7 @@ impl AdminController {
         &mut self,
         op_id: &[u8; 32],
         fee_bps: u16,
-    ) -> Result<(), String> {
+    ) -> Result<(), GovernanceError> {
         let payload = fee_bps.to_le_bytes();
         self.governance.execute_operation(op_id, &payload)?;
         self.state.fee_bps = fee_bps;
@@ -101,10 +101,12 @@ impl AdminController {
         &mut self,
         proposer: &str,
         new_size: u64,
-    ) -> Result<[u8; 32], String> {
+    ) -> Result<[u8; 32], GovernanceError> {
         match self.governance.current_phase() {
             GovernancePhase::MultiSig | GovernancePhase::DaoOnly => {}
-            _ => return Err("Multi-sig proposal only required in Phase 3+".into()),
+            _ => return Err(GovernanceError::PhaseRestricted(
+                "Multi-sig proposal only required in Phase 3+".into(),
+            )),
         }
         let payload = new_size.to_le_bytes();
         self.governance.propose_multisig(proposer, "set_max_trade_size", &payload)
@@ -114,7 +116,7 @@ impl AdminController {
         &mut self,
         proposal_id: &[u8; 32],
         signer: &str,
-    ) -> Result<usize, String> {
+    ) -> Result<usize, GovernanceError> {
         self.governance.approve_multisig(proposal_id, signer)
     }
 
@@ -122,27 +124,39 @@ impl AdminController {
         &mut self,
         proposal_id: &[u8; 32],
         new_size: u64,
-    ) -> Result<(), String> {
+    ) -> Result<(), GovernanceError> {
         let payload = new_size.to_le_bytes();
         self.governance.execute_multisig(proposal_id, &payload)?;
         self.state.max_trade_size = new_size;
         Ok(())
     }
 
-    /// Guardian emergency override (any phase) with Schnorr proof.
+    /// Guardian emergency override (any phase); takes effect once a quorum
+    /// of guardian Schnorr proofs has been collected.
     pub fn guardian_override(
         &mut self,
         proof: &SchnorrProof,
         reason: &str,
-    ) -> Result<(), String> {
+    ) -> Result<OverrideProgress, GovernanceError> {
         self.governance.guardian_override(proof, reason)
     }
 
+    /// Single-guardian emergency pause; halts trading immediately.
+    pub fn guardian_pause(
+        &mut self,
+        proof: &SchnorrProof,
+        reason: &str,
+    ) -> Result<(), GovernanceError> {
+        self.governance.guardian_emergency_pause(proof, reason)?;
+        self.state.trading_paused = true;
+        Ok(())
+    }
+
     // ── Internal ──────────────────────────────────────────────────────────────
 
-    fn assert_admin(&self, caller: &str) -> Result<(), String> {
+    fn assert_admin(&self, caller: &str) -> Result<(), GovernanceError> {
         if caller != self.state.admin {
-            return Err(format!("'{}' is not the admin", caller));
+            return Err(GovernanceError::Unauthorized);
         }
         Ok(())
     }
@@ -0,0 +1,30 @@
+//! Replay protection for retried swaps.
+//!
+//! Clients that re-submit a swap after a timeout attach the same idempotency
+//! key. The contract remembers each `(user, key)` result in temporary storage
+//! for a bounded time and hands it back for a repeat instead of executing
+//! twice. Entries are per user, so one account's traffic can never evict
+//! another's keys.
+
+use soroban_sdk::{Address, BytesN, Env};
+
+use crate::storage::IDEMPOTENCY_KEY;
+
+/// Ledgers a recorded result stays retrievable (about a day at 5s ledgers)
+pub const IDEMPOTENCY_TTL_LEDGERS: u32 = 17_280;
+
+/// Result of an earlier submission by `user` with `key`, if still remembered.
+/// Keys are scoped per user so one account cannot read another's results.
+pub fn lookup(env: &Env, user: &Address, key: &BytesN<32>) -> Option<i128> {
+    env.storage()
+        .temporary()
+        .get(&(IDEMPOTENCY_KEY, user.clone(), key.clone()))
+}
+
+/// Remember `result` for `(user, key)` for `IDEMPOTENCY_TTL_LEDGERS`.
+pub fn record(env: &Env, user: Address, key: BytesN<32>, result: i128) {
+    let storage_key = (IDEMPOTENCY_KEY, user, key);
+    let storage = env.storage().temporary();
+    storage.set(&storage_key, &result);
+    storage.extend_ttl(&storage_key, IDEMPOTENCY_TTL_LEDGERS, IDEMPOTENCY_TTL_LEDGERS);
+}
@@ -6,6 +6,8 @@
 use soroban_sdk::{symbol_short, Address, Env, Symbol, Vec};
 
 use crate::errors::ContractError;
+use crate::liquidity_pool::{LiquidityPool, PoolRegistry};
+use crate::storage::{INVARIANT_CURSOR_KEY, REGISTRY_LP_CURSOR_KEY};
 use crate::portfolio::{Asset, LPPosition, Portfolio};
 
 /// Maximum allowed fee in basis points (1%)
@@ -52,38 +54,65 @@ impl InvariantCheck {
 pub fn verify_contract_invariants(env: &Env, portfolio: &Portfolio) -> Result<(), ContractError> {
     let mut check = InvariantCheck::new(env);
 
-    // Asset conservation invariants
-    if !invariant_non_negative_balances(portfolio) {
-        check.record_failure(symbol_short!("neg_bal"));
-    }
-
-    // Pool liquidity invariants
-    if !invariant_pool_liquidity_non_negative(portfolio) {
-        check.record_failure(symbol_short!("neg_pool"));
-    }
-
-    // LP token invariants
-    if !invariant_lp_token_conservation(portfolio) {
-        check.record_failure(symbol_short!("lp_tok"));
+    for (name, holds) in PORTFOLIO_INVARIANTS.iter() {
+        if !holds(portfolio) {
+            check.record_failure(name.clone());
+        }
     }
 
-    // Metrics invariants
-    if !invariant_metrics_non_negative(portfolio) {
-        check.record_failure(symbol_short!("neg_met"));
+    if check.passed {
+        Ok(())
+    } else {
+        Err(ContractError::InvariantViolation)
     }
+}
 
-    // Fee accumulation invariants
-    if !invariant_fee_accumulation_non_negative(portfolio) {
-        check.record_failure(symbol_short!("neg_fee"));
-    }
+/// A named portfolio invariant check
+pub type PortfolioInvariant = (Symbol, fn(&Portfolio) -> bool);
+
+/// Portfolio-wide invariants in the order `verify_contract_invariants` runs them
+pub const PORTFOLIO_INVARIANTS: [PortfolioInvariant; 6] = [
+    // Asset conservation
+    (symbol_short!("neg_bal"), invariant_non_negative_balances),
+    // Pool liquidity
+    (symbol_short!("neg_pool"), invariant_pool_liquidity_non_negative),
+    // LP tokens
+    (symbol_short!("lp_tok"), invariant_lp_token_conservation),
+    // Metrics
+    (symbol_short!("neg_met"), invariant_metrics_non_negative),
+    // Fee accumulation
+    (symbol_short!("neg_fee"), invariant_fee_accumulation_non_negative),
+    // User counts
+    (symbol_short!("usr_cnt"), invariant_user_counts_consistent),
+];
+
+/// Budgeted variant of `verify_contract_invariants`: runs `checks` invariants
+/// per call, resuming from a cursor kept in instance storage, so repeated
+/// calls cover the full set without paying for all of it every time.
+///
+/// Returns the names of the invariants checked on this call.
+pub fn verify_contract_invariants_sampled(
+    env: &Env,
+    portfolio: &Portfolio,
+    checks: u32,
+) -> Result<Vec<Symbol>, ContractError> {
+    let total = PORTFOLIO_INVARIANTS.len() as u32;
+    let checks = checks.clamp(1, total);
+    let cursor: u32 = env.storage().instance().get(&INVARIANT_CURSOR_KEY).unwrap_or(0) % total;
 
-    // User count invariants
-    if !invariant_user_counts_consistent(portfolio) {
-        check.record_failure(symbol_short!("usr_cnt"));
+    let mut check = InvariantCheck::new(env);
+    let mut ran = Vec::new(env);
+    for i in 0..checks {
+        let (name, holds) = &PORTFOLIO_INVARIANTS[((cursor + i) % total) as usize];
+        ran.push_back(name.clone());
+        if !holds(portfolio) {
+            check.record_failure(name.clone());
+        }
     }
+    env.storage().instance().set(&INVARIANT_CURSOR_KEY, &((cursor + checks) % total));
 
     if check.passed {
-        Ok(())
+        Ok(ran)
     } else {
         Err(ContractError::InvariantViolation)
     }
@@ -135,6 +164,45 @@ pub fn verify_swap_invariants(
     }
 }
 
+/// Verify invariants for a single registry pool
+///
+/// Called after add/remove/swap before the updated pool is stored.
+pub fn verify_pool_invariants(env: &Env, pool: &LiquidityPool) -> Result<(), ContractError> {
+    let mut check = InvariantCheck::new(env);
+
+    if !invariant_pool_reserves_match_lp(pool) {
+        check.record_failure(symbol_short!("pool_lp"));
+    }
+
+    if check.passed {
+        Ok(())
+    } else {
+        Err(ContractError::InvariantViolation)
+    }
+}
+
+/// Verify LP supply conservation for one registry pool per call, resuming
+/// from a cursor in instance storage so repeated calls walk every pool
+/// without paying for a full registry scan each time. Registry add and
+/// remove liquidity run it before returning.
+///
+/// Returns the id of the pool checked, or `None` if the registry is empty.
+pub fn verify_registry_lp_conservation_sampled(env: &Env, registry: &PoolRegistry) -> Result<Option<u64>, ContractError> {
+    let count = registry.pool_count();
+    if count == 0 {
+        return Ok(None);
+    }
+    let cursor: u64 = env.storage().instance().get(&REGISTRY_LP_CURSOR_KEY).unwrap_or(0) % count;
+    env.storage().instance().set(&REGISTRY_LP_CURSOR_KEY, &((cursor + 1) % count));
+
+    let pool = registry.get_all_pools(env, cursor, 1).get(0).ok_or(ContractError::LPPositionNotFound)?;
+    if invariant_pool_lp_conservation(registry, &pool) {
+        Ok(Some(pool.pool_id))
+    } else {
+        Err(ContractError::InvariantViolation)
+    }
+}
+
 /// Verify invariants after liquidity provision
 ///
 /// Checks:
@@ -163,8 +231,7 @@ pub fn verify_add_liquidity_invariants(
     }
 
     // Pool liquidity should increase
-    let usdc_sym = Symbol::new(env, "USDCSIM");
-    let usdc_asset = Asset::Custom(usdc_sym);
+    let usdc_asset = portfolio.quote_asset();
     let xlm_after = portfolio.get_liquidity(Asset::XLM);
     let usdc_after = portfolio.get_liquidity(usdc_asset);
 
@@ -216,7 +283,7 @@ pub fn verify_remove_liquidity_invariants(
 
     // Pool liquidity should decrease
     let xlm_after = portfolio.get_liquidity(Asset::XLM);
-    let usdc_after = portfolio.get_liquidity(Asset::Custom(symbol_short!("USDCSIM")));
+    let usdc_after = portfolio.get_liquidity(portfolio.quote_asset());
 
     if xlm_after > xlm_before || usdc_after > usdc_before {
         check.record_failure(symbol_short!("pool_dec"));
@@ -279,7 +346,7 @@ pub fn verify_batch_invariants(
 pub fn invariant_non_negative_balances(portfolio: &Portfolio) -> bool {
     // Check pool reserves (aggregate of all positions)
     portfolio.get_liquidity(Asset::XLM) >= 0
-        && portfolio.get_liquidity(Asset::Custom(symbol_short!("USDCSIM"))) >= 0
+        && portfolio.get_liquidity(portfolio.quote_asset()) >= 0
 }
 
 /// INVARIANT: Pool liquidity must always be non-negative
@@ -298,6 +365,27 @@ pub fn invariant_lp_token_conservation(portfolio: &Portfolio) -> bool {
     portfolio.get_total_lp_tokens() >= 0
 }
 
+/// INVARIANT: Registry LP Conservation
+///
+/// Every registry pool's LP supply equals its providers' balances plus the
+/// tokens locked at registration. Drift means an add/remove path minted or
+/// burned on one side only. Iterates every pool and balance; see
+/// `verify_registry_lp_conservation_sampled` for the budgeted form.
+pub fn invariant_registry_lp_conservation(env: &Env, registry: &PoolRegistry) -> bool {
+    registry
+        .get_all_pools(env, 0, registry.pool_count())
+        .iter()
+        .all(|pool| invariant_pool_lp_conservation(registry, &pool))
+}
+
+/// Single-pool form of `invariant_registry_lp_conservation`
+pub fn invariant_pool_lp_conservation(registry: &PoolRegistry, pool: &LiquidityPool) -> bool {
+    match registry.sum_lp_balances(pool.pool_id) {
+        Ok(held) => held.checked_add(registry.get_locked_lp(pool.pool_id)) == Some(pool.total_lp_tokens),
+        Err(_) => false,
+    }
+}
+
 /// INVARIANT: Metrics must be non-negative
 ///
 /// All statistical counters should never be negative.
@@ -415,6 +503,19 @@ pub fn invariant_lp_position_integrity(position: &LPPosition) -> bool {
             || (position.xlm_deposited > 0 && position.usdc_deposited > 0))
 }
 
+/// INVARIANT: Pool Reserves Match LP Supply
+///
+/// Reserves and LP supply must be non-negative, and the pool has LP tokens
+/// outstanding if and only if it holds reserves. Positive reserves with zero
+/// LP tokens would be stranded funds nobody can withdraw.
+pub fn invariant_pool_reserves_match_lp(pool: &LiquidityPool) -> bool {
+    if pool.reserve_a < 0 || pool.reserve_b < 0 || pool.total_lp_tokens < 0 {
+        return false;
+    }
+    let reserves_empty = pool.reserve_a == 0 && pool.reserve_b == 0;
+    (pool.total_lp_tokens == 0) == reserves_empty
+}
+
 /// INVARIANT: Rate Limit Consistency
 ///
 /// Rate limit counters should never decrease within a time window.
@@ -422,6 +523,18 @@ pub fn invariant_rate_limit_monotonic(previous_count: u32, current_count: u32) -
     current_count >= previous_count
 }
 
+/// INVARIANT: Metrics Monotonicity
+///
+/// Trade and failed-order counters only ever grow.
+pub fn invariant_metrics_monotonic(
+    previous_trades: u32,
+    current_trades: u32,
+    previous_failed: u32,
+    current_failed: u32,
+) -> bool {
+    current_trades >= previous_trades && current_failed >= previous_failed
+}
+
 /// INVARIANT: Version Monotonicity
 ///
 /// Contract version should only increase during migrations.
@@ -513,6 +626,35 @@ mod tests {
     use super::*;
     use soroban_sdk::testutils::Address as _;
 
+    #[test]
+    fn test_sampled_invariants_cover_every_check() {
+        let env = Env::default();
+        let contract_id = env.register(crate::CounterContract, ());
+        let portfolio = Portfolio::new(&env);
+
+        env.as_contract(&contract_id, || {
+            let total = PORTFOLIO_INVARIANTS.len() as u32;
+            let mut seen = Vec::new(&env);
+            // Two at a time: three calls cover all six, and the cursor wraps
+            for _ in 0..total.div_ceil(2) {
+                let ran = verify_contract_invariants_sampled(&env, &portfolio, 2).unwrap();
+                assert_eq!(ran.len(), 2);
+                for name in ran.iter() {
+                    if !seen.contains(&name) {
+                        seen.push_back(name);
+                    }
+                }
+            }
+            assert_eq!(seen.len(), total);
+            for (name, _) in PORTFOLIO_INVARIANTS.iter() {
+                assert!(seen.contains(name));
+            }
+
+            let next = verify_contract_invariants_sampled(&env, &portfolio, 1).unwrap();
+            assert_eq!(next.get(0), Some(PORTFOLIO_INVARIANTS[0].0.clone()));
+        });
+    }
+
     #[test]
     fn test_invariant_non_negative_balances_pass() {
         let env = Env::default();
@@ -651,4 +793,108 @@ mod tests {
     fn test_invariant_timestamp_monotonic_fail() {
         assert!(!invariant_timestamp_monotonic(2000, 1000));
     }
+
+    #[test]
+    fn test_registry_lp_conservation_detects_desync() {
+        let env = Env::default();
+        env.mock_all_auths();
+        let contract_id = env.register(crate::CounterContract, ());
+        let admin = Address::generate(&env);
+        let provider = Address::generate(&env);
+
+        let mut registry = env.as_contract(&contract_id, || PoolRegistry::new(&env));
+        let ids = crate::lp_tests::register_pools(
+            &env,
+            &contract_id,
+            &mut registry,
+            &admin,
+            &[
+                (symbol_short!("TOKA"), symbol_short!("TOKB"), 10_000, 10_000, 30),
+                (symbol_short!("TOKA"), symbol_short!("TOKC"), 4_000, 9_000, 30),
+            ],
+        );
+        let (p0, p1) = (ids[0], ids[1]);
+
+        env.as_contract(&contract_id, || {
+            // Each add/remove samples one pool, advancing the cursor p0, p1, p0
+            let lp = registry.add_liquidity(&env, p0, 1_000, 1_000, provider.clone()).unwrap();
+            registry.add_liquidity(&env, p1, 400, 900, provider.clone()).unwrap();
+            registry.remove_liquidity(&env, p0, lp / 2, provider.clone()).unwrap();
+            assert!(invariant_registry_lp_conservation(&env, &registry));
+
+            // Sampling visits one pool per call and wraps
+            assert_eq!(verify_registry_lp_conservation_sampled(&env, &registry), Ok(Some(p1)));
+            assert_eq!(verify_registry_lp_conservation_sampled(&env, &registry), Ok(Some(p0)));
+            assert_eq!(verify_registry_lp_conservation_sampled(&env, &registry), Ok(Some(p1)));
+
+            // Supply bumped without crediting anyone
+            let mut drifted = registry.get_pool(p1).unwrap();
+            drifted.total_lp_tokens += 1;
+            crate::lp_tests::overwrite_pool(&env, &mut registry, drifted);
+            assert!(!invariant_registry_lp_conservation(&env, &registry));
+
+            // The sampled check on the liquidity path lands on p0 first, then p1
+            assert!(registry.add_liquidity(&env, p0, 100, 100, provider.clone()).is_ok());
+            assert_eq!(
+                registry.add_liquidity(&env, p0, 100, 100, provider),
+                Err(ContractError::InvariantViolation)
+            );
+            assert_eq!(verify_registry_lp_conservation_sampled(&env, &registry), Ok(Some(p0)));
+            assert_eq!(
+                verify_registry_lp_conservation_sampled(&env, &registry),
+                Err(ContractError::InvariantViolation)
+            );
+        });
+    }
+
+    fn pool_with(env: &Env, reserve_a: i128, reserve_b: i128, total_lp_tokens: i128) -> LiquidityPool {
+        LiquidityPool {
+            pool_id: 0,
+            token_a: symbol_short!("USDCSIM"),
+            token_b: symbol_short!("XLM"),
+            reserve_a,
+            reserve_b,
+            total_lp_tokens,
+            fee_tier: 30,
+            cumulative_volume: 0,
+            cumulative_fees: 0,
+            last_swap_timestamp: env.ledger().timestamp(),
+            fee_growth_a: 0,
+            fee_growth_b: 0,
+            unclaimed_fees_a: 0,
+            unclaimed_fees_b: 0,
+            max_price_impact_bps: 0,
+        }
+    }
+
+    #[test]
+    fn test_invariant_pool_reserves_match_lp_healthy_pool() {
+        let env = Env::default();
+        let pool = pool_with(&env, 1_000, 4_000, 2_000);
+        assert!(invariant_pool_reserves_match_lp(&pool));
+        assert_eq!(verify_pool_invariants(&env, &pool), Ok(()));
+    }
+
+    #[test]
+    fn test_invariant_pool_reserves_match_lp_zero_pool() {
+        let env = Env::default();
+        let pool = pool_with(&env, 0, 0, 0);
+        assert!(invariant_pool_reserves_match_lp(&pool));
+    }
+
+    #[test]
+    fn test_invariant_pool_reserves_match_lp_stranded_reserves_fail() {
+        let env = Env::default();
+        // Reserves left behind with no LP tokens to redeem them
+        let pool = pool_with(&env, 500, 250, 0);
+        assert!(!invariant_pool_reserves_match_lp(&pool));
+        assert_eq!(
+            verify_pool_invariants(&env, &pool),
+            Err(ContractError::InvariantViolation)
+        );
+
+        // LP tokens outstanding against an empty pool, or negative values, also fail
+        assert!(!invariant_pool_reserves_match_lp(&pool_with(&env, 0, 0, 10)));
+        assert!(!invariant_pool_reserves_match_lp(&pool_with(&env, -1, 5, 3)));
+    }
 }
@@ -1,27 +1,46 @@
-#![cfg_attr(not(test), no_std)]
-use soroban_sdk::{contract, contractimpl, symbol_short, Address, Env, Symbol, Vec};
+#![cfg_attr(not(any(test, feature = "native-tools")), no_std)]
+use soroban_sdk::{contract, contractimpl, panic_with_error, symbol_short, Address, BytesN, Env, Map, Symbol, Vec};
 
 // Bring in modules from parent directory
 mod admin;
+mod allowance;
+mod attestation;
+mod commit_reveal;
 mod errors;
 mod events;
+mod fixed;
+mod idempotency;
 mod invariants;
 mod alerts;
 #[cfg(test)]
-mod alerts_tests;
+mod alert_tests;
 mod rate_limit;
 mod storage;
 mod liquidity_pool;
+mod migration;
+mod validation;
+mod windowed_counter;
 mod batch {
     include!("../batch.rs");
 }
 mod tiers {
     include!("../tiers.rs");
 }
+#[cfg(test)]
 mod batch_event_tests;
+#[cfg(test)]
 mod batch_opt_simple_test;
+#[cfg(test)]
 mod batch_performance_tests;
 mod oracle;
+mod price_history;
+mod fee_progression;
+mod overview;
+#[cfg(test)]
+mod overview_tests;
+mod referral {
+    include!("../referral.rs");
+}
 
 mod portfolio {
     include!("../portfolio.rs");
@@ -30,22 +49,24 @@ mod trading {
     include!("../trading.rs");
 }
 mod analytics;
-mod analytics;
 
 // Re-export invariant functions for external use
 pub use invariants::verify_contract_invariants;
-pub use liquidity_pool::{LiquidityPool, PoolRegistry, Route};
+pub use liquidity_pool::{LiquidityPool, PoolAnalytics, PoolRegistry, Route, SwapFill, SwapResult};
 
 use portfolio::{Asset, LPPosition, Portfolio};
-pub use portfolio::{Badge, Metrics, Transaction};
-pub use rate_limit::{RateLimitStatus, RateLimiter};
+pub use portfolio::{Badge, FailureReason, Metrics, Transaction};
+pub use rate_limit::{RateLimitSnapshot, RateLimitStatus, RateLimiter};
 pub use tiers::UserTier;
+pub use attestation::{AttestedRoots, PoolReserves, ReservesAttestation};
+pub use commit_reveal::{SwapCommitment, SwapParams};
+pub use overview::UserOverview;
 use trading::perform_swap;
-use analytics::{PortfolioAnalytics, TimeWindow, PerformanceMetrics, AssetAllocation, BenchmarkComparison, PeriodReturns};
+use analytics::PortfolioAnalytics;
 pub use analytics::{TimeWindow, PerformanceMetrics, AssetAllocation, BenchmarkComparison, PeriodReturns};
 
-use crate::errors::SwapTradeError;
-use crate::storage::{ADMIN_KEY, PAUSED_KEY};
+use crate::errors::{ContractError, SwapTradeError};
+use crate::storage::{ADMIN_KEY, PAUSED_KEY, POOL_REGISTRY_KEY};
 
 pub fn pause_trading(env: Env) -> Result<bool, SwapTradeError> {
     // NOTE: Authentication check (invoker) removed for compatibility with SDK versions
@@ -93,12 +114,19 @@ impl CounterContract {
         migration::get_stored_version(&env)
     }
 
-    /// Migrate contract data from V1 to V2
-    pub fn migrate(env: Env) -> Result<(), SwapTradeError> {
-        migration::migrate_from_v1_to_v2(&env)
+    /// Run registered upgrade steps up to `target_version`, re-verifying
+    /// contract invariants before committing. Admin only.
+    pub fn migrate(env: Env, admin: Address, target_version: u32) -> Result<(), SwapTradeError> {
+        admin.require_auth();
+        admin::require_admin(&env, &admin)?;
+        migration::migrate_to(&env, &admin, target_version)
     }
 
     pub fn mint(env: Env, token: Symbol, to: Address, amount: i128) {
+        if let Err(err) = admin::require_not_paused(&env) {
+            panic_with_error!(&env, err);
+        }
+
         let mut portfolio: Portfolio = env
             .storage()
             .instance()
@@ -137,8 +165,20 @@ impl CounterContract {
         Self::balance_of(env, token, owner)
     }
 
-    /// Swap tokens using simplified AMM (1:1 XLM <-> USDC-SIM)
+    /// Swap tokens using simplified AMM (1:1 XLM <-> USDC-SIM).
+    /// Returns 0 without trading when the user is rate limited.
     pub fn swap(env: Env, from: Symbol, to: Symbol, amount: i128, user: Address) -> i128 {
+        user.require_auth();
+        Self::execute_swap(env, from, to, amount, user)
+    }
+
+    /// Body of `swap` for entrypoints that have already authorized the
+    /// trade on the user's behalf (an allowance, a revealed commitment)
+    fn execute_swap(env: Env, from: Symbol, to: Symbol, amount: i128, user: Address) -> i128 {
+        if let Err(err) = admin::require_not_paused(&env) {
+            panic_with_error!(&env, err);
+        }
+
         let mut portfolio: Portfolio = env
             .storage()
             .instance()
@@ -148,9 +188,24 @@ impl CounterContract {
         // Get user's current tier for fee calculation and rate limiting
         let user_tier = portfolio.get_user_tier(&env, user.clone());
 
-        // Check rate limit before executing swap
-        if let Err(_limit_status) = RateLimiter::check_swap_limit(&env, &user, &user_tier) {
-            panic!("RATELIMIT");
+        // Check rate limit before executing swap. Decline without panicking so
+        // the penalty, metric and audit event are not rolled back.
+        if let Err(limit_status) = RateLimiter::check_swap_limit(&env, &user, &user_tier) {
+            crate::events::Events::rate_limit_blocked(
+                &env,
+                user.clone(),
+                "RATE_LIMIT_SWAP",
+                &limit_status,
+                env.ledger().timestamp() as i64,
+            );
+            portfolio.inc_rate_limited();
+            env.storage().instance().set(&(), &portfolio);
+            return 0;
+        }
+
+        // Reject dust or oversized trades outside the asset's configured band
+        if let Err(err) = validation::validate_trade_bounds(&env, from.clone(), amount) {
+            panic_with_error!(&env, err);
         }
 
         let fee_bps = user_tier.effective_fee_bps();
@@ -203,22 +258,156 @@ impl CounterContract {
         out_amount
     }
 
+    /// Retry-safe swap: a repeated `idempotency_key` from the same user returns
+    /// the original output without executing again or touching reserves.
+    /// Keys are remembered for `idempotency::IDEMPOTENCY_TTL_LEDGERS`.
+    pub fn swap_idempotent(
+        env: Env,
+        from: Symbol,
+        to: Symbol,
+        amount: i128,
+        user: Address,
+        idempotency_key: BytesN<32>,
+    ) -> i128 {
+        user.require_auth();
+        if let Some(previous) = idempotency::lookup(&env, &user, &idempotency_key) {
+            return previous;
+        }
+
+        let out_amount = Self::execute_swap(env.clone(), from, to, amount, user.clone());
+        // A rate-limited attempt did not execute, so the key stays retryable
+        if out_amount > 0 {
+            idempotency::record(&env, user, idempotency_key, out_amount);
+        }
+        out_amount
+    }
+
+    /// First half of a front-running-resistant swap: record
+    /// `sha256(xdr((params, salt)))` for `user`, revealable from
+    /// `REVEAL_MIN_DELAY_SECS` after now until `deadline`. Replaces any
+    /// commitment the user still has pending.
+    pub fn commit_swap(env: Env, user: Address, commitment: BytesN<32>, deadline: u64) -> Result<(), ContractError> {
+        user.require_auth();
+        commit_reveal::commit(&env, user, commitment, deadline)
+    }
+
+    /// Second half of `commit_swap`: execute the committed swap once `params`
+    /// and `salt` are shown to match it, failing below `params.min_out`.
+    /// A rate-limited reveal fails and leaves the commitment pending.
+    pub fn reveal_swap(env: Env, user: Address, params: SwapParams, salt: BytesN<32>) -> Result<i128, ContractError> {
+        user.require_auth();
+        commit_reveal::consume(&env, &user, &params, &salt)?;
+
+        // `execute_swap` declines a rate-limited swap with 0, which would
+        // pass a zero `min_out` and spend the commitment for nothing
+        let portfolio: Portfolio = env
+            .storage()
+            .instance()
+            .get(&())
+            .unwrap_or_else(|| Portfolio::new(&env));
+        let user_tier = portfolio.get_user_tier(&env, user.clone());
+        if RateLimiter::check_swap_limit(&env, &user, &user_tier).is_err() {
+            return Err(ContractError::RateLimitExceeded);
+        }
+
+        let out_amount = Self::execute_swap(env, params.token_in, params.token_out, params.amount, user);
+        if out_amount < params.min_out {
+            return Err(ContractError::SlippageExceeded);
+        }
+        Ok(out_amount)
+    }
+
+    /// Let `spender` use up to `amount` of `owner`'s `token`, replacing any
+    /// earlier approval. An amount of 0 revokes it.
+    pub fn approve(env: Env, owner: Address, spender: Address, token: Symbol, amount: i128) -> Result<(), ContractError> {
+        owner.require_auth();
+        allowance::approve(&env, &owner, &spender, &token, amount)
+    }
+
+    pub fn allowance(env: Env, owner: Address, spender: Address, token: Symbol) -> i128 {
+        allowance::get_allowance(&env, &owner, &spender, &token)
+    }
+
+    /// Move `amount` of `owner`'s `token` to `to` on `spender`'s signature,
+    /// drawing down `spender`'s allowance
+    pub fn transfer_from(
+        env: Env,
+        spender: Address,
+        owner: Address,
+        to: Address,
+        token: Symbol,
+        amount: i128,
+    ) -> Result<(), ContractError> {
+        spender.require_auth();
+        admin::require_not_paused(&env)?;
+
+        let mut portfolio: Portfolio = env
+            .storage()
+            .instance()
+            .get(&())
+            .unwrap_or_else(|| Portfolio::new(&env));
+
+        let asset = if token == symbol_short!("XLM") {
+            Asset::XLM
+        } else {
+            Asset::Custom(token.clone())
+        };
+        if portfolio.balance_of(&env, asset.clone(), owner.clone()) < amount {
+            return Err(ContractError::InsufficientBalance);
+        }
+        allowance::spend(&env, &owner, &spender, &token, amount)?;
+
+        portfolio.debit(&env, asset.clone(), owner, amount);
+        portfolio.credit(&env, asset, to, amount);
+        env.storage().instance().set(&(), &portfolio);
+        Ok(())
+    }
+
+    /// Swap `amount` of `owner`'s `from` into `to` on `spender`'s signature
+    /// instead of the owner's. The input is drawn from `spender`'s allowance
+    /// over `from`; the output is credited to `owner`.
+    pub fn swap_from(
+        env: Env,
+        spender: Address,
+        owner: Address,
+        from: Symbol,
+        to: Symbol,
+        amount: i128,
+    ) -> Result<i128, ContractError> {
+        spender.require_auth();
+        if allowance::get_allowance(&env, &owner, &spender, &from) < amount {
+            return Err(ContractError::InsufficientAllowance);
+        }
+        let out_amount = Self::execute_swap(env.clone(), from.clone(), to, amount, owner.clone());
+        // Only an executed swap draws down the allowance
+        if out_amount > 0 {
+            allowance::spend(&env, &owner, &spender, &from, amount)?;
+        }
+        Ok(out_amount)
+    }
+
     /// Non-panicking swap that counts failed orders and returns 0 on failure
     pub fn safe_swap(env: Env, from: Symbol, to: Symbol, amount: i128, user: Address) -> i128 {
+        // A paused contract is not a failed order; just decline
+        if admin::is_paused(&env) {
+            return 0;
+        }
+
         let mut portfolio: Portfolio = env
             .storage()
             .instance()
             .get(&())
             .unwrap_or_else(|| Portfolio::new(&env));
 
-        let tokens_ok = (from == symbol_short!("XLM") || from == symbol_short!("USDCSIM"))
-            && (to == symbol_short!("XLM") || to == symbol_short!("USDCSIM"));
+        let quote = portfolio.quote_symbol();
+        let tokens_ok = (from == symbol_short!("XLM") || from == quote)
+            && (to == symbol_short!("XLM") || to == quote);
         let pair_ok = from != to;
         let amount_ok = amount > 0;
 
         if !(tokens_ok && pair_ok && amount_ok) {
             // Count failed order
-            portfolio.inc_failed_order();
+            portfolio.inc_failed_order(FailureReason::InvalidInput);
             env.storage().instance().set(&(), &portfolio);
 
             #[cfg(feature = "logging")]
@@ -230,6 +419,27 @@ impl CounterContract {
             return 0;
         }
 
+        // Rate-limited attempts are tracked separately from failed orders
+        let user_tier = portfolio.get_user_tier(&env, user.clone());
+        if let Err(limit_status) = RateLimiter::check_swap_limit(&env, &user, &user_tier) {
+            crate::events::Events::rate_limit_blocked(
+                &env,
+                user.clone(),
+                "RATE_LIMIT_SWAP",
+                &limit_status,
+                env.ledger().timestamp() as i64,
+            );
+            portfolio.inc_rate_limited();
+            env.storage().instance().set(&(), &portfolio);
+            return 0;
+        }
+
+        if let Err(reason) = trading::check_swap(&env, &portfolio, &from, &to, amount, &user) {
+            portfolio.inc_failed_order(reason);
+            env.storage().instance().set(&(), &portfolio);
+            return 0;
+        }
+
         let out_amount = perform_swap(&env, &mut portfolio, from, to, amount, user.clone());
         portfolio.record_trade(&env, user);
         env.storage().instance().set(&(), &portfolio);
@@ -282,6 +492,17 @@ impl CounterContract {
         portfolio.get_metrics()
     }
 
+    /// Failed orders broken down by reason
+    pub fn get_failure_breakdown(env: Env) -> Map<FailureReason, u32> {
+        let portfolio: Portfolio = env
+            .storage()
+            .instance()
+            .get(&())
+            .unwrap_or_else(|| Portfolio::new(&env));
+
+        portfolio.get_failure_breakdown()
+    }
+
     /// Check if a user has earned a specific badge
     pub fn has_badge(env: Env, user: Address, badge: Badge) -> bool {
         let portfolio: Portfolio = env
@@ -325,6 +546,31 @@ impl CounterContract {
         portfolio.get_user_tier(&env, user)
     }
 
+    /// Balances, tier, fee, referral, alert and rate-limit state for `user` in one call
+    pub fn get_user_overview(env: Env, user: Address) -> UserOverview {
+        overview::build_user_overview(&env, user)
+    }
+
+    /// Reserves, LP supply and fees of every pool plus the anchored audit and
+    /// governance roots, hashed into one digest for proof-of-reserves checks
+    pub fn attestation(env: Env) -> ReservesAttestation {
+        attestation::build_attestation(&env)
+    }
+
+    /// Anchor the off-chain audit-log and governance Merkle roots that later
+    /// attestations commit to. Admin only.
+    pub fn anchor_attestation_roots(
+        env: Env,
+        admin: Address,
+        audit_root: BytesN<32>,
+        governance_root: BytesN<32>,
+    ) -> Result<(), SwapTradeError> {
+        admin.require_auth();
+        admin::require_admin(&env, &admin)?;
+        attestation::anchor_roots(&env, audit_root, governance_root);
+        Ok(())
+    }
+
     // ===== RATE LIMITING =====
 
     /// Get rate limit status for swap operations
@@ -406,8 +652,11 @@ impl CounterContract {
     // ===== LIQUIDITY PROVIDER (LP) FUNCTIONS =====
 
     /// Add liquidity to the pool and mint LP tokens
-    /// Returns the number of LP tokens minted
+    /// Returns the number of LP tokens minted, or 0 when rate limited
     pub fn add_liquidity(env: Env, xlm_amount: i128, usdc_amount: i128, user: Address) -> i128 {
+        if let Err(err) = admin::require_not_paused(&env) {
+            panic_with_error!(&env, err);
+        }
         assert!(xlm_amount > 0, "XLM amount must be positive");
         assert!(usdc_amount > 0, "USDC amount must be positive");
 
@@ -417,21 +666,31 @@ impl CounterContract {
             .get(&())
             .unwrap_or_else(|| Portfolio::new(&env));
 
-        // Check rate limit for LP operations
+        // Check rate limit for LP operations; like swap, decline with 0 rather
+        // than panic so the recorded violation sticks
         let user_tier = portfolio.get_user_tier(&env, user.clone());
-        if let Err(_) = RateLimiter::check_lp_limit(&env, &user, &user_tier) {
-            panic!("RATELIMIT");
+        if let Err(limit_status) = RateLimiter::check_lp_limit(&env, &user, &user_tier) {
+            crate::events::Events::rate_limit_blocked(
+                &env,
+                user.clone(),
+                "RATE_LIMIT_LP",
+                &limit_status,
+                env.ledger().timestamp() as i64,
+            );
+            portfolio.inc_rate_limited();
+            env.storage().instance().set(&(), &portfolio);
+            return 0;
         }
 
         // Get current pool state
         let current_xlm = portfolio.get_liquidity(Asset::XLM);
-        let current_usdc = portfolio.get_liquidity(Asset::Custom(symbol_short!("USDCSIM")));
+        let current_usdc = portfolio.get_liquidity(portfolio.quote_asset());
         let total_lp_tokens = portfolio.get_total_lp_tokens();
 
         // Check user has sufficient balance
         let user_xlm_balance = portfolio.balance_of(&env, Asset::XLM, user.clone());
         let user_usdc_balance =
-            portfolio.balance_of(&env, Asset::Custom(symbol_short!("USDCSIM")), user.clone());
+            portfolio.balance_of(&env, portfolio.quote_asset(), user.clone());
 
         assert!(user_xlm_balance >= xlm_amount, "Insufficient XLM balance");
         assert!(
@@ -491,7 +750,7 @@ impl CounterContract {
         portfolio.debit(&env, Asset::XLM, user.clone(), xlm_amount);
         portfolio.debit(
             &env,
-            Asset::Custom(symbol_short!("USDCSIM")),
+            portfolio.quote_asset(),
             user.clone(),
             usdc_amount,
         );
@@ -556,7 +815,7 @@ impl CounterContract {
 
         // Get current pool state
         let current_xlm = portfolio.get_liquidity(Asset::XLM);
-        let current_usdc = portfolio.get_liquidity(Asset::Custom(symbol_short!("USDCSIM")));
+        let current_usdc = portfolio.get_liquidity(portfolio.quote_asset());
         let total_lp_tokens = portfolio.get_total_lp_tokens();
 
         assert!(total_lp_tokens > 0, "No LP tokens in pool");
@@ -589,7 +848,7 @@ impl CounterContract {
         // Update pool liquidity (subtract)
         portfolio.set_liquidity(Asset::XLM, current_xlm.saturating_sub(xlm_amount));
         portfolio.set_liquidity(
-            Asset::Custom(symbol_short!("USDCSIM")),
+            portfolio.quote_asset(),
             current_usdc.saturating_sub(usdc_amount),
         );
 
@@ -597,7 +856,7 @@ impl CounterContract {
         portfolio.mint(&env, Asset::XLM, user.clone(), xlm_amount);
         portfolio.mint(
             &env,
-            Asset::Custom(symbol_short!("USDCSIM")),
+            portfolio.quote_asset(),
             user.clone(),
             usdc_amount,
         );
@@ -639,6 +898,205 @@ impl CounterContract {
         result
     }
 
+    /// Get 24h volume, cumulative fees and fee-APY estimate for a pool
+    pub fn get_pool_analytics(env: Env, pool_id: u64) -> Result<PoolAnalytics, ContractError> {
+        let registry: PoolRegistry = env
+            .storage()
+            .instance()
+            .get(&POOL_REGISTRY_KEY)
+            .unwrap_or_else(|| PoolRegistry::new(&env));
+
+        registry.get_pool_analytics(&env, pool_id)
+    }
+
+    /// Pay `provider`'s accrued swap fees in `pool_id` into their balances,
+    /// scaled by the fee share of their tier. Returns the `(token_a, token_b)`
+    /// amounts credited.
+    pub fn collect_pool_fees(env: Env, pool_id: u64, provider: Address) -> Result<(i128, i128), ContractError> {
+        provider.require_auth();
+        let mut registry: PoolRegistry = env
+            .storage()
+            .instance()
+            .get(&POOL_REGISTRY_KEY)
+            .unwrap_or_else(|| PoolRegistry::new(&env));
+        let pool = registry.get_pool(pool_id).ok_or(ContractError::LPPositionNotFound)?;
+
+        let mut portfolio: Portfolio = env
+            .storage()
+            .instance()
+            .get(&())
+            .unwrap_or_else(|| Portfolio::new(&env));
+        let (fee_a, fee_b) = registry.collect_fees_for_tier(&env, &portfolio, pool_id, provider.clone())?;
+        for (token, amount) in [(pool.token_a, fee_a), (pool.token_b, fee_b)] {
+            let asset = if token == symbol_short!("XLM") {
+                Asset::XLM
+            } else {
+                Asset::Custom(token)
+            };
+            portfolio.credit(&env, asset, provider.clone(), amount);
+        }
+
+        env.storage().instance().set(&POOL_REGISTRY_KEY, &registry);
+        env.storage().instance().set(&(), &portfolio);
+        Ok((fee_a, fee_b))
+    }
+
+    /// Replace the fee tiers accepted for newly registered pools
+    pub fn set_allowed_fee_tiers(env: Env, admin: Address, fee_tiers: Vec<u32>) -> Result<(), ContractError> {
+        let mut registry: PoolRegistry = env
+            .storage()
+            .instance()
+            .get(&POOL_REGISTRY_KEY)
+            .unwrap_or_else(|| PoolRegistry::new(&env));
+
+        registry.set_allowed_fee_tiers(&env, admin, fee_tiers)?;
+        env.storage().instance().set(&POOL_REGISTRY_KEY, &registry);
+        Ok(())
+    }
+
+    /// Set the share (bps) of its pro-rata pool fees an LP of `tier` collects
+    pub fn set_tier_fee_share(env: Env, admin: Address, tier: UserTier, share_bps: u32) -> Result<(), ContractError> {
+        let mut registry: PoolRegistry = env
+            .storage()
+            .instance()
+            .get(&POOL_REGISTRY_KEY)
+            .unwrap_or_else(|| PoolRegistry::new(&env));
+
+        registry.set_tier_fee_share(&env, admin, tier, share_bps)?;
+        env.storage().instance().set(&POOL_REGISTRY_KEY, &registry);
+        Ok(())
+    }
+
+    /// Cap the price impact of a single swap through `pool_id`; 0 disables it
+    pub fn set_max_price_impact(env: Env, admin: Address, pool_id: u64, max_impact_bps: u32) -> Result<(), ContractError> {
+        let mut registry: PoolRegistry = env
+            .storage()
+            .instance()
+            .get(&POOL_REGISTRY_KEY)
+            .unwrap_or_else(|| PoolRegistry::new(&env));
+
+        registry.set_max_price_impact(&env, admin, pool_id, max_impact_bps)?;
+        env.storage().instance().set(&POOL_REGISTRY_KEY, &registry);
+        Ok(())
+    }
+
+    pub fn get_allowed_fee_tiers(env: Env) -> Vec<u32> {
+        let registry: PoolRegistry = env
+            .storage()
+            .instance()
+            .get(&POOL_REGISTRY_KEY)
+            .unwrap_or_else(|| PoolRegistry::new(&env));
+
+        registry.get_allowed_fee_tiers()
+    }
+
+    /// List registered pools, `limit` at a time starting from `offset`
+    pub fn get_all_pools(env: Env, offset: u64, limit: u64) -> Vec<LiquidityPool> {
+        let registry: PoolRegistry = env
+            .storage()
+            .instance()
+            .get(&POOL_REGISTRY_KEY)
+            .unwrap_or_else(|| PoolRegistry::new(&env));
+
+        registry.get_all_pools(&env, offset, limit)
+    }
+
+    pub fn pool_count(env: Env) -> u64 {
+        let registry: PoolRegistry = env
+            .storage()
+            .instance()
+            .get(&POOL_REGISTRY_KEY)
+            .unwrap_or_else(|| PoolRegistry::new(&env));
+
+        registry.pool_count()
+    }
+
+    /// Circuit breaker: halt swaps, liquidity adds and mints. Admin only.
+    /// One-time setup of the admin that gates pausing, migrations and pool
+    /// configuration. Fails once an admin is stored.
+    pub fn init_admin(env: Env, admin: Address) -> Result<(), SwapTradeError> {
+        admin::init_admin(&env, &admin)
+    }
+
+    pub fn pause(env: Env, admin: Address) -> Result<(), SwapTradeError> {
+        admin::pause(&env, &admin)
+    }
+
+    pub fn unpause(env: Env, admin: Address) -> Result<(), SwapTradeError> {
+        admin::unpause(&env, &admin)
+    }
+
+    pub fn is_paused(env: Env) -> bool {
+        admin::is_paused(&env)
+    }
+
+    /// Exempt (or stop exempting) a trusted integrator from rate limits. Admin only.
+    pub fn set_allowlisted(env: Env, user: Address, allowed: bool, admin: Address) -> Result<(), SwapTradeError> {
+        RateLimiter::set_allowlisted(&env, &user, allowed, &admin)
+    }
+
+    /// Set the min/max swap input accepted for `token`. Admin only.
+    pub fn set_trade_bounds(
+        env: Env,
+        admin: Address,
+        token: Symbol,
+        min_trade_amount: i128,
+        max_trade_amount: i128,
+    ) -> Result<(), SwapTradeError> {
+        validation::set_trade_bounds(&env, &admin, token, min_trade_amount, max_trade_amount)
+    }
+
+    pub fn get_trade_bounds(env: Env, token: Symbol) -> (i128, i128) {
+        validation::get_trade_bounds(&env, token)
+    }
+
+    /// Set the decimal scale used to normalize `asset` amounts. Admin only.
+    pub fn set_asset_decimals(env: Env, admin: Address, asset: Asset, decimals: u32) -> Result<(), SwapTradeError> {
+        admin.require_auth();
+        admin::require_admin(&env, &admin)?;
+        let mut portfolio: Portfolio = env
+            .storage()
+            .instance()
+            .get(&())
+            .unwrap_or_else(|| Portfolio::new(&env));
+        portfolio.set_asset_decimals(asset, decimals);
+        env.storage().instance().set(&(), &portfolio);
+        Ok(())
+    }
+
+    /// Set the asset the built-in XLM pool is quoted in (default `USDCSIM`),
+    /// e.g. to the real USDC symbol on mainnet. Only while that pool is empty.
+    pub fn set_quote_asset(env: Env, admin: Address, symbol: Symbol) -> Result<(), SwapTradeError> {
+        admin.require_auth();
+        admin::require_admin(&env, &admin)?;
+        let mut portfolio: Portfolio = env
+            .storage()
+            .instance()
+            .get(&())
+            .unwrap_or_else(|| Portfolio::new(&env));
+        portfolio.set_quote_symbol(symbol);
+        env.storage().instance().set(&(), &portfolio);
+        Ok(())
+    }
+
+    pub fn get_quote_asset(env: Env) -> Symbol {
+        let portfolio: Portfolio = env
+            .storage()
+            .instance()
+            .get(&())
+            .unwrap_or_else(|| Portfolio::new(&env));
+        portfolio.quote_symbol()
+    }
+
+    pub fn get_asset_decimals(env: Env, asset: Asset) -> u32 {
+        let portfolio: Portfolio = env
+            .storage()
+            .instance()
+            .get(&())
+            .unwrap_or_else(|| Portfolio::new(&env));
+        portfolio.get_asset_decimals(&asset)
+    }
+
     pub fn set_price(env: Env, token_pair: (Symbol, Symbol), price: u128) {
         set_stored_price(&env, token_pair, price);
     }
@@ -649,12 +1107,42 @@ impl CounterContract {
             .unwrap_or(0)
     }
 
+    /// Record an oracle close for `asset` (fixed-point: 7 decimals) in its price history. Admin only.
+    pub fn push_price(env: Env, admin: Address, asset: Asset, price: i128) -> Result<(), SwapTradeError> {
+        admin.require_auth();
+        admin::require_admin(&env, &admin)?;
+        price_history::record_price(&env, asset, price, env.ledger().timestamp());
+        Ok(())
+    }
+
+    /// Up to `days` most recent daily closes for `asset`, oldest first
+    pub fn get_price_history(env: Env, asset: Asset, days: u32) -> Vec<i128> {
+        price_history::get_price_history(&env, &asset, days)
+    }
+
     pub fn set_price_update_tolerance_bps(env: Env, token_pair: (Symbol, Symbol), bps: u32) {
         oracle::set_price_update_tolerance_bps(&env, token_pair, bps);
     }
 
     pub fn set_pool_liquidity(env: Env, token: Symbol, amount: i128) {
         let mut portfolio: Portfolio = env
+            .storage()
+            .instance()
+            .get(&())
+            .unwrap_or_else(|| Portfolio::new(&env));
+        let asset = if token == symbol_short!("XLM") {
+            Asset::XLM
+        } else {
+            Asset::Custom(token)
+        };
+        portfolio.set_liquidity(asset, amount);
+        env.storage().instance().set(&(), &portfolio);
+    }
+
+    pub fn set_max_slippage_bps(env: Env, bps: u32) {
+        env.storage().instance().set(&symbol_short!("MAX_SLIP"), &bps);
+    }
+
     /// Get comprehensive performance metrics for a user
     pub fn get_performance_metrics(
         env: Env,
@@ -670,15 +1158,17 @@ impl CounterContract {
         PortfolioAnalytics::get_performance_metrics(&env, &portfolio, user, time_window)
     }
 
-    /// Get asset allocation breakdown with correlation analysis
-    pub fn get_asset_allocation(env: Env, user: Address) -> AssetAllocation {
+    /// Get asset allocation breakdown with correlation analysis, valuing each
+    /// asset at the supplied fixed-point price (7 decimals), or at 1.0 when
+    /// `prices` is `None`
+    pub fn get_asset_allocation(env: Env, user: Address, prices: Option<Map<Asset, i128>>) -> AssetAllocation {
         let portfolio: Portfolio = env
             .storage()
             .instance()
             .get(&())
             .unwrap_or_else(|| Portfolio::new(&env));
 
-        PortfolioAnalytics::get_asset_allocation(&env, &portfolio, user)
+        PortfolioAnalytics::get_asset_allocation(&env, &portfolio, user, prices)
     }
 
     /// Compare portfolio performance against a benchmark
@@ -693,17 +1183,6 @@ impl CounterContract {
             .instance()
             .get(&())
             .unwrap_or_else(|| Portfolio::new(&env));
-        let asset = if token == symbol_short!("XLM") {
-            Asset::XLM
-        } else {
-            Asset::Custom(token)
-        };
-        portfolio.set_liquidity(asset, amount);
-        env.storage().instance().set(&(), &portfolio);
-    }
-
-    pub fn set_max_slippage_bps(env: Env, bps: u32) {
-        env.storage().instance().set(&symbol_short!("MAX_SLIP"), &bps);
 
         PortfolioAnalytics::get_benchmark_comparison(&env, &portfolio, user, benchmark_id, time_window)
     }
@@ -725,15 +1204,33 @@ impl CounterContract {
     }
 }
 
+// Host-side governance and audit tooling. These use std (SystemTime,
+// std::io) and serde, so they only exist with `native-tools` and never in
+// the wasm32 contract build.
+#[cfg(feature = "native-tools")]
+#[path = "governance/governance.rs"]
+pub mod governance;
+#[cfg(feature = "native-tools")]
+#[path = "audit_tools/audit_log.rs"]
+pub mod audit_log;
+
+#[cfg(test)]
+mod allowance_tests;
 #[cfg(test)]
 mod analytics_tests;
 #[cfg(test)]
+mod attestation_tests;
+#[cfg(test)]
 mod balance_test;
 #[cfg(test)]
 mod batch_tests;
 #[cfg(test)]
+mod commit_reveal_tests;
+#[cfg(test)]
 mod enhanced_trading_tests; // NEW: Enhanced trading tests for better coverage
 #[cfg(test)]
+mod fee_progression_tests;
+#[cfg(test)]
 mod fuzz_tests;
 #[cfg(test)]
 mod lp_tests;
@@ -743,6 +1240,9 @@ mod oracle_tests;
 #[cfg(test)]
 mod rate_limit_tests;
 #[cfg(test)]
+mod referral_tests;
+#[cfg(test)]
+mod trading_tests;
+#[cfg(test)]
 mod transaction_tests; // NEW: Fuzz tests for security hardening
 
-// trading tests are provided as integration/unit tests in the repository tests/ folder
@@ -1,5 +1,7 @@
 use soroban_sdk::{contracttype, Address, Env, Map, Symbol, Vec};
 use crate::errors::ContractError;
+use crate::portfolio::Portfolio;
+use crate::tiers::UserTier;
 
 #[derive(Clone, Debug, PartialEq)]
 #[contracttype]
@@ -11,6 +13,46 @@ pub struct LiquidityPool {
     pub reserve_b: i128,
     pub total_lp_tokens: i128,
     pub fee_tier: u32,
+    /// Lifetime swap volume, denominated in `token_a`
+    pub cumulative_volume: i128,
+    /// Lifetime fee income, denominated in `token_a`
+    pub cumulative_fees: i128,
+    /// Ledger timestamp of the last swap through this pool
+    pub last_swap_timestamp: u64,
+    /// Lifetime LP fees per LP token in `token_a`, scaled by `FEE_GROWTH_PRECISION`
+    pub fee_growth_a: i128,
+    /// Lifetime LP fees per LP token in `token_b`, scaled by `FEE_GROWTH_PRECISION`
+    pub fee_growth_b: i128,
+    /// Swap fees in `token_a` held outside the reserves until LPs collect them
+    pub unclaimed_fees_a: i128,
+    /// Swap fees in `token_b` held outside the reserves until LPs collect them
+    pub unclaimed_fees_b: i128,
+    /// Largest price impact (bps) a single swap may cause; 0 disables the cap
+    pub max_price_impact_bps: u32,
+}
+
+/// Single swap observation used to window pool volume and fees
+#[derive(Clone, Debug, PartialEq)]
+#[contracttype]
+pub struct VolumeSample {
+    pub timestamp: u64,
+    pub volume: i128,
+    pub fees: i128,
+}
+
+/// Pool performance summary for liquidity providers.
+/// All amounts are denominated in `token_a`.
+#[derive(Clone, Debug, PartialEq)]
+#[contracttype]
+pub struct PoolAnalytics {
+    pub pool_id: u64,
+    pub volume_24h: i128,
+    pub fees_24h: i128,
+    pub cumulative_volume: i128,
+    pub cumulative_fees: i128,
+    pub tvl: i128,
+    /// Annualized fee yield estimate (24h fees / TVL * 365) in basis points
+    pub fee_apy_bps: u32,
 }
 
 #[derive(Clone, Debug, PartialEq)]
@@ -22,6 +64,36 @@ pub struct Route {
     pub total_price_impact_bps: u32,
 }
 
+/// Outcome of a single pool swap, for clients that need more than the output amount
+#[derive(Clone, Debug, PartialEq)]
+#[contracttype]
+pub struct SwapResult {
+    pub output: i128,
+    /// Fee withheld from the input, in `token_in` units
+    pub fee_paid: i128,
+    pub price_impact_bps: u32,
+    /// Pool reserves `(reserve_a, reserve_b)` once the swap settled
+    pub reserves_after: (i128, i128),
+}
+
+/// Input actually consumed and output received by `swap_with_fill`
+#[derive(Clone, Debug, PartialEq)]
+#[contracttype]
+pub struct SwapFill {
+    /// Portion of the requested `amount_in` that was swapped
+    pub filled_in: i128,
+    pub output: i128,
+}
+
+/// Fee tiers (bps) accepted by `register_pool` until an admin overrides them
+pub const DEFAULT_FEE_TIERS: [u32; 3] = [1, 5, 30];
+
+/// Window used for rolling pool volume and fee figures
+pub const POOL_ANALYTICS_WINDOW_SECS: u64 = 86_400;
+
+/// Scale of `LiquidityPool::fee_growth_a` and `fee_growth_b`
+pub const FEE_GROWTH_PRECISION: i128 = 1_000_000_000_000;
+
 #[derive(Clone)]
 #[contracttype]
 pub struct PoolRegistry {
@@ -29,6 +101,18 @@ pub struct PoolRegistry {
     pair_to_pool: Map<(Symbol, Symbol), u64>,
     next_pool_id: u64,
     lp_balances: Map<(u64, Address), i128>,
+    volume_samples: Map<u64, Vec<VolumeSample>>,
+    token_pools: Map<Symbol, Vec<u64>>,
+    allowed_fee_tiers: Vec<u32>,
+    /// `(fee_growth_a, fee_growth_b)` when each position last settled
+    fee_checkpoints: Map<(u64, Address), (i128, i128)>,
+    /// Settled fees not yet collected, as `(token_a, token_b)`
+    fees_owed: Map<(u64, Address), (i128, i128)>,
+    /// Share (bps) of its pro-rata fees each tier collects; missing tiers collect in full
+    tier_fee_share_bps: Map<UserTier, u32>,
+    /// LP tokens minted at registration against the seed reserves; no
+    /// provider holds them, so they stay outside `lp_balances` for good
+    locked_lp: Map<u64, i128>,
 }
 
 impl PoolRegistry {
@@ -38,6 +122,13 @@ impl PoolRegistry {
             pair_to_pool: Map::new(env),
             next_pool_id: 1,
             lp_balances: Map::new(env),
+            volume_samples: Map::new(env),
+            token_pools: Map::new(env),
+            allowed_fee_tiers: Vec::from_array(env, DEFAULT_FEE_TIERS),
+            fee_checkpoints: Map::new(env),
+            fees_owed: Map::new(env),
+            tier_fee_share_bps: Map::new(env),
+            locked_lp: Map::new(env),
         }
     }
 
@@ -57,16 +148,20 @@ impl PoolRegistry {
     ) -> Result<u64, ContractError> {
         admin.require_auth();
         
-        if ![1, 5, 30].contains(&fee_tier) {
+        if !self.allowed_fee_tiers.contains(fee_tier) {
             return Err(ContractError::InvalidAmount);
         }
         if token_a == token_b || initial_a <= 0 || initial_b <= 0 {
             return Err(ContractError::InvalidSwapPair);
         }
 
+        // A pair may have one pool per fee tier; the first one registered stays
+        // the pair's primary pool for single-pool routing.
         let (norm_a, norm_b) = Self::normalize_pair(token_a.clone(), token_b.clone());
-        if self.pair_to_pool.contains_key((norm_a.clone(), norm_b.clone())) {
-            return Err(ContractError::InvalidSwapPair);
+        for id in self.pools_for_pair(env, norm_a.clone(), norm_b.clone()).iter() {
+            if self.pools.get(id).is_some_and(|p| p.fee_tier == fee_tier) {
+                return Err(ContractError::InvalidSwapPair);
+            }
         }
 
         let pool_id = self.next_pool_id;
@@ -76,12 +171,72 @@ impl PoolRegistry {
         self.pools.set(pool_id, LiquidityPool {
             pool_id, token_a: norm_a.clone(), token_b: norm_b.clone(),
             reserve_a, reserve_b, total_lp_tokens: initial_lp, fee_tier,
+            cumulative_volume: 0, cumulative_fees: 0, last_swap_timestamp: env.ledger().timestamp(),
+            fee_growth_a: 0, fee_growth_b: 0, unclaimed_fees_a: 0, unclaimed_fees_b: 0,
+            max_price_impact_bps: 0,
         });
-        self.pair_to_pool.set((norm_a, norm_b), pool_id);
+        self.locked_lp.set(pool_id, initial_lp);
+        if !self.pair_to_pool.contains_key((norm_a.clone(), norm_b.clone())) {
+            self.pair_to_pool.set((norm_a.clone(), norm_b.clone()), pool_id);
+        }
+        self.index_pool(env, norm_a, pool_id);
+        self.index_pool(env, norm_b, pool_id);
         self.next_pool_id += 1;
         Ok(pool_id)
     }
 
+    /// Replace the set of fee tiers new pools may use. Existing pools keep their tier.
+    pub fn set_allowed_fee_tiers(&mut self, env: &Env, admin: Address, fee_tiers: Vec<u32>) -> Result<(), ContractError> {
+        admin.require_auth();
+        crate::admin::require_admin(env, &admin)?;
+
+        if fee_tiers.is_empty() {
+            return Err(ContractError::InvalidAmount);
+        }
+        for tier in fee_tiers.iter() {
+            if tier == 0 || tier >= 10000 {
+                return Err(ContractError::InvalidAmount);
+            }
+        }
+        self.allowed_fee_tiers = fee_tiers;
+        Ok(())
+    }
+
+    pub fn get_allowed_fee_tiers(&self) -> Vec<u32> { self.allowed_fee_tiers.clone() }
+
+    /// Set the share (bps, at most 10000) of its pro-rata fees an LP of `tier`
+    /// collects. Capping every share at 100% keeps total payouts within the
+    /// fees actually earned; what a lower tier leaves behind goes to the pool.
+    pub fn set_tier_fee_share(&mut self, env: &Env, admin: Address, tier: UserTier, share_bps: u32) -> Result<(), ContractError> {
+        admin.require_auth();
+        crate::admin::require_admin(env, &admin)?;
+
+        if share_bps > 10000 {
+            return Err(ContractError::InvalidAmount);
+        }
+        self.tier_fee_share_bps.set(tier, share_bps);
+        Ok(())
+    }
+
+    pub fn get_tier_fee_share(&self, tier: UserTier) -> u32 {
+        self.tier_fee_share_bps.get(tier).unwrap_or(10000)
+    }
+
+    /// Cap the price impact (bps, at most 10000) of any single swap through
+    /// `pool_id`. Zero removes the cap.
+    pub fn set_max_price_impact(&mut self, env: &Env, admin: Address, pool_id: u64, max_impact_bps: u32) -> Result<(), ContractError> {
+        admin.require_auth();
+        crate::admin::require_admin(env, &admin)?;
+
+        if max_impact_bps > 10000 {
+            return Err(ContractError::InvalidAmount);
+        }
+        let mut pool = self.pools.get(pool_id).ok_or(ContractError::LPPositionNotFound)?;
+        pool.max_price_impact_bps = max_impact_bps;
+        self.pools.set(pool_id, pool);
+        Ok(())
+    }
+
     pub fn add_liquidity(&mut self, env: &Env, pool_id: u64, amount_a: i128, amount_b: i128, provider: Address) -> Result<i128, ContractError> {
         let mut pool = self.pools.get(pool_id).ok_or(ContractError::LPPositionNotFound)?;
         if amount_a <= 0 || amount_b <= 0 || pool.reserve_a == 0 || pool.reserve_b == 0 {
@@ -97,15 +252,18 @@ impl PoolRegistry {
         };
 
         if lp_tokens <= 0 { return Err(ContractError::InvalidAmount); }
+        self.settle_fees(&pool, (pool_id, provider.clone()))?;
 
         pool.reserve_a = pool.reserve_a.checked_add(amount_a).ok_or(ContractError::AmountOverflow)?;
         pool.reserve_b = pool.reserve_b.checked_add(amount_b).ok_or(ContractError::AmountOverflow)?;
         pool.total_lp_tokens = pool.total_lp_tokens.checked_add(lp_tokens).ok_or(ContractError::AmountOverflow)?;
+        crate::invariants::verify_pool_invariants(env, &pool)?;
         self.pools.set(pool_id, pool);
 
         let key = (pool_id, provider);
         let current = self.lp_balances.get(key.clone()).unwrap_or(0);
         self.lp_balances.set(key, current.checked_add(lp_tokens).ok_or(ContractError::AmountOverflow)?);
+        crate::invariants::verify_registry_lp_conservation_sampled(env, self)?;
         Ok(lp_tokens)
     }
 
@@ -114,6 +272,7 @@ impl PoolRegistry {
         let key = (pool_id, provider);
         let balance = self.lp_balances.get(key.clone()).unwrap_or(0);
         if balance < lp_tokens { return Err(ContractError::InsufficientLPTokens); }
+        self.settle_fees(&pool, key.clone())?;
 
         let amount_a = ((lp_tokens as u128).checked_mul(pool.reserve_a as u128).ok_or(ContractError::AmountOverflow)? / (pool.total_lp_tokens as u128)) as i128;
         let amount_b = ((lp_tokens as u128).checked_mul(pool.reserve_b as u128).ok_or(ContractError::AmountOverflow)? / (pool.total_lp_tokens as u128)) as i128;
@@ -121,12 +280,52 @@ impl PoolRegistry {
         pool.reserve_a = pool.reserve_a.checked_sub(amount_a).ok_or(ContractError::InsufficientBalance)?;
         pool.reserve_b = pool.reserve_b.checked_sub(amount_b).ok_or(ContractError::InsufficientBalance)?;
         pool.total_lp_tokens = pool.total_lp_tokens.checked_sub(lp_tokens).ok_or(ContractError::InsufficientLPTokens)?;
+        crate::invariants::verify_pool_invariants(env, &pool)?;
         self.pools.set(pool_id, pool);
         self.lp_balances.set(key, balance.checked_sub(lp_tokens).ok_or(ContractError::InsufficientLPTokens)?);
+        crate::invariants::verify_registry_lp_conservation_sampled(env, self)?;
         Ok((amount_a, amount_b))
     }
 
+    /// Exit a position entirely into `token_out`: burn `lp_tokens` for the
+    /// proportional amounts, then swap the other side into `token_out`
+    /// through the same pool. The internal swap pays the usual fee and price
+    /// impact against the post-withdrawal reserves. A `min_out` miss leaves
+    /// the pool and position untouched.
+    pub fn remove_liquidity_single(
+        &mut self,
+        env: &Env,
+        pool_id: u64,
+        lp_tokens: i128,
+        token_out: Symbol,
+        min_out: i128,
+        provider: Address,
+    ) -> Result<i128, ContractError> {
+        let pool = self.pools.get(pool_id).ok_or(ContractError::LPPositionNotFound)?;
+        let out_is_a = token_out == pool.token_a;
+        if !out_is_a && token_out != pool.token_b {
+            return Err(ContractError::InvalidTokenSymbol);
+        }
+        let token_in = if out_is_a { pool.token_b.clone() } else { pool.token_a.clone() };
+        if lp_tokens <= 0 { return Err(ContractError::InvalidAmount); }
+
+        let mut staged = self.clone();
+        let (amount_a, amount_b) = staged.remove_liquidity(env, pool_id, lp_tokens, provider)?;
+        let (kept, to_swap) = if out_is_a { (amount_a, amount_b) } else { (amount_b, amount_a) };
+        let swapped = if to_swap > 0 { staged.swap(env, pool_id, token_in, to_swap, 0)? } else { 0 };
+        let total_out = kept.checked_add(swapped).ok_or(ContractError::AmountOverflow)?;
+        if total_out < min_out { return Err(ContractError::SlippageExceeded); }
+
+        *self = staged;
+        Ok(total_out)
+    }
+
     pub fn swap(&mut self, env: &Env, pool_id: u64, token_in: Symbol, amount_in: i128, min_amount_out: i128) -> Result<i128, ContractError> {
+        self.swap_detailed(env, pool_id, token_in, amount_in, min_amount_out).map(|r| r.output)
+    }
+
+    /// Same as `swap`, but also reports the fee, price impact and post-swap reserves
+    pub fn swap_detailed(&mut self, env: &Env, pool_id: u64, token_in: Symbol, amount_in: i128, min_amount_out: i128) -> Result<SwapResult, ContractError> {
         let mut pool = self.pools.get(pool_id).ok_or(ContractError::LPPositionNotFound)?;
         if amount_in <= 0 { return Err(ContractError::InvalidAmount); }
 
@@ -138,57 +337,298 @@ impl PoolRegistry {
             return Err(ContractError::InvalidTokenSymbol);
         };
 
-        let amount_in_with_fee = (amount_in as u128).checked_mul(10000 - pool.fee_tier as u128).ok_or(ContractError::AmountOverflow)? / 10000;
+        // The fee rounds down, so the trader never pays more than fee_tier bps
+        let fee = (amount_in as u128).checked_mul(pool.fee_tier as u128).ok_or(ContractError::AmountOverflow)? / 10000;
+        let amount_in_with_fee = amount_in as u128 - fee;
         let numerator = (reserve_out as u128).checked_mul(amount_in_with_fee).ok_or(ContractError::AmountOverflow)?;
         let denominator = (reserve_in as u128).checked_add(amount_in_with_fee).ok_or(ContractError::AmountOverflow)?;
         let amount_out = (numerator / denominator) as i128;
 
         if amount_out < min_amount_out { return Err(ContractError::SlippageExceeded); }
+        let price_impact_bps = self.calculate_price_impact(&pool, token_in.clone(), amount_in);
+        if pool.max_price_impact_bps > 0 && price_impact_bps > pool.max_price_impact_bps {
+            return Err(ContractError::PriceImpactTooHigh);
+        }
 
+        // Normalize volume and fees into token_a using pre-swap reserves
+        let (volume, fees) = if token_in == pool.token_a {
+            (amount_in, fee as i128)
+        } else {
+            let to_a = |x: u128| -> Result<i128, ContractError> {
+                Ok((x.checked_mul(pool.reserve_a as u128).ok_or(ContractError::AmountOverflow)? / (pool.reserve_b as u128)) as i128)
+            };
+            (to_a(amount_in as u128)?, to_a(fee)?)
+        };
+
+        let net_in = amount_in_with_fee as i128;
         if token_in == pool.token_a {
-            pool.reserve_a = pool.reserve_a.checked_add(amount_in).ok_or(ContractError::AmountOverflow)?;
+            pool.reserve_a = pool.reserve_a.checked_add(net_in).ok_or(ContractError::AmountOverflow)?;
             pool.reserve_b = pool.reserve_b.checked_sub(amount_out).ok_or(ContractError::InsufficientBalance)?;
         } else {
-            pool.reserve_b = pool.reserve_b.checked_add(amount_in).ok_or(ContractError::AmountOverflow)?;
+            pool.reserve_b = pool.reserve_b.checked_add(net_in).ok_or(ContractError::AmountOverflow)?;
             pool.reserve_a = pool.reserve_a.checked_sub(amount_out).ok_or(ContractError::InsufficientBalance)?;
         }
+        Self::accrue_swap_fee(&mut pool, &token_in, fee as i128)?;
+        let now = env.ledger().timestamp();
+        pool.cumulative_volume = pool.cumulative_volume.checked_add(volume).ok_or(ContractError::AmountOverflow)?;
+        pool.cumulative_fees = pool.cumulative_fees.checked_add(fees).ok_or(ContractError::AmountOverflow)?;
+        pool.last_swap_timestamp = now;
+        crate::invariants::verify_pool_invariants(env, &pool)?;
+        let reserves_after = (pool.reserve_a, pool.reserve_b);
+        self.pools.set(pool_id, pool);
+        self.record_volume_sample(env, pool_id, VolumeSample { timestamp: now, volume, fees });
+        Ok(SwapResult { output: amount_out, fee_paid: fee as i128, price_impact_bps, reserves_after })
+    }
+
+    /// Swap that can settle for less than `amount_in` instead of reverting.
+    /// Without `allow_partial` this is `swap`. With it, a fill that misses
+    /// `min_amount_out` shrinks to the largest input whose output stays within
+    /// `max_slippage_bps` of the pool's pre-trade spot price, net of fee, and
+    /// whose price impact stays within the pool's `max_price_impact_bps`.
+    pub fn swap_with_fill(
+        &mut self,
+        env: &Env,
+        pool_id: u64,
+        token_in: Symbol,
+        amount_in: i128,
+        min_amount_out: i128,
+        allow_partial: bool,
+        max_slippage_bps: u32,
+    ) -> Result<SwapFill, ContractError> {
+        if !allow_partial {
+            let output = self.swap(env, pool_id, token_in, amount_in, min_amount_out)?;
+            return Ok(SwapFill { filled_in: amount_in, output });
+        }
+
+        let pool = self.pools.get(pool_id).ok_or(ContractError::LPPositionNotFound)?;
+        if amount_in <= 0 { return Err(ContractError::InvalidAmount); }
+        if token_in != pool.token_a && token_in != pool.token_b { return Err(ContractError::InvalidTokenSymbol); }
+        if max_slippage_bps > 10000 { return Err(ContractError::InvalidAmount); }
+
+        let within_bounds = |amount: i128| -> bool {
+            let output = self.calculate_output(&pool, token_in.clone(), amount);
+            let impact_ok = pool.max_price_impact_bps == 0
+                || self.calculate_price_impact(&pool, token_in.clone(), amount) <= pool.max_price_impact_bps;
+            output > 0 && impact_ok && crate::invariants::invariant_slippage_bounds(
+                self.calculate_spot_output(&pool, token_in.clone(), amount) as u128,
+                output as u128,
+                max_slippage_bps,
+            )
+        };
+
+        let full_output = self.calculate_output(&pool, token_in.clone(), amount_in);
+        let filled_in = if full_output >= min_amount_out && within_bounds(amount_in) {
+            amount_in
+        } else {
+            // Slippage grows with trade size, so the largest acceptable input is a boundary search
+            let (mut lo, mut hi) = (0i128, amount_in);
+            while lo < hi {
+                let mid = lo + (hi - lo + 1) / 2;
+                if within_bounds(mid) { lo = mid; } else { hi = mid - 1; }
+            }
+            lo
+        };
+        if filled_in == 0 { return Err(ContractError::SlippageExceeded); }
+
+        let output = self.swap(env, pool_id, token_in, filled_in, 0)?;
+        Ok(SwapFill { filled_in, output })
+    }
+
+    /// Exact-output counterpart to `swap`: charges the smallest input that buys
+    /// at least `amount_out` of the other token and returns it, failing if it
+    /// would exceed `max_amount_in`.
+    pub fn swap_for_exact_output(&mut self, env: &Env, pool_id: u64, token_in: Symbol, amount_out: i128, max_amount_in: i128) -> Result<i128, ContractError> {
+        let pool = self.pools.get(pool_id).ok_or(ContractError::LPPositionNotFound)?;
+        if amount_out <= 0 { return Err(ContractError::InvalidAmount); }
+
+        let (reserve_in, reserve_out) = if token_in == pool.token_a {
+            (pool.reserve_a, pool.reserve_b)
+        } else if token_in == pool.token_b {
+            (pool.reserve_b, pool.reserve_a)
+        } else {
+            return Err(ContractError::InvalidTokenSymbol);
+        };
+        if amount_out >= reserve_out { return Err(ContractError::InsufficientBalance); }
+
+        // Invert x*y=k, rounding up so `swap` yields at least amount_out
+        let numerator = (reserve_in as u128).checked_mul(amount_out as u128).ok_or(ContractError::AmountOverflow)?;
+        let denominator = (reserve_out - amount_out) as u128;
+        let net_in = numerator.div_ceil(denominator);
+        // Gross up for the fee. `swap` rounds the fee down, so the ceiling can
+        // overshoot by a unit or two; step back to the smallest sufficient input.
+        let fee_tier = pool.fee_tier as u128;
+        let net_of_fee = |gross: u128| gross - gross * fee_tier / 10000;
+        let mut amount_in = net_in.checked_mul(10000).ok_or(ContractError::AmountOverflow)?.div_ceil(10000 - fee_tier);
+        while amount_in > 1 && net_of_fee(amount_in - 1) >= net_in {
+            amount_in -= 1;
+        }
+        if amount_in > i128::MAX as u128 { return Err(ContractError::AmountOverflow); }
+        let amount_in = amount_in as i128;
+
+        if amount_in > max_amount_in { return Err(ContractError::SlippageExceeded); }
+        self.swap(env, pool_id, token_in, amount_in, amount_out)?;
+        Ok(amount_in)
+    }
+
+    /// Pay out `provider`'s share of swap fees accrued since their last
+    /// collection as `(token_a, token_b)`, removing it from the pool's
+    /// unclaimed fees. The caller credits it to the provider. Returns
+    /// `(0, 0)` when nothing is owed.
+    pub fn collect_fees(&mut self, pool_id: u64, provider: Address) -> Result<(i128, i128), ContractError> {
+        let mut pool = self.pools.get(pool_id).ok_or(ContractError::LPPositionNotFound)?;
+        let key = (pool_id, provider);
+        let (owed_a, owed_b) = self.settle_fees(&pool, key.clone())?;
+        self.fees_owed.set(key, (0, 0));
+        // Fee growth rounds down, so payouts never exceed what swaps left behind
+        pool.unclaimed_fees_a -= owed_a;
+        pool.unclaimed_fees_b -= owed_b;
+        if pool.unclaimed_fees_a < 0 || pool.unclaimed_fees_b < 0 {
+            return Err(ContractError::InvariantViolation);
+        }
         self.pools.set(pool_id, pool);
-        Ok(amount_out)
+        Ok((owed_a, owed_b))
+    }
+
+    /// `collect_fees` scaled by the fee share configured for the provider's
+    /// current tier in `portfolio`. The withheld remainder of each token is
+    /// added to that token's reserve, so it is split across both sides in
+    /// proportion to the fees each earned and accrues to every LP.
+    pub fn collect_fees_for_tier(&mut self, env: &Env, portfolio: &Portfolio, pool_id: u64, provider: Address) -> Result<(i128, i128), ContractError> {
+        let tier = portfolio.get_user_tier(env, provider.clone());
+        let (owed_a, owed_b) = self.collect_fees(pool_id, provider)?;
+        let share_bps = self.get_tier_fee_share(tier) as i128;
+        let payout_a = owed_a.checked_mul(share_bps).ok_or(ContractError::AmountOverflow)? / 10000;
+        let payout_b = owed_b.checked_mul(share_bps).ok_or(ContractError::AmountOverflow)? / 10000;
+        let (withheld_a, withheld_b) = (owed_a - payout_a, owed_b - payout_b);
+        if withheld_a > 0 || withheld_b > 0 {
+            let mut pool = self.pools.get(pool_id).ok_or(ContractError::LPPositionNotFound)?;
+            pool.reserve_a = pool.reserve_a.checked_add(withheld_a).ok_or(ContractError::AmountOverflow)?;
+            pool.reserve_b = pool.reserve_b.checked_add(withheld_b).ok_or(ContractError::AmountOverflow)?;
+            self.pools.set(pool_id, pool);
+        }
+        Ok((payout_a, payout_b))
+    }
+
+    /// Book a swap fee paid in `token_in`. The fee is held outside the
+    /// reserves and owed to LPs through that token's fee growth; with no LP
+    /// supply to owe it to, it is credited to the input reserve instead.
+    pub(crate) fn accrue_swap_fee(pool: &mut LiquidityPool, token_in: &Symbol, fee: i128) -> Result<(), ContractError> {
+        let is_a = *token_in == pool.token_a;
+        if pool.total_lp_tokens == 0 {
+            let reserve = if is_a { &mut pool.reserve_a } else { &mut pool.reserve_b };
+            *reserve = reserve.checked_add(fee).ok_or(ContractError::AmountOverflow)?;
+            return Ok(());
+        }
+        let growth = fee.checked_mul(FEE_GROWTH_PRECISION).ok_or(ContractError::AmountOverflow)? / pool.total_lp_tokens;
+        if is_a {
+            pool.fee_growth_a = pool.fee_growth_a.checked_add(growth).ok_or(ContractError::AmountOverflow)?;
+            pool.unclaimed_fees_a = pool.unclaimed_fees_a.checked_add(fee).ok_or(ContractError::AmountOverflow)?;
+        } else {
+            pool.fee_growth_b = pool.fee_growth_b.checked_add(growth).ok_or(ContractError::AmountOverflow)?;
+            pool.unclaimed_fees_b = pool.unclaimed_fees_b.checked_add(fee).ok_or(ContractError::AmountOverflow)?;
+        }
+        Ok(())
+    }
+
+    /// Move fees accrued by a position since its checkpoint into `fees_owed`.
+    /// Must run before the position's LP balance changes.
+    fn settle_fees(&mut self, pool: &LiquidityPool, key: (u64, Address)) -> Result<(i128, i128), ContractError> {
+        let balance = self.lp_balances.get(key.clone()).unwrap_or(0);
+        let (checkpoint_a, checkpoint_b) = self.fee_checkpoints.get(key.clone()).unwrap_or((0, 0));
+        let accrue = |growth: i128, checkpoint: i128| -> Result<i128, ContractError> {
+            Ok(balance.checked_mul(growth - checkpoint).ok_or(ContractError::AmountOverflow)? / FEE_GROWTH_PRECISION)
+        };
+        let (owed_a, owed_b) = self.fees_owed.get(key.clone()).unwrap_or((0, 0));
+        let owed = (
+            owed_a.checked_add(accrue(pool.fee_growth_a, checkpoint_a)?).ok_or(ContractError::AmountOverflow)?,
+            owed_b.checked_add(accrue(pool.fee_growth_b, checkpoint_b)?).ok_or(ContractError::AmountOverflow)?,
+        );
+        self.fees_owed.set(key.clone(), owed);
+        self.fee_checkpoints.set(key, (pool.fee_growth_a, pool.fee_growth_b));
+        Ok(owed)
+    }
+
+    /// Append a swap observation, dropping samples that fell out of the analytics window
+    fn record_volume_sample(&mut self, env: &Env, pool_id: u64, sample: VolumeSample) {
+        let cutoff = sample.timestamp.saturating_sub(POOL_ANALYTICS_WINDOW_SECS);
+        let mut kept = Vec::new(env);
+        for s in self.volume_samples.get(pool_id).unwrap_or(Vec::new(env)).iter() {
+            if s.timestamp > cutoff { kept.push_back(s); }
+        }
+        kept.push_back(sample);
+        self.volume_samples.set(pool_id, kept);
+    }
+
+    /// Volume, fee income and an annualized fee-APY estimate for a pool.
+    /// The 24h figures only include swaps inside the trailing window ending at the current ledger time.
+    pub fn get_pool_analytics(&self, env: &Env, pool_id: u64) -> Result<PoolAnalytics, ContractError> {
+        let pool = self.pools.get(pool_id).ok_or(ContractError::LPPositionNotFound)?;
+        let cutoff = env.ledger().timestamp().saturating_sub(POOL_ANALYTICS_WINDOW_SECS);
+
+        let mut volume_24h = 0i128;
+        let mut fees_24h = 0i128;
+        for s in self.volume_samples.get(pool_id).unwrap_or(Vec::new(env)).iter() {
+            if s.timestamp > cutoff {
+                volume_24h = volume_24h.saturating_add(s.volume);
+                fees_24h = fees_24h.saturating_add(s.fees);
+            }
+        }
+
+        // Both sides valued in token_a at the spot price, so TVL is twice reserve_a
+        let tvl = pool.reserve_a.saturating_mul(2);
+        let fee_apy_bps = if tvl > 0 {
+            ((fees_24h as u128).saturating_mul(365 * 10000) / (tvl as u128)).min(u32::MAX as u128) as u32
+        } else {
+            0
+        };
+
+        Ok(PoolAnalytics {
+            pool_id,
+            volume_24h,
+            fees_24h,
+            cumulative_volume: pool.cumulative_volume,
+            cumulative_fees: pool.cumulative_fees,
+            tvl,
+            fee_apy_bps,
+        })
     }
 
     pub fn find_best_route(&self, env: &Env, token_in: Symbol, token_out: Symbol, amount_in: i128) -> Option<Route> {
+        let mut best_route: Option<Route> = None;
+        let mut best_output = 0i128;
+
         let (norm_in, norm_out) = Self::normalize_pair(token_in.clone(), token_out.clone());
         if let Some(pool_id) = self.pair_to_pool.get((norm_in, norm_out)) {
             if let Some(pool) = self.pools.get(pool_id) {
                 let output = self.calculate_output(&pool, token_in.clone(), amount_in);
                 let impact = self.calculate_price_impact(&pool, token_in.clone(), amount_in);
                 let mut pools = Vec::new(env); pools.push_back(pool_id);
-                let mut tokens = Vec::new(env); tokens.push_back(token_in); tokens.push_back(token_out);
-                return Some(Route { pools, tokens, expected_output: output, total_price_impact_bps: impact });
+                let mut tokens = Vec::new(env); tokens.push_back(token_in.clone()); tokens.push_back(token_out.clone());
+                best_output = output;
+                best_route = Some(Route { pools, tokens, expected_output: output, total_price_impact_bps: impact });
             }
         }
 
-        let mut best_route: Option<Route> = None;
-        let mut best_output = 0i128;
-        for i in 0..self.next_pool_id {
+        // A shallow direct pool can lose to a deeper 2-hop path, so 2-hop routes
+        // are still compared. Only pools adjacent to token_in can start one, so
+        // walk the token index instead of scanning every registered pool id.
+        for i in self.candidate_pools(env, token_in.clone()).iter() {
             if let Some(pool1) = self.pools.get(i) {
-                if pool1.token_a == token_in || pool1.token_b == token_in {
-                    let intermediate = if pool1.token_a == token_in { pool1.token_b.clone() } else { pool1.token_a.clone() };
-                    if intermediate != token_out {
-                        let (norm_int, norm_out) = Self::normalize_pair(intermediate.clone(), token_out.clone());
-                        if let Some(pool2_id) = self.pair_to_pool.get((norm_int, norm_out)) {
-                            if let Some(pool2) = self.pools.get(pool2_id) {
-                                let out1 = self.calculate_output(&pool1, token_in.clone(), amount_in);
-                                let out2 = self.calculate_output(&pool2, intermediate.clone(), out1);
-                                let impact1 = self.calculate_price_impact(&pool1, token_in.clone(), amount_in);
-                                let impact2 = self.calculate_price_impact(&pool2, intermediate.clone(), out1);
-                                let total_impact = impact1.saturating_add(impact2);
-                                if out2 > best_output {
-                                    best_output = out2;
-                                    let mut pools = Vec::new(env); pools.push_back(i); pools.push_back(pool2_id);
-                                    let mut tokens = Vec::new(env); tokens.push_back(token_in.clone()); tokens.push_back(intermediate); tokens.push_back(token_out.clone());
-                                    best_route = Some(Route { pools, tokens, expected_output: out2, total_price_impact_bps: total_impact });
-                                }
+                let intermediate = if pool1.token_a == token_in { pool1.token_b.clone() } else { pool1.token_a.clone() };
+                if intermediate != token_out {
+                    let (norm_int, norm_out) = Self::normalize_pair(intermediate.clone(), token_out.clone());
+                    if let Some(pool2_id) = self.pair_to_pool.get((norm_int, norm_out)) {
+                        if let Some(pool2) = self.pools.get(pool2_id) {
+                            let out1 = self.calculate_output(&pool1, token_in.clone(), amount_in);
+                            let out2 = self.calculate_output(&pool2, intermediate.clone(), out1);
+                            let impact1 = self.calculate_price_impact(&pool1, token_in.clone(), amount_in);
+                            let impact2 = self.calculate_price_impact(&pool2, intermediate.clone(), out1);
+                            let total_impact = impact1.saturating_add(impact2);
+                            if out2 > best_output {
+                                best_output = out2;
+                                let mut pools = Vec::new(env); pools.push_back(i); pools.push_back(pool2_id);
+                                let mut tokens = Vec::new(env); tokens.push_back(token_in.clone()); tokens.push_back(intermediate); tokens.push_back(token_out.clone());
+                                best_route = Some(Route { pools, tokens, expected_output: out2, total_price_impact_bps: total_impact });
                             }
                         }
                     }
@@ -198,12 +638,100 @@ impl PoolRegistry {
         best_route
     }
 
+    /// Swap across every pool for the pair, allocating `amount_in` in
+    /// proportion to each pool's input-side reserve so every leg moves its
+    /// pool's price by roughly the same fraction. The whole order is quoted
+    /// before any leg executes, so a `min_out` miss leaves every pool untouched.
+    pub fn swap_split(
+        &mut self,
+        env: &Env,
+        token_in: Symbol,
+        token_out: Symbol,
+        amount_in: i128,
+        min_out: i128,
+    ) -> Result<i128, ContractError> {
+        if amount_in <= 0 { return Err(ContractError::InvalidAmount); }
+        let pool_ids = self.pools_for_pair(env, token_in.clone(), token_out);
+        if pool_ids.is_empty() { return Err(ContractError::InvalidSwapPair); }
+
+        let mut depths = Vec::new(env);
+        let mut total_depth: u128 = 0;
+        for id in pool_ids.iter() {
+            let pool = self.pools.get(id).ok_or(ContractError::LPPositionNotFound)?;
+            let reserve_in = if token_in == pool.token_a { pool.reserve_a } else { pool.reserve_b };
+            depths.push_back(reserve_in as u128);
+            total_depth = total_depth.checked_add(reserve_in as u128).ok_or(ContractError::AmountOverflow)?;
+        }
+        if total_depth == 0 { return Err(ContractError::InsufficientLiquidity); }
+
+        // Floor each share; the last pool absorbs the rounding remainder
+        let mut legs: Vec<(u64, i128)> = Vec::new(env);
+        let mut allocated = 0i128;
+        let last = pool_ids.len() - 1;
+        for (i, id) in pool_ids.iter().enumerate() {
+            let share = if i as u32 == last {
+                amount_in - allocated
+            } else {
+                ((amount_in as u128).checked_mul(depths.get(i as u32).unwrap_or(0)).ok_or(ContractError::AmountOverflow)? / total_depth) as i128
+            };
+            if share > 0 {
+                legs.push_back((id, share));
+                allocated += share;
+            }
+        }
+
+        let mut quoted = 0i128;
+        for (id, share) in legs.iter() {
+            let pool = self.pools.get(id).ok_or(ContractError::LPPositionNotFound)?;
+            quoted = quoted.checked_add(self.calculate_output(&pool, token_in.clone(), share)).ok_or(ContractError::AmountOverflow)?;
+        }
+        if quoted < min_out { return Err(ContractError::SlippageExceeded); }
+
+        let mut total_out = 0i128;
+        for (id, share) in legs.iter() {
+            let out = self.swap(env, id, token_in.clone(), share, 0)?;
+            total_out = total_out.checked_add(out).ok_or(ContractError::AmountOverflow)?;
+        }
+        Ok(total_out)
+    }
+
+    /// Ids of every pool trading `token_a` against `token_b`, in registration order
+    pub fn pools_for_pair(&self, env: &Env, token_a: Symbol, token_b: Symbol) -> Vec<u64> {
+        let mut ids = Vec::new(env);
+        for id in self.candidate_pools(env, token_a).iter() {
+            if let Some(pool) = self.pools.get(id) {
+                if pool.token_a == token_b || pool.token_b == token_b {
+                    ids.push_back(id);
+                }
+            }
+        }
+        ids
+    }
+
+    /// Pool ids touching `token`, in registration order
+    pub fn candidate_pools(&self, env: &Env, token: Symbol) -> Vec<u64> {
+        self.token_pools.get(token).unwrap_or(Vec::new(env))
+    }
+
+    fn index_pool(&mut self, env: &Env, token: Symbol, pool_id: u64) {
+        let mut ids = self.token_pools.get(token.clone()).unwrap_or(Vec::new(env));
+        ids.push_back(pool_id);
+        self.token_pools.set(token, ids);
+    }
+
     fn calculate_output(&self, pool: &LiquidityPool, token_in: Symbol, amount_in: i128) -> i128 {
         let (reserve_in, reserve_out) = if token_in == pool.token_a { (pool.reserve_a, pool.reserve_b) } else { (pool.reserve_b, pool.reserve_a) };
-        let amount_in_with_fee = (amount_in as u128) * (10000 - pool.fee_tier as u128) / 10000;
+        let amount_in_with_fee = (amount_in as u128) - (amount_in as u128) * pool.fee_tier as u128 / 10000;
         ((reserve_out as u128) * amount_in_with_fee / ((reserve_in as u128) + amount_in_with_fee)) as i128
     }
 
+    /// Output at the pre-trade spot price, net of fee; the zero-slippage reference
+    fn calculate_spot_output(&self, pool: &LiquidityPool, token_in: Symbol, amount_in: i128) -> i128 {
+        let (reserve_in, reserve_out) = if token_in == pool.token_a { (pool.reserve_a, pool.reserve_b) } else { (pool.reserve_b, pool.reserve_a) };
+        let amount_in_with_fee = (amount_in as u128) - (amount_in as u128) * pool.fee_tier as u128 / 10000;
+        ((reserve_out as u128) * amount_in_with_fee / (reserve_in as u128)) as i128
+    }
+
     fn calculate_price_impact(&self, pool: &LiquidityPool, token_in: Symbol, amount_in: i128) -> u32 {
         let reserve_in = if token_in == pool.token_a { pool.reserve_a } else { pool.reserve_b };
         if reserve_in == 0 { return 10000; }
@@ -211,7 +739,31 @@ impl PoolRegistry {
     }
 
     pub fn get_pool(&self, pool_id: u64) -> Option<LiquidityPool> { self.pools.get(pool_id) }
+    pub fn pool_count(&self) -> u64 { self.pools.len() as u64 }
+
+    /// Page of registered pools in ascending id order; missing ids are skipped, not counted
+    pub fn get_all_pools(&self, env: &Env, offset: u64, limit: u64) -> Vec<LiquidityPool> {
+        let mut page = Vec::new(env);
+        // Map keys are kept sorted, so iteration is already in id order
+        for (_, pool) in self.pools.iter().skip(offset as usize).take(limit as usize) {
+            page.push_back(pool);
+        }
+        page
+    }
     pub fn get_lp_balance(&self, pool_id: u64, provider: Address) -> i128 { self.lp_balances.get((pool_id, provider)).unwrap_or(0) }
+    pub fn get_locked_lp(&self, pool_id: u64) -> i128 { self.locked_lp.get(pool_id).unwrap_or(0) }
+
+    /// Sum of every provider's LP balance in `pool_id`. Walks all balances
+    /// across the registry, so callers should sample rather than run it per call.
+    pub fn sum_lp_balances(&self, pool_id: u64) -> Result<i128, ContractError> {
+        let mut total = 0i128;
+        for ((id, _), balance) in self.lp_balances.iter() {
+            if id == pool_id {
+                total = total.checked_add(balance).ok_or(ContractError::AmountOverflow)?;
+            }
+        }
+        Ok(total)
+    }
 
     fn sqrt(y: u128) -> u128 {
         if y < 4 { return if y == 0 { 0 } else { 1 }; }
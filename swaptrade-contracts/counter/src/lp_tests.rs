@@ -1,6 +1,98 @@
 use super::*;
 use crate::portfolio::{Asset, LPPosition};
-use soroban_sdk::{symbol_short, testutils::Address as _, Address, Env, Symbol, Vec};
+use soroban_sdk::{symbol_short, testutils::Address as _, Address, Env, IntoVal, Map, Symbol, Val, Vec};
+
+/// Generate an address and store it as the contract admin
+fn register_admin(env: &Env, contract_id: &Address) -> Address {
+    let admin = Address::generate(env);
+    env.as_contract(contract_id, || {
+        env.storage().persistent().set(&crate::storage::ADMIN_KEY, &admin);
+    });
+    admin
+}
+
+/// `(token_a, token_b, initial_a, initial_b, fee_tier)` for `register_pools`
+pub(crate) type PoolSpec = (Symbol, Symbol, i128, i128, u32);
+
+/// Register each pool in its own contract frame: `register_pool` authorizes
+/// `admin`, and an address can only authorize once per frame
+pub(crate) fn register_pools(
+    env: &Env,
+    contract_id: &Address,
+    registry: &mut PoolRegistry,
+    admin: &Address,
+    specs: &[PoolSpec],
+) -> std::vec::Vec<u64> {
+    specs
+        .iter()
+        .map(|(token_a, token_b, initial_a, initial_b, fee_tier)| {
+            env.as_contract(contract_id, || {
+                registry
+                    .register_pool(env, admin.clone(), token_a.clone(), token_b.clone(), *initial_a, *initial_b, *fee_tier)
+                    .unwrap()
+            })
+        })
+        .collect()
+}
+
+/// Replace one pool's state, for driving the registry into states the public
+/// API can't reach. Rewrites the registry through its contracttype encoding.
+pub(crate) fn overwrite_pool(env: &Env, registry: &mut PoolRegistry, pool: LiquidityPool) {
+    let encoded: Val = registry.clone().into_val(env);
+    let mut fields: Map<Symbol, Val> = encoded.into_val(env);
+    let key = Symbol::new(env, "pools");
+    let mut pools: Map<u64, LiquidityPool> = fields.get(key.clone()).unwrap().into_val(env);
+    pools.set(pool.pool_id, pool);
+    fields.set(key, pools.into_val(env));
+    *registry = fields.to_val().into_val(env);
+}
+
+/// Constant-product output of one hop, net of the pool's fee
+fn quote_hop(pool: &LiquidityPool, token_in: &Symbol, amount_in: i128) -> i128 {
+    let (reserve_in, reserve_out) = if *token_in == pool.token_a { (pool.reserve_a, pool.reserve_b) } else { (pool.reserve_b, pool.reserve_a) };
+    let net_in = amount_in - amount_in * pool.fee_tier as i128 / 10000;
+    reserve_out * net_in / (reserve_in + net_in)
+}
+
+fn hop_price_impact(pool: &LiquidityPool, token_in: &Symbol, amount_in: i128) -> u32 {
+    let reserve_in = if *token_in == pool.token_a { pool.reserve_a } else { pool.reserve_b };
+    if reserve_in == 0 { return 10000; }
+    (amount_in * 10000 / reserve_in).min(10000) as u32
+}
+
+/// Reference 2-hop router that scans every registered pool instead of the
+/// token index, for checking `find_best_route` against
+fn find_best_route_scan(env: &Env, registry: &PoolRegistry, token_in: Symbol, token_out: Symbol, amount_in: i128) -> Option<Route> {
+    let mut best_route: Option<Route> = None;
+    let mut best_output = 0i128;
+    for pool1 in registry.get_all_pools(env, 0, registry.pool_count()).iter() {
+        if pool1.token_a != token_in && pool1.token_b != token_in {
+            continue;
+        }
+        let intermediate = if pool1.token_a == token_in { pool1.token_b.clone() } else { pool1.token_a.clone() };
+        if intermediate == token_out {
+            continue;
+        }
+        // The pair's first registered pool is the one single-pool routing uses
+        let Some(pool2_id) = registry.pools_for_pair(env, intermediate.clone(), token_out.clone()).first() else {
+            continue;
+        };
+        let pool2 = registry.get_pool(pool2_id).unwrap();
+        let out1 = quote_hop(&pool1, &token_in, amount_in);
+        let out2 = quote_hop(&pool2, &intermediate, out1);
+        if out2 > best_output {
+            best_output = out2;
+            let impact = hop_price_impact(&pool1, &token_in, amount_in).saturating_add(hop_price_impact(&pool2, &intermediate, out1));
+            best_route = Some(Route {
+                pools: Vec::from_array(env, [pool1.pool_id, pool2_id]),
+                tokens: Vec::from_array(env, [token_in.clone(), intermediate, token_out.clone()]),
+                expected_output: out2,
+                total_price_impact_bps: impact,
+            });
+        }
+    }
+    best_route
+}
 
 // ===== LEGACY LP TESTS (XLM/USDC) =====
 
@@ -38,20 +130,80 @@ fn test_add_liquidity_first_provider() {
 
 // ===== MULTI-TOKEN POOL TESTS =====
 
+/// Drives the contract's `PoolRegistry` directly, with the same argument
+/// shapes as the client, and stores it back so entrypoints see the changes.
+struct Pools<'a> {
+    env: &'a Env,
+    contract_id: &'a Address,
+}
+
+impl<'a> Pools<'a> {
+    fn new(env: &'a Env, contract_id: &'a Address) -> Self {
+        Self { env, contract_id }
+    }
+
+    fn with<R>(&self, f: impl FnOnce(&mut PoolRegistry) -> R) -> R {
+        let env = self.env;
+        env.as_contract(self.contract_id, || {
+            let mut registry: PoolRegistry = env
+                .storage()
+                .instance()
+                .get(&crate::storage::POOL_REGISTRY_KEY)
+                .unwrap_or_else(|| PoolRegistry::new(env));
+            let out = f(&mut registry);
+            env.storage().instance().set(&crate::storage::POOL_REGISTRY_KEY, &registry);
+            out
+        })
+    }
+
+    fn register(&self, admin: &Address, token_a: &Symbol, token_b: &Symbol, initial_a: &i128, initial_b: &i128, fee_tier: &u32) -> u64 {
+        self.with(|r| r.register_pool(self.env, admin.clone(), token_a.clone(), token_b.clone(), *initial_a, *initial_b, *fee_tier))
+            .unwrap()
+    }
+
+    fn add_liquidity(&self, pool_id: &u64, amount_a: &i128, amount_b: &i128, provider: &Address) -> i128 {
+        self.with(|r| r.add_liquidity(self.env, *pool_id, *amount_a, *amount_b, provider.clone()))
+            .unwrap()
+    }
+
+    fn remove_liquidity(&self, pool_id: &u64, lp_tokens: &i128, provider: &Address) -> (i128, i128) {
+        self.with(|r| r.remove_liquidity(self.env, *pool_id, *lp_tokens, provider.clone()))
+            .unwrap()
+    }
+
+    fn swap(&self, pool_id: &u64, token_in: &Symbol, amount_in: &i128, min_amount_out: &i128) -> i128 {
+        self.with(|r| r.swap(self.env, *pool_id, token_in.clone(), *amount_in, *min_amount_out))
+            .unwrap()
+    }
+
+    fn get(&self, pool_id: &u64) -> Option<LiquidityPool> {
+        self.with(|r| r.get_pool(*pool_id))
+    }
+
+    fn best_route(&self, token_in: &Symbol, token_out: &Symbol, amount_in: &i128) -> Option<Route> {
+        self.with(|r| r.find_best_route(self.env, token_in.clone(), token_out.clone(), *amount_in))
+    }
+
+    fn lp_balance(&self, pool_id: &u64, provider: &Address) -> i128 {
+        self.with(|r| r.get_lp_balance(*pool_id, provider.clone()))
+    }
+}
+
 #[test]
 fn test_register_pool() {
     let env = Env::default();
+    env.mock_all_auths();
     let contract_id = env.register(CounterContract, ());
-    let client = CounterContractClient::new(&env, &contract_id);
+    let pools = Pools::new(&env, &contract_id);
     let admin = Address::generate(&env);
 
     let btc = symbol_short!("BTC");
     let eth = symbol_short!("ETH");
     
-    let pool_id = client.register_pool(&admin, &btc, &eth, &1000, &2000, &30);
+    let pool_id = pools.register(&admin, &btc, &eth, &1000, &2000, &30);
     assert_eq!(pool_id, 1);
 
-    let pool = client.get_pool(&pool_id).unwrap();
+    let pool = pools.get(&pool_id).unwrap();
     assert_eq!(pool.reserve_a, 1000);
     assert_eq!(pool.reserve_b, 2000);
     assert_eq!(pool.fee_tier, 30);
@@ -60,20 +212,21 @@ fn test_register_pool() {
 #[test]
 fn test_pool_add_liquidity() {
     let env = Env::default();
+    env.mock_all_auths();
     let contract_id = env.register(CounterContract, ());
-    let client = CounterContractClient::new(&env, &contract_id);
+    let pools = Pools::new(&env, &contract_id);
     let admin = Address::generate(&env);
     let provider = Address::generate(&env);
 
     let usdt = symbol_short!("USDT");
     let dai = symbol_short!("DAI");
     
-    let pool_id = client.register_pool(&admin, &usdt, &dai, &1000, &1000, &5);
-    let lp_tokens = client.pool_add_liquidity(&pool_id, &500, &500, &provider);
+    let pool_id = pools.register(&admin, &usdt, &dai, &1000, &1000, &5);
+    let lp_tokens = pools.add_liquidity(&pool_id, &500, &500, &provider);
     
     assert!(lp_tokens > 0);
     
-    let pool = client.get_pool(&pool_id).unwrap();
+    let pool = pools.get(&pool_id).unwrap();
     assert_eq!(pool.reserve_a, 1500);
     assert_eq!(pool.reserve_b, 1500);
 }
@@ -81,16 +234,17 @@ fn test_pool_add_liquidity() {
 #[test]
 fn test_pool_swap() {
     let env = Env::default();
+    env.mock_all_auths();
     let contract_id = env.register(CounterContract, ());
-    let client = CounterContractClient::new(&env, &contract_id);
+    let pools = Pools::new(&env, &contract_id);
     let admin = Address::generate(&env);
 
     let token_a = symbol_short!("TOKA");
     let token_b = symbol_short!("TOKB");
     
-    let pool_id = client.register_pool(&admin, &token_a, &token_b, &10000, &10000, &30);
+    let pool_id = pools.register(&admin, &token_a, &token_b, &10000, &10000, &30);
     
-    let amount_out = client.pool_swap(&pool_id, &token_a, &100, &90);
+    let amount_out = pools.swap(&pool_id, &token_a, &100, &90);
     
     assert!(amount_out >= 90);
     assert!(amount_out < 100);
@@ -99,18 +253,19 @@ fn test_pool_swap() {
 #[test]
 fn test_pool_remove_liquidity() {
     let env = Env::default();
+    env.mock_all_auths();
     let contract_id = env.register(CounterContract, ());
-    let client = CounterContractClient::new(&env, &contract_id);
+    let pools = Pools::new(&env, &contract_id);
     let admin = Address::generate(&env);
     let provider = Address::generate(&env);
 
     let token_a = symbol_short!("TOKA");
     let token_b = symbol_short!("TOKB");
     
-    let pool_id = client.register_pool(&admin, &token_a, &token_b, &1000, &2000, &30);
-    let lp_tokens = client.pool_add_liquidity(&pool_id, &1000, &2000, &provider);
+    let pool_id = pools.register(&admin, &token_a, &token_b, &1000, &2000, &30);
+    let lp_tokens = pools.add_liquidity(&pool_id, &1000, &2000, &provider);
     
-    let (amount_a, amount_b) = client.pool_remove_liquidity(&pool_id, &lp_tokens / 2, &provider);
+    let (amount_a, amount_b) = pools.remove_liquidity(&pool_id, &(lp_tokens / 2), &provider);
     
     assert!(amount_a > 0);
     assert!(amount_b > 0);
@@ -119,16 +274,17 @@ fn test_pool_remove_liquidity() {
 #[test]
 fn test_find_best_route_direct() {
     let env = Env::default();
+    env.mock_all_auths();
     let contract_id = env.register(CounterContract, ());
-    let client = CounterContractClient::new(&env, &contract_id);
+    let pools = Pools::new(&env, &contract_id);
     let admin = Address::generate(&env);
 
     let xlm = symbol_short!("XLM");
     let usdc = symbol_short!("USDC");
     
-    client.register_pool(&admin, &xlm, &usdc, &10000, &10000, &30);
+    pools.register(&admin, &xlm, &usdc, &10000, &10000, &30);
     
-    let route = client.find_best_route(&xlm, &usdc, &100);
+    let route = pools.best_route(&xlm, &usdc, &100);
     assert!(route.is_some());
     
     let r = route.unwrap();
@@ -140,18 +296,19 @@ fn test_find_best_route_direct() {
 #[test]
 fn test_find_best_route_multihop() {
     let env = Env::default();
+    env.mock_all_auths();
     let contract_id = env.register(CounterContract, ());
-    let client = CounterContractClient::new(&env, &contract_id);
+    let pools = Pools::new(&env, &contract_id);
     let admin = Address::generate(&env);
 
     let xlm = symbol_short!("XLM");
     let usdc = symbol_short!("USDC");
     let btc = symbol_short!("BTC");
     
-    client.register_pool(&admin, &xlm, &usdc, &10000, &10000, &30);
-    client.register_pool(&admin, &usdc, &btc, &10000, &5000, &30);
+    pools.register(&admin, &xlm, &usdc, &10000, &10000, &30);
+    pools.register(&admin, &usdc, &btc, &10000, &5000, &30);
     
-    let route = client.find_best_route(&xlm, &btc, &100);
+    let route = pools.best_route(&xlm, &btc, &100);
     assert!(route.is_some());
     
     let r = route.unwrap();
@@ -163,20 +320,21 @@ fn test_find_best_route_multihop() {
 #[test]
 fn test_multiple_fee_tiers() {
     let env = Env::default();
+    env.mock_all_auths();
     let contract_id = env.register(CounterContract, ());
-    let client = CounterContractClient::new(&env, &contract_id);
+    let pools = Pools::new(&env, &contract_id);
     let admin = Address::generate(&env);
 
     let token_a = symbol_short!("TOKA");
     let token_b = symbol_short!("TOKB");
     
-    let pool1 = client.register_pool(&admin, &token_a, &token_b, &10000, &10000, &1);
-    let pool2 = client.register_pool(&admin, &symbol_short!("TOKC"), &symbol_short!("TOKD"), &10000, &10000, &5);
-    let pool3 = client.register_pool(&admin, &symbol_short!("TOKE"), &symbol_short!("TOKF"), &10000, &10000, &30);
+    let pool1 = pools.register(&admin, &token_a, &token_b, &10000, &10000, &1);
+    let pool2 = pools.register(&admin, &symbol_short!("TOKC"), &symbol_short!("TOKD"), &10000, &10000, &5);
+    let pool3 = pools.register(&admin, &symbol_short!("TOKE"), &symbol_short!("TOKF"), &10000, &10000, &30);
     
-    let p1 = client.get_pool(&pool1).unwrap();
-    let p2 = client.get_pool(&pool2).unwrap();
-    let p3 = client.get_pool(&pool3).unwrap();
+    let p1 = pools.get(&pool1).unwrap();
+    let p2 = pools.get(&pool2).unwrap();
+    let p3 = pools.get(&pool3).unwrap();
     
     assert_eq!(p1.fee_tier, 1);
     assert_eq!(p2.fee_tier, 5);
@@ -186,18 +344,19 @@ fn test_multiple_fee_tiers() {
 #[test]
 fn test_pool_lp_balance() {
     let env = Env::default();
+    env.mock_all_auths();
     let contract_id = env.register(CounterContract, ());
-    let client = CounterContractClient::new(&env, &contract_id);
+    let pools = Pools::new(&env, &contract_id);
     let admin = Address::generate(&env);
     let provider = Address::generate(&env);
 
     let token_a = symbol_short!("TOKA");
     let token_b = symbol_short!("TOKB");
     
-    let pool_id = client.register_pool(&admin, &token_a, &token_b, &1000, &1000, &30);
-    let lp_tokens = client.pool_add_liquidity(&pool_id, &500, &500, &provider);
+    let pool_id = pools.register(&admin, &token_a, &token_b, &1000, &1000, &30);
+    let lp_tokens = pools.add_liquidity(&pool_id, &500, &500, &provider);
     
-    let balance = client.get_pool_lp_balance(&pool_id, &provider);
+    let balance = pools.lp_balance(&pool_id, &provider);
     assert_eq!(balance, lp_tokens);
 }
 
@@ -205,12 +364,668 @@ fn test_pool_lp_balance() {
 #[should_panic(expected = "InvalidAmount")]
 fn test_invalid_fee_tier() {
     let env = Env::default();
+    env.mock_all_auths();
     let contract_id = env.register(CounterContract, ());
-    let client = CounterContractClient::new(&env, &contract_id);
+    let pools = Pools::new(&env, &contract_id);
     let admin = Address::generate(&env);
 
     let token_a = symbol_short!("TOKA");
     let token_b = symbol_short!("TOKB");
     
-    client.register_pool(&admin, &token_a, &token_b, &1000, &1000, &100);
+    pools.register(&admin, &token_a, &token_b, &1000, &1000, &100);
+}
+
+#[test]
+fn test_pool_analytics_24h_window() {
+    use soroban_sdk::testutils::Ledger;
+
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(CounterContract, ());
+    let client = CounterContractClient::new(&env, &contract_id);
+    let pools = Pools::new(&env, &contract_id);
+    let admin = Address::generate(&env);
+
+    let token_a = symbol_short!("TOKA");
+    let token_b = symbol_short!("TOKB");
+
+    env.ledger().set_timestamp(1_000);
+    let pool_id = pools.register(&admin, &token_a, &token_b, &1_000_000, &1_000_000, &30);
+
+    // Old swap, will fall outside the 24h window
+    pools.swap(&pool_id, &token_a, &10_000, &0);
+
+    env.ledger().set_timestamp(1_000 + 86_400 + 100);
+    pools.swap(&pool_id, &token_a, &2_000, &0);
+    pools.swap(&pool_id, &token_a, &3_000, &0);
+
+    let analytics = client.get_pool_analytics(&pool_id);
+    assert_eq!(analytics.volume_24h, 5_000);
+    assert_eq!(analytics.cumulative_volume, 15_000);
+    assert_eq!(analytics.fees_24h, 6 + 9);
+    assert_eq!(analytics.cumulative_fees, 30 + 6 + 9);
+    assert!(analytics.tvl > 0);
+    assert!(analytics.fee_apy_bps > 0);
+}
+
+#[test]
+fn test_find_best_route_indexed_matches_scan() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(CounterContract, ());
+    let admin = Address::generate(&env);
+
+    let xlm = symbol_short!("XLM");
+    let usdc = symbol_short!("USDC");
+    let mut specs: std::vec::Vec<PoolSpec> = std::vec::Vec::new();
+    // 5 intermediate hops between XLM and USDC with varying depth
+    for i in 0..5u32 {
+        let mid = Symbol::new(&env, &std::format!("MID{}", i));
+        let depth = 10_000 + (i as i128) * 2_000;
+        specs.push((xlm.clone(), mid.clone(), depth, depth, 30));
+        specs.push((mid, usdc.clone(), depth, depth / 2, 30));
+    }
+    // 40 unrelated pools that the router should never look at
+    for i in 0..40u32 {
+        let a = Symbol::new(&env, &std::format!("UA{}", i));
+        let b = Symbol::new(&env, &std::format!("UB{}", i));
+        specs.push((a, b, 1_000, 1_000, 30));
+    }
+    let mut registry = env.as_contract(&contract_id, || PoolRegistry::new(&env));
+    register_pools(&env, &contract_id, &mut registry, &admin, &specs);
+
+    env.as_contract(&contract_id, || {
+        let indexed = registry.find_best_route(&env, xlm.clone(), usdc.clone(), 100);
+        let scanned = find_best_route_scan(&env, &registry, xlm.clone(), usdc.clone(), 100);
+        assert!(indexed.is_some());
+        assert_eq!(indexed, scanned);
+
+        let candidates = registry.candidate_pools(&env, xlm);
+        assert_eq!(candidates.len(), 5);
+        assert!(candidates.len() < 50 / 5);
+    });
+}
+
+#[test]
+fn test_default_fee_tiers_preserved() {
+    let env = Env::default();
+    let contract_id = env.register(CounterContract, ());
+    let client = CounterContractClient::new(&env, &contract_id);
+
+    let tiers = client.get_allowed_fee_tiers();
+    assert_eq!(tiers, Vec::from_array(&env, [1u32, 5, 30]));
+}
+
+#[test]
+fn test_custom_fee_tier_allowed() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(CounterContract, ());
+    let client = CounterContractClient::new(&env, &contract_id);
+    let pools = Pools::new(&env, &contract_id);
+    let admin = register_admin(&env, &contract_id);
+
+    client.set_allowed_fee_tiers(&admin, &Vec::from_array(&env, [1u32, 5, 30, 100]));
+
+    let pool_id = pools.register(&admin, &symbol_short!("EXO"), &symbol_short!("XLM"), &1000, &1000, &100);
+    let pool = pools.get(&pool_id).unwrap();
+    assert_eq!(pool.fee_tier, 100);
+}
+
+#[test]
+#[should_panic(expected = "InvalidAmount")]
+fn test_removed_fee_tier_rejected() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(CounterContract, ());
+    let client = CounterContractClient::new(&env, &contract_id);
+    let pools = Pools::new(&env, &contract_id);
+    let admin = register_admin(&env, &contract_id);
+
+    client.set_allowed_fee_tiers(&admin, &Vec::from_array(&env, [5u32, 30]));
+    pools.register(&admin, &symbol_short!("TOKA"), &symbol_short!("TOKB"), &1000, &1000, &1);
+}
+
+#[test]
+fn test_set_allowed_fee_tiers_requires_admin() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(CounterContract, ());
+    let client = CounterContractClient::new(&env, &contract_id);
+    register_admin(&env, &contract_id);
+    let outsider = Address::generate(&env);
+
+    assert_eq!(
+        client.try_set_allowed_fee_tiers(&outsider, &Vec::from_array(&env, [5u32])),
+        Err(Ok(ContractError::NotAdmin))
+    );
+    assert_eq!(client.get_allowed_fee_tiers(), Vec::from_array(&env, [1u32, 5, 30]));
+}
+
+#[test]
+fn test_get_all_pools_pagination() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(CounterContract, ());
+    let client = CounterContractClient::new(&env, &contract_id);
+    let pools = Pools::new(&env, &contract_id);
+    let admin = Address::generate(&env);
+
+    let p1 = pools.register(&admin, &symbol_short!("TOKA"), &symbol_short!("TOKB"), &1000, &1000, &30);
+    let p2 = pools.register(&admin, &symbol_short!("TOKC"), &symbol_short!("TOKD"), &1000, &1000, &5);
+    let p3 = pools.register(&admin, &symbol_short!("TOKE"), &symbol_short!("TOKF"), &1000, &1000, &1);
+
+    assert_eq!(client.pool_count(), 3);
+
+    let first = client.get_all_pools(&0, &2);
+    assert_eq!(first.len(), 2);
+    assert_eq!(first.get(0).unwrap().pool_id, p1);
+    assert_eq!(first.get(1).unwrap().pool_id, p2);
+
+    let second = client.get_all_pools(&2, &2);
+    assert_eq!(second.len(), 1);
+    assert_eq!(second.get(0).unwrap().pool_id, p3);
+
+    assert_eq!(client.get_all_pools(&3, &10).len(), 0);
+}
+
+#[test]
+fn test_swap_split_beats_single_pool() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(CounterContract, ());
+    let admin = Address::generate(&env);
+
+    let toka = symbol_short!("TOKA");
+    let tokb = symbol_short!("TOKB");
+    let mut registry = env.as_contract(&contract_id, || PoolRegistry::new(&env));
+    let ids = register_pools(&env, &contract_id, &mut registry, &admin, &[
+        (toka.clone(), tokb.clone(), 10_000, 10_000, 30),
+        (toka.clone(), tokb.clone(), 40_000, 40_000, 5),
+    ]);
+    let (shallow, deep) = (ids[0], ids[1]);
+
+    env.as_contract(&contract_id, || {
+        assert_eq!(registry.pools_for_pair(&env, toka.clone(), tokb.clone()).len(), 2);
+
+        let amount = 5_000;
+        let only_shallow = registry.clone().swap(&env, shallow, toka.clone(), amount, 0).unwrap();
+        let only_deep = registry.clone().swap(&env, deep, toka.clone(), amount, 0).unwrap();
+
+        let split = registry.swap_split(&env, toka.clone(), tokb.clone(), amount, 0).unwrap();
+        assert!(split > only_shallow);
+        assert!(split > only_deep);
+
+        // Both legs executed: 1/5 of the input hit the shallow pool, the rest
+        // the deep one (reserves grow by the input net of each pool's fee)
+        assert_eq!(registry.get_pool(shallow).unwrap().reserve_a, 10_997);
+        assert_eq!(registry.get_pool(deep).unwrap().reserve_a, 43_998);
+    });
+}
+
+#[test]
+fn test_swap_split_min_out_leaves_pools_untouched() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(CounterContract, ());
+    let admin = Address::generate(&env);
+
+    let toka = symbol_short!("TOKA");
+    let tokb = symbol_short!("TOKB");
+    let mut registry = env.as_contract(&contract_id, || PoolRegistry::new(&env));
+    let p1 = register_pools(&env, &contract_id, &mut registry, &admin, &[
+        (toka.clone(), tokb.clone(), 10_000, 10_000, 30),
+        (toka.clone(), tokb.clone(), 40_000, 40_000, 5),
+    ])[0];
+
+    // Same pair and fee tier twice is still rejected
+    env.as_contract(&contract_id, || {
+        assert!(registry.register_pool(&env, admin.clone(), tokb.clone(), toka.clone(), 1_000, 1_000, 30).is_err());
+    });
+
+    env.as_contract(&contract_id, || {
+        let result = registry.swap_split(&env, toka, tokb, 5_000, 5_000);
+        assert_eq!(result, Err(ContractError::SlippageExceeded));
+        assert_eq!(registry.get_pool(p1).unwrap().reserve_a, 10_000);
+    });
+}
+
+#[test]
+fn test_swap_split_without_input_reserves_is_insufficient_liquidity() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(CounterContract, ());
+    let admin = Address::generate(&env);
+    let toka = symbol_short!("TOKA");
+    let tokb = symbol_short!("TOKB");
+
+    let mut registry = env.as_contract(&contract_id, || PoolRegistry::new(&env));
+    let pool_ids = register_pools(&env, &contract_id, &mut registry, &admin, &[
+        (toka.clone(), tokb.clone(), 10_000, 10_000, 30),
+        (toka.clone(), tokb.clone(), 10_000, 10_000, 5),
+    ]);
+
+    env.as_contract(&contract_id, || {
+        // Empty the TOKA side of every pool for the pair
+        for id in pool_ids {
+            let mut pool = registry.get_pool(id).unwrap();
+            pool.reserve_a = 0;
+            overwrite_pool(&env, &mut registry, pool);
+        }
+        assert_eq!(
+            registry.swap_split(&env, toka, tokb, 1_000, 0),
+            Err(ContractError::InsufficientLiquidity)
+        );
+    });
+}
+
+#[test]
+fn test_collect_fees_split_proportionally() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(CounterContract, ());
+    let admin = Address::generate(&env);
+    let small = Address::generate(&env);
+    let large = Address::generate(&env);
+
+    env.as_contract(&contract_id, || {
+        let mut registry = PoolRegistry::new(&env);
+        let toka = symbol_short!("TOKA");
+        let tokb = symbol_short!("TOKB");
+        let pool_id = registry.register_pool(&env, admin.clone(), toka.clone(), tokb.clone(), 10_000, 10_000, 30).unwrap();
+        assert_eq!(registry.add_liquidity(&env, pool_id, 10_000, 10_000, small.clone()).unwrap(), 10_000);
+        assert_eq!(registry.add_liquidity(&env, pool_id, 30_000, 30_000, large.clone()).unwrap(), 30_000);
+
+        // 0.3% of 100_000 + 50_000 = 450 in fees over 50_000 LP tokens
+        registry.swap(&env, pool_id, toka.clone(), 100_000, 0).unwrap();
+        registry.swap(&env, pool_id, toka.clone(), 50_000, 0).unwrap();
+
+        let (small_fees, _) = registry.collect_fees(pool_id, small.clone()).unwrap();
+        let (large_fees, _) = registry.collect_fees(pool_id, large.clone()).unwrap();
+        assert_eq!(small_fees, 90);
+        assert_eq!(large_fees, 270);
+        assert_eq!(large_fees, 3 * small_fees);
+
+        // Nothing left until the next swap
+        assert_eq!(registry.collect_fees(pool_id, small).unwrap(), (0, 0));
+    });
+}
+
+#[test]
+fn test_collect_pool_fees_credits_both_tokens_to_provider() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(CounterContract, ());
+    let client = CounterContractClient::new(&env, &contract_id);
+    let pools = Pools::new(&env, &contract_id);
+    let admin = Address::generate(&env);
+    let provider = Address::generate(&env);
+    let toka = symbol_short!("TOKA");
+    let tokb = symbol_short!("TOKB");
+
+    let pool_id = pools.register(&admin, &toka, &tokb, &10_000, &10_000, &30);
+    pools.add_liquidity(&pool_id, &10_000, &10_000, &provider);
+
+    // 30 in fees each way over 20_000 LP tokens, half of them the provider's
+    pools.swap(&pool_id, &toka, &10_000, &0);
+    pools.swap(&pool_id, &tokb, &10_000, &0);
+    let held_before = pools.get(&pool_id).unwrap();
+    assert_eq!((held_before.unclaimed_fees_a, held_before.unclaimed_fees_b), (30, 30));
+    assert_eq!(client.get_balance(&toka, &provider), 0);
+    assert_eq!(client.get_balance(&tokb, &provider), 0);
+
+    assert_eq!(client.collect_pool_fees(&pool_id, &provider), (15, 15));
+
+    assert_eq!(client.get_balance(&toka, &provider), 15);
+    assert_eq!(client.get_balance(&tokb, &provider), 15);
+    let held_after = pools.get(&pool_id).unwrap();
+    assert_eq!((held_after.unclaimed_fees_a, held_after.unclaimed_fees_b), (15, 15));
+    assert_eq!((held_after.reserve_a, held_after.reserve_b), (held_before.reserve_a, held_before.reserve_b));
+
+    // A second collection has nothing new to pay
+    assert_eq!(client.collect_pool_fees(&pool_id, &provider), (0, 0));
+    assert_eq!(client.get_balance(&toka, &provider), 15);
+}
+
+#[test]
+fn test_tier_fee_share_favors_higher_tiers_within_collected_fees() {
+    use crate::tiers::UserTier;
+
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(CounterContract, ());
+    let admin = register_admin(&env, &contract_id);
+    let outsider = Address::generate(&env);
+    let trader = Address::generate(&env);
+    let novice = Address::generate(&env);
+    let toka = symbol_short!("TOKA");
+    let tokb = symbol_short!("TOKB");
+
+    // The provider's tier comes from the portfolio, not the caller
+    let portfolio = env.as_contract(&contract_id, || {
+        let mut portfolio = Portfolio::new(&env);
+        portfolio.mint(&env, Asset::XLM, trader.clone(), 100);
+        assert_eq!(portfolio.get_user_tier(&env, trader.clone()), UserTier::Trader);
+        assert_eq!(portfolio.get_user_tier(&env, novice.clone()), UserTier::Novice);
+        portfolio
+    });
+
+    // Each admin call gets its own frame, as it would its own invocation
+    let mut registry = env.as_contract(&contract_id, || PoolRegistry::new(&env));
+    let pool_id = env.as_contract(&contract_id, || {
+        let pool_id = registry.register_pool(&env, admin.clone(), toka.clone(), tokb.clone(), 10_000, 10_000, 30).unwrap();
+        registry.add_liquidity(&env, pool_id, 20_000, 20_000, trader.clone()).unwrap();
+        registry.add_liquidity(&env, pool_id, 20_000, 20_000, novice.clone()).unwrap();
+        pool_id
+    });
+
+    // Equal share by default; only the admin may change it, and never above 100%
+    assert_eq!(registry.get_tier_fee_share(UserTier::Novice), 10000);
+    env.as_contract(&contract_id, || {
+        assert_eq!(registry.set_tier_fee_share(&env, outsider.clone(), UserTier::Novice, 8000), Err(ContractError::NotAdmin));
+    });
+    env.as_contract(&contract_id, || {
+        assert_eq!(registry.set_tier_fee_share(&env, admin.clone(), UserTier::Novice, 10001), Err(ContractError::InvalidAmount));
+    });
+    env.as_contract(&contract_id, || registry.set_tier_fee_share(&env, admin.clone(), UserTier::Novice, 8000).unwrap());
+
+    env.as_contract(&contract_id, || {
+        // Fees land on both sides: 300 TOKA and 150 TOKB over 50_000 LP tokens
+        let fees_a = registry.swap_detailed(&env, pool_id, toka.clone(), 100_000, 0).unwrap().fee_paid;
+        let fees_b = registry.swap_detailed(&env, pool_id, tokb.clone(), 50_000, 0).unwrap().fee_paid;
+        let before = registry.get_pool(pool_id).unwrap();
+
+        let trader_fees = registry.collect_fees_for_tier(&env, &portfolio, pool_id, trader.clone()).unwrap();
+        let novice_fees = registry.collect_fees_for_tier(&env, &portfolio, pool_id, novice.clone()).unwrap();
+
+        // Each position accrued (120, 60); the Novice keeps 80% of each side
+        assert_eq!(trader_fees, (120, 60));
+        assert_eq!(novice_fees, (96, 48));
+        assert!(trader_fees.0 + novice_fees.0 <= fees_a);
+        assert!(trader_fees.1 + novice_fees.1 <= fees_b);
+
+        // The Novice's withheld 20% returns to each reserve in proportion to its fees
+        let after = registry.get_pool(pool_id).unwrap();
+        assert_eq!(after.reserve_a, before.reserve_a + 24);
+        assert_eq!(after.reserve_b, before.reserve_b + 12);
+    });
+}
+
+#[test]
+fn test_swap_for_exact_output_round_trips() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(CounterContract, ());
+    let admin = Address::generate(&env);
+
+    let toka = symbol_short!("TOKA");
+    let tokb = symbol_short!("TOKB");
+
+    for (reserve_a, reserve_b, fee, want) in [(10_000i128, 10_000i128, 30u32, 90i128), (50_000, 12_345, 5, 1_000), (7_777, 99_999, 30, 3_333)] {
+        let mut registry = env.as_contract(&contract_id, || PoolRegistry::new(&env));
+        let pool_id = register_pools(&env, &contract_id, &mut registry, &admin, &[(toka.clone(), tokb.clone(), reserve_a, reserve_b, fee)])[0];
+
+        env.as_contract(&contract_id, || {
+            let mut probe = registry.clone();
+            let mut short_probe = registry.clone();
+
+            let charged = registry.swap_for_exact_output(&env, pool_id, toka.clone(), want, i128::MAX).unwrap();
+
+            // Feeding the computed input into an exact-input swap meets the target...
+            let delivered = probe.swap(&env, pool_id, toka.clone(), charged, 0).unwrap();
+            assert!(delivered >= want);
+            // ...and one unit less would not
+            assert!(short_probe.swap(&env, pool_id, toka.clone(), charged - 1, 0).unwrap() < want);
+
+            // Input is whole units, so the pool can pay out slightly more than asked
+            assert_eq!(registry.get_pool(pool_id).unwrap().reserve_b, reserve_b - delivered);
+        });
+    }
+}
+
+#[test]
+fn test_swap_for_exact_output_guards() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(CounterContract, ());
+    let admin = Address::generate(&env);
+
+    env.as_contract(&contract_id, || {
+        let mut registry = PoolRegistry::new(&env);
+        let toka = symbol_short!("TOKA");
+        let tokb = symbol_short!("TOKB");
+        let pool_id = registry.register_pool(&env, admin.clone(), toka.clone(), tokb.clone(), 10_000, 10_000, 30).unwrap();
+
+        assert_eq!(registry.swap_for_exact_output(&env, pool_id, toka.clone(), 10_000, i128::MAX), Err(ContractError::InsufficientBalance));
+        // 90 out costs exactly 91 in
+        assert_eq!(registry.swap_for_exact_output(&env, pool_id, toka.clone(), 90, 90), Err(ContractError::SlippageExceeded));
+        assert_eq!(registry.get_pool(pool_id).unwrap().reserve_a, 10_000);
+    });
+}
+
+#[test]
+fn test_swap_with_fill_full_fill_within_bounds() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(CounterContract, ());
+    let admin = Address::generate(&env);
+
+    env.as_contract(&contract_id, || {
+        let mut registry = PoolRegistry::new(&env);
+        let toka = symbol_short!("TOKA");
+        let tokb = symbol_short!("TOKB");
+        let pool_id = registry.register_pool(&env, admin.clone(), toka.clone(), tokb.clone(), 1_000_000, 1_000_000, 30).unwrap();
+        let mut probe = registry.clone();
+
+        let fill = registry.swap_with_fill(&env, pool_id, toka.clone(), 1_000, 990, true, 100).unwrap();
+
+        assert_eq!(fill.filled_in, 1_000);
+        assert_eq!(fill.output, probe.swap(&env, pool_id, toka.clone(), 1_000, 0).unwrap());
+        assert_eq!(registry.get_pool(pool_id), probe.get_pool(pool_id));
+    });
+}
+
+#[test]
+fn test_swap_with_fill_partial_fill_caps_slippage() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(CounterContract, ());
+    let admin = Address::generate(&env);
+
+    env.as_contract(&contract_id, || {
+        let mut registry = PoolRegistry::new(&env);
+        let toka = symbol_short!("TOKA");
+        let tokb = symbol_short!("TOKB");
+        let pool_id = registry.register_pool(&env, admin.clone(), toka.clone(), tokb.clone(), 1_000_000, 1_000_000, 30).unwrap();
+
+        // 100k into a 1M pool slips ~9%, so an all-or-nothing swap with a 1% floor reverts untouched
+        assert_eq!(
+            registry.swap_with_fill(&env, pool_id, toka.clone(), 100_000, 98_700, false, 100),
+            Err(ContractError::SlippageExceeded)
+        );
+        assert_eq!(registry.get_pool(pool_id).unwrap().reserve_a, 1_000_000);
+
+        let fill = registry.swap_with_fill(&env, pool_id, toka.clone(), 100_000, 98_700, true, 100).unwrap();
+
+        // Only about 1% of reserves can move within 100 bps of spot
+        assert!(fill.filled_in > 9_000 && fill.filled_in < 11_000);
+        let spot = fill.filled_in - fill.filled_in * 30 / 10_000;
+        assert!((spot - fill.output) * 10_000 / spot <= 100);
+
+        // One more unit of input would have breached the bound
+        let net_next = (fill.filled_in + 1) - (fill.filled_in + 1) * 30 / 10_000;
+        let output_next = 1_000_000 * net_next / (1_000_000 + net_next);
+        assert!((net_next - output_next) * 10_000 / net_next > 100);
+
+        let pool = registry.get_pool(pool_id).unwrap();
+        assert_eq!(pool.reserve_a, 1_000_000 + fill.filled_in - fill.filled_in * 30 / 10_000);
+        assert_eq!(pool.reserve_b, 1_000_000 - fill.output);
+    });
+}
+
+#[test]
+fn test_swap_detailed_matches_swap() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(CounterContract, ());
+    let admin = Address::generate(&env);
+
+    env.as_contract(&contract_id, || {
+        let mut registry = PoolRegistry::new(&env);
+        let toka = symbol_short!("TOKA");
+        let tokb = symbol_short!("TOKB");
+        let pool_id = registry.register_pool(&env, admin.clone(), toka.clone(), tokb.clone(), 100_000, 100_000, 30).unwrap();
+        let mut plain = registry.clone();
+
+        let detailed = registry.swap_detailed(&env, pool_id, toka.clone(), 10_000, 0).unwrap();
+        let output = plain.swap(&env, pool_id, toka, 10_000, 0).unwrap();
+
+        assert_eq!(detailed.output, output);
+        assert_eq!(detailed.fee_paid, 10_000 * 30 / 10000);
+        assert_eq!(detailed.price_impact_bps, 1000);
+        let pool = registry.get_pool(pool_id).unwrap();
+        assert_eq!(detailed.reserves_after, (pool.reserve_a, pool.reserve_b));
+        assert_eq!(detailed.reserves_after, (100_000 + 9_970, 100_000 - output));
+    });
+}
+
+#[test]
+fn test_swap_detailed_fee_rounds_down() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(CounterContract, ());
+    let admin = Address::generate(&env);
+
+    env.as_contract(&contract_id, || {
+        let mut registry = PoolRegistry::new(&env);
+        let toka = symbol_short!("TOKA");
+        let tokb = symbol_short!("TOKB");
+        let pool_id = registry.register_pool(&env, admin.clone(), toka.clone(), tokb, 100_000, 100_000, 30).unwrap();
+
+        // 10_001 * 30 / 10000 = 30.003, so the trader pays 30 and 9_971 reaches the reserve
+        let detailed = registry.swap_detailed(&env, pool_id, toka, 10_001, 0).unwrap();
+        assert_eq!(detailed.fee_paid, 30);
+        assert_eq!(detailed.reserves_after.0, 100_000 + 9_971);
+    });
+}
+
+#[test]
+fn test_swap_fee_without_lp_supply_stays_in_reserve() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(CounterContract, ());
+    let admin = Address::generate(&env);
+
+    env.as_contract(&contract_id, || {
+        let mut registry = PoolRegistry::new(&env);
+        let toka = symbol_short!("TOKA");
+        let tokb = symbol_short!("TOKB");
+        let pool_id = registry.register_pool(&env, admin.clone(), toka.clone(), tokb.clone(), 100_000, 100_000, 30).unwrap();
+        let mut pool = registry.get_pool(pool_id).unwrap();
+
+        // With LPs the fee is owed to them and the reserves are untouched
+        PoolRegistry::accrue_swap_fee(&mut pool, &toka, 30).unwrap();
+        assert_eq!(pool.unclaimed_fees_a, 30);
+        assert_eq!((pool.reserve_a, pool.reserve_b), (100_000, 100_000));
+
+        // Without any LP supply the fee has no owner, so it joins the input reserve
+        pool.total_lp_tokens = 0;
+        PoolRegistry::accrue_swap_fee(&mut pool, &tokb, 30).unwrap();
+        assert_eq!(pool.unclaimed_fees_b, 0);
+        assert_eq!(pool.fee_growth_b, 0);
+        assert_eq!((pool.reserve_a, pool.reserve_b), (100_000, 100_030));
+    });
+}
+
+#[test]
+fn test_price_impact_cap() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(CounterContract, ());
+    let admin = register_admin(&env, &contract_id);
+    let outsider = Address::generate(&env);
+    let toka = symbol_short!("TOKA");
+    let tokb = symbol_short!("TOKB");
+
+    // Each admin call gets its own frame, as it would its own invocation
+    let mut registry = env.as_contract(&contract_id, || PoolRegistry::new(&env));
+    let pool_id = env.as_contract(&contract_id, || {
+        registry.register_pool(&env, admin.clone(), toka.clone(), tokb.clone(), 100_000, 100_000, 30).unwrap()
+    });
+    env.as_contract(&contract_id, || {
+        assert_eq!(registry.set_max_price_impact(&env, outsider.clone(), pool_id, 500), Err(ContractError::NotAdmin));
+    });
+    env.as_contract(&contract_id, || registry.set_max_price_impact(&env, admin.clone(), pool_id, 500).unwrap());
+
+    env.as_contract(&contract_id, || {
+        // 1% of the input reserve stays within a 5% cap
+        let small = registry.swap_detailed(&env, pool_id, toka.clone(), 1_000, 0).unwrap();
+        assert!(small.price_impact_bps <= 500);
+
+        // Draining most of the pool is rejected and leaves reserves untouched
+        let before = registry.get_pool(pool_id).unwrap();
+        assert_eq!(registry.swap(&env, pool_id, toka.clone(), 90_000, 0), Err(ContractError::PriceImpactTooHigh));
+        assert_eq!(registry.get_pool(pool_id).unwrap(), before);
+
+        // A partial fill shrinks to the cap instead of failing
+        let reserve_in = before.reserve_a;
+        let fill = registry.swap_with_fill(&env, pool_id, toka.clone(), 90_000, 0, true, 10_000).unwrap();
+        assert!(fill.filled_in > 0);
+        assert!(fill.filled_in * 10_000 / reserve_in <= 500);
+    });
+
+    // Zero disables the cap
+    env.as_contract(&contract_id, || registry.set_max_price_impact(&env, admin.clone(), pool_id, 0).unwrap());
+    env.as_contract(&contract_id, || {
+        assert!(registry.swap(&env, pool_id, toka.clone(), 90_000, 0).is_ok());
+    });
+    env.as_contract(&contract_id, || {
+        assert_eq!(registry.set_max_price_impact(&env, admin.clone(), pool_id, 10_001), Err(ContractError::InvalidAmount));
+    });
+}
+
+#[test]
+fn test_remove_liquidity_single_sided() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(CounterContract, ());
+    let admin = Address::generate(&env);
+    let provider = Address::generate(&env);
+
+    env.as_contract(&contract_id, || {
+        let mut registry = PoolRegistry::new(&env);
+        let toka = symbol_short!("TOKA");
+        let tokb = symbol_short!("TOKB");
+        let pool_id = registry.register_pool(&env, admin, toka.clone(), tokb.clone(), 100_000, 100_000, 30).unwrap();
+        let lp_tokens = registry.add_liquidity(&env, pool_id, 10_000, 10_000, provider.clone()).unwrap();
+
+        // Proportional exit plus a fee-paying swap of the B side: less than 2x the A share
+        let (share_a, share_b) = registry.clone().remove_liquidity(&env, pool_id, lp_tokens, provider.clone()).unwrap();
+        let quoted = registry.clone().remove_liquidity_single(&env, pool_id, lp_tokens, toka.clone(), 0, provider.clone()).unwrap();
+        assert!(quoted > share_a && quoted < share_a + share_b);
+
+        // A floor above what the pool can deliver reverts without touching anything
+        let before = registry.get_pool(pool_id).unwrap();
+        assert_eq!(
+            registry.remove_liquidity_single(&env, pool_id, lp_tokens, toka.clone(), quoted + 1, provider.clone()),
+            Err(ContractError::SlippageExceeded)
+        );
+        assert_eq!(registry.get_pool(pool_id).unwrap(), before);
+        assert_eq!(registry.get_lp_balance(pool_id, provider.clone()), lp_tokens);
+
+        let out = registry.remove_liquidity_single(&env, pool_id, lp_tokens, toka.clone(), quoted, provider.clone()).unwrap();
+        assert_eq!(out, quoted);
+        assert_eq!(registry.get_lp_balance(pool_id, provider.clone()), 0);
+
+        // All of the provider's B went back in; only the swap fee, held outside reserves, left the B side
+        let after = registry.get_pool(pool_id).unwrap();
+        assert_eq!(after.reserve_a, before.reserve_a - out);
+        let fee_b = share_b - share_b * 9_970 / 10_000;
+        assert_eq!(after.reserve_b, before.reserve_b - fee_b);
+
+        assert_eq!(
+            registry.remove_liquidity_single(&env, pool_id, 1, symbol_short!("TOKC"), 0, provider),
+            Err(ContractError::InvalidTokenSymbol)
+        );
+    });
 }
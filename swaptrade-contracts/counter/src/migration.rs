@@ -1,17 +1,57 @@
 use crate::errors::SwapTradeError;
+use crate::events::Events;
+use crate::invariants::{invariant_version_monotonic, verify_contract_invariants};
 use crate::portfolio::Portfolio;
-use soroban_sdk::{Env, Symbol};
+use soroban_sdk::{Address, Env, Symbol};
 
-pub fn migrate_from_v1_to_v2(env: &Env) -> Result<(), SwapTradeError> {
-    // 1. Check current version
-    let current_version = get_stored_version(env);
+/// A single upgrade step that reshapes storage from version `n` to `n + 1`.
+pub type MigrationStep = fn(&Env) -> Result<(), SwapTradeError>;
 
-    // If already V2, return success (idempotency)
-    if current_version >= 2 {
+/// Registered upgrade steps, keyed by the version they migrate *from*.
+fn migration_step(from_version: u32) -> Option<MigrationStep> {
+    match from_version {
+        1 => Some(upgrade_v1_to_v2),
+        _ => None,
+    }
+}
+
+/// Run every registered step between the stored version and `target_version`,
+/// then re-verify contract invariants. Any error is returned to the host, which
+/// reverts all storage writes made during the call.
+pub fn migrate_to(env: &Env, admin: &Address, target_version: u32) -> Result<(), SwapTradeError> {
+    let from_version = get_stored_version(env);
+    if !invariant_version_monotonic(from_version, target_version) {
+        return Err(SwapTradeError::InvalidMigrationTarget);
+    }
+
+    // Idempotent: nothing to do when already at the target.
+    if from_version == target_version {
         return Ok(());
     }
 
-    // 2. Perform data migration
+    let mut version = from_version;
+    while version < target_version {
+        let step = migration_step(version).ok_or(SwapTradeError::InvalidMigrationTarget)?;
+        step(env)?;
+        version += 1;
+        set_stored_version(env, version);
+    }
+
+    let portfolio: Portfolio = env
+        .storage()
+        .instance()
+        .get(&())
+        .unwrap_or_else(|| Portfolio::new(env));
+    verify_contract_invariants(env, &portfolio)
+        .map_err(|_| SwapTradeError::MigrationInvariantViolation)?;
+
+    let timestamp = env.ledger().timestamp() as i64;
+    Events::contract_migrated(env, admin.clone(), from_version, target_version, timestamp);
+    Events::migration_audited(env, admin.clone(), from_version, target_version, timestamp);
+    Ok(())
+}
+
+fn upgrade_v1_to_v2(env: &Env) -> Result<(), SwapTradeError> {
     // We load the portfolio. In a real upgrade, if the struct layout changed incompatibly,
     // we would deserialize into a PortfolioV1 struct, map it to Portfolio (V2), and save.
     // Here we simulate the schema evolution by populating the new `migration_time` field.
@@ -29,9 +69,6 @@ pub fn migrate_from_v1_to_v2(env: &Env) -> Result<(), SwapTradeError> {
         env.storage().instance().set(&(), &portfolio);
     }
 
-    // 3. Update version to 2
-    set_stored_version(env, 2);
-
     Ok(())
 }
 
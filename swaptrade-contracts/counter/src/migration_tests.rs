@@ -1,7 +1,17 @@
 #![cfg(test)]
 
+use crate::errors::SwapTradeError;
+use crate::storage::ADMIN_KEY;
 use crate::{CounterContract, CounterContractClient};
-use soroban_sdk::{testutils::Address as _, Address, Env, Symbol};
+use soroban_sdk::{testutils::{Address as _, Events as _}, Address, Env, Symbol, TryIntoVal};
+
+fn setup_admin(env: &Env, contract_id: &Address) -> Address {
+    let admin = Address::generate(env);
+    env.as_contract(contract_id, || {
+        env.storage().persistent().set(&ADMIN_KEY, &admin);
+    });
+    admin
+}
 
 #[test]
 fn test_migration_v1_to_v2() {
@@ -11,6 +21,7 @@ fn test_migration_v1_to_v2() {
     // Register contract
     let contract_id = env.register_contract(None, CounterContract);
     let client = CounterContractClient::new(&env, &contract_id);
+    let admin = setup_admin(&env, &contract_id);
 
     // 1. Initialize (sets version to 1)
     client.initialize();
@@ -29,7 +40,7 @@ fn test_migration_v1_to_v2() {
 
     // 3. Perform Migration
     // This should detect version < 2, detect migration_time is None, set it, and bump version.
-    client.migrate();
+    client.migrate(&admin, &2);
 
     // 4. Verify version is 2
     assert_eq!(client.get_contract_version(), 2);
@@ -39,9 +50,103 @@ fn test_migration_v1_to_v2() {
 
     // 6. Idempotency check
     // Calling migrate again should do nothing and stay at version 2
-    client.migrate();
+    client.migrate(&admin, &2);
     assert_eq!(client.get_contract_version(), 2);
 
     // Optional: We could add a getter to verify migration_time is Some,
     // but the version bump implies the logic executed.
 }
+
+#[test]
+fn test_migration_reverifies_invariants() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let contract_id = env.register_contract(None, CounterContract);
+    let client = CounterContractClient::new(&env, &contract_id);
+    let admin = setup_admin(&env, &contract_id);
+    client.initialize();
+
+    let user = Address::generate(&env);
+    client.mint(&Symbol::short("XLM"), &user, &500);
+
+    client.migrate(&admin, &2);
+    assert_eq!(client.get_contract_version(), 2);
+
+    env.as_contract(&contract_id, || {
+        let portfolio: crate::portfolio::Portfolio =
+            env.storage().instance().get(&()).unwrap();
+        assert!(portfolio.migration_time.is_some(), "v1->v2 transform applied");
+        assert!(crate::verify_contract_invariants(&env, &portfolio).is_ok());
+    });
+}
+
+#[test]
+fn test_migration_rejects_downgrade_and_unknown_target() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let contract_id = env.register_contract(None, CounterContract);
+    let client = CounterContractClient::new(&env, &contract_id);
+    let admin = setup_admin(&env, &contract_id);
+    client.initialize();
+    client.migrate(&admin, &2);
+
+    assert_eq!(
+        client.try_migrate(&admin, &1),
+        Err(Ok(SwapTradeError::InvalidMigrationTarget))
+    );
+    // No step registered from v2 yet; the version must stay put.
+    assert_eq!(
+        client.try_migrate(&admin, &3),
+        Err(Ok(SwapTradeError::InvalidMigrationTarget))
+    );
+    assert_eq!(client.get_contract_version(), 2);
+}
+
+#[test]
+fn test_migration_requires_admin() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let contract_id = env.register_contract(None, CounterContract);
+    let client = CounterContractClient::new(&env, &contract_id);
+    setup_admin(&env, &contract_id);
+    client.initialize();
+
+    let stranger = Address::generate(&env);
+    assert_eq!(
+        client.try_migrate(&stranger, &2),
+        Err(Ok(SwapTradeError::NotAdmin))
+    );
+    assert_eq!(client.get_contract_version(), 1);
+}
+
+#[test]
+fn test_migration_is_recorded_in_audit_log() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let contract_id = env.register_contract(None, CounterContract);
+    let client = CounterContractClient::new(&env, &contract_id);
+    let admin = setup_admin(&env, &contract_id);
+    client.initialize();
+
+    client.migrate(&admin, &2);
+
+    let mut audits: std::vec::Vec<(Symbol, u32, u32)> = std::vec::Vec::new();
+    for (_, topics, data) in env.events().all().iter() {
+        let name: Symbol = topics.get(0).unwrap().try_into_val(&env).unwrap();
+        if name != Symbol::new(&env, "AuditEvent") {
+            continue;
+        }
+        let category: Symbol = topics.get(1).unwrap().try_into_val(&env).unwrap();
+        let actor: Address = topics.get(2).unwrap().try_into_val(&env).unwrap();
+        assert_eq!(category, Symbol::new(&env, "Administrative"));
+        assert_eq!(actor, admin);
+        let payload: (Symbol, u32, u32, i64) = data.try_into_val(&env).unwrap();
+        audits.push((payload.0, payload.1, payload.2));
+    }
+
+    assert_eq!(audits, std::vec![(Symbol::new(&env, "MIGRATION"), 1, 2)]);
+}
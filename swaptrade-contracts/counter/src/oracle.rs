@@ -79,67 +79,87 @@ pub fn get_price_safe(env: &Env, pair: (Symbol, Symbol)) -> Result<u128, Contrac
     }
 }
 
-pub struct DecentralizedOracle {
-    feeds: Vec<FeedProvider>,
-}
-
-impl DecentralizedOracle {
-    pub fn new() -> Self {
-        Self { feeds: Vec::new() }
+// Off-chain aggregation over several price feeds, used by keeper tooling to
+// decide what to submit via `set_price`. Needs heap collections, so it only
+// exists on host builds.
+#[cfg(any(test, feature = "native-tools"))]
+pub use aggregator::{DecentralizedOracle, FeedProvider, TokenPair, ANOMALY_THRESHOLD_BPS};
+
+#[cfg(any(test, feature = "native-tools"))]
+mod aggregator {
+    use std::boxed::Box;
+    use std::vec::Vec;
+
+    pub type TokenPair = (&'static str, &'static str);
+
+    /// A feed is flagged when it strays this far from the median
+    pub const ANOMALY_THRESHOLD_BPS: u128 = 1_000;
+
+    pub trait FeedProvider {
+        fn submit_price(&self, token_pair: TokenPair, price: u128, timestamp: u64);
+        fn get_price(&self, token_pair: TokenPair) -> Option<u128>;
+        fn get_price_history(&self, token_pair: TokenPair, lookback_periods: usize) -> Vec<u128>;
     }
 
-    pub fn register_feed(&mut self, feed: FeedProvider) {
-        self.feeds.push(feed);
+    #[derive(Default)]
+    pub struct DecentralizedOracle {
+        feeds: Vec<Box<dyn FeedProvider>>,
     }
 
-    pub fn submit_price(&self, feed_id: usize, token_pair: (Symbol, Symbol), price: u128, timestamp: u64) {
-        if let Some(feed) = self.feeds.get(feed_id) {
-            feed.submit_price(token_pair, price, timestamp);
+    impl DecentralizedOracle {
+        pub fn new() -> Self {
+            Self { feeds: Vec::new() }
         }
-    }
 
-    pub fn get_consensus_price(&self, token_pair: (Symbol, Symbol)) -> Option<u128> {
-        let mut prices: Vec<u128> = self
-            .feeds
-            .iter()
-            .filter_map(|feed| feed.get_price(token_pair))
-            .collect();
+        pub fn register_feed(&mut self, feed: impl FeedProvider + 'static) {
+            self.feeds.push(Box::new(feed));
+        }
 
-        if prices.is_empty() {
-            return None;
+        pub fn submit_price(&self, feed_id: usize, token_pair: TokenPair, price: u128, timestamp: u64) {
+            if let Some(feed) = self.feeds.get(feed_id) {
+                feed.submit_price(token_pair, price, timestamp);
+            }
         }
 
-        prices.sort_unstable();
-        Some(prices[prices.len() / 2]) // Median
-    }
+        pub fn get_consensus_price(&self, token_pair: TokenPair) -> Option<u128> {
+            let mut prices: Vec<u128> = self
+                .feeds
+                .iter()
+                .filter_map(|feed| feed.get_price(token_pair))
+                .collect();
 
-    pub fn detect_anomalies(&self, token_pair: (Symbol, Symbol)) -> Vec<usize> {
-        let prices: Vec<u128> = self
-            .feeds
-            .iter()
-            .filter_map(|feed| feed.get_price(token_pair))
-            .collect();
-
-        let mean: u128 = prices.iter().sum::<u128>() / prices.len() as u128;
-        let variance: u128 = prices
-            .iter()
-            .map(|&price| (price as i128 - mean as i128).pow(2) as u128)
-            .sum::<u128>()
-            / prices.len() as u128;
-        let std_dev = (variance as f64).sqrt() as u128;
-
-        prices
-            .iter()
-            .enumerate()
-            .filter(|&(_, &price)| (price as i128 - mean as i128).abs() as u128 > 5 * std_dev)
-            .map(|(idx, _)| idx)
-            .collect()
-    }
+            if prices.is_empty() {
+                return None;
+            }
 
-    pub fn get_price_history(&self, token_pair: (Symbol, Symbol), lookback_periods: usize) -> Vec<u128> {
-        self.feeds
-            .iter()
-            .flat_map(|feed| feed.get_price_history(token_pair, lookback_periods))
-            .collect()
+            prices.sort_unstable();
+            Some(prices[prices.len() / 2]) // Median
+        }
+
+        /// Indices of feeds whose price is more than `ANOMALY_THRESHOLD_BPS`
+        /// away from the median. The median is used rather than the mean so a
+        /// single bad feed cannot drag the reference toward itself.
+        pub fn detect_anomalies(&self, token_pair: TokenPair) -> Vec<usize> {
+            let median = match self.get_consensus_price(token_pair) {
+                Some(median) => median,
+                None => return Vec::new(),
+            };
+            let threshold = median.saturating_mul(ANOMALY_THRESHOLD_BPS) / 10_000;
+
+            self.feeds
+                .iter()
+                .enumerate()
+                .filter_map(|(idx, feed)| feed.get_price(token_pair).map(|price| (idx, price)))
+                .filter(|&(_, price)| price.abs_diff(median) > threshold)
+                .map(|(idx, _)| idx)
+                .collect()
+        }
+
+        pub fn get_price_history(&self, token_pair: TokenPair, lookback_periods: usize) -> Vec<u128> {
+            self.feeds
+                .iter()
+                .flat_map(|feed| feed.get_price_history(token_pair, lookback_periods))
+                .collect()
+        }
     }
 }
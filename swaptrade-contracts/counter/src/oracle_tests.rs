@@ -3,6 +3,8 @@
 use super::*;
 use soroban_sdk::testutils::{Address as _, Ledger as _};
 use soroban_sdk::{symbol_short, Address, Env};
+use crate::oracle::{DecentralizedOracle, FeedProvider, TokenPair};
+use core::cell::RefCell;
 
 const PRECISION: u128 = 1_000_000_000_000_000_000;
 
@@ -27,6 +29,7 @@ fn test_oracle_set_and_get() {
 #[test]
 fn test_slippage_calculation() {
     let env = Env::default();
+    env.mock_all_auths();
     let contract_id = env.register(CounterContract, ());
     let client = CounterContractClient::new(&env, &contract_id);
 
@@ -61,6 +64,7 @@ fn test_slippage_calculation() {
 #[should_panic(expected = "Slippage exceeded")]
 fn test_max_slippage_enforcement() {
     let env = Env::default();
+    env.mock_all_auths();
     let contract_id = env.register(CounterContract, ());
     let client = CounterContractClient::new(&env, &contract_id);
 
@@ -83,6 +87,7 @@ fn test_max_slippage_enforcement() {
 #[should_panic(expected = "Oracle price is stale")]
 fn test_stale_price() {
     let env = Env::default();
+    env.mock_all_auths();
 
     let contract_id = env.register(CounterContract, ());
     let client = CounterContractClient::new(&env, &contract_id);
@@ -107,6 +112,7 @@ fn test_stale_price() {
 #[test]
 fn test_price_impact_on_pool() {
     let env = Env::default();
+    env.mock_all_auths();
     let contract_id = env.register(CounterContract, ());
     let client = CounterContractClient::new(&env, &contract_id);
 
@@ -183,6 +189,7 @@ fn test_coalescing_above_threshold_persists() {
 #[test]
 fn test_coalesced_price_respects_slippage_limits() {
     let env = Env::default();
+    env.mock_all_auths();
     let contract_id = env.register(CounterContract, ());
     let client = CounterContractClient::new(&env, &contract_id);
     let user = Address::generate(&env);
@@ -214,6 +221,42 @@ fn test_per_pair_tolerance_config() {
     assert_eq!(client.get_current_price(&pair), change_03pct);
 }
 
+/// In-memory feed: a list of `(pair, price, timestamp)` observations
+struct MockFeedProvider {
+    observations: RefCell<std::vec::Vec<(TokenPair, u128, u64)>>,
+}
+
+impl MockFeedProvider {
+    fn new(observations: std::vec::Vec<(TokenPair, u128, u64)>) -> Self {
+        Self { observations: RefCell::new(observations) }
+    }
+}
+
+impl FeedProvider for MockFeedProvider {
+    fn submit_price(&self, token_pair: TokenPair, price: u128, timestamp: u64) {
+        self.observations.borrow_mut().push((token_pair, price, timestamp));
+    }
+
+    fn get_price(&self, token_pair: TokenPair) -> Option<u128> {
+        self.observations
+            .borrow()
+            .iter()
+            .filter(|(pair, _, _)| *pair == token_pair)
+            .max_by_key(|(_, _, timestamp)| *timestamp)
+            .map(|(_, price, _)| *price)
+    }
+
+    fn get_price_history(&self, token_pair: TokenPair, lookback_periods: usize) -> std::vec::Vec<u128> {
+        let observations = self.observations.borrow();
+        let prices: std::vec::Vec<u128> = observations
+            .iter()
+            .filter(|(pair, _, _)| *pair == token_pair)
+            .map(|(_, price, _)| *price)
+            .collect();
+        prices[prices.len().saturating_sub(lookback_periods)..].to_vec()
+    }
+}
+
 #[test]
 fn test_register_and_get_consensus_price() {
     let mut oracle = DecentralizedOracle::new();
@@ -0,0 +1,70 @@
+use soroban_sdk::{contracttype, Address, Env, Vec};
+
+use crate::alerts;
+use crate::fee_progression::{FeeCalculationResult, FeeProgression};
+use crate::portfolio::{Asset, Portfolio};
+use crate::rate_limit::{RateLimitStatus, RateLimiter};
+use crate::referral::{ReferralInfo, ReferralSystem};
+use crate::storage::{FEE_PROGRESSION_KEY, REFERRAL_KEY};
+use crate::tiers::UserTier;
+
+/// Most balances a single overview reports; the rest are flagged via `balances_truncated`
+pub const OVERVIEW_MAX_BALANCES: u32 = 20;
+
+/// Everything the front end shows for one user, gathered in a single call
+#[derive(Clone, Debug)]
+#[contracttype]
+pub struct UserOverview {
+    pub user: Address,
+    /// Non-zero balances, at most `OVERVIEW_MAX_BALANCES` of them
+    pub balances: Vec<(Asset, i128)>,
+    pub balances_truncated: bool,
+    pub tier: UserTier,
+    /// Fee the user's next trade would pay, with achievement discounts applied
+    pub fee: FeeCalculationResult,
+    pub referral: ReferralInfo,
+    /// Alerts that are active and not expired; bounded by `MAX_ALERTS_PER_USER`
+    pub active_alerts: u32,
+    pub swap_rate_limit: RateLimitStatus,
+    pub lp_rate_limit: RateLimitStatus,
+}
+
+/// Compose a `UserOverview` from each subsystem's stored state. Read-only:
+/// the fee quote is computed on a copy of the fee state, which is not saved.
+pub fn build_user_overview(env: &Env, user: Address) -> UserOverview {
+    let portfolio: Portfolio = env
+        .storage()
+        .instance()
+        .get(&())
+        .unwrap_or_else(|| Portfolio::new(env));
+    let mut fees: FeeProgression = env
+        .storage()
+        .instance()
+        .get(&FEE_PROGRESSION_KEY)
+        .unwrap_or_else(|| FeeProgression::new(env));
+    let referrals: ReferralSystem = env
+        .storage()
+        .instance()
+        .get(&REFERRAL_KEY)
+        .unwrap_or_else(|| ReferralSystem::new(env));
+
+    let held = portfolio.held_assets(env, user.clone());
+    let mut balances = Vec::new(env);
+    for entry in held.iter().take(OVERVIEW_MAX_BALANCES as usize) {
+        balances.push_back(entry);
+    }
+
+    let tier = portfolio.get_user_tier(env, user.clone());
+
+    UserOverview {
+        balances_truncated: held.len() > OVERVIEW_MAX_BALANCES,
+        balances,
+        fee: fees.calculate_effective_fee(env, &user, &tier),
+        referral: referrals.get_referral_stats(env, user.clone()),
+        active_alerts: alerts::get_active_alerts(env, user.clone()).len(),
+        swap_rate_limit: RateLimiter::get_swap_status(env, &user, &tier),
+        lp_rate_limit: RateLimiter::get_lp_status(env, &user, &tier),
+        tier,
+        user,
+    }
+}
@@ -0,0 +1,90 @@
+use super::*;
+use crate::alerts::{create_price_alert, NotificationMethod, PriceDirection};
+use crate::fee_progression::{Achievement, AchievementCategory, FeeProgression};
+use crate::referral::ReferralSystem;
+use crate::storage::{FEE_PROGRESSION_KEY, REFERRAL_KEY};
+use soroban_sdk::{symbol_short, testutils::{Address as _, Ledger}, Address, Env};
+
+#[test]
+fn test_user_overview_composes_every_subsystem() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(CounterContract, ());
+    let client = CounterContractClient::new(&env, &contract_id);
+    let user = Address::generate(&env);
+    let referee = Address::generate(&env);
+
+    // Portfolio and LP rate limit
+    client.mint(&symbol_short!("XLM"), &user, &1000);
+    client.mint(&symbol_short!("USDCSIM"), &user, &1000);
+    client.add_liquidity(&100, &100, &user);
+
+    env.as_contract(&contract_id, || {
+        // Fee discount
+        let mut fees = FeeProgression::new(&env);
+        let now = env.ledger().timestamp();
+        fees.apply_achievement_bonus(&env, &user, Achievement {
+            category: AchievementCategory::Volume,
+            discount_bps: 5,
+            earned_at: now,
+            expires_at: now + (30 * 24 * 60 * 60),
+            metadata: 50_000,
+            is_active: true,
+        }).unwrap();
+        env.storage().instance().set(&FEE_PROGRESSION_KEY, &fees);
+
+        // One direct referral
+        let mut referrals = ReferralSystem::new(&env);
+        let code = referrals.generate_referral_code(&env, user.clone());
+        referrals.register_with_code(&env, code, referee.clone()).unwrap();
+        env.storage().instance().set(&REFERRAL_KEY, &referrals);
+
+        // One live alert and one already expired
+        create_price_alert(&env, user.clone(), symbol_short!("XLM"), 100, PriceDirection::Above, 0, NotificationMethod::Event);
+        env.ledger().with_mut(|li| li.timestamp = 10);
+        create_price_alert(&env, user.clone(), symbol_short!("XLM"), 50, PriceDirection::Below, 5, NotificationMethod::Event);
+    });
+
+    let overview = client.get_user_overview(&user);
+
+    assert_eq!(overview.user, user);
+    assert!(!overview.balances_truncated);
+    assert_eq!(overview.balances.len(), 2);
+    for (asset, balance) in overview.balances.iter() {
+        let token = match asset {
+            Asset::XLM => symbol_short!("XLM"),
+            Asset::Custom(symbol) => symbol,
+        };
+        assert_eq!(balance, client.balance_of(&token, &user));
+        assert_eq!(balance, 900);
+    }
+
+    assert_eq!(overview.tier, client.get_user_tier(&user));
+    assert_eq!(overview.fee.base_fee_bps, overview.tier.effective_fee_bps());
+    assert!(overview.fee.applied_discounts.contains(&AchievementCategory::Volume));
+    assert!(overview.fee.achievement_discount_bps > 0);
+    assert!(overview.fee.achievement_discount_bps <= overview.fee.max_discount_bps);
+    assert_eq!(
+        overview.fee.effective_fee_bps,
+        overview.fee.base_fee_bps - overview.fee.achievement_discount_bps
+    );
+
+    assert_eq!(overview.referral.direct_referral_count, 1);
+    assert_eq!(overview.referral.total_referral_count, 1);
+
+    assert_eq!(overview.active_alerts, 1);
+
+    let lp = client.get_lp_rate_limit(&user);
+    assert_eq!(overview.lp_rate_limit.used, 1);
+    assert_eq!(overview.lp_rate_limit.used, lp.used);
+    assert_eq!(overview.lp_rate_limit.limit, lp.limit);
+    let swaps = client.get_swap_rate_limit(&user);
+    assert_eq!(overview.swap_rate_limit.used, swaps.used);
+    assert_eq!(overview.swap_rate_limit.limit, swaps.limit);
+
+    // The fee quote is a view; stored fee state is left as it was
+    env.as_contract(&contract_id, || {
+        let fees: FeeProgression = env.storage().instance().get(&FEE_PROGRESSION_KEY).unwrap();
+        assert_eq!(fees.get_achievement_status(&user).unwrap().last_recalculation, 0);
+    });
+}
@@ -0,0 +1,112 @@
+//! Daily closing prices per asset.
+//!
+//! Analytics needs a price series to compute asset returns, volatility and
+//! correlations. Each asset keeps a bounded ring of daily closes, quoted in
+//! the simulated USD asset with 7 decimals. A day's close is the last price
+//! observed that day, whether it came from a swap or an admin oracle push.
+
+use soroban_sdk::{contracttype, symbol_short, Env, Symbol, Vec};
+
+use crate::portfolio::Asset;
+
+/// Days of closes kept per asset; the oldest day is evicted beyond this.
+pub const PRICE_HISTORY_CAP_DAYS: u32 = 365;
+
+const SECONDS_PER_DAY: u64 = 86_400;
+
+/// Closing price of an asset for one UTC day.
+#[derive(Clone, Debug, PartialEq)]
+#[contracttype]
+pub struct DailyClose {
+    /// Days since the Unix epoch
+    pub day: u64,
+    /// Fixed-point price (7 decimals)
+    pub price: i128,
+}
+
+fn history_key(asset: &Asset) -> (Symbol, Asset) {
+    (symbol_short!("px_hist"), asset.clone())
+}
+
+fn load(env: &Env, asset: &Asset) -> Vec<DailyClose> {
+    env.storage()
+        .instance()
+        .get(&history_key(asset))
+        .unwrap_or_else(|| Vec::new(env))
+}
+
+/// Record `price` as the latest close for the day containing `timestamp`.
+/// Non-positive prices and observations older than the latest stored day are ignored.
+pub fn record_price(env: &Env, asset: Asset, price: i128, timestamp: u64) {
+    if price <= 0 {
+        return;
+    }
+    let day = timestamp / SECONDS_PER_DAY;
+    let mut closes = load(env, &asset);
+    match closes.last() {
+        Some(last) if last.day == day => {
+            closes.set(closes.len() - 1, DailyClose { day, price });
+        }
+        Some(last) if last.day > day => return,
+        _ => {
+            while closes.len() >= PRICE_HISTORY_CAP_DAYS {
+                closes.pop_front();
+            }
+            closes.push_back(DailyClose { day, price });
+        }
+    }
+    env.storage().instance().set(&history_key(&asset), &closes);
+}
+
+/// Up to `days` most recent closes for `asset`, oldest first.
+pub fn get_closes(env: &Env, asset: &Asset, days: u32) -> Vec<DailyClose> {
+    let closes = load(env, asset);
+    let skip = closes.len().saturating_sub(days);
+    closes.slice(skip..)
+}
+
+/// Up to `days` most recent closing prices for `asset`, oldest first.
+pub fn get_price_history(env: &Env, asset: &Asset, days: u32) -> Vec<i128> {
+    let mut prices = Vec::new(env);
+    for close in get_closes(env, asset, days).iter() {
+        prices.push_back(close.price);
+    }
+    prices
+}
+
+/// Closing prices of `a` and `b` on the days within the last `days` where both have one.
+pub fn get_aligned_history(env: &Env, a: &Asset, b: &Asset, days: u32) -> (Vec<i128>, Vec<i128>) {
+    let closes_a = get_closes(env, a, days);
+    let closes_b = get_closes(env, b, days);
+    let mut prices_a = Vec::new(env);
+    let mut prices_b = Vec::new(env);
+    let (mut i, mut j) = (0, 0);
+    // Both rings are sorted by day, so a merge walk finds the shared days
+    while i < closes_a.len() && j < closes_b.len() {
+        let (close_a, close_b) = (closes_a.get(i).unwrap(), closes_b.get(j).unwrap());
+        if close_a.day == close_b.day {
+            prices_a.push_back(close_a.price);
+            prices_b.push_back(close_b.price);
+            i += 1;
+            j += 1;
+        } else if close_a.day < close_b.day {
+            i += 1;
+        } else {
+            j += 1;
+        }
+    }
+    (prices_a, prices_b)
+}
+
+/// Time-weighted average of the last `days` daily closes, if any exist.
+pub fn twap(env: &Env, asset: &Asset, days: u32) -> Option<i128> {
+    let prices = get_price_history(env, asset, days);
+    if prices.is_empty() {
+        return None;
+    }
+    let mut sum: i128 = 0;
+    for price in prices.iter() {
+        sum = sum.saturating_add(price);
+    }
+    Some(sum / prices.len() as i128)
+}
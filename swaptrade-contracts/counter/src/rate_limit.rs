@@ -1,5 +1,12 @@
+use crate::admin::require_admin;
+use crate::errors::SwapTradeError;
+use crate::storage::RATE_LIMIT_ALLOWLIST_KEY;
 use crate::tiers::UserTier;
-use soroban_sdk::{contracttype, symbol_short, Address, Env};
+use crate::windowed_counter::{WindowedCounter, WINDOWED_COUNTER_CAPACITY};
+use soroban_sdk::{contracttype, symbol_short, Address, Env, Symbol, Vec};
+
+/// Prefix of the per-user event logs behind every limit
+const RATE_LOG_KEY: Symbol = symbol_short!("rl_log");
 
 /// Cached window boundaries for optimization
 #[contracttype]
@@ -70,10 +77,51 @@ pub struct RateLimitStatus {
     pub used: u32,
     /// Limit for this time window
     pub limit: u32,
-    /// Milliseconds until limit resets
+    /// Milliseconds until the oldest counted operation leaves the window,
+    /// or until a backoff penalty ends if that is later
     pub cooldown_ms: u64,
 }
 
+/// Escalating penalty for repeated rate-limit violations
+#[contracttype]
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct PenaltyState {
+    /// Consecutive windows in which the user hit their limit
+    pub strikes: u32,
+    /// Start of the most recent window with a violation
+    pub last_violation_window: u64,
+    /// Timestamp before which all attempts are rejected
+    pub blocked_until: u64,
+}
+
+/// Point-in-time view of every rate-limit input for one user, for support and audits
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub struct RateLimitSnapshot {
+    pub user: Address,
+    /// Ledger timestamp the snapshot was taken at
+    pub taken_at: u64,
+    pub tier: UserTier,
+    pub allowlisted: bool,
+    /// First second inside the trailing hourly swap window
+    pub swap_window_start: u64,
+    pub swap_count: u32,
+    pub swaps_per_hour: u32,
+    /// First second inside the trailing daily LP window
+    pub lp_window_start: u64,
+    pub lp_count: u32,
+    pub lp_ops_per_day: u32,
+    /// Whether a swap penalty is on record; `swap_penalty` is all zero when not
+    pub has_swap_penalty: bool,
+    pub swap_penalty: PenaltyState,
+    /// Whether an LP penalty is on record; `lp_penalty` is all zero when not
+    pub has_lp_penalty: bool,
+    pub lp_penalty: PenaltyState,
+}
+
+/// Upper bound on the penalty multiplier (2^4 windows)
+pub const MAX_PENALTY_MULTIPLIER: u64 = 16;
+
 /// Time window info with optimized caching
 #[contracttype]
 #[derive(Clone, Debug)]
@@ -128,10 +176,10 @@ impl TimeWindow {
 
     /// Get hourly window using cached boundary if available
     pub fn hourly_cached(env: &Env, current_timestamp: u64) -> Self {
-        let cache_key = symbol_short!("hourly_cache");
+        let cache_key = symbol_short!("hr_cache");
         
         // Try to get cached boundary
-        if let Some(cached) = env.storage().persistent().get::<CachedWindowBoundary>(&cache_key) {
+        if let Some(cached) = env.storage().persistent().get::<_, CachedWindowBoundary>(&cache_key) {
             if cached.is_valid(current_timestamp) {
                 return TimeWindow {
                     window_start: cached.window_start,
@@ -150,10 +198,10 @@ impl TimeWindow {
 
     /// Get daily window using cached boundary if available
     pub fn daily_cached(env: &Env, current_timestamp: u64) -> Self {
-        let cache_key = symbol_short!("daily_cache");
+        let cache_key = symbol_short!("day_cache");
         
         // Try to get cached boundary
-        if let Some(cached) = env.storage().persistent().get::<CachedWindowBoundary>(&cache_key) {
+        if let Some(cached) = env.storage().persistent().get::<_, CachedWindowBoundary>(&cache_key) {
             if cached.is_valid(current_timestamp) {
                 return TimeWindow {
                     window_start: cached.window_start,
@@ -185,6 +233,41 @@ impl TimeWindow {
 pub struct RateLimiter;
 
 impl RateLimiter {
+    /// Whether `user` is exempt from per-tier rate limits
+    pub fn is_allowlisted(env: &Env, user: &Address) -> bool {
+        env.storage()
+            .persistent()
+            .get(&(RATE_LIMIT_ALLOWLIST_KEY, user.clone()))
+            .unwrap_or(false)
+    }
+
+    /// Add or remove a trusted integrator from the rate-limit allowlist (admin only)
+    pub fn set_allowlisted(
+        env: &Env,
+        user: &Address,
+        allowed: bool,
+        admin: &Address,
+    ) -> Result<(), SwapTradeError> {
+        admin.require_auth();
+        require_admin(env, admin)?;
+
+        let key = (RATE_LIMIT_ALLOWLIST_KEY, user.clone());
+        if allowed {
+            env.storage().persistent().set(&key, &true);
+        } else {
+            env.storage().persistent().remove(&key);
+        }
+
+        crate::events::Events::rate_limit_allowlist_changed(
+            env,
+            admin.clone(),
+            user.clone(),
+            allowed,
+            env.ledger().timestamp() as i64,
+        );
+        Ok(())
+    }
+
     /// Check and record a swap operation for the user
     /// Returns Ok(()) if operation is allowed, Err with cooldown if rate limited
     pub fn check_swap_limit(
@@ -192,6 +275,10 @@ impl RateLimiter {
         user: &Address,
         tier: &UserTier,
     ) -> Result<(), RateLimitStatus> {
+        if Self::is_allowlisted(env, user) {
+            return Ok(());
+        }
+
         let config = RateLimitConfig::for_tier(tier);
 
         // Unlimited for Whale tier with max u32 limit
@@ -201,16 +288,17 @@ impl RateLimiter {
 
         let timestamp = env.ledger().timestamp();
         let window = TimeWindow::hourly_cached(env, timestamp);
-        let count_key = (user.clone(), symbol_short!("swap"), window.window_start);
+        let swaps = Self::load_log(env, user, symbol_short!("swap"), &window);
+        let current_count = swaps.count_in_window(timestamp, window.window_duration);
 
-        // Get current count
-        let current_count: u32 = env.storage().persistent().get(&count_key).unwrap_or(0);
-
-        if current_count >= config.swaps_per_hour {
+        if current_count >= config.swaps_per_hour
+            || Self::is_penalized(env, user, symbol_short!("pen_swap"), timestamp)
+        {
+            let penalty_ms = Self::apply_violation(env, user, symbol_short!("pen_swap"), &window, timestamp);
             return Err(RateLimitStatus {
                 used: current_count,
                 limit: config.swaps_per_hour,
-                cooldown_ms: window.cooldown_ms(timestamp),
+                cooldown_ms: Self::expiry_ms(&swaps, timestamp, window.window_duration).max(penalty_ms),
             });
         }
 
@@ -220,13 +308,9 @@ impl RateLimiter {
     /// Record a swap operation in storage
     pub fn record_swap(env: &Env, user: &Address, timestamp: u64) {
         let window = TimeWindow::hourly_cached(env, timestamp);
-        let count_key = (user.clone(), symbol_short!("swap"), window.window_start);
-
-        let current_count: u32 = env.storage().persistent().get(&count_key).unwrap_or(0);
-
-        env.storage()
-            .persistent()
-            .set(&count_key, &(current_count + 1));
+        let mut swaps = Self::load_log(env, user, symbol_short!("swap"), &window);
+        swaps.record(timestamp);
+        Self::save_log(env, user, symbol_short!("swap"), &window, swaps, timestamp);
     }
 
     /// Check and record an LP operation for the user
@@ -235,6 +319,10 @@ impl RateLimiter {
         user: &Address,
         tier: &UserTier,
     ) -> Result<(), RateLimitStatus> {
+        if Self::is_allowlisted(env, user) {
+            return Ok(());
+        }
+
         let config = RateLimitConfig::for_tier(tier);
 
         // Unlimited for Expert+ tiers with max u32 limit
@@ -244,15 +332,17 @@ impl RateLimiter {
 
         let timestamp = env.ledger().timestamp();
         let window = TimeWindow::daily_cached(env, timestamp);
-        let count_key = (user.clone(), symbol_short!("lp_op"), window.window_start);
-
-        let current_count: u32 = env.storage().persistent().get(&count_key).unwrap_or(0);
+        let lp_ops = Self::load_log(env, user, symbol_short!("lp_op"), &window);
+        let current_count = lp_ops.count_in_window(timestamp, window.window_duration);
 
-        if current_count >= config.lp_ops_per_day {
+        if current_count >= config.lp_ops_per_day
+            || Self::is_penalized(env, user, symbol_short!("pen_lp"), timestamp)
+        {
+            let penalty_ms = Self::apply_violation(env, user, symbol_short!("pen_lp"), &window, timestamp);
             return Err(RateLimitStatus {
                 used: current_count,
                 limit: config.lp_ops_per_day,
-                cooldown_ms: window.cooldown_ms(timestamp),
+                cooldown_ms: Self::expiry_ms(&lp_ops, timestamp, window.window_duration).max(penalty_ms),
             });
         }
 
@@ -262,13 +352,98 @@ impl RateLimiter {
     /// Record an LP operation in storage
     pub fn record_lp_op(env: &Env, user: &Address, timestamp: u64) {
         let window = TimeWindow::daily_cached(env, timestamp);
-        let count_key = (user.clone(), symbol_short!("lp_op"), window.window_start);
+        let mut lp_ops = Self::load_log(env, user, symbol_short!("lp_op"), &window);
+        lp_ops.record(timestamp);
+        Self::save_log(env, user, symbol_short!("lp_op"), &window, lp_ops, timestamp);
+    }
 
-        let current_count: u32 = env.storage().persistent().get(&count_key).unwrap_or(0);
+    /// Check an arbitrary operation against `limit` per trailing `window_secs`.
+    /// Logs are keyed by (user, op, window_secs), so a custom window never
+    /// shares a log with the built-in hourly/daily ones. Limits above
+    /// `WINDOWED_COUNTER_CAPACITY` are enforced at the capacity.
+    pub fn check_limit_windowed(
+        env: &Env,
+        user: &Address,
+        op: Symbol,
+        limit: u32,
+        window_secs: u64,
+    ) -> Result<(), RateLimitStatus> {
+        if limit == u32::MAX || window_secs == 0 {
+            return Ok(());
+        }
 
+        let timestamp = env.ledger().timestamp();
+        let ops = Self::load_windowed_log(env, user, op, window_secs);
+        let current_count = ops.count_in_window(timestamp, window_secs);
+
+        if current_count >= limit.min(WINDOWED_COUNTER_CAPACITY as u32) {
+            return Err(RateLimitStatus {
+                used: current_count,
+                limit,
+                cooldown_ms: Self::expiry_ms(&ops, timestamp, window_secs),
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Record an operation against a custom window
+    pub fn record_windowed(env: &Env, user: &Address, op: Symbol, window_secs: u64, timestamp: u64) {
+        if window_secs == 0 {
+            return;
+        }
+        let mut ops = Self::load_windowed_log(env, user, op.clone(), window_secs);
+        ops.record(timestamp);
+        ops.prune(timestamp, window_secs);
         env.storage()
             .persistent()
-            .set(&count_key, &(current_count + 1));
+            .set(&(RATE_LOG_KEY, user.clone(), op, window_secs), &Self::to_vec(env, &ops));
+    }
+
+    /// Event log behind a built-in limit. Counts stored by the earlier
+    /// fixed-window scheme for the window in progress are folded in as events
+    /// at its start, so upgrading doesn't hand out a fresh allowance.
+    fn load_log(env: &Env, user: &Address, op: Symbol, window: &TimeWindow) -> WindowedCounter {
+        let log: Vec<u64> = env
+            .storage()
+            .persistent()
+            .get(&(RATE_LOG_KEY, user.clone(), op.clone()))
+            .unwrap_or_else(|| Vec::new(env));
+        let legacy: u32 = env
+            .storage()
+            .persistent()
+            .get(&(user.clone(), op, window.window_start))
+            .unwrap_or(0);
+        WindowedCounter::from_timestamps((0..legacy).map(|_| window.window_start).chain(log.iter()))
+    }
+
+    fn save_log(env: &Env, user: &Address, op: Symbol, window: &TimeWindow, mut log: WindowedCounter, timestamp: u64) {
+        log.prune(timestamp, window.window_duration);
+        env.storage()
+            .persistent()
+            .set(&(RATE_LOG_KEY, user.clone(), op.clone()), &Self::to_vec(env, &log));
+        env.storage().persistent().remove(&(user.clone(), op, window.window_start));
+    }
+
+    fn load_windowed_log(env: &Env, user: &Address, op: Symbol, window_secs: u64) -> WindowedCounter {
+        let log: Vec<u64> = env
+            .storage()
+            .persistent()
+            .get(&(RATE_LOG_KEY, user.clone(), op, window_secs))
+            .unwrap_or_else(|| Vec::new(env));
+        WindowedCounter::from_timestamps(log.iter())
+    }
+
+    fn to_vec(env: &Env, log: &WindowedCounter) -> Vec<u64> {
+        let mut out = Vec::new(env);
+        for ts in log.iter() {
+            out.push_back(ts);
+        }
+        out
+    }
+
+    fn expiry_ms(log: &WindowedCounter, timestamp: u64, window_secs: u64) -> u64 {
+        log.time_until_expiry(timestamp, window_secs).saturating_mul(1000)
     }
 
     /// Get rate limit status for swaps
@@ -276,14 +451,18 @@ impl RateLimiter {
         let config = RateLimitConfig::for_tier(tier);
         let timestamp = env.ledger().timestamp();
         let window = TimeWindow::hourly_cached(env, timestamp);
-        let count_key = (user.clone(), symbol_short!("swap"), window.window_start);
-
-        let used: u32 = env.storage().persistent().get(&count_key).unwrap_or(0);
+        let swaps = Self::load_log(env, user, symbol_short!("swap"), &window);
 
         RateLimitStatus {
-            used,
+            used: swaps.count_in_window(timestamp, window.window_duration),
             limit: config.swaps_per_hour,
-            cooldown_ms: window.cooldown_ms(timestamp),
+            cooldown_ms: Self::penalized_cooldown_ms(
+                env,
+                user,
+                symbol_short!("pen_swap"),
+                Self::expiry_ms(&swaps, timestamp, window.window_duration),
+                timestamp,
+            ),
         }
     }
 
@@ -292,15 +471,126 @@ impl RateLimiter {
         let config = RateLimitConfig::for_tier(tier);
         let timestamp = env.ledger().timestamp();
         let window = TimeWindow::daily_cached(env, timestamp);
-        let count_key = (user.clone(), symbol_short!("lp_op"), window.window_start);
-
-        let used: u32 = env.storage().persistent().get(&count_key).unwrap_or(0);
+        let lp_ops = Self::load_log(env, user, symbol_short!("lp_op"), &window);
 
         RateLimitStatus {
-            used,
+            used: lp_ops.count_in_window(timestamp, window.window_duration),
             limit: config.lp_ops_per_day,
-            cooldown_ms: window.cooldown_ms(timestamp),
+            cooldown_ms: Self::penalized_cooldown_ms(
+                env,
+                user,
+                symbol_short!("pen_lp"),
+                Self::expiry_ms(&lp_ops, timestamp, window.window_duration),
+                timestamp,
+            ),
+        }
+    }
+
+    /// Capture `user`'s counters, windows, tier limits, penalties and allowlist
+    /// status as of the current ledger timestamp. The tier is read from the
+    /// stored portfolio, as the contract does when enforcing limits.
+    pub fn export_user_state(env: &Env, user: &Address) -> RateLimitSnapshot {
+        let portfolio: crate::portfolio::Portfolio = env
+            .storage()
+            .instance()
+            .get(&())
+            .unwrap_or_else(|| crate::portfolio::Portfolio::new(env));
+        let tier = portfolio.get_user_tier(env, user.clone());
+        let config = RateLimitConfig::for_tier(&tier);
+
+        let timestamp = env.ledger().timestamp();
+        let swap_window = TimeWindow::hourly_cached(env, timestamp);
+        let lp_window = TimeWindow::daily_cached(env, timestamp);
+        let swaps = Self::load_log(env, user, symbol_short!("swap"), &swap_window);
+        let lp_ops = Self::load_log(env, user, symbol_short!("lp_op"), &lp_window);
+        // First second still inside each trailing window
+        let window_start = |duration: u64| (timestamp + 1).saturating_sub(duration);
+        let swap_penalty = Self::get_penalty(env, user, symbol_short!("pen_swap"));
+        let lp_penalty = Self::get_penalty(env, user, symbol_short!("pen_lp"));
+
+        RateLimitSnapshot {
+            user: user.clone(),
+            taken_at: timestamp,
+            tier,
+            allowlisted: Self::is_allowlisted(env, user),
+            swap_window_start: window_start(swap_window.window_duration),
+            swap_count: swaps.count_in_window(timestamp, swap_window.window_duration),
+            swaps_per_hour: config.swaps_per_hour,
+            lp_window_start: window_start(lp_window.window_duration),
+            lp_count: lp_ops.count_in_window(timestamp, lp_window.window_duration),
+            lp_ops_per_day: config.lp_ops_per_day,
+            has_swap_penalty: swap_penalty.is_some(),
+            swap_penalty: swap_penalty.unwrap_or_default(),
+            has_lp_penalty: lp_penalty.is_some(),
+            lp_penalty: lp_penalty.unwrap_or_default(),
+        }
+    }
+
+    /// Get the current backoff penalty for an operation (`pen_swap` / `pen_lp`)
+    pub fn get_penalty(env: &Env, user: &Address, penalty_op: Symbol) -> Option<PenaltyState> {
+        env.storage().persistent().get(&(user.clone(), penalty_op))
+    }
+
+    fn is_penalized(env: &Env, user: &Address, penalty_op: Symbol, timestamp: u64) -> bool {
+        Self::get_penalty(env, user, penalty_op)
+            .map(|p| timestamp < p.blocked_until)
+            .unwrap_or(false)
+    }
+
+    /// Record a violation and return the penalized cooldown in milliseconds.
+    ///
+    /// Strikes escalate once per window with a violation; a window that passes
+    /// without one resets them. The block extends past the current window by
+    /// (2^(strikes-1) - 1) windows, capped at `MAX_PENALTY_MULTIPLIER`.
+    fn apply_violation(
+        env: &Env,
+        user: &Address,
+        penalty_op: Symbol,
+        window: &TimeWindow,
+        timestamp: u64,
+    ) -> u64 {
+        let key = (user.clone(), penalty_op);
+        let mut state: PenaltyState = env.storage().persistent().get(&key).unwrap_or(PenaltyState {
+            strikes: 0,
+            last_violation_window: 0,
+            blocked_until: 0,
+        });
+
+        let had_clean_window = state.strikes > 0
+            && state.last_violation_window.saturating_add(window.window_duration) < window.window_start
+            && timestamp >= state.blocked_until;
+        if had_clean_window {
+            state.strikes = 0;
+        }
+
+        if state.strikes == 0 || state.last_violation_window != window.window_start {
+            state.strikes = state.strikes.saturating_add(1);
+            state.last_violation_window = window.window_start;
+
+            let multiplier = 1u64
+                .checked_shl(state.strikes.saturating_sub(1))
+                .unwrap_or(MAX_PENALTY_MULTIPLIER)
+                .min(MAX_PENALTY_MULTIPLIER);
+            let window_end = window.window_start.saturating_add(window.window_duration);
+            let extension = window.window_duration.saturating_mul(multiplier - 1);
+            state.blocked_until = state.blocked_until.max(window_end.saturating_add(extension));
         }
+
+        env.storage().persistent().set(&key, &state);
+        state.blocked_until.saturating_sub(timestamp).saturating_mul(1000)
+    }
+
+    fn penalized_cooldown_ms(
+        env: &Env,
+        user: &Address,
+        penalty_op: Symbol,
+        window_cooldown_ms: u64,
+        timestamp: u64,
+    ) -> u64 {
+        let penalty_ms = Self::get_penalty(env, user, penalty_op)
+            .map(|p| p.blocked_until.saturating_sub(timestamp).saturating_mul(1000))
+            .unwrap_or(0);
+        window_cooldown_ms.max(penalty_ms)
     }
 }
 
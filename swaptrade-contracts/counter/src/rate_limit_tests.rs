@@ -1,5 +1,6 @@
 #[cfg(test)]
 mod rate_limit_tests {
+    use crate::rate_limit::PenaltyState;
     use crate::{CounterContract, RateLimiter, UserTier};
     use soroban_sdk::{
         symbol_short,
@@ -413,6 +414,52 @@ mod rate_limit_tests {
         assert!(status_after.cooldown_ms > status_before.cooldown_ms);
     }
 
+    #[test]
+    fn test_hourly_limit_trails_across_boundary() {
+        let (env, user) = create_test_env();
+        let contract_id = env.register(CounterContract, ());
+        let novice = UserTier::Novice;
+
+        env.as_contract(&contract_id, || {
+            // A burst late in hour 0 still counts just after the boundary
+            for i in 0..5 {
+                env.ledger().set_timestamp(3500 + i);
+                RateLimiter::record_swap(&env, &user, env.ledger().timestamp());
+            }
+            env.ledger().set_timestamp(3600);
+            let status = RateLimiter::get_swap_status(&env, &user, &novice);
+            assert_eq!(status.used, 5);
+            assert_eq!(status.cooldown_ms, 3_500_000);
+
+            // Once every swap is an hour old, the allowance is back
+            env.ledger().set_timestamp(3504 + 3600);
+            assert!(RateLimiter::check_swap_limit(&env, &user, &novice).is_ok());
+        });
+    }
+
+    #[test]
+    fn test_daily_limit_trails_across_boundary() {
+        let (env, user) = create_test_env();
+        let contract_id = env.register(CounterContract, ());
+        let novice = UserTier::Novice;
+
+        env.as_contract(&contract_id, || {
+            // A burst late in day 0 still counts just after the boundary
+            for i in 0..10 {
+                env.ledger().set_timestamp(86_000 + i);
+                RateLimiter::record_lp_op(&env, &user, env.ledger().timestamp());
+            }
+            env.ledger().set_timestamp(86_400);
+            let status = RateLimiter::get_lp_status(&env, &user, &novice);
+            assert_eq!(status.used, 10);
+            assert_eq!(status.cooldown_ms, 86_000_000);
+
+            // Once every op is a day old, the allowance is back
+            env.ledger().set_timestamp(86_009 + 86_400);
+            assert!(RateLimiter::check_lp_limit(&env, &user, &novice).is_ok());
+        });
+    }
+
     #[test]
     fn test_high_frequency_operations_with_cache() {
         let (env, user) = create_test_env();
@@ -451,4 +498,320 @@ mod rate_limit_tests {
         assert_eq!(status.used, 3);
         assert_eq!(status.limit, 5);
     }
+
+    #[test]
+    fn test_rate_limited_count_metric() {
+        use crate::CounterContractClient;
+
+        let (env, user) = create_test_env();
+        let contract_id = env.register(CounterContract, ());
+        let client = CounterContractClient::new(&env, &contract_id);
+
+        // Stay under the Trader balance threshold so the Novice limit applies
+        client.mint(&symbol_short!("XLM"), &user, &50);
+
+        // Max out the Novice hourly swap allowance
+        env.ledger().set_timestamp(3600);
+        env.as_contract(&contract_id, || {
+            for _ in 0..5 {
+                RateLimiter::record_swap(&env, &user, env.ledger().timestamp());
+            }
+        });
+
+        let out = client.safe_swap(&symbol_short!("XLM"), &symbol_short!("USDCSIM"), &10, &user);
+        assert_eq!(out, 0);
+
+        let metrics = client.get_metrics();
+        assert_eq!(metrics.rate_limited_count, 1);
+        assert_eq!(metrics.failed_orders, 0);
+        assert_eq!(client.balance_of(&symbol_short!("XLM"), &user), 50);
+    }
+
+    #[test]
+    fn test_rate_limited_swap_emits_security_audit_event() {
+        use crate::CounterContractClient;
+        use soroban_sdk::{testutils::Events as _, TryIntoVal};
+
+        let (env, user) = create_test_env();
+        let contract_id = env.register(CounterContract, ());
+        let client = CounterContractClient::new(&env, &contract_id);
+
+        client.mint(&symbol_short!("XLM"), &user, &50);
+
+        env.ledger().set_timestamp(3600);
+        env.as_contract(&contract_id, || {
+            for _ in 0..5 {
+                RateLimiter::record_swap(&env, &user, env.ledger().timestamp());
+            }
+        });
+
+        let out = client.safe_swap(&symbol_short!("XLM"), &symbol_short!("USDCSIM"), &10, &user);
+        assert_eq!(out, 0);
+
+        let mut audits: std::vec::Vec<(Symbol, u32, u32)> = std::vec::Vec::new();
+        for (_, topics, data) in env.events().all().iter() {
+            let name: Symbol = topics.get(0).unwrap().try_into_val(&env).unwrap();
+            if name != Symbol::new(&env, "AuditEvent") {
+                continue;
+            }
+            let category: Symbol = topics.get(1).unwrap().try_into_val(&env).unwrap();
+            let actor: Address = topics.get(2).unwrap().try_into_val(&env).unwrap();
+            assert_eq!(category, Symbol::new(&env, "Security"));
+            assert_eq!(actor, user);
+            let payload: (Symbol, u32, u32, u64, i64) = data.try_into_val(&env).unwrap();
+            audits.push((payload.0, payload.1, payload.2));
+        }
+
+        assert_eq!(audits.len(), 1);
+        assert_eq!(audits[0], (Symbol::new(&env, "RATE_LIMIT_SWAP"), 5, 5));
+    }
+
+    #[test]
+    fn test_rate_limited_swap_and_lp_add_return_zero_and_keep_penalty() {
+        use crate::CounterContractClient;
+
+        let (env, user) = create_test_env();
+        env.mock_all_auths();
+        let contract_id = env.register(CounterContract, ());
+        let client = CounterContractClient::new(&env, &contract_id);
+
+        client.mint(&symbol_short!("XLM"), &user, &50);
+        client.mint(&symbol_short!("USDCSIM"), &user, &20);
+
+        env.ledger().set_timestamp(3600);
+        env.as_contract(&contract_id, || {
+            for _ in 0..5 {
+                RateLimiter::record_swap(&env, &user, env.ledger().timestamp());
+            }
+            for _ in 0..10 {
+                RateLimiter::record_lp_op(&env, &user, env.ledger().timestamp());
+            }
+        });
+
+        // Declined calls return 0 instead of trapping, so nothing moves but
+        // the violation is still on record
+        assert_eq!(client.swap(&symbol_short!("XLM"), &symbol_short!("USDCSIM"), &10, &user), 0);
+        assert_eq!(client.add_liquidity(&10, &10, &user), 0);
+        assert_eq!(client.balance_of(&symbol_short!("XLM"), &user), 50);
+        assert_eq!(client.balance_of(&symbol_short!("USDCSIM"), &user), 20);
+        assert_eq!(client.get_metrics().rate_limited_count, 2);
+
+        env.as_contract(&contract_id, || {
+            assert_eq!(RateLimiter::get_penalty(&env, &user, symbol_short!("pen_swap")).unwrap().strikes, 1);
+            assert_eq!(RateLimiter::get_penalty(&env, &user, symbol_short!("pen_lp")).unwrap().strikes, 1);
+        });
+    }
+
+    #[test]
+    fn test_windowed_limit_15_minutes() {
+        let (env, user) = create_test_env();
+        let contract_id = env.register(CounterContract, ());
+        let op = symbol_short!("swap");
+
+        env.as_contract(&contract_id, || {
+            // 3 swaps per 15-minute window, starting inside window [900, 1800)
+            for i in 0..3 {
+                env.ledger().set_timestamp(900 + i * 60);
+                assert!(RateLimiter::check_limit_windowed(&env, &user, op.clone(), 3, 900).is_ok());
+                RateLimiter::record_windowed(&env, &user, op.clone(), 900, env.ledger().timestamp());
+            }
+
+            env.ledger().set_timestamp(1799);
+            let status = RateLimiter::check_limit_windowed(&env, &user, op.clone(), 3, 900).unwrap_err();
+            assert_eq!(status.used, 3);
+            assert_eq!(status.limit, 3);
+            assert_eq!(status.cooldown_ms, 1000);
+
+            // Counter resets exactly at the next 900s boundary
+            env.ledger().set_timestamp(1800);
+            assert!(RateLimiter::check_limit_windowed(&env, &user, op, 3, 900).is_ok());
+        });
+    }
+
+    #[test]
+    fn test_windowed_limit_independent_of_hourly() {
+        let (env, user) = create_test_env();
+        let contract_id = env.register(CounterContract, ());
+        let novice = UserTier::Novice;
+
+        env.as_contract(&contract_id, || {
+            env.ledger().set_timestamp(3600);
+            for _ in 0..2 {
+                RateLimiter::record_windowed(&env, &user, symbol_short!("swap"), 900, env.ledger().timestamp());
+            }
+
+            // Custom window counts don't leak into the built-in hourly counter
+            assert_eq!(RateLimiter::get_swap_status(&env, &user, &novice).used, 0);
+        });
+    }
+
+    #[test]
+    fn test_allowlisted_novice_bypasses_swap_cap() {
+        use crate::CounterContractClient;
+
+        let (env, trusted) = create_test_env();
+        env.mock_all_auths();
+        let other = Address::generate(&env);
+        let admin = Address::generate(&env);
+        let novice = UserTier::Novice;
+        let contract_id = env.register(CounterContract, ());
+        let client = CounterContractClient::new(&env, &contract_id);
+
+        client.init_admin(&admin);
+        client.set_allowlisted(&trusted, &true, &admin);
+
+        env.ledger().set_timestamp(3600);
+        env.as_contract(&contract_id, || {
+            for i in 0..10 {
+                assert!(
+                    RateLimiter::check_swap_limit(&env, &trusted, &novice).is_ok(),
+                    "Allowlisted swap {} should be allowed",
+                    i + 1
+                );
+                RateLimiter::record_swap(&env, &trusted, env.ledger().timestamp());
+                RateLimiter::record_swap(&env, &other, env.ledger().timestamp());
+            }
+            assert!(RateLimiter::check_swap_limit(&env, &other, &novice).is_err());
+        });
+
+        // Removing the exemption restores the tier cap
+        client.set_allowlisted(&trusted, &false, &admin);
+        env.as_contract(&contract_id, || {
+            assert!(RateLimiter::check_swap_limit(&env, &trusted, &novice).is_err());
+        });
+    }
+
+    #[test]
+    fn test_set_allowlisted_requires_admin() {
+        let (env, user) = create_test_env();
+        env.mock_all_auths();
+        let impostor = Address::generate(&env);
+        let contract_id = env.register(CounterContract, ());
+
+        env.as_contract(&contract_id, || {
+            assert!(RateLimiter::set_allowlisted(&env, &user, true, &impostor).is_err());
+            assert!(!RateLimiter::is_allowlisted(&env, &user));
+        });
+    }
+
+    #[test]
+    fn test_backoff_escalates_on_consecutive_violations() {
+        let (env, user) = create_test_env();
+        let contract_id = env.register(CounterContract, ());
+        let novice = UserTier::Novice;
+
+        env.as_contract(&contract_id, || {
+            // Hour 1: hit the cap -> base cooldown to end of window
+            for i in 0..5 {
+                env.ledger().set_timestamp(3600 + i);
+                RateLimiter::record_swap(&env, &user, env.ledger().timestamp());
+            }
+            env.ledger().set_timestamp(3700);
+            let first = RateLimiter::check_swap_limit(&env, &user, &novice).unwrap_err();
+            assert_eq!(first.cooldown_ms, (7200 - 3700) * 1000);
+
+            // Hour 2: hit the cap again -> one extra window of penalty
+            for i in 0..5 {
+                env.ledger().set_timestamp(7200 + i);
+                RateLimiter::record_swap(&env, &user, env.ledger().timestamp());
+            }
+            env.ledger().set_timestamp(7300);
+            let second = RateLimiter::check_swap_limit(&env, &user, &novice).unwrap_err();
+            assert_eq!(second.cooldown_ms, (10800 + 3600 - 7300) * 1000);
+            assert!(second.cooldown_ms > first.cooldown_ms);
+
+            // Hour 3: retrying while penalized is a third violation in a row
+            env.ledger().set_timestamp(10900);
+            let third = RateLimiter::check_swap_limit(&env, &user, &novice).unwrap_err();
+            assert_eq!(third.used, 0);
+            assert!(third.cooldown_ms > second.cooldown_ms);
+
+            let penalty = RateLimiter::get_penalty(&env, &user, symbol_short!("pen_swap")).unwrap();
+            assert_eq!(penalty.strikes, 3);
+            assert_eq!(RateLimiter::get_swap_status(&env, &user, &novice).cooldown_ms, third.cooldown_ms);
+        });
+    }
+
+    #[test]
+    fn test_backoff_resets_after_clean_window() {
+        let (env, user) = create_test_env();
+        let contract_id = env.register(CounterContract, ());
+        let novice = UserTier::Novice;
+
+        env.as_contract(&contract_id, || {
+            for window in [3600u64, 7200] {
+                for i in 0..5 {
+                    env.ledger().set_timestamp(window + i);
+                    RateLimiter::record_swap(&env, &user, env.ledger().timestamp());
+                }
+                env.ledger().set_timestamp(window + 100);
+                assert!(RateLimiter::check_swap_limit(&env, &user, &novice).is_err());
+            }
+
+            // Penalty from hour 2 runs until 14400; hour 3 passes with no attempts.
+            // Hour 5 violation starts over at a single strike.
+            for i in 0..5 {
+                env.ledger().set_timestamp(18000 + i);
+                RateLimiter::record_swap(&env, &user, env.ledger().timestamp());
+            }
+            env.ledger().set_timestamp(18100);
+            let status = RateLimiter::check_swap_limit(&env, &user, &novice).unwrap_err();
+            assert_eq!(status.cooldown_ms, (21600 - 18100) * 1000);
+            assert_eq!(RateLimiter::get_penalty(&env, &user, symbol_short!("pen_swap")).unwrap().strikes, 1);
+        });
+    }
+
+    #[test]
+    fn test_export_user_state_reflects_counts_and_windows() {
+        let (env, user) = create_test_env();
+        let contract_id = env.register(CounterContract, ());
+        let novice = UserTier::Novice;
+
+        env.as_contract(&contract_id, || {
+            // Three swaps in the 02:00 hour, two LP ops on day one
+            for i in 0..3 {
+                env.ledger().set_timestamp(7200 + i);
+                RateLimiter::record_swap(&env, &user, env.ledger().timestamp());
+            }
+            for i in 0..2 {
+                env.ledger().set_timestamp(7300 + i);
+                RateLimiter::record_lp_op(&env, &user, env.ledger().timestamp());
+            }
+
+            env.ledger().set_timestamp(7400);
+            let snapshot = RateLimiter::export_user_state(&env, &user);
+            assert_eq!(snapshot.user, user);
+            assert_eq!(snapshot.taken_at, 7400);
+            assert_eq!(snapshot.tier, novice);
+            assert!(!snapshot.allowlisted);
+            assert_eq!(snapshot.swap_window_start, 3801);
+            assert_eq!(snapshot.swap_count, 3);
+            assert_eq!(snapshot.swaps_per_hour, 5);
+            assert_eq!(snapshot.lp_window_start, 0);
+            assert_eq!(snapshot.lp_count, 2);
+            assert_eq!(snapshot.lp_ops_per_day, 10);
+            assert!(!snapshot.has_swap_penalty);
+            assert_eq!(snapshot.swap_penalty, PenaltyState::default());
+            assert!(!snapshot.has_lp_penalty);
+            assert_eq!(snapshot.lp_penalty, PenaltyState::default());
+
+            // Filling the hour and retrying leaves a penalty in the snapshot
+            for _ in 0..2 {
+                RateLimiter::record_swap(&env, &user, env.ledger().timestamp());
+            }
+            assert!(RateLimiter::check_swap_limit(&env, &user, &novice).is_err());
+            let snapshot = RateLimiter::export_user_state(&env, &user);
+            assert_eq!(snapshot.swap_count, 5);
+            assert!(snapshot.has_swap_penalty);
+            assert_eq!(snapshot.swap_penalty.strikes, 1);
+            assert!(!snapshot.has_lp_penalty);
+
+            // An hour after the first swap, only that one has aged out
+            env.ledger().set_timestamp(10_800);
+            let snapshot = RateLimiter::export_user_state(&env, &user);
+            assert_eq!(snapshot.swap_window_start, 7201);
+            assert_eq!(snapshot.swap_count, 4);
+            assert_eq!(snapshot.lp_count, 2);
+        });
+    }
 }
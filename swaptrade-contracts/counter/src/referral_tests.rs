@@ -0,0 +1,747 @@
+use soroban_sdk::{testutils::{Address as _, Ledger}, Address, Env, Symbol};
+use crate::CounterContract;
+use crate::referral::{ReferralSystem, CommissionConfig, CommissionTier, ReferralMilestone, CLAIM_RATE_LIMIT_SECS};
+
+#[test]
+fn test_generate_referral_code_with_nft() {
+    let env = Env::default();
+    let mut system = ReferralSystem::new(&env);
+    
+    let user = Address::generate(&env);
+    let code = system.generate_referral_code(&env, user.clone());
+    
+    assert!(!code.to_string().is_empty());
+    
+    // Check that user received a starter badge
+    let stats = system.get_referral_stats(&env, user);
+    assert_eq!(stats.badges.len(), 1);
+    assert_eq!(stats.badges.get(0).unwrap().milestone, ReferralMilestone::Starter);
+    assert_eq!(stats.referral_code, code);
+}
+
+#[test]
+fn test_register_with_code_nft_reward() {
+    let env = Env::default();
+    let mut system = ReferralSystem::new(&env);
+    
+    let referrer = Address::generate(&env);
+    let referee = Address::generate(&env);
+    
+    // Generate referral code
+    let code = system.generate_referral_code(&env, referrer.clone());
+    
+    // Register referee with code
+    let result = system.register_with_code(&env, code.clone(), referee.clone());
+    assert!(result.is_ok());
+    
+    let welcome_badge = result.unwrap();
+    assert_eq!(welcome_badge.milestone, ReferralMilestone::Starter);
+    
+    // Check referrer stats updated
+    let referrer_stats = system.get_referral_stats(&env, referrer);
+    assert_eq!(referrer_stats.direct_referral_count, 1);
+    assert_eq!(referrer_stats.total_referral_count, 1);
+}
+
+#[test]
+fn test_three_tier_commission_distribution() {
+    let env = Env::default();
+    let mut system = ReferralSystem::new(&env);
+    
+    // Create 3-level referral chain: A -> B -> C -> D
+    let user_a = Address::generate(&env);
+    let user_b = Address::generate(&env);
+    let user_c = Address::generate(&env);
+    let user_d = Address::generate(&env);
+    
+    // Set up referral chain
+    let code_a = system.generate_referral_code(&env, user_a.clone());
+    let _badge_b = system.register_with_code(&env, code_a.clone(), user_b.clone()).unwrap();
+    let code_b = system.generate_referral_code(&env, user_b.clone());
+    let _badge_c = system.register_with_code(&env, code_b.clone(), user_c.clone()).unwrap();
+    let code_c = system.generate_referral_code(&env, user_c.clone());
+    let _badge_d = system.register_with_code(&env, code_c.clone(), user_d.clone()).unwrap();
+    
+    // User D makes a trade with 1000 fee
+    let trade_fee = 1000i128;
+    let distributions = system.distribute_commission(&env, user_d.clone(), trade_fee, 1);
+    
+    // Should have 3 distributions (20%, 10%, 5%)
+    assert_eq!(distributions.len(), 3);
+    
+    // Check distribution amounts
+    let mut found_direct = false;
+    let mut found_secondary = false;
+    let mut found_tertiary = false;
+    
+    for i in 0..distributions.len() {
+        if let Some((recipient, amount, tier)) = distributions.get(i) {
+            match tier {
+                CommissionTier::Direct => {
+                    assert_eq!(amount, 200); // 20% of 1000
+                    assert_eq!(recipient, user_c);
+                    found_direct = true;
+                }
+                CommissionTier::Secondary => {
+                    assert_eq!(amount, 100); // 10% of 1000
+                    assert_eq!(recipient, user_b);
+                    found_secondary = true;
+                }
+                CommissionTier::Tertiary => {
+                    assert_eq!(amount, 50); // 5% of 1000
+                    assert_eq!(recipient, user_a);
+                    found_tertiary = true;
+                }
+            }
+        }
+    }
+    
+    assert!(found_direct && found_secondary && found_tertiary);
+}
+
+#[test]
+fn test_commission_distribution_emits_audit_events() {
+    use soroban_sdk::{testutils::Events as _, TryIntoVal};
+
+    let env = Env::default();
+    let contract_id = env.register(CounterContract, ());
+
+    env.as_contract(&contract_id, || {
+        let mut system = ReferralSystem::new(&env);
+
+        // A -> B -> C -> D
+        let user_a = Address::generate(&env);
+        let user_b = Address::generate(&env);
+        let user_c = Address::generate(&env);
+        let user_d = Address::generate(&env);
+        let code_a = system.generate_referral_code(&env, user_a.clone());
+        system.register_with_code(&env, code_a.clone(), user_b.clone()).unwrap();
+        let code_b = system.generate_referral_code(&env, user_b.clone());
+        system.register_with_code(&env, code_b.clone(), user_c.clone()).unwrap();
+        let code_c = system.generate_referral_code(&env, user_c.clone());
+        system.register_with_code(&env, code_c.clone(), user_d.clone()).unwrap();
+
+        system.distribute_commission(&env, user_d.clone(), 1000i128, 1);
+
+        let mut paid: std::vec::Vec<(Address, CommissionTier, i128)> = std::vec::Vec::new();
+        let mut summaries: std::vec::Vec<(u32, i128)> = std::vec::Vec::new();
+        for (_, topics, data) in env.events().all().iter() {
+            let name: Symbol = topics.get(0).unwrap().try_into_val(&env).unwrap();
+            if name != Symbol::new(&env, "AuditEvent") {
+                continue;
+            }
+            let category: Symbol = topics.get(1).unwrap().try_into_val(&env).unwrap();
+            let trader: Address = topics.get(2).unwrap().try_into_val(&env).unwrap();
+            assert_eq!(category, Symbol::new(&env, "Trading"));
+            assert_eq!(trader, user_d);
+
+            let fields: soroban_sdk::Vec<soroban_sdk::Val> = data.try_into_val(&env).unwrap();
+            let action: Symbol = fields.get(0).unwrap().try_into_val(&env).unwrap();
+            if action == Symbol::new(&env, "COMMISSION_PAID") {
+                let (_, referrer, tier, amount, _): (Symbol, Address, CommissionTier, i128, i64) = data.try_into_val(&env).unwrap();
+                paid.push((referrer, tier, amount));
+            } else {
+                let (_, recipients, total, _): (Symbol, u32, i128, i64) = data.try_into_val(&env).unwrap();
+                summaries.push((recipients, total));
+            }
+        }
+
+        assert_eq!(
+            paid,
+            std::vec![
+                (user_c, CommissionTier::Direct, 200),
+                (user_b, CommissionTier::Secondary, 100),
+                (user_a, CommissionTier::Tertiary, 50),
+            ]
+        );
+        assert_eq!(summaries, std::vec![(3, 350)]);
+    });
+}
+
+#[test]
+fn test_anti_gaming_30_day_holding_period() {
+    let env = Env::default();
+    let mut system = ReferralSystem::new(&env);
+    
+    let referrer = Address::generate(&env);
+    let referee = Address::generate(&env);
+    
+    // Set up referral
+    let code = system.generate_referral_code(&env, referrer.clone());
+    let _badge = system.register_with_code(&env, code.clone(), referee.clone()).unwrap();
+    
+    // Distribute commission
+    let trade_fee = 1000i128;
+    system.distribute_commission(&env, referee.clone(), trade_fee, 1);
+    
+    // Try to claim immediately - should fail due to holding period
+    let claim_result = system.claim_commission(&env, referrer.clone());
+    assert!(claim_result.is_err());
+    assert_eq!(claim_result.unwrap_err(), "No commission available to claim");
+    
+    // Check pending commission
+    let pending = system.get_pending_commission(&env, referrer.clone());
+    assert_eq!(pending, 0); // Not claimable yet
+    
+    // Advance time by 30 days
+    env.ledger().set_timestamp(env.ledger().timestamp() + (30 * 24 * 60 * 60));
+    
+    // Now should be claimable
+    let pending = system.get_pending_commission(&env, referrer.clone());
+    assert_eq!(pending, 200); // 20% of 1000
+    
+    let claim_result = system.claim_commission(&env, referrer.clone());
+    assert!(claim_result.is_ok());
+    assert_eq!(claim_result.unwrap(), 200);
+}
+
+#[test]
+fn test_referrer_commission_grows_with_each_referee_trade() {
+    let env = Env::default();
+    let mut system = ReferralSystem::new(&env);
+
+    let referrer = Address::generate(&env);
+    let referee1 = Address::generate(&env);
+    let referee2 = Address::generate(&env);
+    let code = system.generate_referral_code(&env, referrer.clone());
+    system.register_with_code(&env, code.clone(), referee1.clone()).unwrap();
+    system.register_with_code(&env, code, referee2.clone()).unwrap();
+
+    // The referrer's own trades earn them nothing
+    system.distribute_commission(&env, referrer.clone(), 1000i128, 1);
+    assert_eq!(system.get_referral_stats(&env, referrer.clone()).available_commission, 0);
+
+    system.distribute_commission(&env, referee1, 1000i128, 1);
+    assert_eq!(system.get_referral_stats(&env, referrer.clone()).available_commission, 200);
+    system.distribute_commission(&env, referee2.clone(), 1000i128, 1);
+    assert_eq!(system.get_referral_stats(&env, referrer.clone()).available_commission, 400);
+
+    // Referees earn nothing from their own trades either
+    assert_eq!(system.get_referral_stats(&env, referee2).available_commission, 0);
+}
+
+#[test]
+fn test_rate_limited_commission_claims() {
+    let env = Env::default();
+    let mut system = ReferralSystem::new(&env);
+    
+    let referrer = Address::generate(&env);
+    let referee = Address::generate(&env);
+    
+    // Set up referral and commission
+    let code = system.generate_referral_code(&env, referrer.clone());
+    let _badge = system.register_with_code(&env, code.clone(), referee.clone()).unwrap();
+    
+    // Distribute commission and let it mature
+    system.distribute_commission(&env, referee.clone(), 1000i128, 1);
+    env.ledger().set_timestamp(env.ledger().timestamp() + (30 * 24 * 60 * 60));
+    
+    // First claim should succeed
+    let claim1 = system.claim_commission(&env, referrer.clone());
+    assert!(claim1.is_ok());
+    assert_eq!(claim1.unwrap(), 200);
+    
+    // Second claim immediately should fail due to rate limit
+    let claim2 = system.claim_commission(&env, referrer.clone());
+    assert!(claim2.is_err());
+    assert_eq!(claim2.unwrap_err(), "Rate limit: Please wait before claiming again");
+    
+    // Advance time by 1 hour - should work again
+    env.ledger().set_timestamp(env.ledger().timestamp() + 3600);
+    
+    // Need more commission to claim
+    system.distribute_commission(&env, referee.clone(), 1000i128, 1);
+    env.ledger().set_timestamp(env.ledger().timestamp() + (30 * 24 * 60 * 60));
+    
+    let claim3 = system.claim_commission(&env, referrer.clone());
+    assert!(claim3.is_ok());
+    assert_eq!(claim3.unwrap(), 200);
+}
+
+#[test]
+fn test_milestone_badge_awarding() {
+    let env = Env::default();
+    let mut system = ReferralSystem::new(&env);
+    
+    let referrer = Address::generate(&env);
+    let code = system.generate_referral_code(&env, referrer.clone());
+    
+    // Register 10 referees to trigger Recruiter milestone
+    for i in 0..10 {
+        let referee = Address::generate(&env);
+        let _badge = system.register_with_code(&env, code.clone(), referee.clone()).unwrap();
+        
+        // Check milestone progression
+        let stats = system.get_referral_stats(&env, referrer.clone());
+        
+        if i < 1 {
+            assert_eq!(stats.badges.len(), 1); // Only Starter
+        } else if i < 9 {
+            assert_eq!(stats.badges.len(), 1); // Still only Starter
+        } else {
+            // Should have Recruiter badge now
+            assert_eq!(stats.badges.len(), 2);
+            
+            let has_recruiter = stats.badges.iter().any(|badge| badge.milestone == ReferralMilestone::Recruiter);
+            assert!(has_recruiter);
+        }
+    }
+}
+
+#[test]
+fn test_referral_chain_validation() {
+    let env = Env::default();
+    let mut system = ReferralSystem::new(&env);
+    
+    // Create referral chain: A -> B -> C -> D -> E (4 levels)
+    let users: Vec<Address> = (0..5).map(|_| Address::generate(&env)).collect();
+    
+    // Set up chain
+    let code_a = system.generate_referral_code(&env, users.get(0).unwrap().clone());
+    let _badge_b = system.register_with_code(&env, code_a.clone(), users.get(1).unwrap().clone()).unwrap();
+    let code_b = system.generate_referral_code(&env, users.get(1).unwrap().clone());
+    let _badge_c = system.register_with_code(&env, code_b.clone(), users.get(2).unwrap().clone()).unwrap();
+    let code_c = system.generate_referral_code(&env, users.get(2).unwrap().clone());
+    let _badge_d = system.register_with_code(&env, code_c.clone(), users.get(3).unwrap().clone()).unwrap();
+    let code_d = system.generate_referral_code(&env, users.get(3).unwrap().clone());
+    let _badge_e = system.register_with_code(&env, code_d.clone(), users.get(4).unwrap().clone()).unwrap();
+    
+    // User E (4th level) makes trade - should only distribute to first 3 levels
+    let distributions = system.distribute_commission(&env, users.get(4).unwrap().clone(), 1000i128, 1);
+    
+    // Should only have 3 distributions (max depth)
+    assert_eq!(distributions.len(), 3);
+    
+    // User D should get direct commission (20%)
+    let user_d_got = distributions.iter().any(|(addr, _, tier)| {
+        addr == *users.get(3).unwrap() && matches!(tier, CommissionTier::Direct)
+    });
+    assert!(user_d_got);
+    
+    // User C should get secondary commission (10%)
+    let user_c_got = distributions.iter().any(|(addr, _, tier)| {
+        addr == *users.get(2).unwrap() && matches!(tier, CommissionTier::Secondary)
+    });
+    assert!(user_c_got);
+    
+    // User B should get tertiary commission (5%)
+    let user_b_got = distributions.iter().any(|(addr, _, tier)| {
+        addr == *users.get(1).unwrap() && matches!(tier, CommissionTier::Tertiary)
+    });
+    assert!(user_b_got);
+    
+    // User A should get nothing (beyond 3 levels)
+    let user_a_got = distributions.iter().any(|(addr, _, _)| {
+        addr == *users.get(0).unwrap()
+    });
+    assert!(!user_a_got);
+}
+
+#[test]
+fn test_self_referral_prevention() {
+    let env = Env::default();
+    let mut system = ReferralSystem::new(&env);
+    
+    let user = Address::generate(&env);
+    let code = system.generate_referral_code(&env, user.clone());
+    
+    // Try to register with own code
+    let result = system.register_with_code(&env, code.clone(), user.clone());
+    assert!(result.is_err());
+    assert_eq!(result.unwrap_err(), "Cannot refer yourself");
+}
+
+#[test]
+fn test_invalid_referral_code() {
+    let env = Env::default();
+    let mut system = ReferralSystem::new(&env);
+    
+    let user = Address::generate(&env);
+    let invalid_code = Symbol::new(&env, "INVALID");
+    
+    // Try to register with invalid code
+    let result = system.register_with_code(&env, invalid_code, user);
+    assert!(result.is_err());
+    assert_eq!(result.unwrap_err(), "Invalid referral code");
+}
+
+#[test]
+fn test_duplicate_registration_prevention() {
+    let env = Env::default();
+    let mut system = ReferralSystem::new(&env);
+    
+    let referrer = Address::generate(&env);
+    let referee = Address::generate(&env);
+    
+    let code = system.generate_referral_code(&env, referrer.clone());
+    
+    // First registration should succeed
+    let result1 = system.register_with_code(&env, code.clone(), referee.clone());
+    assert!(result1.is_ok());
+    
+    // Second registration should fail
+    let result2 = system.register_with_code(&env, code.clone(), referee.clone());
+    assert!(result2.is_err());
+    assert_eq!(result2.unwrap_err(), "User already registered");
+}
+
+#[test]
+fn test_comprehensive_referral_stats() {
+    let env = Env::default();
+    env.ledger().set_timestamp(1_000);
+    let mut system = ReferralSystem::new(&env);
+    
+    let referrer = Address::generate(&env);
+    let code = system.generate_referral_code(&env, referrer.clone());
+    
+    // Register multiple referees
+    for i in 0..5 {
+        let referee = Address::generate(&env);
+        let _badge = system.register_with_code(&env, code.clone(), referee.clone()).unwrap();
+        
+        // Simulate some trading activity
+        if i < 3 {
+            system.distribute_commission(&env, referee, 1000i128, 1);
+        }
+    }
+    
+    let stats = system.get_referral_stats(&env, referrer);
+    assert_eq!(stats.direct_referral_count, 5);
+    assert_eq!(stats.total_referral_count, 5);
+    assert_eq!(stats.referral_code, code);
+    assert!(stats.registration_timestamp > 0);
+    assert_eq!(stats.badges.len(), 1); // Starter badge
+}
+
+#[test]
+fn test_global_statistics_tracking() {
+    let env = Env::default();
+    let mut system = ReferralSystem::new(&env);
+    
+    let referrer = Address::generate(&env);
+    let referee = Address::generate(&env);
+    
+    let code = system.generate_referral_code(&env, referrer.clone());
+    let _badge = system.register_with_code(&env, code.clone(), referee.clone()).unwrap();
+    
+    // Check initial global stats
+    let (total_referrals, total_commission) = system.get_global_stats();
+    assert_eq!(total_referrals, 1);
+    assert_eq!(total_commission, 0);
+    
+    // Distribute, let it mature and claim commission
+    system.distribute_commission(&env, referee, 1000i128, 1);
+    env.ledger().set_timestamp(env.ledger().timestamp() + (30 * 24 * 60 * 60));
+    let _claimed = system.claim_commission(&env, referrer).unwrap();
+    
+    // Check updated global stats
+    let (total_referrals, total_commission) = system.get_global_stats();
+    assert_eq!(total_referrals, 1);
+    assert_eq!(total_commission, 200); // 20% of 1000
+}
+
+#[test]
+fn test_nft_badge_uniqueness() {
+    let env = Env::default();
+    let mut system = ReferralSystem::new(&env);
+    
+    let user1 = Address::generate(&env);
+    let user2 = Address::generate(&env);
+    
+    // Generate referral codes for both users
+    let _code1 = system.generate_referral_code(&env, user1.clone());
+    let _code2 = system.generate_referral_code(&env, user2.clone());
+    
+    let stats1 = system.get_referral_stats(&env, user1);
+    let stats2 = system.get_referral_stats(&env, user2);
+    
+    // Each should have unique badge with different token IDs
+    assert_eq!(stats1.badges.len(), 1);
+    assert_eq!(stats2.badges.len(), 1);
+    
+    let badge1 = stats1.badges.get(0).unwrap();
+    let badge2 = stats2.badges.get(0).unwrap();
+    
+    assert_ne!(badge1.token_id, badge2.token_id);
+    assert_eq!(badge1.milestone, ReferralMilestone::Starter);
+    assert_eq!(badge2.milestone, ReferralMilestone::Starter);
+}
+
+#[test]
+fn test_churn_scenario_referee_leaves() {
+    let env = Env::default();
+    let mut system = ReferralSystem::new(&env);
+    
+    let referrer = Address::generate(&env);
+    let referee = Address::generate(&env);
+    
+    // Set up referral
+    let code = system.generate_referral_code(&env, referrer.clone());
+    let _badge = system.register_with_code(&env, code.clone(), referee.clone()).unwrap();
+    
+    // Referee generates commission
+    system.distribute_commission(&env, referee.clone(), 1000i128, 1);
+    
+    // Referrer should have pending commission
+    let pending = system.get_pending_commission(&env, referrer.clone());
+    assert_eq!(pending, 0); // Not claimable yet
+    
+    // Advance time and claim
+    env.ledger().set_timestamp(env.ledger().timestamp() + (30 * 24 * 60 * 60));
+    let claimed = system.claim_commission(&env, referrer.clone()).unwrap();
+    assert_eq!(claimed, 200);
+    
+    // Referrer's stats should be preserved
+    let stats = system.get_referral_stats(&env, referrer);
+    assert_eq!(stats.direct_referral_count, 1);
+    assert_eq!(stats.total_commission_earned, 200);
+    assert_eq!(stats.available_commission, 0);
+}
+
+#[test]
+fn test_referral_codes_unique_and_well_distributed_in_one_ledger() {
+    let env = Env::default();
+    let mut system = ReferralSystem::new(&env);
+
+    let mut codes = std::collections::HashSet::new();
+    let mut counts = std::collections::HashMap::new();
+    for _ in 0..100 {
+        let user = Address::generate(&env);
+        let code = system.generate_referral_code(&env, user).to_string();
+        assert_eq!(code.len(), 8);
+        for c in code.chars() {
+            *counts.entry(c).or_insert(0u32) += 1;
+        }
+        assert!(codes.insert(code), "duplicate referral code");
+    }
+
+    // 800 characters over a 36-symbol alphabet: ~22 each. A seed that only
+    // varied with the ledger would pile onto a handful of characters.
+    assert!(counts.len() >= 30, "only {} distinct characters", counts.len());
+    assert!(counts.values().all(|&n| n < 60), "skewed character distribution");
+}
+
+#[test]
+fn test_referrer_override_changes_only_that_referrer() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(CounterContract, ());
+    let admin = Address::generate(&env);
+
+    env.as_contract(&contract_id, || {
+        env.storage().persistent().set(&crate::storage::ADMIN_KEY, &admin);
+        let mut system = ReferralSystem::new(&env);
+
+        let partner = Address::generate(&env);
+        let regular = Address::generate(&env);
+        let partner_referee = Address::generate(&env);
+        let regular_referee = Address::generate(&env);
+
+        let partner_code = system.generate_referral_code(&env, partner.clone());
+        system.register_with_code(&env, partner_code, partner_referee.clone()).unwrap();
+        let regular_code = system.generate_referral_code(&env, regular.clone());
+        system.register_with_code(&env, regular_code, regular_referee.clone()).unwrap();
+
+        let custom = CommissionConfig { direct_pct: 30, secondary_pct: 10, tertiary_pct: 5 };
+        system.set_referrer_override(&env, admin.clone(), partner.clone(), custom).unwrap();
+
+        let partner_dist = system.distribute_commission(&env, partner_referee, 1000i128, 1);
+        let (to, amount, tier) = partner_dist.get(0).unwrap();
+        assert_eq!(to, partner);
+        assert_eq!(amount, 300);
+        assert_eq!(tier, CommissionTier::Direct);
+
+        let regular_dist = system.distribute_commission(&env, regular_referee, 1000i128, 1);
+        let (to, amount, _) = regular_dist.get(0).unwrap();
+        assert_eq!(to, regular);
+        assert_eq!(amount, 200, "default 20% still applies");
+    });
+}
+
+#[test]
+fn test_referrer_override_requires_admin() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(CounterContract, ());
+
+    env.as_contract(&contract_id, || {
+        env.storage().persistent().set(&crate::storage::ADMIN_KEY, &Address::generate(&env));
+        let mut system = ReferralSystem::new(&env);
+
+        let stranger = Address::generate(&env);
+        let result = system.set_referrer_override(
+            &env,
+            stranger,
+            Address::generate(&env),
+            CommissionConfig::default_split(),
+        );
+        assert_eq!(result, Err("Unauthorized"));
+    });
+}
+
+#[test]
+fn test_configurable_holding_period() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(CounterContract, ());
+    let admin = Address::generate(&env);
+
+    env.as_contract(&contract_id, || {
+        env.storage().persistent().set(&crate::storage::ADMIN_KEY, &admin);
+        let mut system = ReferralSystem::new(&env);
+        assert_eq!(system.get_holding_period(), 30 * 24 * 60 * 60);
+
+        let referrer = Address::generate(&env);
+        let referee = Address::generate(&env);
+        let code = system.generate_referral_code(&env, referrer.clone());
+        system.register_with_code(&env, code.clone(), referee.clone()).unwrap();
+
+        // Earned under the 30-day default
+        system.distribute_commission(&env, referee.clone(), 1000i128, 1);
+
+        let week = 7 * 24 * 60 * 60;
+        assert_eq!(system.set_holding_period(&env, Address::generate(&env), week), Err("Unauthorized"));
+        system.set_holding_period(&env, admin.clone(), week).unwrap();
+        system.distribute_commission(&env, referee.clone(), 500i128, 1);
+
+        env.ledger().set_timestamp(env.ledger().timestamp() + week - 1);
+        assert_eq!(system.get_pending_commission(&env, referrer.clone()), 0);
+
+        // Only the commission earned under the 7-day hold has matured
+        env.ledger().set_timestamp(env.ledger().timestamp() + 1);
+        assert_eq!(system.get_pending_commission(&env, referrer.clone()), 100);
+
+        env.ledger().set_timestamp(env.ledger().timestamp() + (23 * 24 * 60 * 60));
+        assert_eq!(system.get_pending_commission(&env, referrer), 300);
+    });
+}
+
+#[test]
+fn test_max_commission_depth_limits_payout_levels() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(CounterContract, ());
+    let admin = Address::generate(&env);
+
+    let mut system = ReferralSystem::new(&env);
+
+    // An address authorizes once per frame, so each admin call gets its own
+    env.as_contract(&contract_id, || {
+        env.storage().persistent().set(&crate::storage::ADMIN_KEY, &admin);
+        assert_eq!(system.set_max_commission_depth(&env, admin.clone(), 4), Err("Commission depth exceeds supported tiers"));
+    });
+    env.as_contract(&contract_id, || {
+        system.set_max_commission_depth(&env, admin.clone(), 2).unwrap();
+
+        // A -> B -> C -> D -> E: E's trade has a 4-deep referrer chain
+        let users: Vec<Address> = (0..5).map(|_| Address::generate(&env)).collect();
+        for i in 0..4 {
+            let code = system.generate_referral_code(&env, users[i].clone());
+            system.register_with_code(&env, code.clone(), users[i + 1].clone()).unwrap();
+        }
+
+        let distributions = system.distribute_commission(&env, users[4].clone(), 1000i128, 1);
+        assert_eq!(distributions.len(), 2);
+        assert_eq!(distributions.get(0).unwrap(), (users[3].clone(), 200, CommissionTier::Direct));
+        assert_eq!(distributions.get(1).unwrap(), (users[2].clone(), 100, CommissionTier::Secondary));
+    });
+}
+
+#[test]
+fn test_claim_all_matured_collects_every_batch_at_once() {
+    let env = Env::default();
+    let mut system = ReferralSystem::new(&env);
+
+    let referrer = Address::generate(&env);
+    let referee = Address::generate(&env);
+    let code = system.generate_referral_code(&env, referrer.clone());
+    let _badge = system.register_with_code(&env, code.clone(), referee.clone()).unwrap();
+
+    // Two batches earned a day apart, so they mature a day apart
+    system.distribute_commission(&env, referee.clone(), 1000i128, 1);
+    env.ledger().set_timestamp(env.ledger().timestamp() + (24 * 60 * 60));
+    system.distribute_commission(&env, referee.clone(), 500i128, 1);
+
+    // Nothing matured yet: the failed claim must not start the hourly window
+    assert_eq!(system.claim_all_matured(&env, referrer.clone()), Err("No commission available to claim"));
+
+    env.ledger().set_timestamp(env.ledger().timestamp() + (30 * 24 * 60 * 60));
+    assert_eq!(system.get_pending_commission(&env, referrer.clone()), 300);
+    assert_eq!(system.claim_all_matured(&env, referrer.clone()), Ok(300));
+    assert_eq!(system.get_pending_commission(&env, referrer.clone()), 0);
+
+    // The paying claim opened the window for both entry points
+    assert_eq!(
+        system.claim_commission(&env, referrer.clone()),
+        Err("Rate limit: Please wait before claiming again")
+    );
+}
+
+#[test]
+fn test_claim_commission_pays_only_the_oldest_matured_batch() {
+    let env = Env::default();
+    let mut system = ReferralSystem::new(&env);
+
+    let referrer = Address::generate(&env);
+    let referee = Address::generate(&env);
+    let code = system.generate_referral_code(&env, referrer.clone());
+    let _badge = system.register_with_code(&env, code.clone(), referee.clone()).unwrap();
+
+    system.distribute_commission(&env, referee.clone(), 1000i128, 1);
+    env.ledger().set_timestamp(env.ledger().timestamp() + (24 * 60 * 60));
+    system.distribute_commission(&env, referee.clone(), 500i128, 1);
+    env.ledger().set_timestamp(env.ledger().timestamp() + (30 * 24 * 60 * 60));
+
+    // Both batches have matured, but the partial claim takes only the first
+    assert_eq!(system.claim_commission(&env, referrer.clone()), Ok(200));
+    assert_eq!(system.get_pending_commission(&env, referrer.clone()), 100);
+
+    // The partial claim opened the window shared with claim_all_matured
+    assert_eq!(
+        system.claim_all_matured(&env, referrer.clone()),
+        Err("Rate limit: Please wait before claiming again")
+    );
+
+    env.ledger().set_timestamp(env.ledger().timestamp() + CLAIM_RATE_LIMIT_SECS);
+    assert_eq!(system.claim_all_matured(&env, referrer.clone()), Ok(100));
+    assert_eq!(system.get_referral_stats(&env, referrer).total_commission_earned, 300);
+}
+
+#[test]
+fn test_referee_rebate_applies_only_within_first_trades() {
+    use crate::fee_progression::FeeProgression;
+    use crate::tiers::UserTier;
+
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(CounterContract, ());
+    let admin = Address::generate(&env);
+
+    env.as_contract(&contract_id, || {
+        env.storage().persistent().set(&crate::storage::ADMIN_KEY, &admin);
+        let mut system = ReferralSystem::new(&env);
+        let mut fees = FeeProgression::new(&env);
+
+        // 10 bps off the first trade
+        system.set_referee_rebate(&env, admin.clone(), 10, 1).unwrap();
+        let referrer = Address::generate(&env);
+        let referee = Address::generate(&env);
+        let code = system.generate_referral_code(&env, referrer.clone());
+        system.register_with_code(&env, code.clone(), referee.clone()).unwrap();
+        assert_eq!(system.get_referee_rebates_remaining(referee.clone()), 1);
+
+        // Novice 30 bps minus the 3 bps risk discount; the 10 bps rebate is
+        // held to the 9 bps cap, leaving 6 bps of rebate: 30 - 3 - 6 = 21
+        let (first, fee1) = fees.charge_trade_fee_with_referrals(&env, &referee, &UserTier::Novice, 10_000, &mut system);
+        assert_eq!((first.achievement_discount_bps, first.referee_rebate_bps, fee1), (3, 6, 21));
+        assert_eq!(system.get_referee_rebates_remaining(referee.clone()), 0);
+
+        // Counter exhausted: back to the achievement-discounted fee
+        let (second, fee2) = fees.charge_trade_fee_with_referrals(&env, &referee, &UserTier::Novice, 10_000, &mut system);
+        assert_eq!((second.referee_rebate_bps, fee2), (0, 27));
+
+        // The referrer was never a referee
+        let (own, _) = fees.charge_trade_fee_with_referrals(&env, &referrer, &UserTier::Novice, 10_000, &mut system);
+        assert_eq!(own.referee_rebate_bps, 0);
+    });
+}
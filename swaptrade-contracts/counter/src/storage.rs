@@ -3,3 +3,13 @@ use soroban_sdk::{Symbol, symbol_short};
 pub const ADMIN_KEY: Symbol = symbol_short!("admin");
 pub const PAUSED_KEY: Symbol = symbol_short!("paused");
 pub const POOL_REGISTRY_KEY: Symbol = symbol_short!("pools");
+pub const RATE_LIMIT_ALLOWLIST_KEY: Symbol = symbol_short!("rl_allow");
+pub const TRADE_BOUNDS_KEY: Symbol = symbol_short!("trd_bnds");
+pub const IDEMPOTENCY_KEY: Symbol = symbol_short!("idem_keys");
+pub const INVARIANT_CURSOR_KEY: Symbol = symbol_short!("inv_cur");
+pub const FEE_PROGRESSION_KEY: Symbol = symbol_short!("fee_prog");
+pub const REFERRAL_KEY: Symbol = symbol_short!("referral");
+pub const SWAP_COMMIT_KEY: Symbol = symbol_short!("swp_cmt");
+pub const REGISTRY_LP_CURSOR_KEY: Symbol = symbol_short!("inv_lpcur");
+pub const ATTESTED_ROOTS_KEY: Symbol = symbol_short!("att_roots");
+pub const ALLOWANCE_KEY: Symbol = symbol_short!("allowance");
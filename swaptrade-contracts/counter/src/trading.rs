@@ -5,12 +5,14 @@ use crate::errors::SwapTradeError;
 use crate::storage::PAUSED_KEY;
 use crate::tiers::UserTier;
 use crate::fee_progression::FeeProgression;
+use crate::referral::ReferralSystem;
 
 pub fn swap(
     env: Env,
     user: Address,
     amount: i128,
     fee_progression: &mut FeeProgression,
+    referrals: &mut ReferralSystem,
     user_tier: &UserTier,
 ) -> Result<i128, SwapTradeError> {
     user.require_auth();
@@ -25,9 +27,9 @@ pub fn swap(
         return Err(SwapTradeError::TradingPaused);
     }
 
-    // Calculate effective fee with achievement discounts
-    let fee_result = user_tier.calculate_effective_fee_with_achievements(fee_progression, &env, &user);
-    let fee_amount = (amount * fee_result.effective_fee_bps as i128) / 10000;
+    // Calculate effective fee with achievement discounts, then any referee rebate
+    let (fee_result, fee_amount) =
+        fee_progression.charge_trade_fee_with_referrals(&env, &user, user_tier, amount, referrals);
 
     // Emit fee calculation event for transparency
     env.events().publish(
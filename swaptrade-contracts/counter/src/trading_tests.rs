@@ -1,12 +1,13 @@
 #![cfg(test)]
 
 use super::*;
-use soroban_sdk::{symbol_short, Address, Env};
+use soroban_sdk::{symbol_short, testutils::Address as _, Address, Env};
 
 // 1) Happy path: simple swap XLM -> USDCSIM
 #[test]
 fn test_swap_happy_path() {
     let env = Env::default();
+    env.mock_all_auths();
     let contract_id = env.register(CounterContract, ());
     let client = CounterContractClient::new(&env, &contract_id);
 
@@ -14,14 +15,14 @@ fn test_swap_happy_path() {
     let xlm = symbol_short!("XLM");
     let usdc = symbol_short!("USDCSIM");
 
-    // Mint XLM and perform swap
+    // Mint XLM and perform swap; the Novice 30 bps fee on 500 floors to 1
     client.mint(&xlm, &user, &1000);
     let out = client.swap(&xlm, &usdc, &500, &user);
-    assert_eq!(out, 500);
+    assert_eq!(out, 499);
 
     // Balances updated
     assert_eq!(client.get_balance(&xlm, &user), 500);
-    assert_eq!(client.get_balance(&usdc, &user), 500);
+    assert_eq!(client.get_balance(&usdc, &user), 499);
 }
 
 // 2) Edge: insufficient balance should panic in swap (perform_swap uses assert)
@@ -29,6 +30,7 @@ fn test_swap_happy_path() {
 #[should_panic(expected = "Insufficient funds")]
 fn test_swap_insufficient_balance_panics() {
     let env = Env::default();
+    env.mock_all_auths();
     let contract_id = env.register(CounterContract, ());
     let client = CounterContractClient::new(&env, &contract_id);
 
@@ -40,9 +42,9 @@ fn test_swap_insufficient_balance_panics() {
     client.swap(&xlm, &usdc, &100, &user);
 }
 
-// 3) try_swap should not panic and should count failed orders
+// 3) safe_swap should not panic and should count failed orders
 #[test]
-fn test_try_swap_handles_invalid_inputs_and_counts_failed() {
+fn test_safe_swap_handles_invalid_inputs_and_counts_failed() {
     let env = Env::default();
     let contract_id = env.register(CounterContract, ());
     let client = CounterContractClient::new(&env, &contract_id);
@@ -51,12 +53,12 @@ fn test_try_swap_handles_invalid_inputs_and_counts_failed() {
     let xlm = symbol_short!("XLM");
 
     // invalid pair (same token) -> returns 0
-    let out = client.try_swap(&xlm, &xlm, &100, &user).expect("client.try_swap failed").expect("try_swap returned error");
+    let out = client.safe_swap(&xlm, &xlm, &100, &user);
     assert_eq!(out, 0);
 
     // negative amount -> returns 0
     let usdc = symbol_short!("USDCSIM");
-    let out2 = client.try_swap(&xlm, &usdc, &-10, &user).expect("client.try_swap failed").expect("try_swap returned error");
+    let out2 = client.safe_swap(&xlm, &usdc, &-10, &user);
     assert_eq!(out2, 0);
 
     // metrics reflect failed orders
@@ -68,6 +70,7 @@ fn test_try_swap_handles_invalid_inputs_and_counts_failed() {
 #[test]
 fn test_swap_precision_truncation() {
     let env = Env::default();
+    env.mock_all_auths();
     let contract_id = env.register(CounterContract, ());
     let client = CounterContractClient::new(&env, &contract_id);
 
@@ -89,6 +92,7 @@ fn test_swap_precision_truncation() {
 #[test]
 fn test_amm_round_trip_identity() {
     let env = Env::default();
+    env.mock_all_auths();
     let contract_id = env.register(CounterContract, ());
     let client = CounterContractClient::new(&env, &contract_id);
 
@@ -112,6 +116,7 @@ fn test_amm_round_trip_identity() {
 #[test]
 fn test_concurrent_like_swaps_isolation() {
     let env = Env::default();
+    env.mock_all_auths();
     let contract_id = env.register(CounterContract, ());
     let client = CounterContractClient::new(&env, &contract_id);
 
@@ -144,6 +149,7 @@ fn test_concurrent_like_swaps_isolation() {
 #[should_panic(expected = "Amount must be positive")]
 fn test_swap_zero_amount_panics() {
     let env = Env::default();
+    env.mock_all_auths();
     let contract_id = env.register(CounterContract, ());
     let client = CounterContractClient::new(&env, &contract_id);
 
@@ -154,3 +160,229 @@ fn test_swap_zero_amount_panics() {
     client.mint(&xlm, &user, &100);
     client.swap(&xlm, &usdc, &0, &user);
 }
+
+// 8) Per-asset trade bounds reject dust and oversized swaps
+fn setup_bounded_xlm(env: &Env) -> (CounterContractClient<'_>, Address) {
+    env.mock_all_auths();
+    let contract_id = env.register(CounterContract, ());
+    let client = CounterContractClient::new(env, &contract_id);
+
+    let admin = Address::generate(env);
+    env.as_contract(&contract_id, || {
+        env.storage().persistent().set(&crate::storage::ADMIN_KEY, &admin);
+    });
+    client.set_trade_bounds(&admin, &symbol_short!("XLM"), &100, &10_000);
+    (client, admin)
+}
+
+#[test]
+fn test_swap_below_min_trade_amount_rejected() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, _admin) = setup_bounded_xlm(&env);
+
+    let user = Address::generate(&env);
+    let xlm = symbol_short!("XLM");
+    let usdc = symbol_short!("USDCSIM");
+    client.mint(&xlm, &user, &1_000);
+
+    let res = client.try_swap(&xlm, &usdc, &99, &user);
+    assert_eq!(
+        res,
+        Err(Ok(soroban_sdk::Error::from_contract_error(ContractError::InvalidAmount as u32)))
+    );
+    assert_eq!(client.get_balance(&xlm, &user), 1_000);
+
+    // Amounts inside the band still go through
+    assert_eq!(client.swap(&xlm, &usdc, &100, &user), 100);
+}
+
+#[test]
+fn test_swap_above_max_trade_amount_rejected() {
+    let env = Env::default();
+    let (client, _admin) = setup_bounded_xlm(&env);
+
+    let user = Address::generate(&env);
+    let xlm = symbol_short!("XLM");
+    let usdc = symbol_short!("USDCSIM");
+    client.mint(&xlm, &user, &50_000);
+
+    let res = client.try_swap(&xlm, &usdc, &10_001, &user);
+    assert_eq!(
+        res,
+        Err(Ok(soroban_sdk::Error::from_contract_error(ContractError::InvalidAmount as u32)))
+    );
+    assert_eq!(client.get_balance(&xlm, &user), 50_000);
+    assert_eq!(client.get_trade_bounds(&xlm), (100, 10_000));
+
+    // Assets without a configured band keep the default, unchecked range
+    assert_eq!(client.get_trade_bounds(&usdc), (1, i128::MAX));
+}
+
+// 9) Repeating an idempotency key returns the first result without re-executing
+#[test]
+fn test_swap_idempotent_repeat_key_returns_first_result() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(CounterContract, ());
+    let client = CounterContractClient::new(&env, &contract_id);
+
+    let user = Address::generate(&env);
+    let xlm = symbol_short!("XLM");
+    let usdc = symbol_short!("USDCSIM");
+    let precision: u128 = 1_000_000_000_000_000_000;
+    let key = BytesN::from_array(&env, &[7u8; 32]);
+
+    client.mint(&xlm, &user, &10_000);
+    client.set_price(&(xlm.clone(), usdc.clone()), &precision);
+    let first = client.swap_idempotent(&xlm, &usdc, &1_000, &user, &key);
+
+    let xlm_after_first = client.get_balance(&xlm, &user);
+    let usdc_after_first = client.get_balance(&usdc, &user);
+    let pool_after_first = env.as_contract(&contract_id, || {
+        let portfolio: Portfolio = env.storage().instance().get(&()).unwrap();
+        portfolio.get_pool_stats()
+    });
+
+    // Price doubles before the client retries
+    client.set_price(&(xlm.clone(), usdc.clone()), &(2 * precision));
+    let second = client.swap_idempotent(&xlm, &usdc, &1_000, &user, &key);
+
+    assert_eq!(second, first);
+    assert_eq!(client.get_balance(&xlm, &user), xlm_after_first);
+    assert_eq!(client.get_balance(&usdc, &user), usdc_after_first);
+    let pool_after_second = env.as_contract(&contract_id, || {
+        let portfolio: Portfolio = env.storage().instance().get(&()).unwrap();
+        portfolio.get_pool_stats()
+    });
+    assert_eq!(pool_after_second, pool_after_first);
+
+    // A fresh key executes at the new price
+    let fresh = BytesN::from_array(&env, &[8u8; 32]);
+    let third = client.swap_idempotent(&xlm, &usdc, &1_000, &user, &fresh);
+    assert!(third > first);
+}
+
+#[test]
+fn test_swap_idempotent_keys_are_per_user_and_expire_after_ttl() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(CounterContract, ());
+    let client = CounterContractClient::new(&env, &contract_id);
+
+    let alice = Address::generate(&env);
+    let bob = Address::generate(&env);
+    let xlm = symbol_short!("XLM");
+    let usdc = symbol_short!("USDCSIM");
+    let key = BytesN::from_array(&env, &[9u8; 32]);
+
+    client.mint(&xlm, &alice, &50);
+    client.mint(&xlm, &bob, &50);
+    client.swap_idempotent(&xlm, &usdc, &10, &alice, &key);
+
+    // The same key from another user is a different submission
+    client.swap_idempotent(&xlm, &usdc, &10, &bob, &key);
+    assert_eq!(client.get_balance(&xlm, &bob), 40);
+
+    // A retry is absorbed, and the record only lives out its TTL
+    client.swap_idempotent(&xlm, &usdc, &10, &alice, &key);
+    assert_eq!(client.get_balance(&xlm, &alice), 40);
+    let ttl = env.as_contract(&contract_id, || {
+        use soroban_sdk::testutils::storage::Temporary as _;
+        env.storage()
+            .temporary()
+            .get_ttl(&(crate::storage::IDEMPOTENCY_KEY, alice.clone(), key.clone()))
+    });
+    assert_eq!(ttl, crate::idempotency::IDEMPOTENCY_TTL_LEDGERS);
+}
+
+#[test]
+fn test_swap_idempotent_requires_user_auth() {
+    let env = Env::default();
+    let contract_id = env.register(CounterContract, ());
+    let client = CounterContractClient::new(&env, &contract_id);
+
+    let user = Address::generate(&env);
+    let xlm = symbol_short!("XLM");
+    let usdc = symbol_short!("USDCSIM");
+    let key = BytesN::from_array(&env, &[3u8; 32]);
+
+    client.mint(&xlm, &user, &50);
+    assert!(client.try_swap_idempotent(&xlm, &usdc, &10, &user, &key).is_err());
+    assert_eq!(client.get_balance(&xlm, &user), 50);
+}
+
+// 10) Pausing halts swaps, liquidity adds and mints but leaves exits open
+fn paused_error() -> Result<soroban_sdk::Error, soroban_sdk::InvokeError> {
+    Ok(soroban_sdk::Error::from_contract_error(ContractError::ContractPaused as u32))
+}
+
+#[test]
+fn test_pause_blocks_entries_but_not_exits() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(CounterContract, ());
+    let client = CounterContractClient::new(&env, &contract_id);
+
+    let admin = Address::generate(&env);
+    client.init_admin(&admin);
+
+    let user = Address::generate(&env);
+    let xlm = symbol_short!("XLM");
+    let usdc = symbol_short!("USDCSIM");
+    client.mint(&xlm, &user, &1_000);
+    client.mint(&usdc, &user, &1_000);
+    let lp_tokens = client.add_liquidity(&100, &100, &user);
+
+    client.pause(&admin);
+    assert!(client.is_paused());
+
+    assert_eq!(client.try_swap(&xlm, &usdc, &10, &user), Err(paused_error()));
+    assert_eq!(client.try_add_liquidity(&10, &10, &user), Err(paused_error()));
+    assert_eq!(client.try_mint(&xlm, &user, &10), Err(paused_error()));
+    assert_eq!(client.safe_swap(&xlm, &usdc, &10, &user), 0);
+    assert_eq!(client.get_balance(&xlm, &user), 900);
+
+    // Users can still pull their liquidity out
+    let (xlm_out, usdc_out) = client.remove_liquidity(&lp_tokens, &user);
+    assert!(xlm_out > 0 && usdc_out > 0);
+
+    client.unpause(&admin);
+    assert!(!client.is_paused());
+    assert!(client.swap(&xlm, &usdc, &10, &user) > 0);
+}
+
+#[test]
+fn test_pause_requires_admin() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(CounterContract, ());
+    let client = CounterContractClient::new(&env, &contract_id);
+
+    let stranger = Address::generate(&env);
+    assert_eq!(client.try_pause(&stranger), Err(Ok(SwapTradeError::NotAdmin)));
+    assert!(!client.is_paused());
+}
+
+#[test]
+fn test_init_admin_is_one_time_and_authenticated() {
+    let env = Env::default();
+    let contract_id = env.register(CounterContract, ());
+    let client = CounterContractClient::new(&env, &contract_id);
+
+    let admin = Address::generate(&env);
+    let stranger = Address::generate(&env);
+
+    // The would-be admin has to sign their own initialization
+    assert!(client.try_init_admin(&admin).is_err());
+
+    env.mock_all_auths();
+    client.init_admin(&admin);
+    assert_eq!(env.auths()[0].0, admin);
+    assert_eq!(client.try_init_admin(&stranger), Err(Ok(SwapTradeError::AdminAlreadySet)));
+
+    // Only the initialized admin can pause
+    assert_eq!(client.try_pause(&stranger), Err(Ok(SwapTradeError::NotAdmin)));
+    client.pause(&admin);
+    assert!(client.is_paused());
+}
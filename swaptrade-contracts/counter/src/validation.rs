@@ -1,12 +1,14 @@
-use soroban_sdk::{Address, Symbol, symbol_short};
-use crate::errors::ContractError;
+use soroban_sdk::{Address, Env, Symbol, symbol_short};
+use crate::admin::require_admin;
+use crate::errors::{ContractError, SwapTradeError};
+use crate::portfolio::Portfolio;
+use crate::storage::TRADE_BOUNDS_KEY;
 
 /// Safe upper bound (prevents overflow abuse)
 const MAX_AMOUNT: i128 = 1_000_000_000_000_000_000;
 
-/// Supported tokens (project-specific)
+/// Native token; the quote side comes from the portfolio's configured quote asset
 const XLM: Symbol = symbol_short!("XLM");
-const USDC_SIM: Symbol = symbol_short!("USDCSIM");
 
 pub fn validate_amount(amount: i128) -> Result<(), ContractError> {
     if amount <= 0 {
@@ -20,8 +22,53 @@ pub fn validate_amount(amount: i128) -> Result<(), ContractError> {
     Ok(())
 }
 
-pub fn validate_token_symbol(token: Symbol) -> Result<(), ContractError> {
-    if token != XLM && token != USDC_SIM {
+/// Default trade bounds for an asset with no configured band (check disabled).
+pub const DEFAULT_MIN_TRADE_AMOUNT: i128 = 1;
+pub const DEFAULT_MAX_TRADE_AMOUNT: i128 = i128::MAX;
+
+/// Configure the `[min, max]` trade size accepted for swaps out of `asset`. Admin only.
+pub fn set_trade_bounds(
+    env: &Env,
+    admin: &Address,
+    asset: Symbol,
+    min_trade_amount: i128,
+    max_trade_amount: i128,
+) -> Result<(), SwapTradeError> {
+    admin.require_auth();
+    require_admin(env, admin)?;
+
+    if min_trade_amount < DEFAULT_MIN_TRADE_AMOUNT || min_trade_amount > max_trade_amount {
+        return Err(SwapTradeError::InvalidTradeBounds);
+    }
+
+    env.storage()
+        .persistent()
+        .set(&(TRADE_BOUNDS_KEY, asset), &(min_trade_amount, max_trade_amount));
+    Ok(())
+}
+
+/// `(min_trade_amount, max_trade_amount)` for `asset`, or the defaults if unset.
+pub fn get_trade_bounds(env: &Env, asset: Symbol) -> (i128, i128) {
+    env.storage()
+        .persistent()
+        .get(&(TRADE_BOUNDS_KEY, asset))
+        .unwrap_or((DEFAULT_MIN_TRADE_AMOUNT, DEFAULT_MAX_TRADE_AMOUNT))
+}
+
+/// Reject trades of `asset` outside its configured band. Assets without a
+/// configured band are not checked, leaving amount validation to the swap itself.
+pub fn validate_trade_bounds(env: &Env, asset: Symbol, amount: i128) -> Result<(), ContractError> {
+    let bounds: Option<(i128, i128)> = env.storage().persistent().get(&(TRADE_BOUNDS_KEY, asset));
+    if let Some((min_trade_amount, max_trade_amount)) = bounds {
+        if amount < min_trade_amount || amount > max_trade_amount {
+            return Err(ContractError::InvalidAmount);
+        }
+    }
+    Ok(())
+}
+
+pub fn validate_token_symbol(portfolio: &Portfolio, token: Symbol) -> Result<(), ContractError> {
+    if token != XLM && token != portfolio.quote_symbol() {
         return Err(ContractError::InvalidTokenSymbol);
     }
     Ok(())
@@ -32,13 +79,13 @@ pub fn validate_user_address(_env: &soroban_sdk::Env, _address: &Address) -> Res
     Ok(())
 }
 
-pub fn validate_swap_pair(from: Symbol, to: Symbol) -> Result<(), ContractError> {
+pub fn validate_swap_pair(portfolio: &Portfolio, from: Symbol, to: Symbol) -> Result<(), ContractError> {
     if from == to {
         return Err(ContractError::InvalidSwapPair);
     }
 
-    validate_token_symbol(from)?;
-    validate_token_symbol(to)?;
+    validate_token_symbol(portfolio, from)?;
+    validate_token_symbol(portfolio, to)?;
 
     Ok(())
 }
@@ -0,0 +1,151 @@
+//! Trailing-window event counting shared by the contract's rate limits and
+//! the audit log's anomaly detector. Uses only `core`, so it builds both in
+//! the `no_std` contract and with `native-tools`.
+
+/// Most recent events a `WindowedCounter` remembers; counts saturate here
+pub const WINDOWED_COUNTER_CAPACITY: usize = 128;
+
+/// Timestamps of the most recent events, oldest first.
+///
+/// An event at `ts` is inside the `window` ending at `now` when
+/// `ts <= now && now - ts < window`. Every instant therefore sees exactly the
+/// last `window` units of history, so a burst straddling a fixed bucket
+/// boundary can't count toward two fresh buckets and reach twice the limit.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct WindowedCounter {
+    timestamps: [u64; WINDOWED_COUNTER_CAPACITY],
+    /// Slot holding the oldest remembered event
+    start: usize,
+    len: usize,
+}
+
+impl Default for WindowedCounter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl WindowedCounter {
+    pub const fn new() -> Self {
+        Self {
+            timestamps: [0; WINDOWED_COUNTER_CAPACITY],
+            start: 0,
+            len: 0,
+        }
+    }
+
+    /// Rebuild a counter from timestamps listed oldest first, as produced by `iter`
+    pub fn from_timestamps<I: IntoIterator<Item = u64>>(timestamps: I) -> Self {
+        let mut counter = Self::new();
+        for ts in timestamps {
+            counter.record(ts);
+        }
+        counter
+    }
+
+    /// Remember an event at `now`, forgetting the oldest one when full
+    pub fn record(&mut self, now: u64) {
+        if self.len == WINDOWED_COUNTER_CAPACITY {
+            self.timestamps[self.start] = now;
+            self.start = (self.start + 1) % WINDOWED_COUNTER_CAPACITY;
+        } else {
+            self.timestamps[(self.start + self.len) % WINDOWED_COUNTER_CAPACITY] = now;
+            self.len += 1;
+        }
+    }
+
+    /// Events inside the `window` ending at `now`
+    pub fn count_in_window(&self, now: u64, window: u64) -> u32 {
+        self.iter().filter(|&ts| in_window(ts, now, window)).count() as u32
+    }
+
+    /// Time until the oldest event inside the window drops out of it; 0 if
+    /// the window is empty
+    pub fn time_until_expiry(&self, now: u64, window: u64) -> u64 {
+        self.iter()
+            .find(|&ts| in_window(ts, now, window))
+            .map(|ts| ts.saturating_add(window) - now)
+            .unwrap_or(0)
+    }
+
+    /// Forget leading events that have already left the `window` ending at `now`
+    pub fn prune(&mut self, now: u64, window: u64) {
+        while self.len > 0 && now.saturating_sub(self.timestamps[self.start]) >= window {
+            self.start = (self.start + 1) % WINDOWED_COUNTER_CAPACITY;
+            self.len -= 1;
+        }
+    }
+
+    /// Remembered timestamps, oldest first
+    pub fn iter(&self) -> impl Iterator<Item = u64> + '_ {
+        (0..self.len).map(move |i| self.timestamps[(self.start + i) % WINDOWED_COUNTER_CAPACITY])
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+}
+
+fn in_window(ts: u64, now: u64, window: u64) -> bool {
+    ts <= now && now - ts < window
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_burst_across_boundary_is_not_reset() {
+        let mut counter = WindowedCounter::new();
+        for _ in 0..5 {
+            counter.record(3599);
+        }
+        // A fixed hourly bucket would start over at 3600
+        assert_eq!(counter.count_in_window(3600, 3600), 5);
+        for _ in 0..5 {
+            counter.record(3600);
+        }
+        assert_eq!(counter.count_in_window(3600, 3600), 10);
+
+        // Each event leaves exactly one window length after it happened
+        assert_eq!(counter.count_in_window(7198, 3600), 10);
+        assert_eq!(counter.count_in_window(7199, 3600), 5);
+        assert_eq!(counter.count_in_window(7200, 3600), 0);
+    }
+
+    #[test]
+    fn test_time_until_expiry_tracks_oldest_event_in_window() {
+        let counter = WindowedCounter::from_timestamps([100, 200, 4000]);
+        assert_eq!(counter.time_until_expiry(500, 3600), 3200);
+        // 100 and 200 have left the window; 4000 is now the oldest inside it
+        assert_eq!(counter.time_until_expiry(3800, 3600), 0);
+        assert_eq!(counter.time_until_expiry(4000, 3600), 3600);
+        assert_eq!(WindowedCounter::new().time_until_expiry(500, 3600), 0);
+    }
+
+    #[test]
+    fn test_future_events_and_zero_window_are_not_counted() {
+        let counter = WindowedCounter::from_timestamps([100, 900]);
+        assert_eq!(counter.count_in_window(500, 3600), 1);
+        assert_eq!(counter.count_in_window(900, 0), 0);
+    }
+
+    #[test]
+    fn test_prune_and_capacity() {
+        let mut counter = WindowedCounter::from_timestamps([10, 20, 30]);
+        counter.prune(3620, 3600);
+        assert_eq!(counter.iter().collect::<std::vec::Vec<_>>(), std::vec![30]);
+
+        let mut full = WindowedCounter::new();
+        for ts in 0..(WINDOWED_COUNTER_CAPACITY as u64 + 10) {
+            full.record(ts);
+        }
+        assert_eq!(full.len(), WINDOWED_COUNTER_CAPACITY);
+        assert_eq!(full.iter().next(), Some(10));
+        assert_eq!(full.count_in_window(1_000, 10_000), WINDOWED_COUNTER_CAPACITY as u32);
+    }
+}
@@ -1,6 +1,6 @@
 use soroban_sdk::{Env, Symbol, Address, symbol_short};
 // use crate::events::SwapExecuted;
-use crate::portfolio::{Portfolio, Asset};
+use crate::portfolio::{Portfolio, Asset, FailureReason};
 use crate::oracle::{get_stored_price, ContractError};
 
 const PRECISION: u128 = 1_000_000_000_000_000_000; // 1e18
@@ -8,10 +8,10 @@ const STALE_THRESHOLD_SECONDS: u64 = 600; // 10 minutes
 const LP_FEE_BPS: u128 = 30; // 0.3% = 30 basis points
 
 
-fn symbol_to_asset(sym: &Symbol) -> Option<Asset> {
+fn symbol_to_asset(portfolio: &Portfolio, sym: &Symbol) -> Option<Asset> {
     if *sym == symbol_short!("XLM") {
         Some(Asset::XLM)
-    } else if *sym == symbol_short!("USDCSIM") {
+    } else if *sym == portfolio.quote_symbol() {
         Some(Asset::Custom(sym.clone()))
     } else {
         None
@@ -43,6 +43,72 @@ fn get_price_with_staleness_check(env: &Env, from: Symbol, to: Symbol) -> Result
     Err(ContractError::PriceNotSet)
 }
 
+/// AMM output for swapping `amount` of `from` into `to` as `(with_fee, without_fee)`.
+/// With an empty pool both sides fall back to the oracle (or 1:1) rate.
+fn quote_outputs(env: &Env, portfolio: &Portfolio, from: &Symbol, to: &Symbol, amount: i128) -> (u128, u128) {
+    let xlm_liquidity = portfolio.get_liquidity(Asset::XLM);
+    let usdc_liquidity = portfolio.get_liquidity(portfolio.quote_asset());
+    let amount_u128 = amount as u128;
+    let (reserve_in, reserve_out) = if *from == symbol_short!("XLM") {
+        (xlm_liquidity as u128, usdc_liquidity as u128)
+    } else {
+        (usdc_liquidity as u128, xlm_liquidity as u128)
+    };
+
+    if reserve_in > 0 && reserve_out > 0 {
+        // Apply fee: amount_in_after_fee = amount_in * (1 - fee_bps / 10000)
+        let amount_in_after_fee = (amount_u128 * (10000 - LP_FEE_BPS)) / 10000;
+
+        // Constant product formula: (x + dx) * (y - dy) = x * y
+        // dy = (y * dx) / (x + dx)
+        let numerator = reserve_out.saturating_mul(amount_in_after_fee);
+        let denominator = reserve_in.saturating_add(amount_in_after_fee);
+        if denominator == 0 {
+            panic!("Division by zero in AMM calculation");
+        }
+
+        // Theoretical output without fee
+        let theoretical_out = reserve_out.saturating_mul(amount_u128) / reserve_in.saturating_add(amount_u128);
+        (numerator / denominator, theoretical_out)
+    } else {
+        // If no liquidity, use oracle price (fallback)
+        let price = match get_price_with_staleness_check(env, from.clone(), to.clone()) {
+            Ok(p) => p,
+            Err(ContractError::StalePrice) => panic!("Oracle price is stale"),
+            Err(ContractError::InvalidPrice) => panic!("Oracle price is invalid"),
+            Err(ContractError::PriceNotSet) => PRECISION, // Fallback to 1:1
+            _ => PRECISION,
+        };
+        ((amount_u128 * price) / PRECISION, amount_u128)
+    }
+}
+
+fn slippage_bps(actual_out: u128, theoretical_out: u128) -> u128 {
+    if theoretical_out == 0 {
+        return 0;
+    }
+    (theoretical_out.saturating_sub(actual_out) * 10000) / theoretical_out
+}
+
+fn max_slippage_bps(env: &Env) -> u32 {
+    env.storage().instance().get(&symbol_short!("MAX_SLIP")).unwrap_or(10000u32)
+}
+
+/// Pre-flight checks for a validated pair and amount: the reason `perform_swap`
+/// would reject the swap, so callers can decline without panicking.
+pub fn check_swap(env: &Env, portfolio: &Portfolio, from: &Symbol, to: &Symbol, amount: i128, user: &Address) -> Result<(), FailureReason> {
+    let from_asset = symbol_to_asset(portfolio, from).ok_or(FailureReason::InvalidInput)?;
+    if portfolio.balance_of(env, from_asset, user.clone()) < amount {
+        return Err(FailureReason::InsufficientBalance);
+    }
+
+    let (actual_out, theoretical_out) = quote_outputs(env, portfolio, from, to, amount);
+    if slippage_bps(actual_out, theoretical_out) > max_slippage_bps(env) as u128 {
+        return Err(FailureReason::SlippageExceeded);
+    }
+    Ok(())
+}
+
 /// Performs a swap with oracle pricing and slippage protection
 pub fn perform_swap(
     env: &Env,
@@ -55,8 +121,8 @@ pub fn perform_swap(
     assert!(amount > 0, "Amount must be positive");
     assert!(from != to, "Tokens must be different");
 
-    let from_asset = symbol_to_asset(&from).expect("Invalid from token");
-    let to_asset = symbol_to_asset(&to).expect("Invalid to token");
+    let from_asset = symbol_to_asset(portfolio, &from).expect("Invalid from token");
+    let to_asset = symbol_to_asset(portfolio, &to).expect("Invalid to token");
 
     // 1. Get Price (Default to 1:1 if not set, to support existing tests/defaults, or panic?)
     // Requirement: "Currently using hardcoded 1:1 (unrealistic)".
@@ -77,10 +143,7 @@ pub fn perform_swap(
 
     // 2. Get current pool liquidity (from LP pool)
     let xlm_liquidity = portfolio.get_liquidity(Asset::XLM);
-    let usdc_liquidity = portfolio.get_liquidity(Asset::Custom(symbol_short!("USDCSIM")));
-
-    // 3. Calculate swap output using constant product AMM formula: x * y = k
-    // With 0.3% fee: amount_out = (y * amount_in * (1 - fee)) / (x + amount_in * (1 - fee))
+    let usdc_liquidity = portfolio.get_liquidity(portfolio.quote_asset());
     let amount_u128 = amount as u128;
     let (reserve_in, reserve_out) = if from_asset == Asset::XLM {
         (xlm_liquidity as u128, usdc_liquidity as u128)
@@ -88,31 +151,8 @@ pub fn perform_swap(
         (usdc_liquidity as u128, xlm_liquidity as u128)
     };
 
-    let actual_out = if reserve_in > 0 && reserve_out > 0 {
-        // Apply fee: amount_in_after_fee = amount_in * (1 - fee_bps / 10000)
-        let amount_in_after_fee = (amount_u128 * (10000 - LP_FEE_BPS)) / 10000;
-        
-        // Constant product formula: (x + dx) * (y - dy) = x * y
-        // dy = (y * dx) / (x + dx)
-        let numerator = reserve_out.saturating_mul(amount_in_after_fee);
-        let denominator = reserve_in.saturating_add(amount_in_after_fee);
-        
-        if denominator == 0 {
-            panic!("Division by zero in AMM calculation");
-        }
-        
-        numerator / denominator
-    } else {
-        // If no liquidity, use oracle price (fallback)
-        let price = match get_price_with_staleness_check(env, from.clone(), to.clone()) {
-            Ok(p) => p,
-            Err(ContractError::StalePrice) => panic!("Oracle price is stale"),
-            Err(ContractError::InvalidPrice) => panic!("Oracle price is invalid"),
-            Err(ContractError::PriceNotSet) => PRECISION, // Fallback to 1:1
-            _ => PRECISION,
-        };
-        (amount_u128 * price) / PRECISION
-    };
+    // 3. Calculate swap output using constant product AMM formula: x * y = k
+    let (actual_out, theoretical_out) = quote_outputs(env, portfolio, &from, &to, amount);
 
     let out_amount = actual_out as i128;
     assert!(out_amount > 0, "Output amount must be positive");
@@ -122,34 +162,18 @@ pub fn perform_swap(
     let fee_amount_i128 = fee_amount as i128;
 
     // 5. Check slippage protection
-    let theoretical_out = if reserve_in > 0 && reserve_out > 0 {
-        // Theoretical output without fee
-        let numerator = reserve_out.saturating_mul(amount_u128);
-        let denominator = reserve_in.saturating_add(amount_u128);
-        if denominator == 0 {
-            amount_u128 // Fallback
-        } else {
-            numerator / denominator
-        }
-    } else {
-        amount_u128 // Fallback to 1:1
-    };
-
-    let max_slip = env.storage().instance().get(&symbol_short!("MAX_SLIP")).unwrap_or(10000u32);
-    if theoretical_out > 0 {
-        let slippage_bps = ((theoretical_out - actual_out) * 10000) / theoretical_out;
-        if slippage_bps > max_slip as u128 {
-            panic!("Slippage exceeded: {} bps > {} bps", slippage_bps, max_slip);
-        }
+    let max_slip = max_slippage_bps(env);
+    let slippage = slippage_bps(actual_out, theoretical_out);
+    if slippage > max_slip as u128 {
+        panic!("Slippage exceeded: {} bps > {} bps", slippage, max_slip);
     }
 
-    // 6. Update Portfolio (User Balances) - transfer from user
-    portfolio.transfer_asset(env, from_asset.clone(), to_asset.clone(), user.clone(), amount);
-    // 4. Update Portfolio (User Balances)
+    // 6. Update Portfolio (User Balances)
     // Debit input Amount
     portfolio.debit(env, from_asset.clone(), user.clone(), amount);
     // Credit output Amount (calculated by AMM/Oracle)
     portfolio.credit(env, to_asset.clone(), user.clone(), out_amount);
+    portfolio.update_stats_on_trade(env, user.clone(), amount);
     
     // 7. Update Pool Liquidity using constant product AMM
     // Add input amount (minus fee) to reserve_in, subtract output from reserve_out
@@ -158,16 +182,26 @@ pub fn perform_swap(
         
         if from_asset == Asset::XLM {
             portfolio.set_liquidity(Asset::XLM, xlm_liquidity.saturating_add(amount_in_after_fee));
-            portfolio.set_liquidity(Asset::Custom(symbol_short!("USDCSIM")), usdc_liquidity.saturating_sub(out_amount));
+            portfolio.set_liquidity(portfolio.quote_asset(), usdc_liquidity.saturating_sub(out_amount));
         } else {
-            portfolio.set_liquidity(Asset::Custom(symbol_short!("USDCSIM")), usdc_liquidity.saturating_add(amount_in_after_fee));
+            portfolio.set_liquidity(portfolio.quote_asset(), usdc_liquidity.saturating_add(amount_in_after_fee));
             portfolio.set_liquidity(Asset::XLM, xlm_liquidity.saturating_sub(out_amount));
         }
     }
 
-    // 8. Collect and attribute fees to LPs
+    // 8. Record the executed XLM rate, in the quote asset, as the day's close
+    let (xlm_amount, usdc_amount) = if from_asset == Asset::XLM { (amount, out_amount) } else { (out_amount, amount) };
+    let xlm_amount = portfolio.normalize_amount(&Asset::XLM, xlm_amount);
+    let usdc_amount = portfolio.normalize_amount(&portfolio.quote_asset(), usdc_amount);
+    if xlm_amount > 0 {
+        let scale = 10i128.pow(crate::portfolio::INTERNAL_DECIMALS);
+        crate::price_history::record_price(env, Asset::XLM, usdc_amount.saturating_mul(scale) / xlm_amount, env.ledger().timestamp());
+    }
+
+    // 9. Collect and attribute fees to LPs
     if fee_amount_i128 > 0 {
-        portfolio.add_lp_fees(fee_amount_i128);
+        // Fees from either side of the pool accumulate on one internal scale
+        portfolio.add_lp_fees(portfolio.normalize_amount(&from_asset, fee_amount_i128));
         // Fees are accumulated and can be distributed proportionally to LPs based on their LP token share
         // This is tracked in lp_fees_accumulated for future distribution
     }